@@ -0,0 +1,262 @@
+use chrono::Local;
+use rand::RngCore;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// 読み取り専用スコープ（アクティビティ・サマリーの参照のみ）
+pub const SCOPE_READ: &str = "read";
+/// 操作スコープ（トラッキングの開始/停止、手動エントリの登録なども可能）
+pub const SCOPE_CONTROL: &str = "control";
+
+/// APIトークン情報（トークン本体はハッシュのみ保存）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiToken {
+    pub id: i64,
+    pub name: String,
+    pub scope: String,
+    pub created_at: String,
+    pub last_used_at: Option<String>,
+    pub revoked: bool,
+}
+
+/// 発行直後のトークン（平文は一度だけ返す）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IssuedToken {
+    pub token: String,
+    pub info: ApiToken,
+}
+
+/// APIリクエストログ
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiRequestLog {
+    pub id: i64,
+    pub token_id: Option<i64>,
+    pub method: String,
+    pub path: String,
+    pub status: u16,
+    pub requested_at: String,
+}
+
+pub fn create_tables(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS api_tokens (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            token_hash TEXT NOT NULL UNIQUE,
+            scope TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            last_used_at TEXT,
+            revoked INTEGER NOT NULL DEFAULT 0
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS api_request_log (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            token_id INTEGER,
+            method TEXT NOT NULL,
+            path TEXT NOT NULL,
+            status INTEGER NOT NULL,
+            requested_at TEXT NOT NULL
+        )",
+        [],
+    )?;
+
+    Ok(())
+}
+
+fn now_string() -> String {
+    Local::now().format("%Y-%m-%dT%H:%M:%S").to_string()
+}
+
+fn hash_token(token: &str) -> String {
+    Sha256::digest(token.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn generate_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    format!("tt_{}", hex)
+}
+
+fn validate_scope(scope: &str) -> Result<(), String> {
+    match scope {
+        SCOPE_READ | SCOPE_CONTROL => Ok(()),
+        _ => Err(format!(
+            "Invalid scope: {} (expected read or control)",
+            scope
+        )),
+    }
+}
+
+fn get_token(conn: &Connection, id: i64) -> Result<ApiToken, String> {
+    conn.query_row(
+        "SELECT id, name, scope, created_at, last_used_at, revoked FROM api_tokens WHERE id = ?1",
+        params![id],
+        |row| {
+            Ok(ApiToken {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                scope: row.get(2)?,
+                created_at: row.get(3)?,
+                last_used_at: row.get(4)?,
+                revoked: row.get(5)?,
+            })
+        },
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// 新しいトークンを発行
+pub fn create_token(conn: &Connection, name: &str, scope: &str) -> Result<IssuedToken, String> {
+    validate_scope(scope)?;
+
+    let token = generate_token();
+    conn.execute(
+        "INSERT INTO api_tokens (name, token_hash, scope, created_at) VALUES (?1, ?2, ?3, ?4)",
+        params![name, hash_token(&token), scope, now_string()],
+    )
+    .map_err(|e| e.to_string())?;
+
+    let info = get_token(conn, conn.last_insert_rowid())?;
+    Ok(IssuedToken { token, info })
+}
+
+/// トークンを再発行（名前・スコープはそのまま、旧トークンは無効化）
+pub fn rotate_token(conn: &Connection, id: i64) -> Result<IssuedToken, String> {
+    let old = get_token(conn, id)?;
+    if old.revoked {
+        return Err(format!("Token {} is revoked", id));
+    }
+
+    let token = generate_token();
+    conn.execute(
+        "UPDATE api_tokens SET token_hash = ?1, created_at = ?2, last_used_at = NULL WHERE id = ?3",
+        params![hash_token(&token), now_string(), id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    let info = get_token(conn, id)?;
+    Ok(IssuedToken { token, info })
+}
+
+/// トークンを失効させる
+pub fn revoke_token(conn: &Connection, id: i64) -> Result<(), String> {
+    let updated = conn
+        .execute(
+            "UPDATE api_tokens SET revoked = 1 WHERE id = ?1",
+            params![id],
+        )
+        .map_err(|e| e.to_string())?;
+
+    if updated == 0 {
+        return Err(format!("Token not found: {}", id));
+    }
+    Ok(())
+}
+
+/// トークン一覧を取得
+pub fn list_tokens(conn: &Connection) -> Result<Vec<ApiToken>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, name, scope, created_at, last_used_at, revoked
+             FROM api_tokens
+             ORDER BY id ASC",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let tokens = stmt
+        .query_map([], |row| {
+            Ok(ApiToken {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                scope: row.get(2)?,
+                created_at: row.get(3)?,
+                last_used_at: row.get(4)?,
+                revoked: row.get(5)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(tokens)
+}
+
+/// Bearerトークンを検証し、要求スコープを満たすか確認する
+///
+/// control スコープは read の操作も許可する。
+pub fn authorize(conn: &Connection, token: &str, required_scope: &str) -> Result<ApiToken, String> {
+    let id: Option<i64> = conn
+        .query_row(
+            "SELECT id FROM api_tokens WHERE token_hash = ?1 AND revoked = 0",
+            params![hash_token(token)],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+
+    let id = id.ok_or_else(|| "Invalid or revoked token".to_string())?;
+    let info = get_token(conn, id)?;
+
+    if required_scope == SCOPE_CONTROL && info.scope != SCOPE_CONTROL {
+        return Err("Token does not have control scope".to_string());
+    }
+
+    let _ = conn.execute(
+        "UPDATE api_tokens SET last_used_at = ?1 WHERE id = ?2",
+        params![now_string(), id],
+    );
+
+    Ok(info)
+}
+
+/// APIリクエストを記録
+pub fn log_request(
+    conn: &Connection,
+    token_id: Option<i64>,
+    method: &str,
+    path: &str,
+    status: u16,
+) {
+    let _ = conn.execute(
+        "INSERT INTO api_request_log (token_id, method, path, status, requested_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![token_id, method, path, status, now_string()],
+    );
+}
+
+/// 直近のAPIリクエストログを取得
+pub fn recent_requests(conn: &Connection, limit: i64) -> Result<Vec<ApiRequestLog>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, token_id, method, path, status, requested_at
+             FROM api_request_log
+             ORDER BY id DESC
+             LIMIT ?1",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let logs = stmt
+        .query_map(params![limit], |row| {
+            Ok(ApiRequestLog {
+                id: row.get(0)?,
+                token_id: row.get(1)?,
+                method: row.get(2)?,
+                path: row.get(3)?,
+                status: row.get(4)?,
+                requested_at: row.get(5)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(logs)
+}
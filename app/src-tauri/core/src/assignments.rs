@@ -0,0 +1,360 @@
+use chrono::Local;
+use parking_lot::RwLock;
+use regex::Regex;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use crate::plugins::config::{AssignmentRule, IntegrationsConfig};
+
+/// 手動で割り当てたことを示すソース
+pub const SOURCE_MANUAL: &str = "manual";
+/// 割り当てルールによる自動割り当てを示すソース
+pub const SOURCE_RULE: &str = "rule";
+/// 過去データへのチケットID抽出（バックフィル）を示すソース
+pub const SOURCE_BACKFILL: &str = "backfill";
+/// トレイで選択したプロジェクトの上書きによる割り当てを示すソース
+pub const SOURCE_OVERRIDE: &str = "override";
+/// プロジェクト定義の検出による自動割り当てを示すソース
+pub const SOURCE_PROJECT: &str = "project";
+/// 他のタイムトラッカーから取り込んだことを示すソース
+pub const SOURCE_IMPORT: &str = "import";
+
+/// アクティビティへのチケット/プロジェクト割り当て
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TicketAssignment {
+    pub activity_id: i64,
+    pub plugin: Option<String>,
+    pub ticket_id: Option<String>,
+    pub project: Option<String>,
+    /// 割り当て元: "manual" など
+    pub source: String,
+    pub assigned_at: String,
+}
+
+/// 進行中のアクティビティに対する割り当て（アクティビティの保存時に適用）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingAssignment {
+    pub plugin: Option<String>,
+    pub ticket_id: Option<String>,
+    pub project: Option<String>,
+}
+
+pub fn create_tables(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS ticket_assignments (
+            activity_id INTEGER PRIMARY KEY,
+            plugin TEXT,
+            ticket_id TEXT,
+            project TEXT,
+            source TEXT NOT NULL,
+            assigned_at TEXT NOT NULL
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_assignments_ticket ON ticket_assignments(plugin, ticket_id)",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// 割り当てを登録（既存の割り当ては上書き）
+pub fn assign(
+    conn: &Connection,
+    activity_id: i64,
+    plugin: Option<&str>,
+    ticket_id: Option<&str>,
+    project: Option<&str>,
+    source: &str,
+) -> Result<TicketAssignment, String> {
+    let ticket_id = ticket_id.map(str::trim).filter(|t| !t.is_empty());
+    let project = project.map(str::trim).filter(|p| !p.is_empty());
+
+    if ticket_id.is_none() && project.is_none() {
+        return Err("Either ticket_id or project is required".to_string());
+    }
+
+    let exists: bool = conn
+        .query_row(
+            "SELECT EXISTS(SELECT 1 FROM activities WHERE id = ?1)",
+            params![activity_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    if !exists {
+        return Err(format!("Activity not found: {}", activity_id));
+    }
+
+    conn.execute(
+        "INSERT OR REPLACE INTO ticket_assignments
+            (activity_id, plugin, ticket_id, project, source, assigned_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            activity_id,
+            plugin,
+            ticket_id,
+            project,
+            source,
+            Local::now().format("%Y-%m-%dT%H:%M:%S").to_string(),
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    get(conn, activity_id)?.ok_or_else(|| format!("Activity not found: {}", activity_id))
+}
+
+/// 割り当てを解除
+pub fn unassign(conn: &Connection, activity_id: i64) -> Result<(), String> {
+    conn.execute(
+        "DELETE FROM ticket_assignments WHERE activity_id = ?1",
+        params![activity_id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// チケットのアクティビティの合計時間（秒）
+///
+/// activity_tickets と同じく割り当てを優先し、チケットの割り当てがなければ保存済みの抽出結果で数える。
+pub fn tracked_seconds(conn: &Connection, plugin: &str, ticket_id: &str) -> Result<i64, String> {
+    conn.query_row(
+        "SELECT COALESCE(SUM(a.duration_seconds), 0)
+         FROM activities a
+         LEFT JOIN ticket_assignments t ON t.activity_id = a.id
+         WHERE (t.plugin IS NOT NULL AND t.ticket_id IS NOT NULL
+                AND t.plugin = ?1 AND t.ticket_id = ?2)
+            OR ((t.plugin IS NULL OR t.ticket_id IS NULL)
+                AND a.extracted_plugin = ?1 AND a.extracted_ticket_id = ?2)",
+        params![plugin, ticket_id],
+        |row| row.get(0),
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// 最近割り当てたプロジェクト（新しい順）
+pub fn recent_projects(conn: &Connection, limit: i64) -> Result<Vec<String>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT project FROM ticket_assignments
+             WHERE project IS NOT NULL AND project != ''
+             GROUP BY project
+             ORDER BY MAX(assigned_at) DESC
+             LIMIT ?1",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let projects = stmt
+        .query_map(params![limit], |row| row.get(0))
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(projects)
+}
+
+/// アクティビティの割り当てを取得
+pub fn get(conn: &Connection, activity_id: i64) -> Result<Option<TicketAssignment>, String> {
+    conn.query_row(
+        "SELECT activity_id, plugin, ticket_id, project, source, assigned_at
+         FROM ticket_assignments WHERE activity_id = ?1",
+        params![activity_id],
+        |row| {
+            Ok(TicketAssignment {
+                activity_id: row.get(0)?,
+                plugin: row.get(1)?,
+                ticket_id: row.get(2)?,
+                project: row.get(3)?,
+                source: row.get(4)?,
+                assigned_at: row.get(5)?,
+            })
+        },
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+/// 過去の割り当て履歴に基づく割り当て候補
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssignmentSuggestion {
+    pub plugin: Option<String>,
+    pub ticket_id: Option<String>,
+    pub project: Option<String>,
+    /// 類似度スコアの合計（大きいほど有力）
+    pub score: f64,
+    /// 根拠となった過去の割り当て件数
+    pub matched_count: usize,
+}
+
+/// タイトルを小文字のトークン集合に分割
+fn title_tokens(title: &str) -> HashSet<String> {
+    title
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|t| t.chars().count() >= 2)
+        .map(|t| t.to_string())
+        .collect()
+}
+
+/// 同じプロセス/ドメインの過去の割り当てから候補を提案する
+///
+/// タイトル完全一致を最優先し、それ以外はタイトルのトークン一致率（Jaccard係数）で重み付けする。
+pub fn suggest(
+    conn: &Connection,
+    activity_id: i64,
+    process_name: &str,
+    window_title: &str,
+    domain: Option<&str>,
+    limit: usize,
+) -> Result<Vec<AssignmentSuggestion>, String> {
+    type History = (
+        String,
+        Option<String>,
+        Option<String>,
+        Option<String>,
+        Option<String>,
+    );
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT a.window_title, a.domain, t.plugin, t.ticket_id, t.project
+             FROM ticket_assignments t
+             JOIN activities a ON a.id = t.activity_id
+             WHERE t.activity_id != ?1 AND (a.process_name = ?2 OR (?3 IS NOT NULL AND a.domain = ?3))
+             ORDER BY t.assigned_at DESC
+             LIMIT 1000",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let history: Vec<History> = stmt
+        .query_map(params![activity_id, process_name, domain], |row| {
+            Ok((
+                row.get(0)?,
+                row.get(1)?,
+                row.get(2)?,
+                row.get(3)?,
+                row.get(4)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let tokens = title_tokens(window_title);
+    type CandidateKey = (Option<String>, Option<String>, Option<String>);
+    let mut candidates: HashMap<CandidateKey, (f64, usize)> = HashMap::new();
+
+    for (title, past_domain, plugin, ticket_id, project) in history {
+        let mut score = if title == window_title {
+            3.0
+        } else {
+            let past_tokens = title_tokens(&title);
+            let union = tokens.union(&past_tokens).count();
+            if union == 0 {
+                0.0
+            } else {
+                tokens.intersection(&past_tokens).count() as f64 / union as f64
+            }
+        };
+
+        if domain.is_some() && past_domain.as_deref() == domain {
+            score += 0.5;
+        }
+
+        if score <= 0.0 {
+            continue;
+        }
+
+        let entry = candidates
+            .entry((plugin, ticket_id, project))
+            .or_insert((0.0, 0));
+        entry.0 += score;
+        entry.1 += 1;
+    }
+
+    let mut suggestions: Vec<AssignmentSuggestion> = candidates
+        .into_iter()
+        .map(
+            |((plugin, ticket_id, project), (score, matched_count))| AssignmentSuggestion {
+                plugin,
+                ticket_id,
+                project,
+                score,
+                matched_count,
+            },
+        )
+        .collect();
+
+    suggestions.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    suggestions.truncate(limit);
+
+    Ok(suggestions)
+}
+
+/// 割り当てルールを評価する（上から順に評価し、最初にマッチしたルールを採用）
+pub struct RuleAssigner {
+    rules: RwLock<Vec<(Regex, AssignmentRule)>>,
+}
+
+impl RuleAssigner {
+    pub fn new() -> Self {
+        Self {
+            rules: RwLock::new(Vec::new()),
+        }
+    }
+
+    /// 設定ファイルから割り当てルールを読み込む
+    pub fn load_from_config(&self) {
+        let config = IntegrationsConfig::load();
+        self.set_rules(&config.assignment_rules);
+    }
+
+    /// ルールを差し替える（コンパイルできないパターンは無視）
+    pub fn set_rules(&self, rules: &[AssignmentRule]) {
+        let compiled = rules
+            .iter()
+            .filter_map(|rule| match Regex::new(&rule.pattern) {
+                Ok(regex) => Some((regex, rule.clone())),
+                Err(e) => {
+                    eprintln!("Invalid assignment pattern {:?}: {}", rule.pattern, e);
+                    None
+                }
+            })
+            .collect();
+
+        *self.rules.write() = compiled;
+    }
+
+    /// マッチしたルールを返す
+    pub fn find_match(
+        &self,
+        process_name: &str,
+        window_title: &str,
+        domain: Option<&str>,
+    ) -> Option<AssignmentRule> {
+        self.rules
+            .read()
+            .iter()
+            .find(|(regex, rule)| {
+                let text = match rule.source.as_str() {
+                    "process_name" => process_name,
+                    "domain" => domain.unwrap_or(""),
+                    _ => window_title,
+                };
+                regex.is_match(text)
+            })
+            .map(|(_, rule)| rule.clone())
+    }
+}
+
+impl Default for RuleAssigner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,99 @@
+use url::Url;
+
+/// ブラウザのプロセス名（Windows の実行ファイル名、macOS のアプリ名、Linux のプロセス名）
+const BROWSER_PROCESSES: &[&str] = &[
+    // Windows
+    "chrome.exe",
+    "msedge.exe",
+    "firefox.exe",
+    "brave.exe",
+    "opera.exe",
+    "vivaldi.exe",
+    "iexplore.exe",
+    // macOS
+    "google chrome",
+    "microsoft edge",
+    "firefox",
+    "brave browser",
+    "safari",
+    "arc",
+    "opera",
+    "vivaldi",
+    // Linux
+    "chrome",
+    "chromium",
+    "chromium-browser",
+    "firefox-bin",
+    "firefox-esr",
+    "msedge",
+    "brave",
+];
+
+/// プロセス名がブラウザかどうかを判定
+pub fn is_browser(process_name: &str) -> bool {
+    let lower = process_name.to_lowercase();
+    BROWSER_PROCESSES.iter().any(|b| lower == *b)
+}
+
+/// URL（プロトコルなしも可）からドメインを取り出す
+pub fn extract_domain(url_str: &str) -> Option<String> {
+    // Try parsing as full URL first
+    if let Ok(url) = Url::parse(url_str) {
+        return url.host_str().map(|h| h.to_string());
+    }
+
+    // Try adding https:// prefix for URLs without protocol
+    // Many browsers show URLs without the protocol (e.g., "github.com" instead of "https://github.com")
+    let with_protocol = format!("https://{}", url_str);
+    if let Ok(url) = Url::parse(&with_protocol) {
+        // Validate it looks like a real domain (has at least one dot)
+        if let Some(host) = url.host_str() {
+            if host.contains('.') {
+                return Some(host.to_string());
+            }
+        }
+    }
+
+    None
+}
+
+/// Check if a string looks like a URL (with or without protocol)
+pub fn looks_like_url(s: &str) -> bool {
+    // Check for protocol prefix
+    if s.starts_with("http://") || s.starts_with("https://") {
+        return true;
+    }
+
+    // Check if it looks like a domain (contains dot and no spaces)
+    // e.g., "github.com", "www.google.com/search?q=test"
+    if !s.contains(' ') && !s.is_empty() {
+        // Find the domain part (before any path)
+        let domain_part = s.split('/').next().unwrap_or(s);
+        // Must contain a dot and have reasonable structure
+        if domain_part.contains('.') {
+            // Check it's not just numbers (like "1.0")
+            let parts: Vec<&str> = domain_part.split('.').collect();
+            if parts.len() >= 2 {
+                // At least one part should not be purely numeric or empty
+                let has_alpha = parts
+                    .iter()
+                    .any(|p| !p.is_empty() && p.chars().any(|c| c.is_alphabetic()));
+                return has_alpha;
+            }
+        }
+    }
+
+    false
+}
+
+/// ウィンドウタイトルに含まれるURLからドメインを推定する
+///
+/// アドレスバーを読めない環境向け（"URL in title" 系の拡張機能でタイトルにURLを含めている場合など）。
+pub fn domain_from_title(title: &str) -> Option<String> {
+    title
+        .split(|c: char| c.is_whitespace() || matches!(c, '|' | '—' | '–' | '(' | ')' | '[' | ']'))
+        .map(|token| token.trim_matches(|c: char| matches!(c, '-' | ',' | '"' | '\'')))
+        .filter(|token| token.contains("://") || token.starts_with("www."))
+        .filter(|token| looks_like_url(token))
+        .find_map(extract_domain)
+}
@@ -0,0 +1,162 @@
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::plugins::config::WeeklyBudget;
+
+/// カテゴリ予算の進捗
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetStatus {
+    pub category: String,
+    /// "max" | "min"
+    pub kind: String,
+    pub budget_seconds: i64,
+    pub tracked_seconds: i64,
+    /// 現在のペースで週末まで続いた場合の見込み
+    pub projected_seconds: i64,
+    /// 予算に対する進捗（%）
+    pub progress: f64,
+    /// max: "ok" | "warning" | "exceeded" / min: "behind" | "on_track" | "met"
+    pub status: String,
+}
+
+/// 1週間分の予算状況
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeeklyBudgetStatus {
+    pub week_start: String,
+    pub week_end: String,
+    pub budgets: Vec<BudgetStatus>,
+}
+
+pub fn create_tables(conn: &Connection) -> rusqlite::Result<()> {
+    // 通知済みの状態（再起動しても同じ期間に同じ通知を繰り返さない）
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS budget_notifications (
+            subject TEXT NOT NULL,
+            period_start TEXT NOT NULL,
+            status TEXT NOT NULL,
+            notified_at TEXT NOT NULL,
+            PRIMARY KEY (subject, period_start, status)
+        )",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// 通知済みとして記録する（既に通知済みなら false）
+///
+/// subject は予算なら "budget:<カテゴリ>"、目標なら "goal:<ID>"。
+pub fn mark_notified(
+    conn: &Connection,
+    subject: &str,
+    period_start: &str,
+    status: &str,
+) -> Result<bool, String> {
+    let inserted = conn
+        .execute(
+            "INSERT OR IGNORE INTO budget_notifications (subject, period_start, status, notified_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![
+                subject,
+                period_start,
+                status,
+                Local::now().format("%Y-%m-%dT%H:%M:%S").to_string()
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+    Ok(inserted > 0)
+}
+
+/// 指定日を含む週（月曜始まり）の初日
+pub fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+/// 指定日を含む週の予算状況を計算する
+pub fn weekly_status(
+    conn: &Connection,
+    budgets: &[WeeklyBudget],
+    date: NaiveDate,
+    now: DateTime<Local>,
+) -> Result<WeeklyBudgetStatus, String> {
+    let start = week_start(date);
+    let end = start + Duration::days(6);
+    let range_start = format!("{}T00:00:00", start.format("%Y-%m-%d"));
+    let range_end = format!("{}T23:59:59", end.format("%Y-%m-%d"));
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT category, SUM(duration_seconds)
+             FROM activities
+             WHERE start_time >= ?1 AND start_time <= ?2 AND category IS NOT NULL
+             GROUP BY category",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let totals: HashMap<String, i64> = stmt
+        .query_map(params![range_start, range_end], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    // 週のうち経過した割合（過去の週は1.0、未来の週は0.0）
+    let week_seconds = 7 * 24 * 3600;
+    let elapsed_seconds = (now.naive_local() - start.and_hms_opt(0, 0, 0).unwrap())
+        .num_seconds()
+        .clamp(0, week_seconds);
+    let elapsed_fraction = elapsed_seconds as f64 / week_seconds as f64;
+
+    let budgets = budgets
+        .iter()
+        .map(|budget| {
+            let budget_seconds = (budget.hours * 3600.0).round() as i64;
+            let tracked_seconds = totals.get(&budget.category).copied().unwrap_or(0);
+            let projected_seconds = if elapsed_fraction > 0.0 {
+                (tracked_seconds as f64 / elapsed_fraction).round() as i64
+            } else {
+                tracked_seconds
+            };
+            let progress = if budget_seconds > 0 {
+                (tracked_seconds as f64 / budget_seconds as f64) * 100.0
+            } else {
+                0.0
+            };
+
+            let status = if budget.kind == "min" {
+                if tracked_seconds >= budget_seconds {
+                    "met"
+                } else if projected_seconds >= budget_seconds {
+                    "on_track"
+                } else {
+                    "behind"
+                }
+            } else if tracked_seconds > budget_seconds {
+                "exceeded"
+            } else if progress >= budget.warn_at_percent {
+                "warning"
+            } else {
+                "ok"
+            };
+
+            BudgetStatus {
+                category: budget.category.clone(),
+                kind: budget.kind.clone(),
+                budget_seconds,
+                tracked_seconds,
+                projected_seconds,
+                progress,
+                status: status.to_string(),
+            }
+        })
+        .collect();
+
+    Ok(WeeklyBudgetStatus {
+        week_start: start.format("%Y-%m-%d").to_string(),
+        week_end: end.format("%Y-%m-%d").to_string(),
+        budgets,
+    })
+}
@@ -0,0 +1,229 @@
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::plugins::config::IntegrationsConfig;
+
+/// ネイティブのフォアグラウンドウィンドウ
+pub const SOURCE_FOREGROUND: &str = "foreground";
+/// ブラウザ拡張から送られるタブ情報
+pub const SOURCE_BROWSER_EXTENSION: &str = "browser_extension";
+/// カレンダーの予定
+pub const SOURCE_CALENDAR: &str = "calendar";
+/// 手動タイマー
+pub const SOURCE_MANUAL_TIMER: &str = "manual_timer";
+
+/// キャプチャソースが返すウィンドウ情報
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CapturedWindow {
+    pub process_name: String,
+    pub window_title: String,
+    pub domain: Option<String>,
+}
+
+/// キャプチャソースの状態
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaptureSourceInfo {
+    pub name: String,
+    pub priority: i32,
+}
+
+/// アクティビティのキャプチャソース
+pub trait WindowProvider: Send + Sync {
+    /// ソース名を返す
+    fn name(&self) -> &str;
+
+    /// 既定の優先度（大きいほど優先。設定ファイルで上書き可能）
+    fn default_priority(&self) -> i32;
+
+    /// 現在のウィンドウ情報（このソースが何も提供しない場合は None）
+    fn current(&self) -> Option<CapturedWindow>;
+}
+
+/// OSのフォアグラウンドウィンドウ
+pub struct ForegroundWindowProvider;
+
+impl WindowProvider for ForegroundWindowProvider {
+    fn name(&self) -> &str {
+        SOURCE_FOREGROUND
+    }
+
+    fn default_priority(&self) -> i32 {
+        100
+    }
+
+    fn current(&self) -> Option<CapturedWindow> {
+        crate::windows_watcher::get_active_window_info().map(
+            |(process_name, window_title, domain)| CapturedWindow {
+                process_name,
+                window_title,
+                domain,
+            },
+        )
+    }
+}
+
+/// 外部から通知されたイベントを有効期限付きで保持するソース（ブラウザ拡張・カレンダー）
+pub struct PushedWindowProvider {
+    name: String,
+    priority: i32,
+    latest: Mutex<Option<(CapturedWindow, Instant)>>,
+}
+
+impl PushedWindowProvider {
+    pub fn new(name: &str, priority: i32) -> Self {
+        Self {
+            name: name.to_string(),
+            priority,
+            latest: Mutex::new(None),
+        }
+    }
+
+    /// イベントを通知（valid_for を過ぎると失効する）
+    pub fn push(&self, window: CapturedWindow, valid_for: Duration) {
+        *self.latest.lock() = Some((window, Instant::now() + valid_for));
+    }
+}
+
+impl WindowProvider for PushedWindowProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn default_priority(&self) -> i32 {
+        self.priority
+    }
+
+    fn current(&self) -> Option<CapturedWindow> {
+        let mut latest = self.latest.lock();
+        match latest.as_ref() {
+            Some((window, expires_at)) if Instant::now() < *expires_at => Some(window.clone()),
+            Some(_) => {
+                *latest = None;
+                None
+            }
+            None => None,
+        }
+    }
+}
+
+/// 手動タイマー（停止するまで指定した内容で記録し続ける）
+pub struct ManualTimerProvider {
+    active: Mutex<Option<CapturedWindow>>,
+}
+
+impl ManualTimerProvider {
+    pub fn new() -> Self {
+        Self {
+            active: Mutex::new(None),
+        }
+    }
+
+    pub fn start(&self, window: CapturedWindow) {
+        *self.active.lock() = Some(window);
+    }
+
+    /// タイマーを停止（動作していなかった場合は false）
+    pub fn stop(&self) -> bool {
+        self.active.lock().take().is_some()
+    }
+}
+
+impl Default for ManualTimerProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WindowProvider for ManualTimerProvider {
+    fn name(&self) -> &str {
+        SOURCE_MANUAL_TIMER
+    }
+
+    fn default_priority(&self) -> i32 {
+        300
+    }
+
+    fn current(&self) -> Option<CapturedWindow> {
+        self.active.lock().clone()
+    }
+}
+
+/// 登録されたキャプチャソースを優先度順に評価する
+pub struct CaptureRegistry {
+    providers: RwLock<Vec<(i32, Arc<dyn WindowProvider>)>>,
+    pub browser_extension: Arc<PushedWindowProvider>,
+    pub calendar: Arc<PushedWindowProvider>,
+    pub manual_timer: Arc<ManualTimerProvider>,
+}
+
+impl CaptureRegistry {
+    /// 組み込みソース（手動タイマー > ブラウザ拡張 > フォアグラウンド > カレンダー）を登録
+    pub fn new() -> Self {
+        let browser_extension = Arc::new(PushedWindowProvider::new(SOURCE_BROWSER_EXTENSION, 200));
+        let calendar = Arc::new(PushedWindowProvider::new(SOURCE_CALENDAR, 50));
+        let manual_timer = Arc::new(ManualTimerProvider::new());
+
+        let registry = Self {
+            providers: RwLock::new(Vec::new()),
+            browser_extension: browser_extension.clone(),
+            calendar: calendar.clone(),
+            manual_timer: manual_timer.clone(),
+        };
+        registry.register(manual_timer);
+        registry.register(browser_extension);
+        registry.register(Arc::new(ForegroundWindowProvider));
+        registry.register(calendar);
+        registry
+    }
+
+    /// ソースを登録
+    pub fn register(&self, provider: Arc<dyn WindowProvider>) {
+        let priority = provider.default_priority();
+        let mut providers = self.providers.write();
+        providers.push((priority, provider));
+        providers.sort_by_key(|(priority, _)| std::cmp::Reverse(*priority));
+    }
+
+    /// 設定ファイルの優先度で上書き
+    pub fn load_from_config(&self) {
+        let config = IntegrationsConfig::load();
+        let mut providers = self.providers.write();
+        for (priority, provider) in providers.iter_mut() {
+            *priority = config
+                .capture_priorities
+                .get(provider.name())
+                .copied()
+                .unwrap_or_else(|| provider.default_priority());
+        }
+        providers.sort_by_key(|(priority, _)| std::cmp::Reverse(*priority));
+    }
+
+    /// 最も優先度の高いソースのウィンドウ情報とソース名
+    pub fn current(&self) -> Option<(String, CapturedWindow)> {
+        self.providers.read().iter().find_map(|(_, provider)| {
+            provider
+                .current()
+                .map(|window| (provider.name().to_string(), window))
+        })
+    }
+
+    /// 登録済みソースの一覧
+    pub fn list(&self) -> Vec<CaptureSourceInfo> {
+        self.providers
+            .read()
+            .iter()
+            .map(|(priority, provider)| CaptureSourceInfo {
+                name: provider.name().to_string(),
+                priority: *priority,
+            })
+            .collect()
+    }
+}
+
+impl Default for CaptureRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,277 @@
+use chrono::Local;
+use parking_lot::RwLock;
+use regex::Regex;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use crate::plugins::config::{CategoryRule, IntegrationsConfig};
+
+/// カテゴリが付いていないアクティビティの集計名
+pub const UNCATEGORIZED: &str = "Uncategorized";
+
+/// カテゴリの定義
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryInfo {
+    pub name: String,
+    /// 表示色（"#4285f4" など）
+    pub color: Option<String>,
+    pub description: Option<String>,
+    pub created_at: String,
+}
+
+pub fn create_tables(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS categories (
+            name TEXT PRIMARY KEY,
+            color TEXT,
+            description TEXT,
+            created_at TEXT NOT NULL
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+/// カテゴリ一覧を取得
+pub fn list(conn: &Connection) -> Result<Vec<CategoryInfo>, String> {
+    let mut stmt = conn
+        .prepare("SELECT name, color, description, created_at FROM categories ORDER BY name")
+        .map_err(|e| e.to_string())?;
+
+    let categories = stmt
+        .query_map([], |row| {
+            Ok(CategoryInfo {
+                name: row.get(0)?,
+                color: row.get(1)?,
+                description: row.get(2)?,
+                created_at: row.get(3)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(categories)
+}
+
+/// 分類ルールが参照しているカテゴリを未登録なら追加する
+pub fn ensure_from_rules(conn: &Connection, rules: &[CategoryRule]) -> Result<(), String> {
+    let now = Local::now().format("%Y-%m-%dT%H:%M:%S").to_string();
+    for rule in rules {
+        conn.execute(
+            "INSERT OR IGNORE INTO categories (name, created_at) VALUES (?1, ?2)",
+            params![rule.category, now],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// カテゴリを作成
+pub fn create(
+    conn: &Connection,
+    name: &str,
+    color: Option<&str>,
+    description: Option<&str>,
+) -> Result<(), String> {
+    let name = name.trim();
+    if name.is_empty() || name == UNCATEGORIZED {
+        return Err(format!("Invalid category name: {:?}", name));
+    }
+
+    conn.execute(
+        "INSERT INTO categories (name, color, description, created_at) VALUES (?1, ?2, ?3, ?4)",
+        params![
+            name,
+            color,
+            description,
+            Local::now().format("%Y-%m-%dT%H:%M:%S").to_string()
+        ],
+    )
+    .map_err(|e| match e {
+        rusqlite::Error::SqliteFailure(err, _)
+            if err.code == rusqlite::ErrorCode::ConstraintViolation =>
+        {
+            format!("Category already exists: {}", name)
+        }
+        e => e.to_string(),
+    })?;
+    Ok(())
+}
+
+/// カテゴリを更新（未指定の項目はそのまま。名前を変えた場合は記録済みアクティビティと分類ルールも付け替える）
+///
+/// 設定ファイルの保存に失敗したらデータベースの変更も取り消す。
+pub fn update(
+    conn: &Connection,
+    config: &mut IntegrationsConfig,
+    name: &str,
+    new_name: Option<&str>,
+    color: Option<&str>,
+    description: Option<&str>,
+) -> Result<(), String> {
+    let new_name = new_name.map(str::trim).unwrap_or(name);
+    if new_name.is_empty() || new_name == UNCATEGORIZED {
+        return Err(format!("Invalid category name: {:?}", new_name));
+    }
+
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    let updated = tx
+        .execute(
+            "UPDATE categories
+             SET name = ?1, color = COALESCE(?2, color), description = COALESCE(?3, description)
+             WHERE name = ?4",
+            params![new_name, color, description, name],
+        )
+        .map_err(|e| e.to_string())?;
+    if updated == 0 {
+        return Err(format!("Category not found: {}", name));
+    }
+
+    if new_name != name {
+        tx.execute(
+            "UPDATE activities SET category = ?1 WHERE category = ?2",
+            params![new_name, name],
+        )
+        .map_err(|e| e.to_string())?;
+
+        for rule in config.categories.iter_mut().filter(|r| r.category == name) {
+            rule.category = new_name.to_string();
+        }
+        for budget in config
+            .weekly_budgets
+            .iter_mut()
+            .filter(|b| b.category == name)
+        {
+            budget.category = new_name.to_string();
+        }
+        if let Some(level) = config.productivity.categories.remove(name) {
+            config
+                .productivity
+                .categories
+                .insert(new_name.to_string(), level);
+        }
+        config.save()?;
+    }
+
+    tx.commit().map_err(|e| e.to_string())
+}
+
+/// カテゴリを削除（分類ルール・週の予算・生産性の区分から参照されている場合は削除しない）
+pub fn delete(conn: &Connection, config: &IntegrationsConfig, name: &str) -> Result<(), String> {
+    let rule_count = config
+        .categories
+        .iter()
+        .filter(|r| r.category == name)
+        .count();
+    if rule_count > 0 {
+        return Err(format!(
+            "Category {} is used by {} rule(s); remove them first",
+            name, rule_count
+        ));
+    }
+    let budget_count = config
+        .weekly_budgets
+        .iter()
+        .filter(|b| b.category == name)
+        .count();
+    if budget_count > 0 {
+        return Err(format!(
+            "Category {} is used by {} weekly budget(s); remove them first",
+            name, budget_count
+        ));
+    }
+    if config.productivity.categories.contains_key(name) {
+        return Err(format!(
+            "Category {} has a productivity level; remove it first",
+            name
+        ));
+    }
+
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    let deleted = tx
+        .execute("DELETE FROM categories WHERE name = ?1", params![name])
+        .map_err(|e| e.to_string())?;
+    if deleted == 0 {
+        return Err(format!("Category not found: {}", name));
+    }
+    tx.execute(
+        "UPDATE activities SET category = NULL WHERE category = ?1",
+        params![name],
+    )
+    .map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())
+}
+
+/// コンパイル済みのカテゴリ分類ルール
+struct CompiledRule {
+    regex: Regex,
+    source: String,
+    category: String,
+}
+
+/// アクティビティにカテゴリを割り当てる分類器
+pub struct Categorizer {
+    rules: RwLock<Vec<CompiledRule>>,
+}
+
+impl Categorizer {
+    pub fn new() -> Self {
+        Self {
+            rules: RwLock::new(Vec::new()),
+        }
+    }
+
+    /// 設定ファイルから分類ルールを読み込む
+    pub fn load_from_config(&self) {
+        let config = IntegrationsConfig::load();
+        self.set_rules(&config.categories);
+    }
+
+    /// 分類ルールを差し替える（コンパイルできないパターンは無視）
+    pub fn set_rules(&self, rules: &[CategoryRule]) {
+        let compiled = rules
+            .iter()
+            .filter_map(|rule| match Regex::new(&rule.pattern) {
+                Ok(regex) => Some(CompiledRule {
+                    regex,
+                    source: rule.source.clone(),
+                    category: rule.category.clone(),
+                }),
+                Err(e) => {
+                    eprintln!("Invalid category pattern {:?}: {}", rule.pattern, e);
+                    None
+                }
+            })
+            .collect();
+
+        *self.rules.write() = compiled;
+    }
+
+    /// 最初にマッチしたルールのカテゴリを返す
+    pub fn classify(
+        &self,
+        process_name: &str,
+        window_title: &str,
+        domain: Option<&str>,
+    ) -> Option<String> {
+        self.rules
+            .read()
+            .iter()
+            .find(|rule| {
+                let text = match rule.source.as_str() {
+                    "process_name" => process_name,
+                    "domain" => domain.unwrap_or(""),
+                    _ => window_title,
+                };
+                rule.regex.is_match(text)
+            })
+            .map(|rule| rule.category.clone())
+    }
+}
+
+impl Default for Categorizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
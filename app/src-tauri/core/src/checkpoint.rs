@@ -0,0 +1,115 @@
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
+use rusqlite::{params, Connection, OptionalExtension};
+
+const TIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+/// 保存前の進行中のアクティビティ
+#[derive(Debug, Clone)]
+pub struct OpenActivity {
+    pub process_name: String,
+    pub window_title: String,
+    pub domain: Option<String>,
+    pub start: DateTime<Local>,
+    /// 最後に記録を確認した時刻（復元時はここで区切る）
+    pub last_seen: DateTime<Local>,
+}
+
+pub fn create_tables(conn: &Connection) -> rusqlite::Result<()> {
+    // 常に1行だけ（id = 1）
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS current_activity (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            process_name TEXT NOT NULL,
+            window_title TEXT NOT NULL,
+            domain TEXT,
+            start_time TEXT NOT NULL,
+            last_seen TEXT NOT NULL
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+/// 進行中のアクティビティを書き出す（前回の内容は置き換える）
+pub fn save(conn: &Connection, activity: &OpenActivity) -> Result<(), String> {
+    conn.execute(
+        "INSERT OR REPLACE INTO current_activity
+            (id, process_name, window_title, domain, start_time, last_seen)
+         VALUES (1, ?1, ?2, ?3, ?4, ?5)",
+        params![
+            activity.process_name,
+            activity.window_title,
+            activity.domain,
+            activity.start.format(TIME_FORMAT).to_string(),
+            activity.last_seen.format(TIME_FORMAT).to_string()
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+pub fn clear(conn: &Connection) -> Result<(), String> {
+    conn.execute("DELETE FROM current_activity", [])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn parse(s: &str) -> Option<DateTime<Local>> {
+    let naive = NaiveDateTime::parse_from_str(s, TIME_FORMAT).ok()?;
+    Local.from_local_datetime(&naive).earliest()
+}
+
+/// 前回の終了時に残っていたアクティビティを取り出して消す
+///
+/// 保存直後に終了した場合は既存の行（前の行にまとめられたものを含む）が開始時刻を覆っているので、
+/// 覆われている分を除き、最後まで保存済みなら返さない。
+pub fn take(conn: &Connection) -> Result<Option<OpenActivity>, String> {
+    let row = conn
+        .query_row(
+            "SELECT process_name, window_title, domain, start_time, last_seen
+             FROM current_activity WHERE id = 1",
+            [],
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, Option<String>>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, String>(4)?,
+                ))
+            },
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    clear(conn)?;
+
+    let Some((process_name, window_title, domain, start_time, last_seen)) = row else {
+        return Ok(None);
+    };
+
+    let saved_until: Option<String> = conn
+        .query_row(
+            "SELECT MAX(end_time) FROM activities
+             WHERE process_name = ?1 AND start_time <= ?2 AND end_time > ?2",
+            params![process_name, start_time],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+
+    let (Some(mut start), Some(last_seen)) = (parse(&start_time), parse(&last_seen)) else {
+        return Ok(None);
+    };
+    if let Some(saved_until) = saved_until.as_deref().and_then(parse) {
+        if saved_until >= last_seen {
+            return Ok(None);
+        }
+        start = saved_until;
+    }
+    Ok(Some(OpenActivity {
+        process_name,
+        window_title,
+        domain,
+        start,
+        last_seen,
+    }))
+}
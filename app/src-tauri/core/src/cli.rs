@@ -0,0 +1,223 @@
+use chrono::{Local, NaiveDate};
+use std::sync::Arc;
+
+use crate::config_watcher;
+use crate::instance;
+use crate::mqtt;
+use crate::plugins::config::IntegrationsConfig;
+use crate::plugins::integrations::SlackIntegration;
+use crate::runtime;
+use crate::slack_status;
+use crate::summary::{self, GroupBy};
+use crate::webhooks;
+use crate::{
+    flush_activities, format_hours_minutes, recover_open_activity, start_calendar_sync,
+    start_compaction_job, start_retention_job, start_sync_queue_flusher,
+    start_ticket_extraction_backfill, start_upload_job, start_watcher_thread, sync_day_activities,
+    timeline_csv, AppState,
+};
+
+const USAGE: &str = "Usage: timetracker-cli <command> [options]
+
+Commands:
+  watch                                   Track the active window without the GUI (Ctrl+C to stop)
+  report [--date D] [--end-date D]        Print per-app and per-category totals
+  export-csv [--date D] [--profile NAME] [--output FILE]
+                                          Export the day's activities as CSV (stdout by default)
+  sync <plugin> [--date D]                Sync the day's activities to an integration
+
+Dates are YYYY-MM-DD and default to today.";
+
+/// ヘッドレスの CLI（timetracker-cli）のエントリーポイント。GUI と同じデータベース・設定を使う
+pub fn run(args: Vec<String>) -> Result<(), String> {
+    let Some((command, rest)) = args.split_first() else {
+        println!("{}", USAGE);
+        return Ok(());
+    };
+    let options = Options::parse(rest)?;
+
+    match command.as_str() {
+        "watch" => watch(),
+        "report" => report(&options),
+        "export-csv" => export_csv(&options),
+        "sync" => sync(&options),
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        _ => Err(format!("Unknown command: {}\n\n{}", command, USAGE)),
+    }
+}
+
+#[derive(Default)]
+struct Options {
+    positional: Vec<String>,
+    date: Option<String>,
+    end_date: Option<String>,
+    profile: Option<String>,
+    output: Option<String>,
+}
+
+impl Options {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut options = Options::default();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let slot = match arg.as_str() {
+                "--date" => &mut options.date,
+                "--end-date" => &mut options.end_date,
+                "--profile" => &mut options.profile,
+                "--output" | "-o" => &mut options.output,
+                _ if arg.starts_with('-') => return Err(format!("Unknown option: {}", arg)),
+                _ => {
+                    options.positional.push(arg.clone());
+                    continue;
+                }
+            };
+            *slot = Some(
+                args.next()
+                    .ok_or_else(|| format!("{} requires a value", arg))?
+                    .clone(),
+            );
+        }
+        Ok(options)
+    }
+
+    fn date(&self) -> Result<NaiveDate, String> {
+        match &self.date {
+            Some(date) => NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|e| e.to_string()),
+            None => Ok(Local::now().date_naive()),
+        }
+    }
+}
+
+fn open_state() -> Result<Arc<AppState>, String> {
+    Ok(Arc::new(AppState::new()?))
+}
+
+/// GUI なしで記録を続ける（GUI などが記録中なら起動しない）
+fn watch() -> Result<(), String> {
+    let _lock = instance::acquire()?;
+    let state = open_state()?;
+    recover_open_activity(&state);
+    *state.is_tracking.lock() = true;
+
+    start_compaction_job(state.clone());
+    start_retention_job(state.clone());
+    start_calendar_sync(state.clone());
+    start_sync_queue_flusher(state.clone());
+    start_upload_job(state.clone());
+    start_ticket_extraction_backfill(state.clone());
+    config_watcher::start(state.clone());
+    let config = IntegrationsConfig::load();
+    if let Some(mqtt_config) = config.mqtt.filter(|c| c.enabled) {
+        mqtt::start(state.clone(), mqtt_config)?;
+    }
+    if let Some(slack_config) = config.slack.filter(|c| c.enabled) {
+        slack_status::start(
+            state.clone(),
+            Arc::new(SlackIntegration::new(slack_config)?),
+        );
+    }
+    webhooks::start(state.clone(), config.webhooks);
+    start_watcher_thread(None, state.clone());
+
+    println!("Tracking the active window. Press Ctrl+C to stop.");
+    runtime::block_on(tokio::signal::ctrl_c())
+        .map_err(|e| format!("Failed to wait for Ctrl+C: {}", e))?;
+
+    // 書き込み待ちの記録を保存して終わる（進行中のアクティビティは次回起動時にチェックポイントから復元する）
+    *state.is_tracking.lock() = false;
+    flush_activities(&state);
+    println!("Stopped tracking.");
+    Ok(())
+}
+
+fn report(options: &Options) -> Result<(), String> {
+    let start = options.date()?;
+    let end = match &options.end_date {
+        Some(end) => NaiveDate::parse_from_str(end, "%Y-%m-%d").map_err(|e| e.to_string())?,
+        None => start,
+    };
+
+    let state = open_state()?;
+    let summary = {
+        let db = state.db.lock();
+        summary::range_summary(&db, start, end, GroupBy::Day)?
+    };
+
+    if start == end {
+        println!("{}", start.format("%Y-%m-%d"));
+    } else {
+        println!("{} - {}", start.format("%Y-%m-%d"), end.format("%Y-%m-%d"));
+    }
+    println!("Total: {}", format_hours_minutes(summary.total_seconds));
+
+    println!("\nApps:");
+    for app in &summary.apps {
+        println!(
+            "  {:>8}  {:>5.1}%  {}",
+            format_hours_minutes(app.total_seconds),
+            app.percentage,
+            app.process_name
+        );
+    }
+    println!("\nCategories:");
+    for category in &summary.categories {
+        println!(
+            "  {:>8}  {:>5.1}%  {}",
+            format_hours_minutes(category.total_seconds),
+            category.percentage,
+            category.category
+        );
+    }
+    Ok(())
+}
+
+fn export_csv(options: &Options) -> Result<(), String> {
+    let date = options.date()?.format("%Y-%m-%d").to_string();
+    let state = open_state()?;
+    let csv = timeline_csv(&state, &date, options.profile.as_deref())?;
+
+    match &options.output {
+        Some(path) => std::fs::write(path, csv).map_err(|e| e.to_string()),
+        None => {
+            print!("{}", csv);
+            Ok(())
+        }
+    }
+}
+
+fn sync(options: &Options) -> Result<(), String> {
+    let plugin_name = options
+        .positional
+        .first()
+        .ok_or_else(|| format!("sync requires a plugin name\n\n{}", USAGE))?;
+    let date = options.date()?.format("%Y-%m-%d").to_string();
+    let state = open_state()?;
+
+    let results = crate::runtime::block_on(sync_day_activities(&state, plugin_name, &date))?;
+    if results.is_empty() {
+        println!("Nothing to sync for {}", date);
+    }
+    let mut failed = 0;
+    for entry in &results {
+        let status = if entry.result.success { "ok" } else { "failed" };
+        if !entry.result.success {
+            failed += 1;
+        }
+        println!(
+            "{:<6}  {}  {}",
+            status, entry.ticket_id, entry.result.message
+        );
+    }
+
+    if failed > 0 {
+        return Err(format!(
+            "{} of {} tickets failed to sync",
+            failed,
+            results.len()
+        ));
+    }
+    Ok(())
+}
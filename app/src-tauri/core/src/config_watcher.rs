@@ -0,0 +1,118 @@
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+use crate::categories;
+use crate::live::LiveEvent;
+use crate::plugins::config::IntegrationsConfig;
+use crate::settings;
+use crate::{refresh_extracted_tickets, AppState};
+
+/// 保存中の書き込みが続く間は待ってから読み直す
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// integrations.toml を読み直して、連携・ルール・除外・プロジェクトなどに反映する
+///
+/// 解析できない・検証で問題が見つかった場合は何も変えずにエラーを返す
+/// （保存途中のファイルや書き間違いで設定が消えないようにする）。
+/// アップロード設定は使うたびに読み込むので、ここでは扱わない。
+/// 抽出ルールが変わったら記録済みのチケットも抽出し直す。
+pub fn reload(state: &Arc<AppState>) -> Result<(), String> {
+    let config = IntegrationsConfig::try_load()?;
+    let issues = settings::validate(&config);
+    if !issues.is_empty() {
+        return Err(settings::format_issues(&issues));
+    }
+
+    state.categorizer.set_rules(&config.categories);
+    {
+        let db = state.db.lock();
+        categories::ensure_from_rules(&db, &config.categories)?;
+    }
+    state.rule_assigner.set_rules(&config.assignment_rules);
+    state.title_normalizer.set_rules(&config.title_rules);
+    state.exclusions.set(&config.exclusions);
+    state.projects.set_projects(&config.projects);
+    state.capture.load_from_config();
+    *state.coalesce.lock() = config.coalesce;
+    *state.idle.lock() = config.idle;
+    state.plugin_manager.load_from_config()?;
+    refresh_extracted_tickets(state)
+}
+
+/// 読み直して結果を ConfigReloaded として配信する
+pub fn reload_and_notify(state: &Arc<AppState>) -> Result<(), String> {
+    let result = reload(state);
+    state.live.publish(LiveEvent::ConfigReloaded {
+        error: result.as_ref().err().cloned(),
+    });
+    result
+}
+
+/// integrations.toml の変更を監視して自動で読み直す
+///
+/// 起動時の設定に問題があればログに出す。
+/// エディタは一時ファイルからの置き換えで保存することが多いので、ディレクトリごと監視する。
+pub fn start(state: Arc<AppState>) {
+    match settings::validate_file() {
+        Ok(issues) if !issues.is_empty() => eprintln!(
+            "Problems in integrations config:\n{}",
+            settings::format_issues(&issues)
+        ),
+        Ok(_) => {}
+        Err(e) => eprintln!("{}", e),
+    }
+
+    let path = IntegrationsConfig::config_path();
+    let (Some(dir), Some(file_name)) = (path.parent(), path.file_name()) else {
+        return;
+    };
+    let file_name = file_name.to_os_string();
+    if let Err(e) = std::fs::create_dir_all(dir) {
+        eprintln!("Failed to create config directory: {}", e);
+        return;
+    }
+
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+    let watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+        let Ok(event) = result else {
+            return;
+        };
+        let changed = matches!(
+            event.kind,
+            EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+        ) && event
+            .paths
+            .iter()
+            .any(|p| p.file_name() == Some(file_name.as_os_str()));
+        if changed {
+            let _ = sender.send(());
+        }
+    });
+    let mut watcher: RecommendedWatcher = match watcher {
+        Ok(watcher) => watcher,
+        Err(e) => {
+            eprintln!("Failed to watch integrations config: {}", e);
+            return;
+        }
+    };
+    if let Err(e) = watcher.watch(dir, RecursiveMode::NonRecursive) {
+        eprintln!("Failed to watch {}: {}", dir.display(), e);
+        return;
+    }
+
+    crate::runtime::spawn(async move {
+        // 監視を止めないよう、タスクが watcher を持ち続ける
+        let _watcher = watcher;
+        while receiver.recv().await.is_some() {
+            tokio::time::sleep(DEBOUNCE).await;
+            while receiver.try_recv().is_ok() {}
+
+            match reload_and_notify(&state) {
+                Ok(()) => println!("Reloaded {}", path.display()),
+                Err(e) => eprintln!("Failed to reload integrations config: {}", e),
+            }
+        }
+    });
+}
@@ -0,0 +1,160 @@
+use rand::RngCore;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::secrets;
+
+/// データベースの暗号鍵を保存するキーリングの名前
+const KEY_SECRET: &str = "database-key";
+
+/// 平文の SQLite ファイルの先頭16バイト
+const PLAIN_HEADER: &[u8; 16] = b"SQLite format 3\0";
+
+/// データベースの暗号化の状態（フロントエンド用）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncryptionStatus {
+    pub encrypted: bool,
+    /// SQLCipher 付きでビルドされているか
+    pub supported: bool,
+}
+
+enum FileState {
+    Missing,
+    Plain,
+    Encrypted,
+}
+
+/// データベースファイルのパス
+pub fn db_path() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("timetracker")
+        .join("activities.db")
+}
+
+pub fn is_supported() -> bool {
+    cfg!(feature = "sqlcipher")
+}
+
+pub fn status(encrypted: bool) -> EncryptionStatus {
+    EncryptionStatus {
+        encrypted,
+        supported: is_supported(),
+    }
+}
+
+fn file_state(path: &Path) -> FileState {
+    let mut header = [0u8; 16];
+    match fs::File::open(path).and_then(|mut f| f.read_exact(&mut header)) {
+        Ok(()) if &header == PLAIN_HEADER => FileState::Plain,
+        Ok(()) => FileState::Encrypted,
+        // 存在しない・空のファイルは新規作成として扱う
+        Err(_) => FileState::Missing,
+    }
+}
+
+fn existing_key() -> Result<String, String> {
+    secrets::get(KEY_SECRET)?.ok_or_else(|| "Database key not found in the OS keyring".to_string())
+}
+
+fn key_or_create() -> Result<String, String> {
+    if let Some(key) = secrets::get(KEY_SECRET)? {
+        return Ok(key);
+    }
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    let key: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    secrets::set(KEY_SECRET, &key)?;
+    Ok(key)
+}
+
+/// データベースを開く
+///
+/// encrypted の指定とファイルの状態が異なる場合は、開く前に暗号化/復号したファイルに置き換える。
+/// 鍵は OS のキーリングに保存し、設定ファイルには書かない。
+pub fn open(path: &Path, encrypted: bool) -> Result<Connection, String> {
+    let key = match (encrypted, file_state(path)) {
+        (true, _) if !is_supported() => {
+            return Err(
+                "Database encryption requires a build with the sqlcipher feature".to_string(),
+            )
+        }
+        (true, FileState::Plain) => {
+            let key = key_or_create()?;
+            convert(path, None, Some(&key))?;
+            Some(key)
+        }
+        (true, FileState::Missing) => Some(key_or_create()?),
+        (true, FileState::Encrypted) => Some(existing_key()?),
+        (false, FileState::Encrypted) => {
+            if !is_supported() {
+                return Err(
+                    "The database is encrypted but this build has no sqlcipher support".to_string(),
+                );
+            }
+            convert(path, Some(&existing_key()?), None)?;
+            secrets::delete(KEY_SECRET)?;
+            None
+        }
+        (false, _) => None,
+    };
+
+    let conn = Connection::open(path).map_err(|e| e.to_string())?;
+    if let Some(key) = &key {
+        conn.pragma_update(None, "key", key)
+            .map_err(|e| e.to_string())?;
+    }
+
+    // 鍵が合わなければここで失敗する
+    conn.query_row("SELECT COUNT(*) FROM sqlite_master", [], |row| {
+        row.get::<_, i64>(0)
+    })
+    .map_err(|e| format!("Failed to open database: {}", e))?;
+
+    // UIからの読み込みとウォッチャーの書き込みが競合しないよう WAL にする
+    conn.pragma_update(None, "journal_mode", "WAL")
+        .map_err(|e| e.to_string())?;
+    conn.pragma_update(None, "synchronous", "NORMAL")
+        .map_err(|e| e.to_string())?;
+    conn.busy_timeout(Duration::from_secs(5))
+        .map_err(|e| e.to_string())?;
+
+    Ok(conn)
+}
+
+/// sqlcipher_export で別の鍵（None なら平文）のファイルに書き出して置き換える
+///
+/// 元のファイルは WAL を含めて削除し、平文のデータを残さない。
+fn convert(path: &Path, from_key: Option<&str>, to_key: Option<&str>) -> Result<(), String> {
+    let converted = path.with_extension("db.converting");
+    let _ = fs::remove_file(&converted);
+
+    {
+        let conn = Connection::open(path).map_err(|e| e.to_string())?;
+        if let Some(key) = from_key {
+            conn.pragma_update(None, "key", key)
+                .map_err(|e| e.to_string())?;
+        }
+        conn.execute(
+            "ATTACH DATABASE ?1 AS converted KEY ?2",
+            params![converted.to_string_lossy(), to_key.unwrap_or("")],
+        )
+        .map_err(|e| e.to_string())?;
+        conn.query_row("SELECT sqlcipher_export('converted')", [], |_| Ok(()))
+            .map_err(|e| format!("Failed to convert database: {}", e))?;
+        conn.execute("DETACH DATABASE converted", [])
+            .map_err(|e| e.to_string())?;
+    }
+
+    for suffix in ["-wal", "-shm"] {
+        let mut side = path.as_os_str().to_owned();
+        side.push(suffix);
+        let _ = fs::remove_file(PathBuf::from(side));
+    }
+    fs::rename(&converted, path).map_err(|e| e.to_string())?;
+    Ok(())
+}
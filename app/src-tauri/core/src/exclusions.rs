@@ -0,0 +1,137 @@
+use parking_lot::RwLock;
+use regex::Regex;
+
+use crate::plugins::config::{ExclusionConfig, IntegrationsConfig};
+
+/// 除外対象のプロセス名
+pub const KIND_PROCESS: &str = "process";
+/// 除外対象のウィンドウタイトル（正規表現）
+pub const KIND_TITLE: &str = "title";
+/// 除外対象のドメイン（サブドメインも含む）
+pub const KIND_DOMAIN: &str = "domain";
+
+#[derive(Default)]
+struct Compiled {
+    processes: Vec<String>,
+    titles: Vec<Regex>,
+    domains: Vec<String>,
+}
+
+/// 記録しないアプリ・タイトル・ドメインの判定
+pub struct ExclusionFilter {
+    compiled: RwLock<Compiled>,
+}
+
+impl ExclusionFilter {
+    pub fn new() -> Self {
+        Self {
+            compiled: RwLock::new(Compiled::default()),
+        }
+    }
+
+    /// 設定ファイルから除外リストを読み込む
+    pub fn load_from_config(&self) {
+        self.set(&IntegrationsConfig::load().exclusions);
+    }
+
+    /// 除外リストを差し替える（コンパイルできないパターンは無視）
+    pub fn set(&self, config: &ExclusionConfig) {
+        let titles = config
+            .title_patterns
+            .iter()
+            .filter_map(|pattern| match Regex::new(pattern) {
+                Ok(regex) => Some(regex),
+                Err(e) => {
+                    eprintln!("Invalid exclusion pattern {:?}: {}", pattern, e);
+                    None
+                }
+            })
+            .collect();
+
+        *self.compiled.write() = Compiled {
+            processes: config.processes.iter().map(|p| p.to_lowercase()).collect(),
+            titles,
+            domains: config
+                .domains
+                .iter()
+                .map(|d| d.trim_start_matches('.').to_lowercase())
+                .collect(),
+        };
+    }
+
+    /// 記録してはいけないアクティビティか
+    pub fn is_excluded(
+        &self,
+        process_name: &str,
+        window_title: &str,
+        domain: Option<&str>,
+    ) -> bool {
+        let compiled = self.compiled.read();
+
+        let process_name = process_name.to_lowercase();
+        if compiled.processes.contains(&process_name) {
+            return true;
+        }
+
+        if compiled.titles.iter().any(|re| re.is_match(window_title)) {
+            return true;
+        }
+
+        match domain.map(str::to_lowercase) {
+            Some(domain) => compiled
+                .domains
+                .iter()
+                .any(|d| domain == *d || domain.ends_with(&format!(".{}", d))),
+            None => false,
+        }
+    }
+}
+
+impl Default for ExclusionFilter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 除外リストに追加（既にあれば何もしない）
+pub fn add(config: &mut ExclusionConfig, kind: &str, value: &str) -> Result<(), String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Err("Exclusion value must not be empty".to_string());
+    }
+    if kind == KIND_TITLE {
+        Regex::new(value).map_err(|e| format!("Invalid pattern {:?}: {}", value, e))?;
+    }
+
+    let list = list_mut(config, kind)?;
+    if !list.iter().any(|v| v == value) {
+        list.push(value.to_string());
+    }
+    Ok(())
+}
+
+/// 除外リストから削除
+pub fn remove(config: &mut ExclusionConfig, kind: &str, value: &str) -> Result<(), String> {
+    let list = list_mut(config, kind)?;
+    let before = list.len();
+    list.retain(|v| v != value);
+    if list.len() == before {
+        return Err(format!("Exclusion not found: {} {}", kind, value));
+    }
+    Ok(())
+}
+
+fn list_mut<'a>(
+    config: &'a mut ExclusionConfig,
+    kind: &str,
+) -> Result<&'a mut Vec<String>, String> {
+    match kind {
+        KIND_PROCESS => Ok(&mut config.processes),
+        KIND_TITLE => Ok(&mut config.title_patterns),
+        KIND_DOMAIN => Ok(&mut config.domains),
+        _ => Err(format!(
+            "Invalid exclusion kind: {} (expected process, title or domain)",
+            kind
+        )),
+    }
+}
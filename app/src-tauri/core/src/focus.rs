@@ -0,0 +1,303 @@
+use chrono::{NaiveDateTime, Timelike};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// 集中とみなす既定の最短時間（分）
+pub const DEFAULT_MIN_FOCUS_MINUTES: i64 = 25;
+/// この秒数を超える途切れ（アイドル・記録なし）は集中を途切れさせる
+const MAX_FOCUS_GAP_SECONDS: i64 = 60;
+
+/// 途切れずに同じアプリ/カテゴリを使い続けた区間
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FocusSession {
+    pub start_time: String,
+    pub end_time: String,
+    pub duration_seconds: i64,
+    /// 区間内で最も長く使ったアプリ
+    pub process_name: String,
+    pub category: Option<String>,
+    pub activity_ids: Vec<i64>,
+}
+
+/// 1日の集中の統計
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FocusReport {
+    pub date: String,
+    pub min_focus_minutes: i64,
+    pub sessions: Vec<FocusSession>,
+    pub longest_seconds: i64,
+    /// 集中区間の合計（ディープワークの時間）
+    pub total_focus_seconds: i64,
+    pub tracked_seconds: i64,
+    /// 別のアプリ/カテゴリへの切り替え回数
+    pub interruptions: usize,
+    /// 記録時間1時間あたりの切り替え回数
+    pub interruptions_per_hour: f64,
+}
+
+/// 1時間ごとの切り替え回数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HourlySwitches {
+    /// 0〜23
+    pub hour: u32,
+    pub switches: usize,
+}
+
+/// 作業に割り込んだアプリ
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Interrupter {
+    pub process_name: String,
+    /// このアプリへ切り替えた回数
+    pub count: usize,
+    /// 元のアプリに戻るまでの平均秒数（戻らなかった切り替えは含めない）
+    pub average_away_seconds: f64,
+}
+
+/// 1日のアプリ切り替えの分析
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextSwitchReport {
+    pub date: String,
+    pub total_switches: usize,
+    pub hourly: Vec<HourlySwitches>,
+    /// 切り替えの多い順
+    pub interrupters: Vec<Interrupter>,
+    /// 離れたアプリに戻るまでの平均秒数
+    pub average_return_seconds: f64,
+}
+
+struct Row {
+    id: i64,
+    process_name: String,
+    category: Option<String>,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    duration_seconds: i64,
+}
+
+impl Row {
+    /// カテゴリがあればカテゴリ単位、なければアプリ単位で同じ作業とみなす
+    fn focus_key(&self) -> (&str, &str) {
+        match &self.category {
+            Some(category) => ("category", category.as_str()),
+            None => ("app", self.process_name.as_str()),
+        }
+    }
+}
+
+struct Block<'a> {
+    rows: Vec<&'a Row>,
+    end: NaiveDateTime,
+}
+
+const TIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+fn load_rows(conn: &Connection, date: &str) -> Result<Vec<Row>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, process_name, category, start_time, end_time, duration_seconds
+             FROM activities
+             WHERE start_time >= ?1 AND start_time <= ?2
+             ORDER BY start_time ASC, id ASC",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map(
+            params![format!("{}T00:00:00", date), format!("{}T23:59:59", date)],
+            |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, Option<String>>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, String>(4)?,
+                    row.get::<_, i64>(5)?,
+                ))
+            },
+        )
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .filter_map(
+            |(id, process_name, category, start, end, duration_seconds)| {
+                Some(Row {
+                    id,
+                    process_name,
+                    category,
+                    start: NaiveDateTime::parse_from_str(&start, TIME_FORMAT).ok()?,
+                    end: NaiveDateTime::parse_from_str(&end, TIME_FORMAT).ok()?,
+                    duration_seconds,
+                })
+            },
+        )
+        .collect();
+
+    Ok(rows)
+}
+
+fn session(block: &Block) -> FocusSession {
+    let mut apps: HashMap<&str, i64> = HashMap::new();
+    for row in &block.rows {
+        *apps.entry(&row.process_name).or_insert(0) += row.duration_seconds;
+    }
+    let process_name = apps
+        .iter()
+        .max_by(|a, b| a.1.cmp(b.1).then_with(|| b.0.cmp(a.0)))
+        .map(|(name, _)| name.to_string())
+        .unwrap_or_default();
+
+    let first = block.rows[0];
+    FocusSession {
+        start_time: first.start.format(TIME_FORMAT).to_string(),
+        end_time: block.end.format(TIME_FORMAT).to_string(),
+        duration_seconds: block.rows.iter().map(|r| r.duration_seconds).sum(),
+        process_name,
+        category: first.category.clone(),
+        activity_ids: block.rows.iter().map(|r| r.id).collect(),
+    }
+}
+
+/// 指定日の集中区間（同じアプリ/カテゴリを min_focus_minutes 以上切り替えずに使った区間）と統計
+pub fn sessions(
+    conn: &Connection,
+    date: &str,
+    min_focus_minutes: i64,
+) -> Result<FocusReport, String> {
+    let rows = load_rows(conn, date)?;
+    let tracked_seconds: i64 = rows.iter().map(|r| r.duration_seconds).sum();
+
+    let mut blocks: Vec<Block> = Vec::new();
+    let mut interruptions = 0;
+    for row in &rows {
+        if let Some(last) = blocks.last_mut() {
+            let same = last.rows[0].focus_key() == row.focus_key();
+            if same && (row.start - last.end).num_seconds() <= MAX_FOCUS_GAP_SECONDS {
+                last.end = last.end.max(row.end);
+                last.rows.push(row);
+                continue;
+            }
+            if !same {
+                interruptions += 1;
+            }
+        }
+        blocks.push(Block {
+            rows: vec![row],
+            end: row.end,
+        });
+    }
+
+    let sessions: Vec<FocusSession> = blocks
+        .iter()
+        .map(session)
+        .filter(|s| s.duration_seconds >= min_focus_minutes * 60)
+        .collect();
+
+    Ok(FocusReport {
+        date: date.to_string(),
+        min_focus_minutes,
+        longest_seconds: sessions
+            .iter()
+            .map(|s| s.duration_seconds)
+            .max()
+            .unwrap_or(0),
+        total_focus_seconds: sessions.iter().map(|s| s.duration_seconds).sum(),
+        sessions,
+        tracked_seconds,
+        interruptions,
+        interruptions_per_hour: if tracked_seconds > 0 {
+            interruptions as f64 / (tracked_seconds as f64 / 3600.0)
+        } else {
+            0.0
+        },
+    })
+}
+
+/// 同じアプリが続く記録をまとめた区間
+struct Run<'a> {
+    process_name: &'a str,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+}
+
+fn average(total: i64, count: usize) -> f64 {
+    if count > 0 {
+        total as f64 / count as f64
+    } else {
+        0.0
+    }
+}
+
+/// 指定日のアプリ切り替えを集計（時間帯別の回数・割り込んだアプリ・元のアプリに戻るまでの時間）
+pub fn context_switches(conn: &Connection, date: &str) -> Result<ContextSwitchReport, String> {
+    let rows = load_rows(conn, date)?;
+
+    let mut runs: Vec<Run> = Vec::new();
+    for row in &rows {
+        if let Some(last) = runs.last_mut() {
+            if last.process_name == row.process_name {
+                last.end = last.end.max(row.end);
+                continue;
+            }
+        }
+        runs.push(Run {
+            process_name: &row.process_name,
+            start: row.start,
+            end: row.end,
+        });
+    }
+
+    let mut hourly = vec![0usize; 24];
+    // プロセス名 → (切り替え回数, 戻るまでの合計秒数, 戻った回数)
+    let mut interrupters: HashMap<&str, (usize, i64, usize)> = HashMap::new();
+    let mut return_total = 0;
+    let mut return_count = 0;
+
+    for (i, pair) in runs.windows(2).enumerate() {
+        let (left, to) = (&pair[0], &pair[1]);
+        hourly[to.start.hour() as usize] += 1;
+        let entry = interrupters.entry(to.process_name).or_insert((0, 0, 0));
+        entry.0 += 1;
+
+        if let Some(back) = runs[i + 2..]
+            .iter()
+            .find(|run| run.process_name == left.process_name)
+        {
+            let away = (back.start - left.end).num_seconds().max(0);
+            entry.1 += away;
+            entry.2 += 1;
+            return_total += away;
+            return_count += 1;
+        }
+    }
+
+    let mut interrupters: Vec<Interrupter> = interrupters
+        .into_iter()
+        .map(
+            |(process_name, (count, away_total, returned))| Interrupter {
+                process_name: process_name.to_string(),
+                count,
+                average_away_seconds: average(away_total, returned),
+            },
+        )
+        .collect();
+    interrupters.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then_with(|| a.process_name.cmp(&b.process_name))
+    });
+
+    Ok(ContextSwitchReport {
+        date: date.to_string(),
+        total_switches: runs.len().saturating_sub(1),
+        hourly: hourly
+            .into_iter()
+            .enumerate()
+            .map(|(hour, switches)| HourlySwitches {
+                hour: hour as u32,
+                switches,
+            })
+            .collect(),
+        interrupters,
+        average_return_seconds: average(return_total, return_count),
+    })
+}
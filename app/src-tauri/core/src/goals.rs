@@ -0,0 +1,251 @@
+use chrono::{Duration, Local, NaiveDate};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::budgets::week_start;
+use crate::categories::UNCATEGORIZED;
+
+/// 1日あたりの目標
+pub const PERIOD_DAY: &str = "day";
+/// 1週間（月曜始まり）あたりの目標
+pub const PERIOD_WEEK: &str = "week";
+
+/// 目標下限（この時間以上）
+pub const KIND_MIN: &str = "min";
+/// 上限（この時間以下）
+pub const KIND_MAX: &str = "max";
+
+/// カテゴリ別の時間目標
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Goal {
+    pub id: i64,
+    pub category: String,
+    /// "day" | "week"
+    pub period: String,
+    /// "min" | "max"
+    pub kind: String,
+    pub hours: f64,
+    pub enabled: bool,
+    pub created_at: String,
+}
+
+/// 目標の進捗
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GoalProgress {
+    pub goal: Goal,
+    pub period_start: String,
+    pub period_end: String,
+    pub target_seconds: i64,
+    pub tracked_seconds: i64,
+    /// 目標に対する進捗（%）
+    pub progress: f64,
+    /// min: "behind" | "met" / max: "ok" | "exceeded"
+    pub status: String,
+}
+
+pub fn create_tables(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS goals (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            category TEXT NOT NULL,
+            period TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            hours REAL NOT NULL,
+            enabled INTEGER NOT NULL DEFAULT 1,
+            created_at TEXT NOT NULL
+        )",
+        [],
+    )?;
+
+    Ok(())
+}
+
+fn validate(period: &str, kind: &str, hours: f64) -> Result<(), String> {
+    if period != PERIOD_DAY && period != PERIOD_WEEK {
+        return Err(format!("Invalid period: {} (expected day or week)", period));
+    }
+    if kind != KIND_MIN && kind != KIND_MAX {
+        return Err(format!("Invalid kind: {} (expected min or max)", kind));
+    }
+    if !hours.is_finite() || hours <= 0.0 {
+        return Err(format!("Hours must be positive: {}", hours));
+    }
+    Ok(())
+}
+
+/// 目標一覧を取得
+pub fn list(conn: &Connection) -> Result<Vec<Goal>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, category, period, kind, hours, enabled, created_at
+             FROM goals ORDER BY id ASC",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let goals = stmt
+        .query_map([], |row| {
+            Ok(Goal {
+                id: row.get(0)?,
+                category: row.get(1)?,
+                period: row.get(2)?,
+                kind: row.get(3)?,
+                hours: row.get(4)?,
+                enabled: row.get(5)?,
+                created_at: row.get(6)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(goals)
+}
+
+/// 目標を作成
+pub fn create(
+    conn: &Connection,
+    category: &str,
+    period: &str,
+    kind: &str,
+    hours: f64,
+) -> Result<Goal, String> {
+    validate(period, kind, hours)?;
+
+    conn.execute(
+        "INSERT INTO goals (category, period, kind, hours, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            category,
+            period,
+            kind,
+            hours,
+            Local::now().format("%Y-%m-%dT%H:%M:%S").to_string()
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    let id = conn.last_insert_rowid();
+    list(conn)?
+        .into_iter()
+        .find(|goal| goal.id == id)
+        .ok_or_else(|| format!("Goal not found: {}", id))
+}
+
+/// 目標を更新（未指定の項目はそのまま）
+pub fn update(
+    conn: &Connection,
+    id: i64,
+    hours: Option<f64>,
+    enabled: Option<bool>,
+) -> Result<(), String> {
+    if let Some(hours) = hours {
+        if !hours.is_finite() || hours <= 0.0 {
+            return Err(format!("Hours must be positive: {}", hours));
+        }
+    }
+
+    let updated = conn
+        .execute(
+            "UPDATE goals SET hours = COALESCE(?1, hours), enabled = COALESCE(?2, enabled)
+             WHERE id = ?3",
+            params![hours, enabled, id],
+        )
+        .map_err(|e| e.to_string())?;
+
+    if updated == 0 {
+        return Err(format!("Goal not found: {}", id));
+    }
+    Ok(())
+}
+
+/// 目標を削除
+pub fn delete(conn: &Connection, id: i64) -> Result<(), String> {
+    let deleted = conn
+        .execute("DELETE FROM goals WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+
+    if deleted == 0 {
+        return Err(format!("Goal not found: {}", id));
+    }
+    Ok(())
+}
+
+/// 期間内のカテゴリ別合計（未分類は "Uncategorized"）
+fn category_totals(
+    conn: &Connection,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<HashMap<String, i64>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT COALESCE(category, ?3), SUM(duration_seconds)
+             FROM activities
+             WHERE start_time >= ?1 AND start_time <= ?2
+             GROUP BY COALESCE(category, ?3)",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let totals = stmt
+        .query_map(
+            params![
+                format!("{}T00:00:00", start.format("%Y-%m-%d")),
+                format!("{}T23:59:59", end.format("%Y-%m-%d")),
+                UNCATEGORIZED
+            ],
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)),
+        )
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(totals)
+}
+
+/// 指定日時点での有効な目標の進捗（週の目標はその日を含む週で評価）
+pub fn progress(conn: &Connection, date: NaiveDate) -> Result<Vec<GoalProgress>, String> {
+    let goals: Vec<Goal> = list(conn)?.into_iter().filter(|g| g.enabled).collect();
+    if goals.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let week_start = week_start(date);
+    let week_end = week_start + Duration::days(6);
+    let daily = category_totals(conn, date, date)?;
+    let weekly = category_totals(conn, week_start, week_end)?;
+
+    let progress = goals
+        .into_iter()
+        .map(|goal| {
+            let (start, end, totals) = if goal.period == PERIOD_WEEK {
+                (week_start, week_end, &weekly)
+            } else {
+                (date, date, &daily)
+            };
+
+            let target_seconds = (goal.hours * 3600.0).round() as i64;
+            let tracked_seconds = totals.get(&goal.category).copied().unwrap_or(0);
+            let status = match (goal.kind.as_str(), tracked_seconds >= target_seconds) {
+                (KIND_MIN, true) => "met",
+                (KIND_MIN, false) => "behind",
+                _ if tracked_seconds > target_seconds => "exceeded",
+                _ => "ok",
+            };
+
+            GoalProgress {
+                period_start: start.format("%Y-%m-%d").to_string(),
+                period_end: end.format("%Y-%m-%d").to_string(),
+                target_seconds,
+                tracked_seconds,
+                progress: if target_seconds > 0 {
+                    (tracked_seconds as f64 / target_seconds as f64) * 100.0
+                } else {
+                    0.0
+                },
+                status: status.to_string(),
+                goal,
+            }
+        })
+        .collect();
+
+    Ok(progress)
+}
@@ -0,0 +1,125 @@
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, Timelike};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use crate::plugins::config::{Productivity, ProductivityConfig};
+
+/// 曜日×時間帯の1マス
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HeatmapCell {
+    /// 0 = 月曜 〜 6 = 日曜
+    pub weekday: u32,
+    /// 0〜23
+    pub hour: u32,
+    pub tracked_seconds: i64,
+    /// productive に区分したアプリ・カテゴリの時間
+    pub productive_seconds: i64,
+}
+
+/// 曜日×時間帯の使用時間（7×24 マス、月曜0時から順）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Heatmap {
+    pub start_date: String,
+    pub end_date: String,
+    pub cells: Vec<HeatmapCell>,
+    /// 色の濃さの基準
+    pub max_tracked_seconds: i64,
+}
+
+const TIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+/// start〜end（両端を含む）の使用時間を曜日×時間帯に集計
+///
+/// 時間帯をまたぐ記録は区間の長さで按分する。
+pub fn build(
+    conn: &Connection,
+    config: &ProductivityConfig,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<Heatmap, String> {
+    if end < start {
+        return Err("end must not be before start".to_string());
+    }
+    let start_date = start.format("%Y-%m-%d").to_string();
+    let end_date = end.format("%Y-%m-%d").to_string();
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT process_name, category, start_time, end_time, duration_seconds
+             FROM activities
+             WHERE start_time >= ?1 AND start_time <= ?2",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows: Vec<(String, Option<String>, String, String, i64)> = stmt
+        .query_map(
+            params![
+                format!("{}T00:00:00", start_date),
+                format!("{}T23:59:59", end_date)
+            ],
+            |row| {
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get(3)?,
+                    row.get(4)?,
+                ))
+            },
+        )
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let mut cells: Vec<HeatmapCell> = (0..7 * 24)
+        .map(|i| HeatmapCell {
+            weekday: i / 24,
+            hour: i % 24,
+            ..HeatmapCell::default()
+        })
+        .collect();
+
+    for (process_name, category, start_time, end_time, duration_seconds) in rows {
+        let (Ok(from), Ok(to)) = (
+            NaiveDateTime::parse_from_str(&start_time, TIME_FORMAT),
+            NaiveDateTime::parse_from_str(&end_time, TIME_FORMAT),
+        ) else {
+            continue;
+        };
+        let productive =
+            config.classify(&process_name, category.as_deref()) == Productivity::Productive;
+        let span = (to - from).num_seconds();
+
+        let mut cursor = from;
+        loop {
+            let hour_start = cursor
+                .date()
+                .and_hms_opt(cursor.hour(), 0, 0)
+                .unwrap_or(cursor);
+            let next = (hour_start + Duration::hours(1)).min(to);
+            // 記録時間（アイドルを除く）を区間の長さで按分する
+            let seconds = if span > 0 {
+                duration_seconds * (next - cursor).num_seconds() / span
+            } else {
+                duration_seconds
+            };
+            let cell =
+                &mut cells[(cursor.weekday().num_days_from_monday() * 24 + cursor.hour()) as usize];
+            cell.tracked_seconds += seconds;
+            if productive {
+                cell.productive_seconds += seconds;
+            }
+            if next >= to {
+                break;
+            }
+            cursor = next;
+        }
+    }
+
+    Ok(Heatmap {
+        start_date,
+        end_date,
+        max_tracked_seconds: cells.iter().map(|c| c.tracked_seconds).max().unwrap_or(0),
+        cells,
+    })
+}
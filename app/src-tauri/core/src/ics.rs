@@ -0,0 +1,175 @@
+use chrono::{NaiveDateTime, Utc};
+
+/// iCalendar に変換するアクティビティ
+pub struct IcsActivity {
+    pub process_name: String,
+    pub window_title: String,
+    pub category: Option<String>,
+    pub start_time: String,
+    pub end_time: String,
+    pub duration_seconds: i64,
+}
+
+/// この秒数以内の途切れは同じブロックとして扱う
+const MAX_BLOCK_GAP_SECONDS: i64 = 120;
+/// 説明欄に載せるウィンドウタイトルの数
+const MAX_TITLES: usize = 5;
+
+/// 連続した同じアプリ/カテゴリのまとまり
+struct Block {
+    summary: String,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    duration_seconds: i64,
+    /// (タイトル, 秒数)
+    titles: Vec<(String, i64)>,
+}
+
+fn parse_time(s: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S").ok()
+}
+
+/// 連続するアクティビティをブロックにまとめる（by_category なら同じカテゴリ、そうでなければ同じアプリ）
+fn group_blocks(activities: &[IcsActivity], by_category: bool) -> Vec<Block> {
+    let mut blocks: Vec<Block> = Vec::new();
+
+    for activity in activities {
+        let (Some(start), Some(end)) = (
+            parse_time(&activity.start_time),
+            parse_time(&activity.end_time),
+        ) else {
+            continue;
+        };
+        let summary = if by_category {
+            activity
+                .category
+                .clone()
+                .unwrap_or_else(|| crate::categories::UNCATEGORIZED.to_string())
+        } else {
+            activity.process_name.clone()
+        };
+
+        let extend = blocks.last().is_some_and(|block| {
+            block.summary == summary && (start - block.end).num_seconds() <= MAX_BLOCK_GAP_SECONDS
+        });
+
+        if extend {
+            let block = blocks.last_mut().expect("checked above");
+            block.end = block.end.max(end);
+            block.duration_seconds += activity.duration_seconds;
+            match block
+                .titles
+                .iter_mut()
+                .find(|(title, _)| *title == activity.window_title)
+            {
+                Some((_, seconds)) => *seconds += activity.duration_seconds,
+                None => block
+                    .titles
+                    .push((activity.window_title.clone(), activity.duration_seconds)),
+            }
+        } else {
+            blocks.push(Block {
+                summary,
+                start,
+                end,
+                duration_seconds: activity.duration_seconds,
+                titles: vec![(activity.window_title.clone(), activity.duration_seconds)],
+            });
+        }
+    }
+
+    blocks
+}
+
+/// TEXT 値のエスケープ（RFC 5545 3.3.11）
+fn escape_text(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// 75オクテットを超える行を折り返す（RFC 5545 3.1）
+fn push_line(out: &mut String, line: &str) {
+    let mut width = 0;
+    for c in line.chars() {
+        let len = c.len_utf8();
+        if width + len > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += len;
+    }
+    out.push_str("\r\n");
+}
+
+fn format_minutes(seconds: i64) -> String {
+    let minutes = seconds / 60;
+    if minutes >= 60 {
+        format!("{}h {}m", minutes / 60, minutes % 60)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+/// 1日分のアクティビティを VEVENT のまとまりとして iCalendar に変換
+pub fn build_calendar(date: &str, activities: &[IcsActivity], by_category: bool) -> String {
+    let stamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let mut out = String::new();
+
+    push_line(&mut out, "BEGIN:VCALENDAR");
+    push_line(&mut out, "VERSION:2.0");
+    push_line(&mut out, "PRODID:-//timetracker//activities//EN");
+    push_line(&mut out, "CALSCALE:GREGORIAN");
+
+    for (index, mut block) in group_blocks(activities, by_category)
+        .into_iter()
+        .enumerate()
+    {
+        block
+            .titles
+            .sort_by_key(|(_, seconds)| std::cmp::Reverse(*seconds));
+        let description = block
+            .titles
+            .iter()
+            .take(MAX_TITLES)
+            .map(|(title, seconds)| format!("{} ({})", title, format_minutes(*seconds)))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        push_line(&mut out, "BEGIN:VEVENT");
+        push_line(&mut out, &format!("UID:{}-{}@timetracker", date, index));
+        push_line(&mut out, &format!("DTSTAMP:{}", stamp));
+        // タイムゾーンなし（フローティング時間）＝記録したローカル時刻のまま表示される
+        push_line(
+            &mut out,
+            &format!("DTSTART:{}", block.start.format("%Y%m%dT%H%M%S")),
+        );
+        push_line(
+            &mut out,
+            &format!("DTEND:{}", block.end.format("%Y%m%dT%H%M%S")),
+        );
+        push_line(
+            &mut out,
+            &format!(
+                "SUMMARY:{}",
+                escape_text(&format!(
+                    "{} ({})",
+                    block.summary,
+                    format_minutes(block.duration_seconds)
+                ))
+            ),
+        );
+        push_line(
+            &mut out,
+            &format!("DESCRIPTION:{}", escape_text(&description)),
+        );
+        push_line(&mut out, "TRANSP:TRANSPARENT");
+        push_line(&mut out, "END:VEVENT");
+    }
+
+    push_line(&mut out, "END:VCALENDAR");
+    out
+}
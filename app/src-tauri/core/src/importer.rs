@@ -0,0 +1,341 @@
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+use crate::assignments;
+use crate::transfer::{ExportBundle, ExportedActivity, EXPORT_VERSION};
+
+/// ActivityWatch のバケットのエクスポート（JSON）
+pub const FORMAT_ACTIVITYWATCH: &str = "activitywatch";
+/// ManicTime のタイムラインのエクスポート（CSV）
+pub const FORMAT_MANICTIME: &str = "manictime";
+/// Toggl Track の詳細レポートのエクスポート（CSV）
+pub const FORMAT_TOGGL: &str = "toggl";
+
+/// CSV の列名の指定（未指定の項目は形式ごとの既定の列名を使う）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CsvMapping {
+    pub process_name: Option<String>,
+    pub window_title: Option<String>,
+    pub start: Option<String>,
+    pub end: Option<String>,
+    pub duration: Option<String>,
+    pub project: Option<String>,
+}
+
+const TIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+/// CSV の日時として受け付ける形式
+const DATE_TIME_FORMATS: &[&str] = &[
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%dT%H:%M:%S",
+    "%Y-%m-%d %H:%M",
+    "%Y/%m/%d %H:%M:%S",
+    "%m/%d/%Y %I:%M:%S %p",
+    "%m/%d/%Y %H:%M:%S",
+    "%d.%m.%Y %H:%M:%S",
+];
+
+/// 他のタイムトラッカーのエクスポートを取り込める形に変換する
+///
+/// 取り込みは transfer::import と同じく、同じ process_name・window_title・start_time の記録をスキップする。
+pub fn parse(
+    format: &str,
+    content: &str,
+    mapping: Option<&CsvMapping>,
+) -> Result<ExportBundle, String> {
+    let activities = match format {
+        FORMAT_ACTIVITYWATCH => parse_activitywatch(content)?,
+        FORMAT_MANICTIME => parse_csv_activities(content, &manictime_mapping(mapping), None)?,
+        FORMAT_TOGGL => parse_toggl(content, mapping)?,
+        _ => return Err(format!("Unknown import format: {}", format)),
+    };
+
+    Ok(ExportBundle {
+        version: EXPORT_VERSION,
+        exported_at: Local::now().format(TIME_FORMAT).to_string(),
+        categories: Vec::new(),
+        activities,
+    })
+}
+
+/// ActivityWatch のエクスポート（全バケットの {"buckets": {...}} または1バケット）
+///
+/// ウィンドウのバケット（currentwindow）のみ取り込む。AFK・Web のバケットはウィンドウの記録と重なるので使わない。
+fn parse_activitywatch(content: &str) -> Result<Vec<ExportedActivity>, String> {
+    let root: Value = serde_json::from_str(content).map_err(|e| e.to_string())?;
+    let buckets: Vec<&Value> = match root.get("buckets").and_then(Value::as_object) {
+        Some(buckets) => buckets.values().collect(),
+        None => vec![&root],
+    };
+
+    let mut activities = Vec::new();
+    for bucket in buckets {
+        if bucket.get("type").and_then(Value::as_str) != Some("currentwindow") {
+            continue;
+        }
+        let Some(events) = bucket.get("events").and_then(Value::as_array) else {
+            continue;
+        };
+        for event in events {
+            let Some(timestamp) = event.get("timestamp").and_then(Value::as_str) else {
+                continue;
+            };
+            let Ok(start) = DateTime::parse_from_rfc3339(timestamp) else {
+                continue;
+            };
+            let duration = event.get("duration").and_then(Value::as_f64).unwrap_or(0.0);
+            let duration_seconds = duration.round() as i64;
+            if duration_seconds < 1 {
+                continue;
+            }
+            let data = event.get("data");
+            let field = |name: &str| {
+                data.and_then(|d| d.get(name))
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_string()
+            };
+            let process_name = field("app");
+            if process_name.is_empty() {
+                continue;
+            }
+
+            let start = start.with_timezone(&Local).naive_local();
+            let end = start + chrono::Duration::seconds(duration_seconds);
+            activities.push(activity(
+                process_name,
+                field("title"),
+                start,
+                end,
+                duration_seconds,
+                None,
+            ));
+        }
+    }
+    Ok(activities)
+}
+
+fn manictime_mapping(mapping: Option<&CsvMapping>) -> CsvMapping {
+    let mapping = mapping.cloned().unwrap_or_default();
+    CsvMapping {
+        process_name: Some(
+            mapping
+                .process_name
+                .unwrap_or_else(|| "Process".to_string()),
+        ),
+        window_title: Some(mapping.window_title.unwrap_or_else(|| "Name".to_string())),
+        start: Some(mapping.start.unwrap_or_else(|| "Start".to_string())),
+        end: Some(mapping.end.unwrap_or_else(|| "End".to_string())),
+        duration: Some(mapping.duration.unwrap_or_else(|| "Duration".to_string())),
+        project: mapping.project,
+    }
+}
+
+/// Toggl の詳細レポート（開始・終了が日付と時刻の列に分かれている）
+fn parse_toggl(
+    content: &str,
+    mapping: Option<&CsvMapping>,
+) -> Result<Vec<ExportedActivity>, String> {
+    let mapping = mapping.cloned().unwrap_or_default();
+    let mapping = CsvMapping {
+        process_name: mapping.process_name,
+        window_title: Some(
+            mapping
+                .window_title
+                .unwrap_or_else(|| "Description".to_string()),
+        ),
+        start: mapping.start,
+        end: mapping.end,
+        duration: Some(mapping.duration.unwrap_or_else(|| "Duration".to_string())),
+        project: Some(mapping.project.unwrap_or_else(|| "Project".to_string())),
+    };
+    // 時間の記録なので、アプリ名の列がなければ "Toggl" として取り込む
+    parse_csv_activities(content, &mapping, Some("Toggl"))
+}
+
+fn parse_csv_activities(
+    content: &str,
+    mapping: &CsvMapping,
+    default_process: Option<&str>,
+) -> Result<Vec<ExportedActivity>, String> {
+    let mut rows = parse_csv(content).into_iter();
+    let header = rows.next().ok_or("CSV is empty")?;
+    let columns: HashMap<&str, usize> = header
+        .iter()
+        .enumerate()
+        .map(|(i, name)| (name.trim().trim_start_matches('\u{feff}'), i))
+        .collect();
+    let column =
+        |name: &Option<String>| name.as_deref().and_then(|name| columns.get(name).copied());
+
+    let process_column = column(&mapping.process_name);
+    if process_column.is_none() && default_process.is_none() {
+        return Err(format!(
+            "CSV has no process column: {}",
+            mapping.process_name.as_deref().unwrap_or_default()
+        ));
+    }
+    let title_column = column(&mapping.window_title);
+    let duration_column = column(&mapping.duration);
+    let project_column = column(&mapping.project);
+    // 列名の指定がなければ Toggl の "Start date" + "Start time" の形式
+    let start_columns = match column(&mapping.start) {
+        Some(i) => (i, None),
+        None => (
+            columns
+                .get("Start date")
+                .copied()
+                .ok_or("CSV has no start column")?,
+            columns.get("Start time").copied(),
+        ),
+    };
+    let end_columns = match column(&mapping.end) {
+        Some(i) => Some((i, None)),
+        None => columns
+            .get("End date")
+            .map(|&i| (i, columns.get("End time").copied())),
+    };
+
+    let mut activities = Vec::new();
+    for (index, row) in rows.enumerate() {
+        let cell = |i: Option<usize>| i.and_then(|i| row.get(i)).map(|s| s.trim());
+        let date_time = |(date, time): (usize, Option<usize>)| {
+            let value = match cell(time) {
+                Some(time) => format!("{} {}", cell(Some(date)).unwrap_or_default(), time),
+                None => cell(Some(date)).unwrap_or_default().to_string(),
+            };
+            parse_date_time(&value)
+        };
+
+        let Some(start) = date_time(start_columns) else {
+            if row.iter().all(|c| c.trim().is_empty()) {
+                continue;
+            }
+            return Err(format!("Invalid start time on line {}", index + 2));
+        };
+        let duration = cell(duration_column).and_then(parse_duration);
+        let end = end_columns.and_then(date_time);
+        let (end, duration_seconds) = match (end, duration) {
+            (Some(end), Some(duration)) => (end, duration),
+            (Some(end), None) => (end, (end - start).num_seconds()),
+            (None, Some(duration)) => (start + chrono::Duration::seconds(duration), duration),
+            (None, None) => return Err(format!("No end time or duration on line {}", index + 2)),
+        };
+        if duration_seconds < 1 {
+            continue;
+        }
+
+        let process_name = cell(process_column)
+            .filter(|s| !s.is_empty())
+            .or(default_process)
+            .unwrap_or_default()
+            .to_string();
+        if process_name.is_empty() {
+            continue;
+        }
+        let project = cell(project_column)
+            .filter(|s| !s.is_empty())
+            .map(str::to_string);
+
+        activities.push(activity(
+            process_name,
+            cell(title_column).unwrap_or_default().to_string(),
+            start,
+            end,
+            duration_seconds,
+            project,
+        ));
+    }
+    Ok(activities)
+}
+
+fn activity(
+    process_name: String,
+    window_title: String,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    duration_seconds: i64,
+    project: Option<String>,
+) -> ExportedActivity {
+    ExportedActivity {
+        process_name,
+        window_title,
+        domain: None,
+        start_time: start.format(TIME_FORMAT).to_string(),
+        end_time: end.format(TIME_FORMAT).to_string(),
+        duration_seconds,
+        category: None,
+        plugin: None,
+        ticket_id: None,
+        assignment_source: project
+            .as_ref()
+            .map(|_| assignments::SOURCE_IMPORT.to_string()),
+        project,
+        note: None,
+    }
+}
+
+fn parse_date_time(value: &str) -> Option<NaiveDateTime> {
+    if let Ok(t) = DateTime::parse_from_rfc3339(value) {
+        return Some(t.with_timezone(&Local).naive_local());
+    }
+    DATE_TIME_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .or_else(|| {
+            // 日付のみ（時刻なし）
+            NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .ok()
+                .map(|date| date.and_time(NaiveTime::MIN))
+        })
+}
+
+/// "1:02:03" / "02:03" / 秒数
+fn parse_duration(value: &str) -> Option<i64> {
+    if let Ok(seconds) = value.parse::<f64>() {
+        return Some(seconds.round() as i64);
+    }
+    let parts: Vec<i64> = value
+        .split(':')
+        .map(|p| p.trim().parse().ok())
+        .collect::<Option<_>>()?;
+    match parts.as_slice() {
+        [h, m, s] => Some(h * 3600 + m * 60 + s),
+        [m, s] => Some(m * 60 + s),
+        _ => None,
+    }
+}
+
+/// RFC 4180 の CSV（ダブルクォート内のカンマ・改行・"" を扱う）
+fn parse_csv(content: &str) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = content.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, quoted) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            ('"', true) => quoted = false,
+            ('"', false) if field.is_empty() => quoted = true,
+            (',', false) => row.push(std::mem::take(&mut field)),
+            ('\r', false) => {}
+            ('\n', false) => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            (c, _) => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    rows
+}
@@ -0,0 +1,209 @@
+use parking_lot::Mutex;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// 前回取り出してからのキー入力の回数（どのキーかは記録しない）
+static KEYSTROKES: AtomicU64 = AtomicU64::new(0);
+/// 前回取り出してからのクリックの回数
+static CLICKS: AtomicU64 = AtomicU64::new(0);
+/// 計測中ならフックを登録したスレッドのID
+static HOOK_THREAD: Mutex<Option<u32>> = Mutex::new(None);
+
+/// 1アクティビティ分の入力回数
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InputCounts {
+    pub keystrokes: u64,
+    pub clicks: u64,
+}
+
+/// アプリごとの入力の多さ（能動的な作業か、見ているだけかの目安）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InputIntensity {
+    pub process_name: String,
+    /// 計測中に記録された時間の合計
+    pub total_seconds: i64,
+    pub keystrokes: i64,
+    pub clicks: i64,
+    /// 1分あたりのキー入力とクリックの合計
+    pub inputs_per_minute: f64,
+}
+
+/// 計測の設定と実際の状態
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InputMeterStatus {
+    pub enabled: bool,
+    pub running: bool,
+}
+
+pub fn status(enabled: bool) -> InputMeterStatus {
+    InputMeterStatus {
+        enabled,
+        running: is_running(),
+    }
+}
+
+pub fn is_running() -> bool {
+    HOOK_THREAD.lock().is_some()
+}
+
+/// 計測中なら前回からの回数を取り出して 0 に戻す（停止中は None）
+pub fn take_counts() -> Option<InputCounts> {
+    if !is_running() {
+        return None;
+    }
+    Some(InputCounts {
+        keystrokes: KEYSTROKES.swap(0, Ordering::Relaxed),
+        clicks: CLICKS.swap(0, Ordering::Relaxed),
+    })
+}
+
+/// 保存しなかった記録の回数を戻して、次に取り出す回数に含める
+pub fn put_back(counts: InputCounts) {
+    KEYSTROKES.fetch_add(counts.keystrokes, Ordering::Relaxed);
+    CLICKS.fetch_add(counts.clicks, Ordering::Relaxed);
+}
+
+/// 計測を始める（既に計測中なら何もしない）
+#[cfg(target_os = "windows")]
+pub fn start() -> Result<(), String> {
+    use std::sync::mpsc;
+    use windows::Win32::Foundation::{HINSTANCE, HWND, LPARAM, LRESULT, WPARAM};
+    use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+    use windows::Win32::System::Threading::GetCurrentThreadId;
+    use windows::Win32::UI::WindowsAndMessaging::{
+        CallNextHookEx, DispatchMessageW, GetMessageW, SetWindowsHookExW, TranslateMessage,
+        UnhookWindowsHookEx, HHOOK, MSG, WH_KEYBOARD_LL, WH_MOUSE_LL, WM_KEYDOWN, WM_LBUTTONDOWN,
+        WM_MBUTTONDOWN, WM_RBUTTONDOWN, WM_SYSKEYDOWN,
+    };
+
+    // 回数を数えるだけで、キーの内容や座標は参照しない
+    unsafe extern "system" fn keyboard_proc(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+        if code >= 0 && matches!(wparam.0 as u32, WM_KEYDOWN | WM_SYSKEYDOWN) {
+            KEYSTROKES.fetch_add(1, Ordering::Relaxed);
+        }
+        CallNextHookEx(HHOOK::default(), code, wparam, lparam)
+    }
+
+    unsafe extern "system" fn mouse_proc(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+        if code >= 0
+            && matches!(
+                wparam.0 as u32,
+                WM_LBUTTONDOWN | WM_RBUTTONDOWN | WM_MBUTTONDOWN
+            )
+        {
+            CLICKS.fetch_add(1, Ordering::Relaxed);
+        }
+        CallNextHookEx(HHOOK::default(), code, wparam, lparam)
+    }
+
+    let mut running = HOOK_THREAD.lock();
+    if running.is_some() {
+        return Ok(());
+    }
+
+    // 低レベルフックは登録したスレッドのメッセージループで呼ばれる
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || unsafe {
+        let hooks = GetModuleHandleW(None).and_then(|module| {
+            let module = HINSTANCE::from(module);
+            let keyboard = SetWindowsHookExW(WH_KEYBOARD_LL, Some(keyboard_proc), module, 0)?;
+            match SetWindowsHookExW(WH_MOUSE_LL, Some(mouse_proc), module, 0) {
+                Ok(mouse) => Ok((keyboard, mouse)),
+                Err(e) => {
+                    let _ = UnhookWindowsHookEx(keyboard);
+                    Err(e)
+                }
+            }
+        });
+        let (keyboard, mouse) = match hooks {
+            Ok(hooks) => hooks,
+            Err(e) => {
+                let _ = tx.send(Err(e.to_string()));
+                return;
+            }
+        };
+        let _ = tx.send(Ok(GetCurrentThreadId()));
+
+        // stop() の WM_QUIT で抜ける
+        let mut msg = MSG::default();
+        while GetMessageW(&mut msg, HWND::default(), 0, 0).as_bool() {
+            let _ = TranslateMessage(&msg);
+            DispatchMessageW(&msg);
+        }
+        let _ = UnhookWindowsHookEx(keyboard);
+        let _ = UnhookWindowsHookEx(mouse);
+    });
+
+    let thread_id = rx.recv().map_err(|e| e.to_string())??;
+    KEYSTROKES.store(0, Ordering::Relaxed);
+    CLICKS.store(0, Ordering::Relaxed);
+    *running = Some(thread_id);
+    Ok(())
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn start() -> Result<(), String> {
+    Err("Input metering is only supported on Windows".to_string())
+}
+
+/// 計測を止めてフックを外す
+pub fn stop() {
+    let Some(_thread_id) = HOOK_THREAD.lock().take() else {
+        return;
+    };
+
+    #[cfg(target_os = "windows")]
+    unsafe {
+        use windows::Win32::Foundation::{LPARAM, WPARAM};
+        use windows::Win32::UI::WindowsAndMessaging::{PostThreadMessageW, WM_QUIT};
+
+        let _ = PostThreadMessageW(_thread_id, WM_QUIT, WPARAM(0), LPARAM(0));
+    }
+}
+
+/// 期間内のアプリごとの入力の多さ（入力回数が記録されたアクティビティのみ）
+pub fn intensity(
+    conn: &Connection,
+    start_date: &str,
+    end_date: &str,
+) -> Result<Vec<InputIntensity>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT process_name, SUM(duration_seconds), SUM(keystrokes), SUM(clicks)
+             FROM activities
+             WHERE start_time >= ?1 AND start_time <= ?2 AND keystrokes IS NOT NULL
+             GROUP BY process_name
+             ORDER BY SUM(duration_seconds) DESC",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map(
+            params![
+                format!("{}T00:00:00", start_date),
+                format!("{}T23:59:59", end_date)
+            ],
+            |row| {
+                let total_seconds: i64 = row.get(1)?;
+                let keystrokes: i64 = row.get(2)?;
+                let clicks: i64 = row.get(3)?;
+                Ok(InputIntensity {
+                    process_name: row.get(0)?,
+                    total_seconds,
+                    keystrokes,
+                    clicks,
+                    inputs_per_minute: if total_seconds > 0 {
+                        (keystrokes + clicks) as f64 * 60.0 / total_seconds as f64
+                    } else {
+                        0.0
+                    },
+                })
+            },
+        )
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(rows)
+}
@@ -0,0 +1,33 @@
+use std::fs::{self, File, OpenOptions, TryLockError};
+
+use crate::database;
+
+/// 記録中のプロセスが持つロック（GUI と timetracker-cli watch が同時に記録しないようにする）
+///
+/// プロセスが終了すると OS が解放するので、異常終了しても残らない。
+pub struct RecorderLock {
+    _file: File,
+}
+
+/// ロックを取る（別のプロセスが記録中なら Err）
+pub fn acquire() -> Result<RecorderLock, String> {
+    let path = database::db_path().with_file_name("recorder.lock");
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+
+    match file.try_lock() {
+        Ok(()) => Ok(RecorderLock { _file: file }),
+        Err(TryLockError::WouldBlock) => Err(
+            "Another TimeTracker instance is already recording (the GUI or timetracker-cli watch)"
+                .to_string(),
+        ),
+        Err(TryLockError::Error(e)) => Err(format!("Failed to lock {}: {}", path.display(), e)),
+    }
+}
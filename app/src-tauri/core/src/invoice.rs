@@ -0,0 +1,187 @@
+use chrono::Local;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use crate::plugins::config::{BillingConfig, ProjectConfig};
+use crate::{escape_csv_field, escape_html};
+
+/// 請求書のプロジェクトごとの行
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InvoiceLine {
+    pub project: String,
+    /// 記録された作業時間（秒）
+    pub tracked_seconds: i64,
+    /// 請求する時間（round_up_minutes 単位に切り上げ）
+    pub billable_hours: f64,
+    pub hourly_rate: f64,
+    pub amount: f64,
+}
+
+/// クライアント単位の請求レポート
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InvoiceReport {
+    pub client: String,
+    pub start_date: String,
+    pub end_date: String,
+    pub currency: String,
+    pub lines: Vec<InvoiceLine>,
+    pub total_hours: f64,
+    pub total_amount: f64,
+    pub generated_at: String,
+}
+
+/// 請求する時間を単位時間に切り上げる（0 なら切り上げない）
+fn billable_hours(seconds: i64, round_up_minutes: u32) -> f64 {
+    if round_up_minutes == 0 {
+        return seconds as f64 / 3600.0;
+    }
+    let unit = round_up_minutes as i64 * 60;
+    let units = seconds / unit + i64::from(seconds % unit != 0);
+    (units * unit) as f64 / 3600.0
+}
+
+fn round_cents(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+/// クライアントのプロジェクトの期間内の作業時間と請求額を集計
+///
+/// 時間単価が未設定のプロジェクトは請求対象外として含めない。
+pub fn generate(
+    conn: &Connection,
+    projects: &[ProjectConfig],
+    billing: &BillingConfig,
+    client: &str,
+    start_date: &str,
+    end_date: &str,
+) -> Result<InvoiceReport, String> {
+    let billable: Vec<(&ProjectConfig, f64)> = projects
+        .iter()
+        .filter(|p| p.client.as_deref() == Some(client))
+        .filter_map(|p| p.hourly_rate.map(|rate| (p, rate)))
+        .collect();
+    if billable.is_empty() {
+        return Err(format!("No billable projects for client: {}", client));
+    }
+
+    let range_start = format!("{}T00:00:00", start_date);
+    let range_end = format!("{}T23:59:59", end_date);
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT COALESCE(SUM(a.duration_seconds), 0)
+             FROM activities a
+             JOIN ticket_assignments t ON t.activity_id = a.id
+             WHERE a.start_time >= ?1 AND a.start_time <= ?2 AND t.project = ?3",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let mut lines = Vec::new();
+    for (project, rate) in billable {
+        let tracked_seconds: i64 = stmt
+            .query_row(params![range_start, range_end, project.name], |row| {
+                row.get(0)
+            })
+            .map_err(|e| e.to_string())?;
+        if tracked_seconds == 0 {
+            continue;
+        }
+
+        let hours = billable_hours(tracked_seconds, billing.round_up_minutes);
+        lines.push(InvoiceLine {
+            project: project.name.clone(),
+            tracked_seconds,
+            billable_hours: hours,
+            hourly_rate: rate,
+            amount: round_cents(hours * rate),
+        });
+    }
+
+    Ok(InvoiceReport {
+        client: client.to_string(),
+        start_date: start_date.to_string(),
+        end_date: end_date.to_string(),
+        currency: billing.currency.clone(),
+        total_hours: lines.iter().map(|l| l.billable_hours).sum(),
+        total_amount: round_cents(lines.iter().map(|l| l.amount).sum()),
+        lines,
+        generated_at: Local::now().format("%Y-%m-%dT%H:%M:%S").to_string(),
+    })
+}
+
+/// CSV 形式（最終行に合計）
+pub fn to_csv(report: &InvoiceReport) -> String {
+    let mut csv = String::new();
+    csv.push_str("project,billable_hours,hourly_rate,amount,currency\n");
+    for line in &report.lines {
+        csv.push_str(&format!(
+            "{},{:.2},{:.2},{:.2},{}\n",
+            escape_csv_field(&line.project),
+            line.billable_hours,
+            line.hourly_rate,
+            line.amount,
+            escape_csv_field(&report.currency),
+        ));
+    }
+    csv.push_str(&format!(
+        "Total,{:.2},,{:.2},{}\n",
+        report.total_hours,
+        report.total_amount,
+        escape_csv_field(&report.currency),
+    ));
+    csv
+}
+
+/// 単体で開ける HTML 形式
+pub fn to_html(report: &InvoiceReport) -> String {
+    let rows: String = report
+        .lines
+        .iter()
+        .map(|line| {
+            format!(
+                "<tr><td>{}</td><td class=\"num\">{:.2}</td><td class=\"num\">{:.2}</td><td class=\"num\">{:.2}</td></tr>\n",
+                escape_html(&line.project),
+                line.billable_hours,
+                line.hourly_rate,
+                line.amount
+            )
+        })
+        .collect();
+
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>Invoice - {client}</title>
+<style>
+body {{ font-family: sans-serif; margin: 2rem; color: #222; }}
+table {{ border-collapse: collapse; width: 100%; }}
+th, td {{ border-bottom: 1px solid #ddd; padding: 0.5rem; text-align: left; }}
+.num {{ text-align: right; }}
+tfoot td {{ font-weight: bold; }}
+</style>
+</head>
+<body>
+<h1>Invoice: {client}</h1>
+<p>Period: {start} - {end}</p>
+<table>
+<thead><tr><th>Project</th><th class="num">Hours</th><th class="num">Rate ({currency})</th><th class="num">Amount ({currency})</th></tr></thead>
+<tbody>
+{rows}</tbody>
+<tfoot><tr><td>Total</td><td class="num">{hours:.2}</td><td></td><td class="num">{amount:.2}</td></tr></tfoot>
+</table>
+<p>Generated at {generated_at}</p>
+</body>
+</html>
+"#,
+        client = escape_html(&report.client),
+        start = escape_html(&report.start_date),
+        end = escape_html(&report.end_date),
+        currency = escape_html(&report.currency),
+        rows = rows,
+        hours = report.total_hours,
+        amount = report.total_amount,
+        generated_at = escape_html(&report.generated_at),
+    )
+}
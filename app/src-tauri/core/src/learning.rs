@@ -0,0 +1,130 @@
+use regex::Regex;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::assignments::SOURCE_MANUAL;
+use crate::plugins::config::AssignmentRule;
+
+/// 手動割り当てから生成したルール候補（確認後に設定へ保存する）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleCandidate {
+    pub rule: AssignmentRule,
+    /// 候補の根拠となった手動割り当ての件数
+    pub match_count: usize,
+    /// 根拠となったウィンドウタイトルの例
+    pub sample_titles: Vec<String>,
+}
+
+/// 候補生成に使う共通トークンの最小文字数
+const MIN_TOKEN_CHARS: usize = 3;
+
+fn tokens(title: &str) -> Vec<String> {
+    title
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|t| t.chars().count() >= MIN_TOKEN_CHARS)
+        .map(|t| t.to_lowercase())
+        .collect()
+}
+
+/// 全タイトルに共通するトークンから正規表現を組み立てる（最初のタイトルの出現順）
+fn common_pattern(titles: &[String]) -> Option<String> {
+    let first = titles.first()?;
+    let others: Vec<Vec<String>> = titles[1..].iter().map(|t| tokens(t)).collect();
+
+    let mut common: Vec<String> = Vec::new();
+    for token in tokens(first) {
+        if !common.contains(&token) && others.iter().all(|o| o.contains(&token)) {
+            common.push(token);
+        }
+    }
+
+    if common.is_empty() {
+        return None;
+    }
+
+    let body = common
+        .iter()
+        .map(|t| regex::escape(t))
+        .collect::<Vec<_>>()
+        .join(".*");
+    Some(format!("(?i){}", body))
+}
+
+/// 同じチケット/プロジェクトへの手動割り当てが min_occurrences 件以上あるものからルール候補を生成
+///
+/// 他のチケット/プロジェクトに割り当てたタイトルにもマッチする候補や、既存ルールと同じ候補は除外する。
+pub fn rule_candidates(
+    conn: &Connection,
+    existing: &[AssignmentRule],
+    min_occurrences: usize,
+) -> Result<Vec<RuleCandidate>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT a.window_title, t.plugin, t.ticket_id, t.project
+             FROM ticket_assignments t
+             JOIN activities a ON a.id = t.activity_id
+             WHERE t.source = ?1",
+        )
+        .map_err(|e| e.to_string())?;
+
+    type Target = (Option<String>, Option<String>, Option<String>);
+    let rows: Vec<(String, Target)> = stmt
+        .query_map([SOURCE_MANUAL], |row| {
+            Ok((row.get(0)?, (row.get(1)?, row.get(2)?, row.get(3)?)))
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let mut groups: HashMap<Target, Vec<String>> = HashMap::new();
+    for (title, target) in &rows {
+        let titles = groups.entry(target.clone()).or_default();
+        if !titles.contains(title) {
+            titles.push(title.clone());
+        }
+    }
+
+    let mut candidates = Vec::new();
+    for (target, titles) in groups {
+        if titles.len() < min_occurrences {
+            continue;
+        }
+
+        let Some(pattern) = common_pattern(&titles) else {
+            continue;
+        };
+        let Ok(regex) = Regex::new(&pattern) else {
+            continue;
+        };
+
+        // 他の割り当て先のタイトルにマッチするなら曖昧すぎる
+        let conflicts = rows
+            .iter()
+            .any(|(title, other)| other != &target && regex.is_match(title));
+        if conflicts {
+            continue;
+        }
+
+        let (plugin, ticket_id, project) = target;
+        let rule = AssignmentRule {
+            pattern,
+            source: "window_title".to_string(),
+            plugin,
+            ticket_id,
+            project,
+        };
+        if existing.contains(&rule) {
+            continue;
+        }
+
+        candidates.push(RuleCandidate {
+            rule,
+            match_count: titles.len(),
+            sample_titles: titles.into_iter().take(5).collect(),
+        });
+    }
+
+    candidates.sort_by_key(|candidate| std::cmp::Reverse(candidate.match_count));
+    Ok(candidates)
+}
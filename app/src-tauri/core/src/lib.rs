@@ -0,0 +1,2522 @@
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime};
+use parking_lot::Mutex;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub mod api_auth;
+pub mod assignments;
+pub mod browser;
+pub mod budgets;
+pub mod capture;
+pub mod categories;
+pub mod checkpoint;
+pub mod cli;
+pub mod config_watcher;
+pub mod database;
+pub mod exclusions;
+pub mod focus;
+pub mod goals;
+pub mod heatmap;
+pub mod ics;
+pub mod importer;
+pub mod input_meter;
+pub mod instance;
+pub mod invoice;
+pub mod learning;
+pub mod live;
+pub mod maintenance;
+pub mod meetings;
+pub mod metrics;
+pub mod migrations;
+pub mod mqtt;
+pub mod normalize;
+pub mod notes;
+pub mod pause;
+pub mod pending_sync;
+pub mod placement;
+pub mod plugins;
+pub mod productivity;
+pub mod projects;
+pub mod redaction;
+pub mod report;
+pub mod retention;
+pub mod rules;
+pub mod runtime;
+pub mod search;
+pub mod secrets;
+pub mod settings;
+pub mod slack_status;
+pub mod summary;
+pub mod sync_log;
+pub mod sync_queue;
+pub mod ticket_info;
+pub mod timeline;
+pub mod transfer;
+pub mod uploader;
+pub mod webhooks;
+pub mod window_events;
+
+use plugins::{
+    config::{CoalesceConfig, IdleConfig, IntegrationsConfig},
+    http::HttpClient,
+    integrations::calendar::CalendarClient,
+    traits::{ActivityInfo, SyncError, TicketSyncResult},
+    PluginManager, UploadConfig,
+};
+
+use assignments::{PendingAssignment, RuleAssigner};
+use capture::{CaptureRegistry, CapturedWindow};
+use categories::Categorizer;
+use checkpoint::OpenActivity;
+use exclusions::ExclusionFilter;
+use input_meter::InputCounts;
+use live::{LiveEvent, LiveEvents};
+use meetings::MeetingReport;
+use metrics::Metrics;
+use normalize::TitleNormalizer;
+use pause::Pause;
+use placement::WindowPlacement;
+use projects::ProjectMatcher;
+use sync_queue::FlushReport;
+
+#[cfg(target_os = "windows")]
+mod windows_watcher {
+    use std::ffi::c_void;
+    use windows::core::HSTRING;
+    use windows::Win32::Foundation::{CloseHandle, HWND};
+    use windows::Win32::Graphics::Gdi::{
+        GetMonitorInfoW, MonitorFromWindow, MONITORINFO, MONITORINFOEXW, MONITORINFOF_PRIMARY,
+        MONITOR_DEFAULTTONEAREST,
+    };
+    use windows::Win32::Storage::FileSystem::{
+        GetFileVersionInfoSizeW, GetFileVersionInfoW, VerQueryValueW,
+    };
+    use windows::Win32::System::Com::{
+        CoCreateInstance, CoInitializeEx, CLSCTX_ALL, COINIT_MULTITHREADED,
+    };
+    use windows::Win32::System::ProcessStatus::{GetModuleBaseNameW, GetModuleFileNameExW};
+    use windows::Win32::System::SystemInformation::GetTickCount;
+    use windows::Win32::System::Threading::{
+        OpenProcess, PROCESS_QUERY_INFORMATION, PROCESS_VM_READ,
+    };
+    use windows::Win32::UI::Accessibility::{
+        CUIAutomation, IUIAutomation, IUIAutomationCondition, IUIAutomationElement,
+        TreeScope_Subtree, UIA_ControlTypePropertyId, UIA_EditControlTypeId, UIA_NamePropertyId,
+        UIA_ValueValuePropertyId,
+    };
+    use windows::Win32::UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO};
+    use windows::Win32::UI::Shell::{IVirtualDesktopManager, VirtualDesktopManager};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        GetForegroundWindow, GetWindowTextW, GetWindowThreadProcessId,
+    };
+
+    use crate::browser::{extract_domain, is_browser, looks_like_url};
+
+    pub fn get_browser_url(hwnd: HWND) -> Option<String> {
+        unsafe {
+            // Initialize COM
+            let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+
+            // Create UI Automation instance
+            let automation: IUIAutomation = match CoCreateInstance(&CUIAutomation, None, CLSCTX_ALL)
+            {
+                Ok(a) => a,
+                Err(_) => return None,
+            };
+
+            // Get element from window handle
+            let element: IUIAutomationElement = match automation.ElementFromHandle(hwnd) {
+                Ok(e) => e,
+                Err(_) => return None,
+            };
+
+            // Create condition to find Edit controls (address bar)
+            let edit_condition: IUIAutomationCondition = match automation.CreatePropertyCondition(
+                UIA_ControlTypePropertyId,
+                &windows::core::VARIANT::from(UIA_EditControlTypeId.0),
+            ) {
+                Ok(c) => c,
+                Err(_) => return None,
+            };
+
+            // Find all Edit elements
+            let elements = match element.FindAll(TreeScope_Subtree, &edit_condition) {
+                Ok(e) => e,
+                Err(_) => return None,
+            };
+
+            let count = elements.Length().unwrap_or(0);
+
+            for i in 0..count {
+                if let Ok(elem) = elements.GetElement(i) {
+                    // Check if this is the address bar by looking at the name property
+                    if let Ok(name) = elem.GetCurrentPropertyValue(UIA_NamePropertyId) {
+                        let name_str = name.to_string().to_lowercase();
+                        // Common address bar identifiers (English, Japanese, and browser-specific)
+                        if name_str.contains("address")
+                            || name_str.contains("url")
+                            || name_str.contains("アドレス")
+                            || name_str.contains("location")
+                            || name_str.contains("omnibox")
+                            || name_str.contains("検索または")
+                            || name_str.contains("search or")
+                            || name_str.contains("検索またはアドレス")
+                        {
+                            // Get the value (URL)
+                            if let Ok(value) =
+                                elem.GetCurrentPropertyValue(UIA_ValueValuePropertyId)
+                            {
+                                let url_str = value.to_string();
+                                // Check for URLs with or without protocol prefix
+                                if looks_like_url(&url_str) {
+                                    return Some(url_str);
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
+            // Fallback: try to find any edit control with a URL-like value
+            for i in 0..count {
+                if let Ok(elem) = elements.GetElement(i) {
+                    if let Ok(value) = elem.GetCurrentPropertyValue(UIA_ValueValuePropertyId) {
+                        let url_str = value.to_string();
+                        // Check for URLs with or without protocol prefix
+                        if looks_like_url(&url_str) {
+                            return Some(url_str);
+                        }
+                    }
+                }
+            }
+
+            None
+        }
+    }
+
+    pub fn get_active_window_info() -> Option<(String, String, Option<String>)> {
+        unsafe {
+            let hwnd: HWND = GetForegroundWindow();
+            if hwnd.0.is_null() {
+                return None;
+            }
+
+            // Get window title
+            let mut title_buf = [0u16; 512];
+            let title_len = GetWindowTextW(hwnd, &mut title_buf);
+            let title = if title_len > 0 {
+                String::from_utf16_lossy(&title_buf[..title_len as usize])
+            } else {
+                String::new()
+            };
+
+            // Get process name
+            let mut process_id: u32 = 0;
+            GetWindowThreadProcessId(hwnd, Some(&mut process_id));
+
+            let process_name = if process_id != 0 {
+                if let Ok(handle) = OpenProcess(
+                    PROCESS_QUERY_INFORMATION | PROCESS_VM_READ,
+                    false,
+                    process_id,
+                ) {
+                    let mut name_buf = [0u16; 256];
+                    let name_len = GetModuleBaseNameW(handle, None, &mut name_buf);
+                    if name_len > 0 {
+                        String::from_utf16_lossy(&name_buf[..name_len as usize])
+                    } else {
+                        String::from("Unknown")
+                    }
+                } else {
+                    String::from("Unknown")
+                }
+            } else {
+                String::from("Unknown")
+            };
+
+            // Get domain if it's a browser
+            let domain = if is_browser(&process_name) {
+                get_browser_url(hwnd).and_then(|url| extract_domain(&url))
+            } else {
+                None
+            };
+
+            Some((process_name, title, domain))
+        }
+    }
+
+    /// Monitor and virtual desktop of the foreground window
+    pub fn get_foreground_placement() -> Option<crate::placement::WindowPlacement> {
+        unsafe {
+            let hwnd: HWND = GetForegroundWindow();
+            if hwnd.0.is_null() {
+                return None;
+            }
+
+            let monitor = MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST);
+            let mut info = MONITORINFOEXW::default();
+            info.monitorInfo.cbSize = std::mem::size_of::<MONITORINFOEXW>() as u32;
+            let (monitor, primary) =
+                if GetMonitorInfoW(monitor, &mut info.monitorInfo as *mut MONITORINFO).as_bool() {
+                    let len = info
+                        .szDevice
+                        .iter()
+                        .position(|&c| c == 0)
+                        .unwrap_or(info.szDevice.len());
+                    (
+                        Some(String::from_utf16_lossy(&info.szDevice[..len])),
+                        Some(info.monitorInfo.dwFlags & MONITORINFOF_PRIMARY != 0),
+                    )
+                } else {
+                    (None, None)
+                };
+
+            let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+            let virtual_desktop = CoCreateInstance::<_, IVirtualDesktopManager>(
+                &VirtualDesktopManager,
+                None,
+                CLSCTX_ALL,
+            )
+            .ok()
+            .and_then(|manager| manager.GetWindowDesktopId(hwnd).ok())
+            .map(|id| format!("{:?}", id));
+
+            Some(crate::placement::WindowPlacement {
+                monitor,
+                primary,
+                virtual_desktop,
+            })
+        }
+    }
+
+    /// Seconds since the last keyboard/mouse input
+    pub fn get_idle_seconds() -> u64 {
+        unsafe {
+            let mut info = LASTINPUTINFO {
+                cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+                dwTime: 0,
+            };
+            if !GetLastInputInfo(&mut info).as_bool() {
+                return 0;
+            }
+            (GetTickCount().wrapping_sub(info.dwTime) / 1000) as u64
+        }
+    }
+
+    /// Get executable path and version resource info of the foreground process
+    pub fn get_foreground_process_metadata() -> Option<super::ProcessMetadata> {
+        unsafe {
+            let hwnd: HWND = GetForegroundWindow();
+            if hwnd.0.is_null() {
+                return None;
+            }
+
+            let mut process_id: u32 = 0;
+            GetWindowThreadProcessId(hwnd, Some(&mut process_id));
+            if process_id == 0 {
+                return None;
+            }
+
+            let handle = OpenProcess(
+                PROCESS_QUERY_INFORMATION | PROCESS_VM_READ,
+                false,
+                process_id,
+            )
+            .ok()?;
+
+            let mut path_buf = [0u16; 1024];
+            let path_len = GetModuleFileNameExW(handle, None, &mut path_buf);
+            let mut name_buf = [0u16; 256];
+            let name_len = GetModuleBaseNameW(handle, None, &mut name_buf);
+            let _ = CloseHandle(handle);
+
+            if path_len == 0 || name_len == 0 {
+                return None;
+            }
+
+            let exe_path = String::from_utf16_lossy(&path_buf[..path_len as usize]);
+            let process_name = String::from_utf16_lossy(&name_buf[..name_len as usize]);
+            let (company_name, product_name, file_version) = read_version_info(&exe_path);
+
+            Some(super::ProcessMetadata {
+                process_name,
+                exe_path: Some(exe_path),
+                company_name,
+                product_name,
+                file_version,
+            })
+        }
+    }
+
+    /// Read CompanyName / ProductName / FileVersion from the executable's version resource
+    fn read_version_info(exe_path: &str) -> (Option<String>, Option<String>, Option<String>) {
+        unsafe {
+            let path = HSTRING::from(exe_path);
+            let size = GetFileVersionInfoSizeW(&path, None);
+            if size == 0 {
+                return (None, None, None);
+            }
+
+            let mut data = vec![0u8; size as usize];
+            if GetFileVersionInfoW(&path, 0, size, data.as_mut_ptr() as *mut c_void).is_err() {
+                return (None, None, None);
+            }
+
+            // Use the first language/codepage pair, falling back to US English + Unicode
+            let mut translation: *mut c_void = std::ptr::null_mut();
+            let mut translation_len: u32 = 0;
+            let (lang, codepage) = if VerQueryValueW(
+                data.as_ptr() as *const c_void,
+                &HSTRING::from("\\VarFileInfo\\Translation"),
+                &mut translation,
+                &mut translation_len,
+            )
+            .as_bool()
+                && !translation.is_null()
+                && translation_len >= 4
+            {
+                let pair = std::slice::from_raw_parts(translation as *const u16, 2);
+                (pair[0], pair[1])
+            } else {
+                (0x0409, 0x04b0)
+            };
+
+            (
+                query_version_string(&data, lang, codepage, "CompanyName"),
+                query_version_string(&data, lang, codepage, "ProductName"),
+                query_version_string(&data, lang, codepage, "FileVersion"),
+            )
+        }
+    }
+
+    unsafe fn query_version_string(
+        data: &[u8],
+        lang: u16,
+        codepage: u16,
+        key: &str,
+    ) -> Option<String> {
+        let sub_block = HSTRING::from(format!(
+            "\\StringFileInfo\\{:04x}{:04x}\\{}",
+            lang, codepage, key
+        ));
+        let mut buffer: *mut c_void = std::ptr::null_mut();
+        let mut len: u32 = 0;
+
+        if !VerQueryValueW(
+            data.as_ptr() as *const c_void,
+            &sub_block,
+            &mut buffer,
+            &mut len,
+        )
+        .as_bool()
+            || buffer.is_null()
+            || len == 0
+        {
+            return None;
+        }
+
+        let chars = std::slice::from_raw_parts(buffer as *const u16, len as usize);
+        let value = String::from_utf16_lossy(chars)
+            .trim_end_matches('\0')
+            .trim()
+            .to_string();
+
+        if value.is_empty() {
+            None
+        } else {
+            Some(value)
+        }
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+mod replay;
+
+#[cfg(target_os = "macos")]
+mod macos_watcher;
+
+#[cfg(target_os = "linux")]
+mod linux_watcher;
+
+#[cfg(not(target_os = "windows"))]
+mod windows_watcher {
+    /// macOS and Linux use native capture unless TIMETRACKER_REPLAY is set (Linux also falls
+    /// back when no display server is found); other platforms replay scripted window events
+    #[cfg(target_os = "macos")]
+    use super::macos_watcher as native;
+
+    #[cfg(target_os = "linux")]
+    use super::linux_watcher as native;
+
+    #[cfg(target_os = "macos")]
+    fn use_native() -> bool {
+        !super::replay::is_enabled()
+    }
+
+    #[cfg(target_os = "linux")]
+    fn use_native() -> bool {
+        !super::replay::is_enabled() && native::is_available()
+    }
+
+    pub fn get_active_window_info() -> Option<(String, String, Option<String>)> {
+        #[cfg(any(target_os = "macos", target_os = "linux"))]
+        if use_native() {
+            return native::get_active_window_info();
+        }
+        super::replay::next_window()
+    }
+
+    pub fn get_foreground_process_metadata() -> Option<super::ProcessMetadata> {
+        #[cfg(any(target_os = "macos", target_os = "linux"))]
+        if use_native() {
+            return native::get_foreground_process_metadata();
+        }
+        super::replay::current_metadata()
+    }
+
+    /// Monitor/virtual desktop are only available on Windows
+    pub fn get_foreground_placement() -> Option<crate::placement::WindowPlacement> {
+        None
+    }
+
+    pub fn get_idle_seconds() -> u64 {
+        #[cfg(any(target_os = "macos", target_os = "linux"))]
+        if use_native() {
+            return native::get_idle_seconds();
+        }
+        super::replay::idle_seconds()
+    }
+}
+
+use windows_watcher::{
+    get_foreground_placement, get_foreground_process_metadata, get_idle_seconds,
+};
+
+// ========== ユーザー情報取得 ==========
+
+#[cfg(target_os = "windows")]
+pub mod user_info {
+    use windows::Win32::Foundation::ERROR_MORE_DATA;
+    use windows::Win32::Security::{GetUserNameExW, NameUserPrincipal, NameSamCompatible};
+
+    /// Get the current Windows user's UPN (User Principal Name)
+    /// e.g., "user@domain.com"
+    pub fn get_user_upn() -> Option<String> {
+        unsafe {
+            // First, try UPN format (user@domain.com)
+            let mut size: u32 = 0;
+            let _ = GetUserNameExW(NameUserPrincipal, None, &mut size);
+
+            if size > 0 {
+                let mut buffer = vec![0u16; size as usize];
+                if GetUserNameExW(
+                    NameUserPrincipal,
+                    Some(windows::core::PWSTR(buffer.as_mut_ptr())),
+                    &mut size,
+                ).is_ok() {
+                    let name = String::from_utf16_lossy(&buffer[..size as usize - 1]);
+                    if !name.is_empty() {
+                        return Some(name);
+                    }
+                }
+            }
+
+            // Fallback to SAM compatible format (DOMAIN\user)
+            let mut size: u32 = 0;
+            let _ = GetUserNameExW(NameSamCompatible, None, &mut size);
+
+            if size > 0 {
+                let mut buffer = vec![0u16; size as usize];
+                if GetUserNameExW(
+                    NameSamCompatible,
+                    Some(windows::core::PWSTR(buffer.as_mut_ptr())),
+                    &mut size,
+                ).is_ok() {
+                    let name = String::from_utf16_lossy(&buffer[..size as usize - 1]);
+                    if !name.is_empty() {
+                        return Some(name);
+                    }
+                }
+            }
+
+            None
+        }
+    }
+
+    /// Get machine name
+    pub fn get_machine_name() -> Option<String> {
+        std::env::var("COMPUTERNAME").ok()
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+pub mod user_info {
+    /// Stub for non-Windows platforms
+    pub fn get_user_upn() -> Option<String> {
+        // For development, return a demo user
+        Some(String::from("demo.user@example.com"))
+    }
+
+    pub fn get_machine_name() -> Option<String> {
+        std::env::var("HOSTNAME").ok().or_else(|| Some(String::from("dev-machine")))
+    }
+}
+
+use user_info::{get_machine_name, get_user_upn};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivityRecord {
+    pub id: i64,
+    pub process_name: String,
+    pub window_title: String,
+    pub domain: Option<String>,
+    pub start_time: String,
+    pub end_time: String,
+    pub duration_seconds: i64,
+    /// 作業内容のメモ（同期時のコメントに使う）
+    pub note: Option<String>,
+}
+
+/// 実行ファイルのバージョン情報（ベンダー/製品名）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessMetadata {
+    pub process_name: String,
+    pub exe_path: Option<String>,
+    pub company_name: Option<String>,
+    pub product_name: Option<String>,
+    pub file_version: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppSummary {
+    pub process_name: String,
+    pub total_seconds: i64,
+    pub percentage: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DomainSummary {
+    pub domain: String,
+    pub total_seconds: i64,
+    pub percentage: f64,
+}
+
+/// カテゴリ別の使用時間サマリー（アプリ別の内訳付き）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategorySummary {
+    pub category: String,
+    pub color: Option<String>,
+    pub total_seconds: i64,
+    pub percentage: f64,
+    pub apps: Vec<AppSummary>,
+}
+
+/// get_app_summary の結果（group_by でアプリ別かカテゴリ別かが決まる）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum AppSummaryResult {
+    Apps(Vec<AppSummary>),
+    Categories(Vec<CategorySummary>),
+}
+
+/// ベンダー（CompanyName）別の使用時間サマリー
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VendorSummary {
+    pub company_name: String,
+    pub total_seconds: i64,
+    pub percentage: f64,
+}
+
+pub struct AppState {
+    pub db: Mutex<Connection>,
+    pub is_tracking: Mutex<bool>,
+    /// 自動再開つきの一時停止（一時停止中は is_tracking も false）
+    pub pause: Mutex<Option<Pause>>,
+    /// 入力がなくアイドルとして扱っている間は true
+    pub is_idle: Mutex<bool>,
+    /// 今日の記録時間の合計（進行中のアクティビティを含む、ウォッチャーが更新）
+    pub today_seconds: Mutex<i64>,
+    pub plugin_manager: PluginManager,
+    pub categorizer: Categorizer,
+    pub rule_assigner: RuleAssigner,
+    pub title_normalizer: TitleNormalizer,
+    pub exclusions: ExclusionFilter,
+    pub projects: ProjectMatcher,
+    pub capture: CaptureRegistry,
+    /// 進行中のアクティビティに手動で指定した割り当て
+    pub current_assignment: Mutex<Option<PendingAssignment>>,
+    /// トレイで選択中のプロジェクト（以降のアクティビティをすべてこのプロジェクトに割り当てる）
+    pub project_override: Mutex<Option<String>>,
+    /// 進行中のアクティビティのモニター・仮想デスクトップ
+    pub current_placement: Mutex<Option<WindowPlacement>>,
+    /// WebSocketで配信するイベント
+    pub live: LiveEvents,
+    /// DBへの書き込み待ちのアクティビティ（ウォッチャーがまとめて書き込む）
+    activity_buffer: Mutex<Vec<BufferedActivity>>,
+    /// 保存時に細切れの記録をまとめる設定（設定ファイルの変更で差し替える）
+    pub coalesce: Mutex<CoalesceConfig>,
+    /// アイドル判定の設定（ウォッチャーが毎回読む、設定ファイルの変更で差し替える）
+    pub idle: Mutex<IdleConfig>,
+    /// /metrics で公開するカウンター
+    pub metrics: Metrics,
+}
+
+/// テーブルを作成し、未適用のマイグレーションを適用する
+fn create_schema(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS activities (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            process_name TEXT NOT NULL,
+            window_title TEXT NOT NULL,
+            domain TEXT,
+            start_time TEXT NOT NULL,
+            end_time TEXT NOT NULL,
+            duration_seconds INTEGER NOT NULL
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_start_time ON activities(start_time)",
+        [],
+    )?;
+
+    // ローカルAPI用のアクセストークンとリクエストログ
+    api_auth::create_tables(conn)?;
+
+    // チケット/プロジェクトの割り当て
+    assignments::create_tables(conn)?;
+
+    // カテゴリの定義（分類ルールは integrations.toml）
+    categories::create_tables(conn)?;
+    if let Err(e) = categories::ensure_from_rules(conn, &IntegrationsConfig::load().categories) {
+        eprintln!("Failed to register categories: {}", e);
+    }
+
+    // オフライン時に保留した同期
+    sync_queue::create_tables(conn)?;
+
+    // 送信前に確認する同期の候補
+    pending_sync::create_tables(conn)?;
+
+    // 同期履歴（二重送信の防止）
+    sync_log::create_tables(conn)?;
+
+    // アップロード履歴・送信済みの印
+    uploader::create_tables(conn)?;
+
+    // アイドル時間（タイムラインの表示用）
+    timeline::create_tables(conn)?;
+
+    // 一時停止していた区間
+    pause::create_tables(conn)?;
+
+    // 予算・目標の通知済みの状態
+    budgets::create_tables(conn)?;
+
+    // カテゴリ別の日/週の目標
+    goals::create_tables(conn)?;
+
+    // 保持期間を過ぎた生データの日別集計
+    retention::create_tables(conn)?;
+
+    // 異常終了に備えた進行中のアクティビティ
+    checkpoint::create_tables(conn)?;
+
+    // カレンダーから取り込んだ会議
+    meetings::create_tables(conn)?;
+
+    // 連携先のチケットの件名・状態のキャッシュ
+    ticket_info::create_tables(conn)?;
+
+    // プロセスごとのベンダー/製品情報（プロセス名単位でキャッシュ）
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS process_metadata (
+            process_name TEXT PRIMARY KEY,
+            exe_path TEXT,
+            company_name TEXT,
+            product_name TEXT,
+            file_version TEXT,
+            updated_at TEXT NOT NULL
+        )",
+        [],
+    )?;
+
+    // 既存データベースへの列の追加など（バージョン管理されたスキーマ変更）
+    migrations::run(conn)?;
+
+    Ok(())
+}
+
+impl AppState {
+    pub fn new() -> Result<Self, String> {
+        let db_path = database::db_path();
+        std::fs::create_dir_all(db_path.parent().unwrap()).ok();
+
+        let config = IntegrationsConfig::load();
+        let conn = database::open(&db_path, config.database.encrypted)?;
+        create_schema(&conn).map_err(|e| e.to_string())?;
+
+        // プラグインマネージャーを初期化
+        let plugin_manager = PluginManager::new();
+        if let Err(e) = plugin_manager.load_from_config() {
+            eprintln!("Failed to load plugins: {}", e);
+        }
+
+        let categorizer = Categorizer::new();
+        categorizer.load_from_config();
+
+        let rule_assigner = RuleAssigner::new();
+        rule_assigner.load_from_config();
+
+        let title_normalizer = TitleNormalizer::new();
+        title_normalizer.load_from_config();
+
+        let exclusions = ExclusionFilter::new();
+        exclusions.load_from_config();
+
+        let projects = ProjectMatcher::new();
+        projects.load_from_config();
+
+        let capture = CaptureRegistry::new();
+        capture.load_from_config();
+
+        Ok(Self {
+            db: Mutex::new(conn),
+            is_tracking: Mutex::new(false),
+            pause: Mutex::new(None),
+            is_idle: Mutex::new(false),
+            today_seconds: Mutex::new(0),
+            plugin_manager,
+            categorizer,
+            rule_assigner,
+            title_normalizer,
+            exclusions,
+            projects,
+            capture,
+            current_assignment: Mutex::new(None),
+            project_override: Mutex::new(None),
+            current_placement: Mutex::new(None),
+            live: LiveEvents::new(),
+            activity_buffer: Mutex::new(Vec::new()),
+            coalesce: Mutex::new(config.coalesce),
+            idle: Mutex::new(config.idle),
+            metrics: Metrics::default(),
+        })
+    }
+}
+
+/// 指定日のアクティビティ（merge なら連続する同じ記録をまとめてからページを切る）
+pub fn load_activities(
+    state: &AppState,
+    date: &str,
+    limit: Option<u32>,
+    offset: Option<u32>,
+    merge: bool,
+) -> Result<Vec<ActivityRecord>, String> {
+    let db = state.db.lock();
+    let start_of_day = format!("{}T00:00:00", date);
+    let end_of_day = format!("{}T23:59:59", date);
+
+    // まとめる場合はまとめた結果に対してページを切るので全件読む（LIMIT -1 は無制限）
+    let (sql_limit, sql_offset) = if merge {
+        (-1, 0)
+    } else {
+        (limit.map_or(-1, i64::from), offset.unwrap_or(0))
+    };
+
+    let mut stmt = db
+        .prepare(
+            "SELECT id, process_name, window_title, domain, start_time, end_time, duration_seconds, note
+             FROM activities
+             WHERE start_time >= ?1 AND start_time <= ?2
+             ORDER BY start_time ASC
+             LIMIT ?3 OFFSET ?4",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let records: Vec<ActivityRecord> = stmt
+        .query_map(
+            params![start_of_day, end_of_day, sql_limit, sql_offset],
+            |row| {
+                Ok(ActivityRecord {
+                    id: row.get(0)?,
+                    process_name: row.get(1)?,
+                    window_title: row.get(2)?,
+                    domain: row.get(3)?,
+                    start_time: row.get(4)?,
+                    end_time: row.get(5)?,
+                    duration_seconds: row.get(6)?,
+                    note: row.get(7)?,
+                })
+            },
+        )
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    if !merge {
+        return Ok(records);
+    }
+    Ok(merge_consecutive_activities(records)
+        .into_iter()
+        .skip(offset.unwrap_or(0) as usize)
+        .take(limit.map_or(usize::MAX, |l| l as usize))
+        .collect())
+}
+
+/// 同じアプリ・タイトル・ドメインが途切れずに続く記録を1件にまとめる（id は先頭の記録）
+fn merge_consecutive_activities(records: Vec<ActivityRecord>) -> Vec<ActivityRecord> {
+    let parse = |s: &str| NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S").ok();
+
+    let mut merged: Vec<ActivityRecord> = Vec::new();
+    for record in records {
+        if let Some(last) = merged.last_mut() {
+            let same = last.process_name == record.process_name
+                && last.window_title == record.window_title
+                && last.domain == record.domain;
+            let gap = parse(&last.end_time)
+                .zip(parse(&record.start_time))
+                .map(|(end, start)| (start - end).num_seconds());
+            let continuous =
+                gap.is_some_and(|gap| gap <= timeline::DEFAULT_MERGE_THRESHOLD_SECONDS);
+            if same && continuous {
+                last.end_time = last.end_time.clone().max(record.end_time);
+                last.duration_seconds += record.duration_seconds;
+                last.note = match (last.note.take(), record.note) {
+                    (Some(a), Some(b)) => Some(format!("{}; {}", a, b)),
+                    (a, b) => a.or(b),
+                };
+                continue;
+            }
+        }
+        merged.push(record);
+    }
+    merged
+}
+
+/// 断片統合を定期実行（起動後しばらくしてから1日1回）
+pub fn start_compaction_job(state: Arc<AppState>) {
+    thread::spawn(move || {
+        thread::sleep(Duration::from_secs(600));
+
+        loop {
+            let config = IntegrationsConfig::load();
+            if config.compaction.enabled {
+                let result = {
+                    let db = state.db.lock();
+                    maintenance::compact_fragments(&db, &config.compaction, Local::now(), false)
+                };
+                match result {
+                    Ok(report) if report.removed > 0 => {
+                        println!(
+                            "Compacted {} fragments into {} activities",
+                            report.removed + report.merged,
+                            report.merged
+                        );
+                        start_ticket_extraction_backfill(state.clone());
+                    }
+                    Ok(_) => {}
+                    Err(e) => eprintln!("Failed to compact activities: {}", e),
+                }
+            }
+
+            thread::sleep(Duration::from_secs(24 * 3600));
+        }
+    });
+}
+
+/// チケットの抽出を一度にまとめて保存する件数
+const TICKET_EXTRACTION_BATCH: i64 = 500;
+
+/// 抽出結果がないアクティビティのチケットを抽出して保存する
+///
+/// 起動時と、タイトルの書き換えや抽出ルールの変更で抽出結果を消したときに呼ぶ。
+/// 記録を止めないよう、DB のロックは1バッチごとに取り直す。
+pub fn start_ticket_extraction_backfill(state: Arc<AppState>) {
+    thread::spawn(move || {
+        let mut total = 0;
+        loop {
+            match extract_stored_tickets(&state, TICKET_EXTRACTION_BATCH) {
+                Ok(0) => break,
+                Ok(count) => total += count,
+                Err(e) => {
+                    eprintln!("Failed to extract ticket IDs: {}", e);
+                    break;
+                }
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+        if total > 0 {
+            println!("Extracted ticket IDs for {} activities", total);
+        }
+    });
+}
+
+/// 抽出ルールが変わっていたら保存済みの抽出結果を消し、バックグラウンドで抽出し直す
+pub fn refresh_extracted_tickets(state: &Arc<AppState>) -> Result<(), String> {
+    if !state.plugin_manager.take_extraction_changed() {
+        return Ok(());
+    }
+    state
+        .db
+        .lock()
+        .execute(
+            "UPDATE activities
+             SET extracted_plugin = NULL, extracted_ticket_id = NULL, extracted_at = NULL
+             WHERE extracted_at IS NOT NULL",
+            [],
+        )
+        .map_err(|e| e.to_string())?;
+    start_ticket_extraction_backfill(state.clone());
+    Ok(())
+}
+
+/// 抽出前のアクティビティを最大 limit 件抽出して保存し、処理した件数を返す
+fn extract_stored_tickets(state: &AppState, limit: i64) -> Result<usize, String> {
+    let db = state.db.lock();
+    let mut stmt = db
+        .prepare(
+            "SELECT id, process_name, window_title, domain, start_time, end_time, duration_seconds, note
+             FROM activities
+             WHERE extracted_at IS NULL
+             LIMIT ?1",
+        )
+        .map_err(|e| e.to_string())?;
+    let activities: Vec<ActivityInfo> = stmt
+        .query_map(params![limit], |row| {
+            Ok(ActivityInfo {
+                id: row.get(0)?,
+                process_name: row.get(1)?,
+                window_title: row.get(2)?,
+                domain: row.get(3)?,
+                start_time: row.get(4)?,
+                end_time: row.get(5)?,
+                duration_seconds: row.get(6)?,
+                note: row.get(7)?,
+                project: None,
+                category: None,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let extracted_at = Local::now().format("%Y-%m-%dT%H:%M:%S").to_string();
+    let tx = db.unchecked_transaction().map_err(|e| e.to_string())?;
+    for activity in &activities {
+        let ticket = state.plugin_manager.extract_ticket_id(activity);
+        tx.execute(
+            "UPDATE activities SET extracted_plugin = ?1, extracted_ticket_id = ?2, extracted_at = ?3
+             WHERE id = ?4",
+            params![
+                ticket.as_ref().map(|(plugin, _)| plugin),
+                ticket.as_ref().map(|(_, ticket_id)| ticket_id),
+                extracted_at,
+                activity.id
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(activities.len())
+}
+
+/// 保持ポリシーを定期実行（断片統合の後、1日1回）
+pub fn start_retention_job(state: Arc<AppState>) {
+    thread::spawn(move || {
+        thread::sleep(Duration::from_secs(900));
+
+        loop {
+            let config = IntegrationsConfig::load();
+            if config.retention.enabled {
+                let db = state.db.lock();
+                match retention::prune(&db, &config.retention, Local::now(), false) {
+                    Ok(report) if report.removed_activities > 0 => println!(
+                        "Rolled up {} activities from {} days before {}",
+                        report.removed_activities, report.days, report.cutoff_date
+                    ),
+                    Ok(_) => {}
+                    Err(e) => eprintln!("Failed to apply retention policy: {}", e),
+                }
+            }
+
+            thread::sleep(Duration::from_secs(24 * 3600));
+        }
+    });
+}
+
+/// カレンダーの予定を取り込み、指定日のアクティビティに会議のタグを付け直す
+///
+/// 取得できなかったカレンダーがあっても残りは取り込み、最後にエラーをまとめて返す。
+pub async fn sync_calendar_events(
+    state: &AppState,
+    date: NaiveDate,
+) -> Result<MeetingReport, String> {
+    let date_str = date.format("%Y-%m-%d").to_string();
+    let mut errors = Vec::new();
+    for config in IntegrationsConfig::load().calendars {
+        let name = config.name.clone();
+        let client = match CalendarClient::new(config) {
+            Ok(client) => client,
+            Err(e) => {
+                errors.push(format!("{}: {}", name, e));
+                continue;
+            }
+        };
+        match client.fetch_events(date).await {
+            Ok(events) => {
+                let db = state.db.lock();
+                meetings::store_events(&db, &date_str, client.name(), &events)?;
+            }
+            Err(e) => errors.push(format!("{}: {}", name, e)),
+        }
+    }
+
+    let db = state.db.lock();
+    meetings::tag_activities(&db, &date_str)?;
+    if !errors.is_empty() {
+        return Err(format!("Failed to fetch calendars: {}", errors.join("; ")));
+    }
+    meetings::report(&db, &date_str)
+}
+
+/// 今日の予定を定期的に取り込む（カレンダー未設定なら何もしない）
+pub fn start_calendar_sync(state: Arc<AppState>) {
+    runtime::spawn(async move {
+        loop {
+            if !IntegrationsConfig::load().calendars.is_empty() {
+                if let Err(e) = sync_calendar_events(&state, Local::now().date_naive()).await {
+                    eprintln!("Failed to sync calendars: {}", e);
+                }
+            }
+            tokio::time::sleep(Duration::from_secs(900)).await;
+        }
+    });
+}
+
+/// プラグインに渡す形式に変換
+pub fn to_activity_info(record: &ActivityRecord) -> ActivityInfo {
+    ActivityInfo {
+        id: record.id,
+        process_name: record.process_name.clone(),
+        window_title: record.window_title.clone(),
+        domain: record.domain.clone(),
+        start_time: record.start_time.clone(),
+        end_time: record.end_time.clone(),
+        duration_seconds: record.duration_seconds,
+        note: record.note.clone(),
+        project: None,
+        category: None,
+    }
+}
+
+/// 同期を履歴に記録し、完了をフロントエンド・リアルタイム配信に通知する
+pub fn record_sync(
+    state: &AppState,
+    db: &Connection,
+    activity_ids: &[i64],
+    plugin: &str,
+    ticket_id: &str,
+    external_id: Option<&str>,
+    duration_seconds: i64,
+) -> Result<(), String> {
+    sync_log::record(
+        db,
+        activity_ids,
+        plugin,
+        ticket_id,
+        external_id,
+        duration_seconds,
+    )?;
+    state.live.publish(LiveEvent::SyncCompleted {
+        plugin: plugin.to_string(),
+        ticket_id: ticket_id.to_string(),
+        activity_ids: activity_ids.to_vec(),
+        external_id: external_id.map(str::to_string),
+    });
+    Ok(())
+}
+
+/// 期間内のアクティビティのチケット（プラグイン名 → アクティビティID → チケットID）
+///
+/// 手動・ルールで割り当てたチケットを保存時に抽出したチケットより優先する。
+/// 抽出前に保存されたアクティビティ（バックフィル待ち）だけはその場で抽出する。
+pub fn activity_tickets(
+    plugin_manager: &PluginManager,
+    db: &Connection,
+    range_start: &str,
+    range_end: &str,
+) -> Result<HashMap<String, HashMap<i64, String>>, String> {
+    let mut stmt = db
+        .prepare(
+            "SELECT a.id, a.process_name, a.window_title, a.domain, a.start_time, a.end_time, a.duration_seconds, a.note,
+                    t.plugin, t.ticket_id, a.extracted_plugin, a.extracted_ticket_id, a.extracted_at
+             FROM activities a
+             LEFT JOIN ticket_assignments t ON t.activity_id = a.id
+             WHERE a.start_time >= ?1 AND a.start_time <= ?2",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![range_start, range_end], |row| {
+            let activity = ActivityInfo {
+                id: row.get(0)?,
+                process_name: row.get(1)?,
+                window_title: row.get(2)?,
+                domain: row.get(3)?,
+                start_time: row.get(4)?,
+                end_time: row.get(5)?,
+                duration_seconds: row.get(6)?,
+                note: row.get(7)?,
+                project: None,
+                category: None,
+            };
+            let assigned = row
+                .get::<_, Option<String>>(8)?
+                .zip(row.get::<_, Option<String>>(9)?);
+            let extracted = row
+                .get::<_, Option<String>>(10)?
+                .zip(row.get::<_, Option<String>>(11)?);
+            let extracted_at: Option<String> = row.get(12)?;
+            Ok((activity, assigned, extracted, extracted_at.is_some()))
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok());
+
+    let mut tickets: HashMap<String, HashMap<i64, String>> = HashMap::new();
+    for (activity, assigned, extracted, is_extracted) in rows {
+        let ticket = match assigned {
+            Some(ticket) => Some(ticket),
+            None if is_extracted => extracted,
+            None => plugin_manager.extract_ticket_id(&activity),
+        };
+        if let Some((plugin, ticket_id)) = ticket {
+            tickets
+                .entry(plugin)
+                .or_default()
+                .insert(activity.id, ticket_id);
+        }
+    }
+    Ok(tickets)
+}
+
+/// 指定日の未同期のアクティビティと、そのチケット（アクティビティID → チケットID）
+pub fn unsynced_day_activities(
+    plugin_manager: &PluginManager,
+    db: &Connection,
+    plugin_name: &str,
+    date: &str,
+) -> Result<(Vec<ActivityInfo>, HashMap<i64, String>), String> {
+    let start_of_day = format!("{}T00:00:00", date);
+    let end_of_day = format!("{}T23:59:59", date);
+
+    let mut stmt = db
+        .prepare(
+            "SELECT a.id, a.process_name, a.window_title, a.domain, a.start_time, a.end_time, a.duration_seconds, a.note,
+                    t.project, a.category
+             FROM activities a
+             LEFT JOIN ticket_assignments t ON t.activity_id = a.id
+             WHERE a.start_time >= ?1 AND a.start_time <= ?2
+             ORDER BY a.start_time ASC",
+        )
+        .map_err(|e| e.to_string())?;
+
+    // 同期済みのアクティビティは二重に送信しない
+    let synced = sync_log::synced_activity_ids(db, plugin_name, &start_of_day, &end_of_day)?;
+
+    let activities: Vec<ActivityInfo> = stmt
+        .query_map(params![start_of_day, end_of_day], |row| {
+            Ok(ActivityInfo {
+                id: row.get(0)?,
+                process_name: row.get(1)?,
+                window_title: row.get(2)?,
+                domain: row.get(3)?,
+                start_time: row.get(4)?,
+                end_time: row.get(5)?,
+                duration_seconds: row.get(6)?,
+                note: row.get(7)?,
+                project: row.get(8)?,
+                category: row.get(9)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .filter(|activity| !synced.contains(&activity.id))
+        .collect();
+
+    let assigned = activity_tickets(plugin_manager, db, &start_of_day, &end_of_day)?
+        .remove(plugin_name)
+        .unwrap_or_default();
+
+    Ok((activities, assigned))
+}
+
+/// 指定日に送信済み・送信予定（確認待ちの候補とキュー）の時間の合計（1日の上限の計算用）
+pub fn day_used_seconds(db: &Connection, plugin_name: &str, date: &str) -> Result<i64, String> {
+    let synced = sync_log::synced_seconds(
+        db,
+        plugin_name,
+        &format!("{}T00:00:00", date),
+        &format!("{}T23:59:59", date),
+    )?;
+    let staged = pending_sync::staged_seconds(db, plugin_name, date)?;
+    let queued = sync_queue::queued_seconds(db, plugin_name, date)?;
+    Ok(synced + staged + queued)
+}
+
+/// 指定日の未同期のアクティビティを同期（接続できなかったチケットはキューに保留）
+pub async fn sync_day_activities(
+    state: &AppState,
+    plugin_name: &str,
+    date: &str,
+) -> Result<Vec<TicketSyncResult>, String> {
+    // 送信前の確認に回したアクティビティは承認済みの候補として、送信待ちのものはキューから送る
+    let (activities, assigned, used_seconds) = {
+        let db = state.db.lock();
+        let (activities, assigned) =
+            unsynced_day_activities(&state.plugin_manager, &db, plugin_name, date)?;
+        let staged = pending_sync::staged_activity_ids(&db, plugin_name)?;
+        let queued = sync_queue::queued_activity_ids(&db, plugin_name)?;
+        let activities: Vec<ActivityInfo> = activities
+            .into_iter()
+            .filter(|activity| !staged.contains(&activity.id) && !queued.contains(&activity.id))
+            .collect();
+        let used_seconds = day_used_seconds(&db, plugin_name, date)?;
+        (activities, assigned, used_seconds)
+    };
+
+    let mut results = state
+        .plugin_manager
+        .sync_day(plugin_name, &activities, &assigned, used_seconds)
+        .await?;
+
+    // 上限に達したチケットは同期済みとして扱い、接続できなかったチケットはキューに保留する
+    let db = state.db.lock();
+    for entry in results.iter_mut() {
+        if entry.capped {
+            sync_log::record(
+                &db,
+                &entry.activity_ids,
+                plugin_name,
+                &entry.ticket_id,
+                None,
+                0,
+            )?;
+            continue;
+        }
+        if entry.result.success {
+            record_sync(
+                state,
+                &db,
+                &entry.activity_ids,
+                plugin_name,
+                &entry.ticket_id,
+                entry.result.external_id.as_deref(),
+                entry.duration_seconds,
+            )?;
+            continue;
+        }
+        state.metrics.sync_failure();
+        if !entry.network_error {
+            continue;
+        }
+        let error = entry.result.message.clone();
+        entry.result.message = match sync_queue::enqueue(
+            &db,
+            plugin_name,
+            &entry.ticket_id,
+            &entry.activity,
+            &entry.activity_ids,
+            &error,
+        )? {
+            Some(_) => "Offline: queued for sync when connectivity returns".to_string(),
+            None => "Already queued or synced".to_string(),
+        };
+    }
+
+    Ok(results)
+}
+
+/// 保留中の同期を送信（接続エラーが出たらその時点で中断）
+///
+/// only_due なら再送時刻を過ぎたエントリのみ（バックグラウンドの自動再送用）。
+pub async fn flush_pending_syncs(state: &AppState, only_due: bool) -> Result<FlushReport, String> {
+    let entries = {
+        let db = state.db.lock();
+        if only_due {
+            sync_queue::due(&db)?
+        } else {
+            sync_queue::pending(&db)?
+        }
+    };
+
+    let mut report = FlushReport {
+        synced: 0,
+        failed: 0,
+        skipped: 0,
+        remaining: 0,
+    };
+
+    for (index, entry) in entries.iter().enumerate() {
+        let duplicate = {
+            let db = state.db.lock();
+            sync_queue::already_synced(&db, entry)?
+                || sync_log::any_synced(&db, &entry.activity_ids, &entry.plugin)?
+        };
+        if duplicate {
+            let db = state.db.lock();
+            sync_queue::discard(&db, entry.id)?;
+            report.skipped += 1;
+            continue;
+        }
+
+        let result = state
+            .plugin_manager
+            .sync_time_entry(&entry.plugin, &entry.activity, &entry.ticket_id)
+            .await;
+
+        let db = state.db.lock();
+        match result {
+            Ok(result) if result.success => {
+                sync_queue::mark_synced(&db, entry.id, result.external_id.as_deref())?;
+                record_sync(
+                    state,
+                    &db,
+                    &entry.activity_ids,
+                    &entry.plugin,
+                    &entry.ticket_id,
+                    result.external_id.as_deref(),
+                    entry.activity.duration_seconds,
+                )?;
+                report.synced += 1;
+            }
+            Ok(result) => {
+                state.metrics.sync_failure();
+                sync_queue::mark_error(&db, entry.id, &SyncError::Failed(result.message))?;
+                report.failed += 1;
+            }
+            Err(e) => {
+                state.metrics.sync_failure();
+                sync_queue::mark_error(&db, entry.id, &e)?;
+                if e.is_network() {
+                    report.remaining = entries.len() - index;
+                    break;
+                }
+                report.failed += 1;
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// 保留中の同期を定期的に送信
+pub fn start_sync_queue_flusher(state: Arc<AppState>) {
+    runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+
+            match flush_pending_syncs(&state, true).await {
+                Ok(report) if report.synced > 0 => {
+                    println!("Flushed {} queued time entries", report.synced)
+                }
+                Ok(_) => {}
+                Err(e) => eprintln!("Failed to flush sync queue: {}", e),
+            }
+        }
+    });
+}
+
+/// 指定日のアクティビティを CSV にする（profile でマスキング）
+pub fn timeline_csv(state: &AppState, date: &str, profile: Option<&str>) -> Result<String, String> {
+    let profile = redaction::resolve_profile(&IntegrationsConfig::load(), profile)?;
+    let db = state.db.lock();
+    let start_of_day = format!("{}T00:00:00", date);
+    let end_of_day = format!("{}T23:59:59", date);
+
+    let mut stmt = db
+        .prepare(
+            "SELECT id, process_name, window_title, domain, start_time, end_time, duration_seconds, note
+             FROM activities
+             WHERE start_time >= ?1 AND start_time <= ?2
+             ORDER BY start_time ASC",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let records: Vec<ActivityRecord> = stmt
+        .query_map(params![start_of_day, end_of_day], |row| {
+            Ok(ActivityRecord {
+                id: row.get(0)?,
+                process_name: row.get(1)?,
+                window_title: row.get(2)?,
+                domain: row.get(3)?,
+                start_time: row.get(4)?,
+                end_time: row.get(5)?,
+                duration_seconds: row.get(6)?,
+                note: row.get(7)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    // Build CSV
+    let mut csv = String::new();
+    csv.push_str("start_time,end_time,duration_seconds,process_name,window_title,domain\n");
+
+    for record in records {
+        let process_name = profile.process_name(&record.process_name);
+        let window_title = profile.window_title(&record.window_title);
+        let domain = profile.domain(record.domain.as_deref());
+
+        csv.push_str(&format!(
+            "{},{},{},{},{},{}\n",
+            escape_csv_field(&record.start_time),
+            escape_csv_field(&record.end_time),
+            record.duration_seconds,
+            escape_csv_field(&process_name.unwrap_or_default()),
+            escape_csv_field(&window_title.unwrap_or_default()),
+            escape_csv_field(&domain.unwrap_or_default()),
+        ));
+    }
+
+    Ok(csv)
+}
+
+/// CSV用にフィールドをエスケープ
+fn escape_csv_field(field: &str) -> String {
+    if field.contains(',') || field.contains('"') || field.contains('\n') || field.contains('\r') {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// HTML用に文字列をエスケープ
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// アプリ使用時間サマリー（アップロード用）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppUsageSummary {
+    pub process_name: String,
+    pub total_seconds: i64,
+    /// ブラウザの場合のドメイン（ブラウザ以外はnull）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub domain: Option<String>,
+}
+
+/// アップロードリクエスト（集計データ形式）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct UploadRequest {
+    user_id: String,
+    machine_name: Option<String>,
+    date: String,
+    min_duration_seconds: u32,
+    app_summaries: Vec<AppUsageSummary>,
+}
+
+/// アップロード結果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UploadResult {
+    pub success: bool,
+    pub message: String,
+    pub uploaded_count: usize,
+}
+
+/// アップロード設定を取得し、送信可能か確認
+pub fn enabled_upload_config() -> Result<UploadConfig, String> {
+    let upload_config =
+        plugins::get_upload_config().ok_or_else(|| "Upload not configured".to_string())?;
+
+    if !upload_config.enabled {
+        return Err("Upload is disabled".to_string());
+    }
+
+    if upload_config.server_url.is_empty() {
+        return Err("Server URL is not configured".to_string());
+    }
+
+    Ok(upload_config)
+}
+
+pub async fn upload_pending(
+    state: &AppState,
+    upload_config: &UploadConfig,
+) -> Result<UploadResult, String> {
+    let dates = {
+        let db = state.db.lock();
+        uploader::pending_dates(&db)?
+    };
+
+    let mut uploaded_count = 0;
+    for date in &dates {
+        uploaded_count += upload_date(state, upload_config, date)
+            .await?
+            .uploaded_count;
+    }
+
+    Ok(UploadResult {
+        success: true,
+        message: format!(
+            "Uploaded {} records for {} day(s)",
+            uploaded_count,
+            dates.len()
+        ),
+        uploaded_count,
+    })
+}
+
+/// 1日分を集計して送信し、結果を記録する（成功時はその日のアクティビティを送信済みにする）
+pub async fn upload_date(
+    state: &AppState,
+    upload_config: &UploadConfig,
+    date: &str,
+) -> Result<UploadResult, String> {
+    let up_to_id = {
+        let db = state.db.lock();
+        uploader::max_activity_id(&db, date)?
+    };
+
+    let result = post_day_summary(state, upload_config, date).await;
+
+    let db = state.db.lock();
+    match &result {
+        Ok(result) => {
+            if let Some(up_to_id) = up_to_id {
+                uploader::mark_uploaded(&db, date, up_to_id)?;
+            }
+            uploader::log_result(&db, date, true, &result.message, result.uploaded_count);
+        }
+        Err(e) => uploader::log_result(&db, date, false, e, 0),
+    }
+    result
+}
+
+/// 自動アップロード（設定の間隔ごとに未送信分を送信）
+pub fn start_upload_job(state: Arc<AppState>) {
+    runtime::spawn(async move {
+        loop {
+            // 設定は毎回読み直す（アプリを再起動せずに有効化・間隔変更できるように）
+            let interval_minutes = plugins::get_upload_config()
+                .map(|c| c.auto_upload_interval_minutes.max(1))
+                .unwrap_or(60);
+
+            tokio::time::sleep(Duration::from_secs(interval_minutes as u64 * 60)).await;
+
+            let upload_config = match enabled_upload_config() {
+                Ok(c) if c.auto_upload => c,
+                _ => continue,
+            };
+
+            match upload_pending(&state, &upload_config).await {
+                Ok(result) if result.uploaded_count > 0 => println!("{}", result.message),
+                Ok(_) => {}
+                Err(e) => eprintln!("Auto upload failed: {}", e),
+            }
+        }
+    });
+}
+
+/// 1日分のアクティビティを集計してPOST
+async fn post_day_summary(
+    state: &AppState,
+    upload_config: &UploadConfig,
+    date: &str,
+) -> Result<UploadResult, String> {
+    let min_duration = upload_config.min_duration_seconds as i64;
+    let profile = redaction::resolve_profile(
+        &IntegrationsConfig::load(),
+        upload_config.redaction_profile.as_deref(),
+    )?;
+
+    // ユーザー情報を取得
+    let user_id = get_user_upn().ok_or_else(|| "Failed to get user information".to_string())?;
+    let machine_name = get_machine_name();
+
+    // 指定日のアクティビティを取得して集計
+    let app_summaries = {
+        let db = state.db.lock();
+        let start_of_day = format!("{}T00:00:00", date);
+        let end_of_day = format!("{}T23:59:59", date);
+
+        // (process_name, domain) をキーにして集計
+        // ブラウザ以外: (process_name, None)
+        // ブラウザ: (process_name, Some(domain))
+        let mut totals: HashMap<(String, Option<String>), i64> = HashMap::new();
+
+        let mut stmt = db
+            .prepare(
+                "SELECT process_name, domain, duration_seconds
+                 FROM activities
+                 WHERE start_time >= ?1 AND start_time <= ?2",
+            )
+            .map_err(|e| e.to_string())?;
+
+        let rows = stmt
+            .query_map(params![start_of_day, end_of_day], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, Option<String>>(1)?,
+                    row.get::<_, i64>(2)?,
+                ))
+            })
+            .map_err(|e| e.to_string())?;
+
+        for row in rows.flatten() {
+            let (process_name, domain, duration) = row;
+
+            let key = if browser::is_browser(&process_name) {
+                // ブラウザの場合は (process_name, domain) で集計
+                let d = domain.filter(|s| !s.is_empty());
+                if d.is_some() {
+                    (process_name, d)
+                } else {
+                    continue; // ドメインがない場合はスキップ
+                }
+            } else {
+                // ブラウザ以外は (process_name, None) で集計
+                (process_name, None)
+            };
+
+            // マスキングプロファイルを適用してから集計（除去されたドメインはアプリ単位にまとまる）
+            let key = (
+                profile.process_name(&key.0).unwrap_or_default(),
+                profile.domain(key.1.as_deref()),
+            );
+
+            *totals.entry(key).or_insert(0) += duration;
+        }
+
+        // 閾値以上のものだけフィルタリング
+        let app_summaries: Vec<AppUsageSummary> = totals
+            .into_iter()
+            .filter(|(_, total)| *total >= min_duration)
+            .map(|((process_name, domain), total_seconds)| AppUsageSummary {
+                process_name,
+                total_seconds,
+                domain,
+            })
+            .collect();
+
+        app_summaries
+    };
+
+    if app_summaries.is_empty() {
+        return Ok(UploadResult {
+            success: true,
+            message: format!(
+                "No data to upload (no apps/domains used for {}+ seconds)",
+                min_duration
+            ),
+            uploaded_count: 0,
+        });
+    }
+
+    let uploaded_count = app_summaries.len();
+
+    // アップロードリクエストを作成
+    let request = UploadRequest {
+        user_id,
+        machine_name,
+        date: date.to_string(),
+        min_duration_seconds: upload_config.min_duration_seconds,
+        app_summaries,
+    };
+
+    // 連携と同じタイムアウト・再試行の設定でアップロード
+    let client = HttpClient::new();
+    let response = client
+        .post(&upload_config.server_url)
+        .header("Content-Type", "application/json")
+        .json(&request)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to upload: {}", e))?;
+
+    if response.status().is_success() {
+        Ok(UploadResult {
+            success: true,
+            message: format!(
+                "Uploaded {} records (min {}s)",
+                uploaded_count, min_duration
+            ),
+            uploaded_count,
+        })
+    } else {
+        let status = response.status();
+        let body = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Unknown error".to_string());
+        Err(format!("Upload failed with status {}: {}", status, body))
+    }
+}
+
+/// アイドル時間を記録
+fn save_idle_period(state: &AppState, start: DateTime<Local>, end: DateTime<Local>) {
+    let db = state.db.lock();
+    if let Err(e) = timeline::record_idle(
+        &db,
+        &start.format("%Y-%m-%dT%H:%M:%S").to_string(),
+        &end.format("%Y-%m-%dT%H:%M:%S").to_string(),
+    ) {
+        eprintln!("Failed to save idle period: {}", e);
+        state.metrics.db_write_error();
+    }
+}
+
+fn save_locked_period(state: &AppState, start: DateTime<Local>, end: DateTime<Local>) {
+    let db = state.db.lock();
+    if let Err(e) = timeline::record_locked(
+        &db,
+        &start.format("%Y-%m-%dT%H:%M:%S").to_string(),
+        &end.format("%Y-%m-%dT%H:%M:%S").to_string(),
+    ) {
+        eprintln!("Failed to save locked period: {}", e);
+        state.metrics.db_write_error();
+    }
+}
+
+/// ループの間隔がこれを超えて空いたらスリープしていたとみなす
+const SLEEP_DETECTION_GAP_SECONDS: i64 = 30;
+
+/// フックがあるときに切り替えの通知を待つ上限
+const HOOK_FALLBACK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// フックがあるときの待機時間（アイドルの判定と書き出しが遅れない範囲で長く待つ）
+fn hook_wait_timeout(state: &AppState, last_flush: Instant, interval: Duration) -> Duration {
+    let mut timeout = HOOK_FALLBACK_INTERVAL;
+    let idle = state.idle.lock().clone();
+    if idle.enabled {
+        let remaining = idle.threshold_seconds.saturating_sub(get_idle_seconds());
+        timeout = timeout.min(Duration::from_secs(remaining));
+    }
+    if !state.activity_buffer.lock().is_empty() {
+        timeout = timeout.min(ACTIVITY_FLUSH_INTERVAL.saturating_sub(last_flush.elapsed()));
+    }
+    timeout.max(interval)
+}
+
+/// 監視スレッドで起きた変化の通知先（GUI はトレイを更新する）
+pub trait WatcherObserver: Send + 'static {
+    /// 今日の合計を更新した
+    fn today_updated(&self, state: &AppState);
+    /// アイドル・画面ロックなどで記録の状態が変わった
+    fn status_changed(&self, state: &AppState);
+}
+
+/// アクティブウィンドウの監視を開始（observer が None なら通知しない、CLI の常駐用）
+pub fn start_watcher_thread(observer: Option<Box<dyn WatcherObserver>>, state: Arc<AppState>) {
+    thread::spawn(move || {
+        let mut last_process = String::new();
+        let mut last_title = String::new();
+        let mut last_domain: Option<String> = None;
+        let mut activity_start: Option<DateTime<Local>> = None;
+        // メタデータ取得済みのプロセス（起動中は1プロセスにつき1回だけ取得）
+        let mut known_processes: HashSet<String> = HashSet::new();
+        let polling = IntegrationsConfig::load().polling;
+        let mut interval = polling.interval();
+        // フックが使えればウィンドウの切り替えで即座に起きる（使えなければポーリングのみ）
+        let hooked = window_events::start();
+        if !hooked && cfg!(target_os = "windows") {
+            eprintln!("Failed to install foreground hook; falling back to polling");
+        }
+        // アイドル中なら最後の入力時刻
+        let mut idle_since: Option<DateTime<Local>> = None;
+        let mut last_flush = Instant::now();
+        // 画面ロック・スリープに入った時刻
+        let mut away_since: Option<DateTime<Local>> = None;
+        let mut last_tick = Local::now();
+        // 待機を除いたループ1回の処理時間（continue でも次の待機の前に計る）
+        let mut loop_started: Option<Instant> = None;
+
+        loop {
+            if let Some(started) = loop_started.take() {
+                state.metrics.observe_watcher_loop(started.elapsed());
+            }
+            // アイドル中は入力の再開を通知で受け取れないのでポーリングする
+            let timeout = if hooked && idle_since.is_none() {
+                hook_wait_timeout(&state, last_flush, interval)
+            } else {
+                interval
+            };
+            window_events::wait(timeout);
+            loop_started = Some(Instant::now());
+            let idle_config = state.idle.lock().clone();
+            // 同じウィンドウが続いたときだけ下で間隔を延ばす
+            let current_interval = std::mem::replace(&mut interval, polling.interval());
+
+            // 通知を受け取れない環境でも、ループが長く止まっていたらスリープしていたとみなす
+            let now = Local::now();
+            let slept_since = Some(last_tick).filter(|tick| {
+                (now - *tick).num_seconds()
+                    > timeout.as_secs() as i64 + SLEEP_DETECTION_GAP_SECONDS
+            });
+            last_tick = now;
+
+            if last_flush.elapsed() >= ACTIVITY_FLUSH_INTERVAL
+                || state.activity_buffer.lock().len() >= ACTIVITY_BUFFER_LIMIT
+            {
+                flush_activities(&state);
+                last_flush = Instant::now();
+
+                // 保存し終えたところで今日の合計をトレイに反映する
+                let open_seconds =
+                    activity_start.map_or(0, |start| (Local::now() - start).num_seconds().max(0));
+                update_today_seconds(&state, open_seconds);
+                if let Some(observer) = &observer {
+                    observer.today_updated(&state);
+                }
+
+                // 異常終了しても進行中のアクティビティを失わないよう書き出しておく
+                let open = activity_start
+                    .filter(|_| {
+                        !state.exclusions.is_excluded(
+                            &last_process,
+                            &last_title,
+                            last_domain.as_deref(),
+                        )
+                    })
+                    .map(|start| OpenActivity {
+                        process_name: last_process.clone(),
+                        window_title: last_title.clone(),
+                        domain: last_domain.clone(),
+                        start,
+                        last_seen: Local::now(),
+                    });
+                checkpoint_activity(&state, open.as_ref());
+            }
+
+            if !*state.is_tracking.lock() {
+                // Save current activity before pausing
+                if let Some(start) = activity_start.take() {
+                    save_activity(
+                        &state,
+                        &last_process,
+                        &last_title,
+                        last_domain.as_deref(),
+                        start,
+                        Local::now(),
+                    );
+                    flush_activities(&state);
+                }
+                if let Some(since) = idle_since.take() {
+                    save_idle_period(&state, since, Local::now());
+                    *state.is_idle.lock() = false;
+                    if let Some(observer) = &observer {
+                        observer.status_changed(&state);
+                    }
+                }
+                if let Some(since) = away_since.take() {
+                    if idle_config.record_locked {
+                        save_locked_period(&state, since, Local::now());
+                    }
+                }
+                last_process.clear();
+                last_title.clear();
+                last_domain = None;
+                continue;
+            }
+
+            // ロック・スリープに入ったらその時点で記録を区切る
+            let locked = window_events::is_away();
+            if let Some(since) = slept_since.or_else(|| locked.then_some(now)) {
+                if away_since.is_none() {
+                    away_since = Some(since);
+                    if let Some(start) = activity_start.take() {
+                        save_activity(
+                            &state,
+                            &last_process,
+                            &last_title,
+                            last_domain.as_deref(),
+                            start,
+                            since.max(start),
+                        );
+                    }
+                    if let Some(idle_start) = idle_since.take() {
+                        save_idle_period(&state, idle_start, since.max(idle_start));
+                        *state.is_idle.lock() = false;
+                        if let Some(observer) = &observer {
+                            observer.status_changed(&state);
+                        }
+                    }
+                    last_process.clear();
+                    last_title.clear();
+                    last_domain = None;
+                }
+            }
+            if locked {
+                continue;
+            }
+            if let Some(since) = away_since.take() {
+                if idle_config.record_locked {
+                    save_locked_period(&state, since, now);
+                }
+            }
+
+            if let Some((
+                source,
+                CapturedWindow {
+                    process_name,
+                    window_title,
+                    domain,
+                },
+            )) = state.capture.current()
+            {
+                // 入力が一定時間なければアイドルとして記録を区切る（手動タイマーなどは対象外）
+                if idle_config.enabled && source == capture::SOURCE_FOREGROUND {
+                    let idle_seconds = get_idle_seconds();
+                    if idle_seconds >= idle_config.threshold_seconds {
+                        if idle_since.is_none() {
+                            let since =
+                                Local::now() - chrono::Duration::seconds(idle_seconds as i64);
+                            idle_since = Some(since);
+
+                            // 最後の入力以降の時間は記録しない
+                            if let Some(start) = activity_start.take() {
+                                save_activity(
+                                    &state,
+                                    &last_process,
+                                    &last_title,
+                                    last_domain.as_deref(),
+                                    start,
+                                    since.max(start),
+                                );
+                            }
+                            last_process.clear();
+                            last_title.clear();
+                            last_domain = None;
+
+                            state.live.publish(LiveEvent::Idle {
+                                idle: true,
+                                at: since.format("%Y-%m-%dT%H:%M:%S").to_string(),
+                            });
+                            *state.is_idle.lock() = true;
+                            if let Some(observer) = &observer {
+                                observer.status_changed(&state);
+                            }
+                        }
+                        continue;
+                    }
+                }
+
+                if let Some(since) = idle_since.take() {
+                    save_idle_period(&state, since, Local::now());
+                    state.live.publish(LiveEvent::Idle {
+                        idle: false,
+                        at: Local::now().format("%Y-%m-%dT%H:%M:%S").to_string(),
+                    });
+                    *state.is_idle.lock() = false;
+                    if let Some(observer) = &observer {
+                        observer.status_changed(&state);
+                    }
+                }
+
+                let changed = process_name != last_process
+                    || window_title != last_title
+                    || domain != last_domain;
+
+                if changed {
+                    // Save previous activity
+                    if let Some(start) = activity_start.take() {
+                        save_activity(
+                            &state,
+                            &last_process,
+                            &last_title,
+                            last_domain.as_deref(),
+                            start,
+                            Local::now(),
+                        );
+                    }
+
+                    // 除外対象のアプリはメタデータも保存しない
+                    if source == capture::SOURCE_FOREGROUND
+                        && !known_processes.contains(&process_name)
+                        && !state.exclusions.is_excluded(
+                            &process_name,
+                            &window_title,
+                            domain.as_deref(),
+                        )
+                    {
+                        if let Some(metadata) = get_foreground_process_metadata() {
+                            if metadata.process_name == process_name {
+                                save_process_metadata(&state, &metadata);
+                                known_processes.insert(process_name.clone());
+                            }
+                        }
+                    }
+
+                    // モニター・仮想デスクトップは OS のフォアグラウンドウィンドウのみ取得できる
+                    *state.current_placement.lock() = if source == capture::SOURCE_FOREGROUND {
+                        get_foreground_placement()
+                    } else {
+                        None
+                    };
+
+                    // Start new activity
+                    let now = Local::now();
+                    if !state.exclusions.is_excluded(
+                        &process_name,
+                        &window_title,
+                        domain.as_deref(),
+                    ) {
+                        state.live.publish(LiveEvent::ActivityStart {
+                            process_name: process_name.clone(),
+                            window_title: window_title.clone(),
+                            domain: domain.clone(),
+                            source,
+                            start_time: now.format("%Y-%m-%dT%H:%M:%S").to_string(),
+                        });
+                    }
+
+                    last_process = process_name;
+                    last_title = window_title;
+                    last_domain = domain;
+                    activity_start = Some(now);
+                } else {
+                    interval = polling.backoff(current_interval);
+                }
+            }
+        }
+    });
+}
+
+fn save_activity(
+    state: &Arc<AppState>,
+    process_name: &str,
+    window_title: &str,
+    domain: Option<&str>,
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+) {
+    let project_override = state.project_override.lock().clone();
+    let placement = state.current_placement.lock().take().unwrap_or_default();
+    // 計測中でなければ None（入力回数は NULL のまま保存する）
+    let input = input_meter::take_counts();
+
+    if process_name.is_empty() {
+        return;
+    }
+
+    let duration = (end - start).num_seconds();
+
+    if duration < 1 {
+        return;
+    }
+
+    // 除外対象はタイトルを含めて一切保存しない
+    if state
+        .exclusions
+        .is_excluded(process_name, window_title, domain)
+    {
+        return;
+    }
+
+    // 保存しない記録では消費せず、次に保存する記録に割り当てる（短すぎて捨てる場合は下で戻す）
+    let pending = state.current_assignment.lock().take();
+    let unconsumed = pending.clone();
+
+    // 正規化で変わったタイトルは元のタイトルも残す（ルールを変えて正規化し直せるように）
+    let normalized_title = state.title_normalizer.normalize(process_name, window_title);
+    let raw_window_title = (normalized_title != window_title).then(|| window_title.to_string());
+    let window_title = normalized_title.as_str();
+    let category = state
+        .categorizer
+        .classify(process_name, window_title, domain);
+
+    let start_time = start.format("%Y-%m-%dT%H:%M:%S").to_string();
+    let end_time = end.format("%Y-%m-%dT%H:%M:%S").to_string();
+
+    // 手動で指定した割り当て > プロジェクトの上書き > 割り当てルール > プロジェクトの検出の順に適用する
+    let detected_project = state.projects.detect(window_title, domain);
+    let assignment = if let Some(pending) = pending {
+        Some(BufferedAssignment {
+            plugin: pending.plugin,
+            ticket_id: pending.ticket_id,
+            project: pending.project.or(project_override),
+            source: assignments::SOURCE_MANUAL,
+        })
+    } else if let Some(project) = project_override {
+        Some(BufferedAssignment {
+            plugin: None,
+            ticket_id: None,
+            project: Some(project),
+            source: assignments::SOURCE_OVERRIDE,
+        })
+    } else {
+        state
+            .rule_assigner
+            .find_match(process_name, window_title, domain)
+            .map(|rule| BufferedAssignment {
+                plugin: rule.plugin,
+                ticket_id: rule.ticket_id,
+                project: rule.project.or_else(|| detected_project.clone()),
+                source: assignments::SOURCE_RULE,
+            })
+            .or_else(|| {
+                detected_project.map(|project| BufferedAssignment {
+                    plugin: None,
+                    ticket_id: None,
+                    project: Some(project),
+                    source: assignments::SOURCE_PROJECT,
+                })
+            })
+    };
+
+    // 連携プラグインで抽出したチケットはアクティビティに保存しておく（同期・集計のたびに抽出し直さない）
+    let ticket = state.plugin_manager.extract_ticket_id(&ActivityInfo {
+        id: 0,
+        process_name: process_name.to_string(),
+        window_title: window_title.to_string(),
+        domain: domain.map(str::to_string),
+        start_time: start_time.clone(),
+        end_time: end_time.clone(),
+        duration_seconds: duration,
+        note: None,
+        project: None,
+        category: None,
+    });
+
+    let activity = BufferedActivity {
+        process_name: process_name.to_string(),
+        window_title: window_title.to_string(),
+        raw_window_title,
+        domain: domain.map(str::to_string),
+        start_time,
+        end_time,
+        duration_seconds: duration,
+        category,
+        placement,
+        input,
+        assignment,
+        ticket,
+    };
+
+    let coalesce = state.coalesce.lock().clone();
+    let coalesced =
+        coalesce.enabled && coalesce_activity(state, &activity, coalesce.merge_gap_seconds);
+    // 素早い切り替えで生まれる数秒の記録は、まとめられなければ捨てる
+    if !coalesced && duration < coalesce.min_segment_seconds {
+        // 手動の割り当てと入力回数は次に保存する記録に回す（その間に新しく指定された割り当てを優先）
+        if let Some(pending) = unconsumed {
+            state.current_assignment.lock().get_or_insert(pending);
+        }
+        if let Some(input) = activity.input {
+            input_meter::put_back(input);
+        }
+        return;
+    }
+
+    state.live.publish(LiveEvent::ActivityEnd {
+        process_name: activity.process_name.clone(),
+        window_title: activity.window_title.clone(),
+        domain: activity.domain.clone(),
+        start_time: activity.start_time.clone(),
+        end_time: activity.end_time.clone(),
+        duration_seconds: duration,
+    });
+    state
+        .metrics
+        .observe_tracked(process_name, activity.category.as_deref(), duration);
+    if !coalesced {
+        state.activity_buffer.lock().push(activity);
+    }
+}
+
+/// 直前の記録と同じアプリ・タイトル・割り当てで merge_gap_seconds 以内に続くなら直前の記録に足す
+///
+/// 書き込み待ちがなければ最後に保存した記録に足す（同期・アップロード済みや送信候補に含まれる記録は変えない）。
+fn coalesce_activity(state: &AppState, activity: &BufferedActivity, gap_seconds: i64) -> bool {
+    let continues = |end_time: &str| {
+        let parse = |s: &str| NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S").ok();
+        parse(end_time)
+            .zip(parse(&activity.start_time))
+            .is_some_and(|(end, start)| (start - end).num_seconds() <= gap_seconds)
+    };
+
+    {
+        let mut buffer = state.activity_buffer.lock();
+        if let Some(last) = buffer.last_mut() {
+            let same = last.process_name == activity.process_name
+                && last.window_title == activity.window_title
+                && last.domain == activity.domain
+                && last.assignment == activity.assignment;
+            if !same || !continues(&last.end_time) {
+                return false;
+            }
+            last.end_time = activity.end_time.clone();
+            last.duration_seconds += activity.duration_seconds;
+            if let Some(input) = activity.input {
+                let counts = last.input.get_or_insert_with(InputCounts::default);
+                counts.keystrokes += input.keystrokes;
+                counts.clicks += input.clicks;
+            }
+            return true;
+        }
+    }
+
+    let db = state.db.lock();
+    let last: Option<(i64, String, String, Option<String>, String)> = db
+        .query_row(
+            "SELECT id, process_name, window_title, domain, end_time
+             FROM activities a
+             WHERE uploaded_at IS NULL
+               AND NOT EXISTS (SELECT 1 FROM sync_log s WHERE s.activity_id = a.id)
+               AND NOT EXISTS (
+                   SELECT 1 FROM pending_sync p, json_each(p.activity_ids) j
+                   WHERE j.value = a.id
+               )
+             ORDER BY start_time DESC, id DESC
+             LIMIT 1",
+            [],
+            |row| {
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get(3)?,
+                    row.get(4)?,
+                ))
+            },
+        )
+        .optional()
+        .ok()
+        .flatten();
+    let Some((id, last_process, last_title, last_domain, last_end)) = last else {
+        return false;
+    };
+    if last_process != activity.process_name
+        || last_title != activity.window_title
+        || last_domain != activity.domain
+        || !continues(&last_end)
+    {
+        return false;
+    }
+    let last_assignment = assignments::get(&db, id).ok().flatten();
+    let same_assignment = match (&last_assignment, &activity.assignment) {
+        (None, None) => true,
+        (Some(a), Some(b)) => {
+            a.plugin == b.plugin && a.ticket_id == b.ticket_id && a.project == b.project
+        }
+        _ => false,
+    };
+    if !same_assignment {
+        return false;
+    }
+
+    let updated = db.execute(
+        "UPDATE activities
+         SET end_time = ?2, duration_seconds = duration_seconds + ?3,
+             keystrokes = CASE WHEN ?4 IS NULL THEN keystrokes ELSE COALESCE(keystrokes, 0) + ?4 END,
+             clicks = CASE WHEN ?5 IS NULL THEN clicks ELSE COALESCE(clicks, 0) + ?5 END
+         WHERE id = ?1",
+        params![
+            id,
+            activity.end_time,
+            activity.duration_seconds,
+            activity.input.map(|input| input.keystrokes as i64),
+            activity.input.map(|input| input.clicks as i64),
+        ],
+    );
+    match updated {
+        Ok(_) => true,
+        Err(e) => {
+            eprintln!("Failed to coalesce activity: {}", e);
+            false
+        }
+    }
+}
+
+/// 書き込み待ちのアクティビティをこの間隔でまとめて保存する
+const ACTIVITY_FLUSH_INTERVAL: Duration = Duration::from_secs(10);
+/// 書き込み待ちがこの件数に達したら間隔を待たずに保存する
+const ACTIVITY_BUFFER_LIMIT: usize = 50;
+
+/// DBへの書き込み待ちのアクティビティ
+struct BufferedActivity {
+    process_name: String,
+    window_title: String,
+    /// 正規化前のタイトル（正規化で変わらなければ None）
+    raw_window_title: Option<String>,
+    domain: Option<String>,
+    start_time: String,
+    end_time: String,
+    duration_seconds: i64,
+    category: Option<String>,
+    placement: WindowPlacement,
+    input: Option<InputCounts>,
+    assignment: Option<BufferedAssignment>,
+    /// 連携プラグインで抽出したチケット（プラグイン名, チケットID）
+    ticket: Option<(String, String)>,
+}
+
+/// 保存時に適用する割り当て
+#[derive(PartialEq)]
+struct BufferedAssignment {
+    plugin: Option<String>,
+    ticket_id: Option<String>,
+    project: Option<String>,
+    source: &'static str,
+}
+
+/// 進行中のアクティビティを書き出す（None なら消す）
+fn checkpoint_activity(state: &AppState, open: Option<&OpenActivity>) {
+    let db = state.db.lock();
+    let result = match open {
+        Some(activity) => checkpoint::save(&db, activity),
+        None => checkpoint::clear(&db),
+    };
+    if let Err(e) = result {
+        eprintln!("Failed to checkpoint current activity: {}", e);
+        state.metrics.db_write_error();
+    }
+}
+
+/// 前回の終了時（クラッシュ・電源断を含む）に保存できなかったアクティビティを最後の確認時刻までで保存する
+pub fn recover_open_activity(state: &Arc<AppState>) {
+    let open = {
+        let db = state.db.lock();
+        checkpoint::take(&db)
+    };
+    match open {
+        Ok(Some(activity)) => {
+            save_activity(
+                state,
+                &activity.process_name,
+                &activity.window_title,
+                activity.domain.as_deref(),
+                activity.start,
+                activity.last_seen,
+            );
+            flush_activities(state);
+        }
+        Ok(None) => {}
+        Err(e) => eprintln!("Failed to recover current activity: {}", e),
+    }
+}
+
+/// 書き込み待ちのアクティビティを1トランザクションで保存（失敗したら書き込み待ちに戻して次回に再試行）
+pub fn flush_activities(state: &AppState) {
+    let buffered = std::mem::take(&mut *state.activity_buffer.lock());
+    if buffered.is_empty() {
+        return;
+    }
+
+    let result = insert_buffered_activities(&state.db.lock(), &buffered);
+    match result {
+        Ok(()) => state.live.publish(LiveEvent::ActivitiesSaved {
+            count: buffered.len(),
+        }),
+        Err(e) => {
+            eprintln!(
+                "Failed to save {} activities, will retry: {}",
+                buffered.len(),
+                e
+            );
+            state.metrics.db_write_error();
+            // 保存できなかった分は、その間に追加されたものより前に戻す
+            let mut buffer = state.activity_buffer.lock();
+            let newer = std::mem::replace(&mut *buffer, buffered);
+            buffer.extend(newer);
+        }
+    }
+}
+
+fn insert_buffered_activities(
+    db: &Connection,
+    buffered: &[BufferedActivity],
+) -> Result<(), String> {
+    let extracted_at = Local::now().format("%Y-%m-%dT%H:%M:%S").to_string();
+    let tx = db.unchecked_transaction().map_err(|e| e.to_string())?;
+    for activity in buffered {
+        tx.execute(
+            "INSERT INTO activities (process_name, window_title, domain, start_time, end_time, duration_seconds, category,
+                                     monitor, monitor_primary, virtual_desktop, keystrokes, clicks,
+                                     extracted_plugin, extracted_ticket_id, extracted_at, raw_window_title)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
+            params![
+                activity.process_name,
+                activity.window_title,
+                activity.domain,
+                activity.start_time,
+                activity.end_time,
+                activity.duration_seconds,
+                activity.category,
+                activity.placement.monitor,
+                activity.placement.primary,
+                activity.placement.virtual_desktop,
+                activity.input.map(|input| input.keystrokes as i64),
+                activity.input.map(|input| input.clicks as i64),
+                activity.ticket.as_ref().map(|(plugin, _)| plugin),
+                activity.ticket.as_ref().map(|(_, ticket_id)| ticket_id),
+                extracted_at,
+                activity.raw_window_title,
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+
+        if let Some(assignment) = &activity.assignment {
+            assignments::assign(
+                &tx,
+                tx.last_insert_rowid(),
+                assignment.plugin.as_deref(),
+                assignment.ticket_id.as_deref(),
+                assignment.project.as_deref(),
+                assignment.source,
+            )?;
+        }
+    }
+    tx.commit().map_err(|e| e.to_string())
+}
+
+fn save_process_metadata(state: &Arc<AppState>, metadata: &ProcessMetadata) {
+    let db = state.db.lock();
+    let _ = db.execute(
+        "INSERT INTO process_metadata (process_name, exe_path, company_name, product_name, file_version, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+         ON CONFLICT(process_name) DO UPDATE SET
+            exe_path = excluded.exe_path,
+            company_name = excluded.company_name,
+            product_name = excluded.product_name,
+            file_version = excluded.file_version,
+            updated_at = excluded.updated_at",
+        params![
+            metadata.process_name,
+            metadata.exe_path,
+            metadata.company_name,
+            metadata.product_name,
+            metadata.file_version,
+            Local::now().format("%Y-%m-%dT%H:%M:%S").to_string(),
+        ],
+    );
+}
+
+/// 保存済みの今日の記録時間に進行中の秒数を足して today_seconds を更新
+pub fn update_today_seconds(state: &AppState, open_seconds: i64) {
+    let today = Local::now().format("%Y-%m-%d").to_string();
+    let saved: Result<i64, _> = state.db.lock().query_row(
+        "SELECT COALESCE(SUM(duration_seconds), 0) FROM activities
+         WHERE start_time >= ?1 AND start_time <= ?2",
+        params![format!("{}T00:00:00", today), format!("{}T23:59:59", today)],
+        |row| row.get(0),
+    );
+    match saved {
+        Ok(saved) => *state.today_seconds.lock() = saved + open_seconds,
+        Err(e) => eprintln!("Failed to load today's total: {}", e),
+    }
+}
+
+/// "3h 05m" 形式
+pub fn format_hours_minutes(seconds: i64) -> String {
+    format!("{}h {:02}m", seconds / 3600, (seconds % 3600) / 60)
+}
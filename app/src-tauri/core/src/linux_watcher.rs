@@ -0,0 +1,244 @@
+use serde_json::Value;
+use std::process::Command;
+use std::sync::OnceLock;
+
+use crate::browser;
+
+/// 実行時に選択するウィンドウ取得の方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Backend {
+    /// Sway (swaymsg の IPC)
+    Sway,
+    /// Hyprland (hyprctl の IPC)
+    Hyprland,
+    /// GNOME Shell 拡張 "Window Calls" のD-Busブリッジ
+    GnomeWindowCalls,
+    /// X11 / XWayland (_NET_ACTIVE_WINDOW)
+    X11,
+}
+
+/// フォアグラウンドウィンドウ（プロセスIDはメタデータ取得用）
+struct ActiveWindow {
+    title: String,
+    pid: Option<u32>,
+    class: Option<String>,
+}
+
+static BACKEND: OnceLock<Option<Backend>> = OnceLock::new();
+
+fn env_set(name: &str) -> bool {
+    std::env::var_os(name).is_some_and(|v| !v.is_empty())
+}
+
+fn run(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn backend() -> Option<Backend> {
+    *BACKEND.get_or_init(|| {
+        let backend = if env_set("WAYLAND_DISPLAY") {
+            if env_set("SWAYSOCK") {
+                Some(Backend::Sway)
+            } else if env_set("HYPRLAND_INSTANCE_SIGNATURE") {
+                Some(Backend::Hyprland)
+            } else if gnome_window_calls_active().is_some() {
+                Some(Backend::GnomeWindowCalls)
+            } else if env_set("DISPLAY") {
+                // XWayland のアプリのみ取得できる
+                Some(Backend::X11)
+            } else {
+                None
+            }
+        } else if env_set("DISPLAY") {
+            Some(Backend::X11)
+        } else {
+            None
+        };
+
+        match backend {
+            Some(backend) => println!("Using {:?} window watcher backend", backend),
+            None => eprintln!("No supported display server found; falling back to replay"),
+        }
+        backend
+    })
+}
+
+/// ネイティブのウィンドウ取得が使えるか（使えなければリプレイにフォールバックする）
+pub fn is_available() -> bool {
+    backend().is_some()
+}
+
+fn active_window() -> Option<ActiveWindow> {
+    match backend()? {
+        Backend::Sway => sway_active(),
+        Backend::Hyprland => hyprland_active(),
+        Backend::GnomeWindowCalls => gnome_window_calls_active(),
+        Backend::X11 => x11_active(),
+    }
+}
+
+fn sway_active() -> Option<ActiveWindow> {
+    let tree: Value = serde_json::from_str(&run("swaymsg", &["-t", "get_tree"])?).ok()?;
+
+    fn find_focused(node: &Value) -> Option<&Value> {
+        if node["focused"].as_bool() == Some(true) && node["pid"].is_u64() {
+            return Some(node);
+        }
+        ["nodes", "floating_nodes"]
+            .iter()
+            .filter_map(|key| node[*key].as_array())
+            .flatten()
+            .find_map(find_focused)
+    }
+
+    let node = find_focused(&tree)?;
+    Some(ActiveWindow {
+        title: node["name"].as_str().unwrap_or("").to_string(),
+        pid: node["pid"].as_u64().map(|pid| pid as u32),
+        class: node["app_id"]
+            .as_str()
+            .or_else(|| node["window_properties"]["class"].as_str())
+            .map(str::to_string),
+    })
+}
+
+fn hyprland_active() -> Option<ActiveWindow> {
+    let window: Value = serde_json::from_str(&run("hyprctl", &["activewindow", "-j"])?).ok()?;
+    Some(ActiveWindow {
+        title: window["title"].as_str().unwrap_or("").to_string(),
+        pid: window["pid"].as_u64().map(|pid| pid as u32),
+        class: window["class"].as_str().map(str::to_string),
+    })
+}
+
+/// gdbus の戻り値 `('...',)` から文字列部分を取り出す
+fn unwrap_gdbus_string(output: &str) -> Option<String> {
+    let inner = output.strip_prefix("('")?.strip_suffix("',)")?;
+    Some(inner.replace("\\'", "'").replace("\\\\", "\\"))
+}
+
+fn gnome_window_calls(method: &str, args: &[&str]) -> Option<String> {
+    let method = format!("org.gnome.Shell.Extensions.Windows.{}", method);
+    let mut command = vec![
+        "call",
+        "--session",
+        "--dest",
+        "org.gnome.Shell",
+        "--object-path",
+        "/org/gnome/Shell/Extensions/Windows",
+        "--method",
+        method.as_str(),
+    ];
+    command.extend_from_slice(args);
+    unwrap_gdbus_string(&run("gdbus", &command)?)
+}
+
+fn gnome_window_calls_active() -> Option<ActiveWindow> {
+    let windows: Value = serde_json::from_str(&gnome_window_calls("List", &[])?).ok()?;
+    let window = windows
+        .as_array()?
+        .iter()
+        .find(|w| w["focus"].as_bool() == Some(true))?;
+
+    let id = window["id"].as_u64()?.to_string();
+    Some(ActiveWindow {
+        title: gnome_window_calls("GetTitle", &[id.as_str()]).unwrap_or_default(),
+        pid: window["pid"].as_u64().map(|pid| pid as u32),
+        class: window["wm_class"].as_str().map(str::to_string),
+    })
+}
+
+/// xprop の `NAME = value` / `NAME: ... # value` 形式から値を取り出す
+fn xprop_value<'a>(output: &'a str, name: &str) -> Option<&'a str> {
+    output
+        .lines()
+        .find(|line| line.starts_with(name))
+        .and_then(|line| line.split_once(" = ").or_else(|| line.split_once(" # ")))
+        .map(|(_, value)| value.trim())
+}
+
+fn x11_active() -> Option<ActiveWindow> {
+    let root = run("xprop", &["-root", "-notype", "_NET_ACTIVE_WINDOW"])?;
+    let window_id = xprop_value(&root, "_NET_ACTIVE_WINDOW")?;
+    if window_id == "0x0" {
+        return None;
+    }
+
+    let props = run(
+        "xprop",
+        &[
+            "-id",
+            window_id,
+            "-notype",
+            "_NET_WM_NAME",
+            "WM_NAME",
+            "_NET_WM_PID",
+            "WM_CLASS",
+        ],
+    )?;
+
+    let unquote = |value: &str| value.trim_matches('"').replace("\\\"", "\"");
+    let title = xprop_value(&props, "_NET_WM_NAME")
+        .or_else(|| xprop_value(&props, "WM_NAME"))
+        .map(unquote)
+        .unwrap_or_default();
+    let pid = xprop_value(&props, "_NET_WM_PID").and_then(|v| v.parse().ok());
+    // WM_CLASS = "instance", "Class"
+    let class = xprop_value(&props, "WM_CLASS")
+        .and_then(|v| v.split(", ").last())
+        .map(unquote);
+
+    Some(ActiveWindow { title, pid, class })
+}
+
+fn process_name(pid: u32) -> Option<String> {
+    let comm = std::fs::read_to_string(format!("/proc/{}/comm", pid)).ok()?;
+    Some(comm.trim().to_string()).filter(|name| !name.is_empty())
+}
+
+pub fn get_active_window_info() -> Option<(String, String, Option<String>)> {
+    let window = active_window()?;
+    let process_name = window
+        .pid
+        .and_then(process_name)
+        .or(window.class)
+        .unwrap_or_else(|| String::from("Unknown"));
+
+    // アドレスバーは読めないため、タイトルに含まれるURLからドメインを推定する
+    let domain = if browser::is_browser(&process_name) {
+        browser::domain_from_title(&window.title)
+    } else {
+        None
+    };
+
+    Some((process_name, window.title, domain))
+}
+
+pub fn get_foreground_process_metadata() -> Option<crate::ProcessMetadata> {
+    let window = active_window()?;
+    let pid = window.pid?;
+    Some(crate::ProcessMetadata {
+        process_name: process_name(pid)?,
+        exe_path: std::fs::read_link(format!("/proc/{}/exe", pid))
+            .ok()
+            .map(|path| path.to_string_lossy().to_string()),
+        company_name: None,
+        product_name: window.class,
+        file_version: None,
+    })
+}
+
+/// 最後の入力からの経過秒数（X11 のみ xprintidle で取得、Wayland では 0）
+pub fn get_idle_seconds() -> u64 {
+    if backend() != Some(Backend::X11) || env_set("WAYLAND_DISPLAY") {
+        return 0;
+    }
+    run("xprintidle", &[])
+        .and_then(|ms| ms.parse::<u64>().ok())
+        .map(|ms| ms / 1000)
+        .unwrap_or(0)
+}
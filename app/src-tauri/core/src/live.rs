@@ -0,0 +1,291 @@
+use chrono::{Local, NaiveDateTime};
+use futures_util::{SinkExt, StreamExt};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast;
+use tokio_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tokio_tungstenite::tungstenite::http::StatusCode;
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::api_auth;
+use crate::plugins::config::LiveStreamConfig;
+use crate::AppState;
+
+/// リアルタイムに配信するイベント
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LiveEvent {
+    /// 新しいアクティビティの開始
+    ActivityStart {
+        process_name: String,
+        window_title: String,
+        domain: Option<String>,
+        source: String,
+        start_time: String,
+    },
+    /// アクティビティの終了（書き込み待ちに追加済み）
+    ActivityEnd {
+        process_name: String,
+        window_title: String,
+        domain: Option<String>,
+        start_time: String,
+        end_time: String,
+        duration_seconds: i64,
+    },
+    /// 書き込み待ちのアクティビティをDBに保存した
+    ActivitiesSaved { count: usize },
+    /// アイドル状態の変化
+    Idle { idle: bool, at: String },
+    /// トラッキングの開始/停止（paused は自動再開つきの一時停止）
+    TrackingState {
+        tracking: bool,
+        #[serde(default)]
+        paused: bool,
+    },
+    /// 外部サービスへの同期の完了
+    SyncCompleted {
+        plugin: String,
+        ticket_id: String,
+        activity_ids: Vec<i64>,
+        external_id: Option<String>,
+    },
+    /// integrations.toml を読み直した（error があれば以前の設定のまま）
+    ConfigReloaded { error: Option<String> },
+}
+
+/// ウィンドウが切り替わったときにフロントエンドへ送るイベント名
+pub const EVENT_ACTIVITY_CHANGED: &str = "activity-changed";
+/// アクティビティが終了したとき
+pub const EVENT_ACTIVITY_ENDED: &str = "activity-ended";
+/// アクティビティをDBに保存したとき（履歴を読み直すきっかけ）
+pub const EVENT_ACTIVITY_SAVED: &str = "activity-saved";
+/// トラッキングの開始・停止・一時停止
+pub const EVENT_TRACKING_STATE_CHANGED: &str = "tracking-state-changed";
+/// 外部サービスへの同期が完了したとき
+pub const EVENT_SYNC_COMPLETED: &str = "sync-completed";
+/// アイドル状態の変化
+pub const EVENT_IDLE_CHANGED: &str = "idle-changed";
+/// 設定ファイルを読み直したとき
+pub const EVENT_CONFIG_RELOADED: &str = "config-reloaded";
+
+impl LiveEvent {
+    /// フロントエンド・SSE で使うイベント名
+    pub fn event_name(&self) -> &'static str {
+        match self {
+            LiveEvent::ActivityStart { .. } => EVENT_ACTIVITY_CHANGED,
+            LiveEvent::ActivityEnd { .. } => EVENT_ACTIVITY_ENDED,
+            LiveEvent::ActivitiesSaved { .. } => EVENT_ACTIVITY_SAVED,
+            LiveEvent::Idle { .. } => EVENT_IDLE_CHANGED,
+            LiveEvent::TrackingState { .. } => EVENT_TRACKING_STATE_CHANGED,
+            LiveEvent::SyncCompleted { .. } => EVENT_SYNC_COMPLETED,
+            LiveEvent::ConfigReloaded { .. } => EVENT_CONFIG_RELOADED,
+        }
+    }
+}
+
+/// 保存前の進行中のアクティビティ
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CurrentActivity {
+    pub process_name: String,
+    pub window_title: String,
+    pub domain: Option<String>,
+    pub source: String,
+    pub start_time: String,
+    /// 開始からの経過秒数（取得した時点）
+    pub elapsed_seconds: i64,
+}
+
+/// イベントの配信チャネル（購読者がいなければ破棄する）
+pub struct LiveEvents {
+    sender: broadcast::Sender<LiveEvent>,
+    /// 最後の ActivityStart（終了・アイドル・停止で消す）
+    current: Mutex<Option<CurrentActivity>>,
+}
+
+impl LiveEvents {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(256);
+        Self {
+            sender,
+            current: Mutex::new(None),
+        }
+    }
+
+    pub fn publish(&self, event: LiveEvent) {
+        match &event {
+            LiveEvent::ActivityStart {
+                process_name,
+                window_title,
+                domain,
+                source,
+                start_time,
+            } => {
+                *self.current.lock() = Some(CurrentActivity {
+                    process_name: process_name.clone(),
+                    window_title: window_title.clone(),
+                    domain: domain.clone(),
+                    source: source.clone(),
+                    start_time: start_time.clone(),
+                    elapsed_seconds: 0,
+                });
+            }
+            LiveEvent::ActivityEnd { .. }
+            | LiveEvent::Idle { idle: true, .. }
+            | LiveEvent::TrackingState {
+                tracking: false, ..
+            } => {
+                *self.current.lock() = None;
+            }
+            _ => {}
+        }
+        let _ = self.sender.send(event);
+    }
+
+    /// 進行中のアクティビティ（経過秒数は呼び出した時点で計算する）
+    pub fn current(&self) -> Option<CurrentActivity> {
+        let mut current = self.current.lock().clone()?;
+        if let Ok(start) = NaiveDateTime::parse_from_str(&current.start_time, "%Y-%m-%dT%H:%M:%S") {
+            current.elapsed_seconds = (Local::now().naive_local() - start).num_seconds().max(0);
+        }
+        Some(current)
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<LiveEvent> {
+        self.sender.subscribe()
+    }
+}
+
+impl Default for LiveEvents {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// ローカルのWebSocketサーバーを起動し、接続ごとにイベントを配信する
+pub async fn serve(state: Arc<AppState>, config: LiveStreamConfig) -> Result<(), String> {
+    let addr = format!("{}:{}", config.bind_address, config.port);
+    let listener = TcpListener::bind(&addr)
+        .await
+        .map_err(|e| format!("Failed to bind {}: {}", addr, e))?;
+    println!("Live activity stream listening on ws://{}", addr);
+
+    loop {
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(e) => {
+                eprintln!("Failed to accept live stream connection: {}", e);
+                continue;
+            }
+        };
+
+        let state = state.clone();
+        let require_token = config.require_token;
+        crate::runtime::spawn(async move {
+            if let Err(e) = handle_client(state, stream, require_token).await {
+                eprintln!("Live stream connection closed: {}", e);
+            }
+        });
+    }
+}
+
+/// ?token=... またはAuthorizationヘッダーからトークンを取り出す
+fn request_token(request: &Request) -> Option<String> {
+    let from_query = request.uri().query().and_then(|query| {
+        query
+            .split('&')
+            .find_map(|pair| pair.strip_prefix("token="))
+            .map(str::to_string)
+    });
+
+    from_query.or_else(|| {
+        request
+            .headers()
+            .get("Authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(str::to_string)
+    })
+}
+
+async fn handle_client(
+    state: Arc<AppState>,
+    stream: TcpStream,
+    require_token: bool,
+) -> Result<(), String> {
+    let auth_state = state.clone();
+    // エラー応答の型は tungstenite が決めている
+    #[allow(clippy::result_large_err)]
+    let callback = move |request: &Request, response: Response| {
+        let path = request.uri().path().to_string();
+        if !require_token {
+            return Ok(response);
+        }
+
+        // コールバックは同期なので spawn_blocking に渡せない。共有のランタイムはマルチスレッドなので、
+        // DB のロックを待つ間はこのワーカーを切り離して他のタスクを止めない
+        tokio::task::block_in_place(|| {
+            let db = auth_state.db.lock();
+            let authorized = request_token(request)
+                .ok_or_else(|| "Missing token".to_string())
+                .and_then(|token| api_auth::authorize(&db, &token, api_auth::SCOPE_READ));
+
+            match authorized {
+                Ok(token) => {
+                    api_auth::log_request(&db, Some(token.id), "GET", &path, 101);
+                    Ok(response)
+                }
+                Err(e) => {
+                    api_auth::log_request(&db, None, "GET", &path, 401);
+                    let mut error = ErrorResponse::new(Some(e));
+                    *error.status_mut() = StatusCode::UNAUTHORIZED;
+                    Err(error)
+                }
+            }
+        })
+    };
+
+    let ws = tokio_tungstenite::accept_hdr_async(stream, callback)
+        .await
+        .map_err(|e| e.to_string())?;
+    let (mut sink, mut incoming) = ws.split();
+    let mut events = state.live.subscribe();
+
+    // 接続直後に現在のトラッキング状態を送る
+    let hello = LiveEvent::TrackingState {
+        tracking: *state.is_tracking.lock(),
+        paused: state.pause.lock().is_some(),
+    };
+    send_event(&mut sink, &hello).await?;
+
+    loop {
+        tokio::select! {
+            event = events.recv() => match event {
+                Ok(event) => send_event(&mut sink, &event).await?,
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            message = incoming.next() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(Message::Ping(payload))) => {
+                    sink.send(Message::Pong(payload)).await.map_err(|e| e.to_string())?;
+                }
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+
+    Ok(())
+}
+
+async fn send_event<S>(sink: &mut S, event: &LiveEvent) -> Result<(), String>
+where
+    S: SinkExt<Message> + Unpin,
+    S::Error: std::fmt::Display,
+{
+    let json = serde_json::to_string(event).map_err(|e| e.to_string())?;
+    sink.send(Message::Text(json))
+        .await
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,249 @@
+use std::ffi::c_void;
+use std::ptr;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use objc2::rc::{autoreleasepool, Retained};
+use objc2::runtime::AnyObject;
+use objc2::AnyThread;
+use objc2_app_kit::NSRunningApplication;
+use objc2_core_foundation::CFRetained;
+use objc2_core_graphics::{
+    kCGNullWindowID, CGEventSource, CGEventSourceStateID, CGEventType, CGWindowListCopyWindowInfo,
+    CGWindowListOption,
+};
+use objc2_foundation::{
+    ns_string, NSAppleScript, NSArray, NSBundle, NSDictionary, NSNumber, NSString,
+};
+
+use crate::browser::{self, extract_domain};
+
+/// URLを取得できるブラウザ（アプリ名, URL取得用スクリプト）
+const BROWSERS: &[(&str, &str)] = &[
+    (
+        "Google Chrome",
+        "tell application \"Google Chrome\" to get URL of active tab of front window",
+    ),
+    (
+        "Microsoft Edge",
+        "tell application \"Microsoft Edge\" to get URL of active tab of front window",
+    ),
+    (
+        "Brave Browser",
+        "tell application \"Brave Browser\" to get URL of active tab of front window",
+    ),
+    (
+        "Safari",
+        "tell application \"Safari\" to get URL of front document",
+    ),
+];
+
+/// 応答しないアプリの問い合わせで watcher が止まらないようにする（秒）
+const AX_MESSAGING_TIMEOUT: f32 = 0.5;
+
+/// kCGAnyInputEventType
+const ANY_INPUT_EVENT: CGEventType = CGEventType(u32::MAX);
+
+type AXUIElementRef = *const c_void;
+
+#[link(name = "ApplicationServices", kind = "framework")]
+extern "C" {
+    fn AXIsProcessTrustedWithOptions(options: *const c_void) -> bool;
+    fn AXUIElementCreateApplication(pid: i32) -> AXUIElementRef;
+    fn AXUIElementSetMessagingTimeout(element: AXUIElementRef, timeout: f32) -> i32;
+    fn AXUIElementCopyAttributeValue(
+        element: AXUIElementRef,
+        attribute: *const c_void,
+        value: *mut *const c_void,
+    ) -> i32;
+}
+
+#[link(name = "CoreFoundation", kind = "framework")]
+extern "C" {
+    fn CFRelease(cf: *const c_void);
+}
+
+static ACCESSIBILITY_PROMPTED: AtomicBool = AtomicBool::new(false);
+
+/// アクセシビリティの許可があるか（初回だけ許可を求めるダイアログを出す）
+fn accessibility_trusted() -> bool {
+    let prompt = !ACCESSIBILITY_PROMPTED.swap(true, Ordering::Relaxed);
+    let options = NSDictionary::from_slices(
+        &[ns_string!("AXTrustedCheckOptionPrompt")],
+        &[&*NSNumber::new_bool(prompt)],
+    );
+    let trusted = unsafe { AXIsProcessTrustedWithOptions(Retained::as_ptr(&options).cast()) };
+    if prompt && !trusted {
+        eprintln!(
+            "Accessibility permission is not granted; window titles are unavailable until it is allowed in System Settings"
+        );
+    }
+    trusted
+}
+
+/// AX 属性を取得する（戻り値は呼び出し側で CFRelease する）
+fn copy_attribute(element: AXUIElementRef, attribute: &NSString) -> Option<*const c_void> {
+    let mut value: *const c_void = ptr::null();
+    let status = unsafe {
+        AXUIElementCopyAttributeValue(element, (attribute as *const NSString).cast(), &mut value)
+    };
+    (status == 0 && !value.is_null()).then_some(value)
+}
+
+/// アクセシビリティ API でフォーカス中のウィンドウのタイトルを取得
+fn focused_window_title(pid: i32) -> Option<String> {
+    unsafe {
+        let app = AXUIElementCreateApplication(pid);
+        if app.is_null() {
+            return None;
+        }
+        AXUIElementSetMessagingTimeout(app, AX_MESSAGING_TIMEOUT);
+
+        let title = copy_attribute(app, ns_string!("AXFocusedWindow")).and_then(|window| {
+            let title = copy_attribute(window, ns_string!("AXTitle"))
+                .and_then(|value| Retained::from_raw(value as *mut AnyObject))
+                .and_then(|value| value.downcast::<NSString>().ok())
+                .map(|title| title.to_string());
+            CFRelease(window);
+            title
+        });
+        CFRelease(app);
+        title
+    }
+}
+
+fn number(dict: &NSDictionary<NSString, AnyObject>, key: &NSString) -> Option<i32> {
+    dict.objectForKey(key)?
+        .downcast::<NSNumber>()
+        .ok()
+        .map(|n| n.as_i32())
+}
+
+fn string(dict: &NSDictionary<NSString, AnyObject>, key: &NSString) -> Option<String> {
+    dict.objectForKey(key)?
+        .downcast::<NSString>()
+        .ok()
+        .map(|s| s.to_string())
+}
+
+struct FrontWindow {
+    app: Option<Retained<NSRunningApplication>>,
+    app_name: String,
+    /// kCGWindowName（画面収録の許可がなければ空）
+    window_name: String,
+    pid: i32,
+}
+
+/// 画面上で最前面の通常ウィンドウ（CGWindowList は手前から順に並ぶ）
+///
+/// NSWorkspace の frontmostApplication はメインのランループがないと更新されないため、
+/// timetracker-cli でも使えるようにウィンドウの一覧から判定する。
+fn front_window() -> Option<FrontWindow> {
+    let list = CGWindowListCopyWindowInfo(
+        CGWindowListOption::OptionOnScreenOnly | CGWindowListOption::ExcludeDesktopElements,
+        kCGNullWindowID,
+    )?;
+    // CFArray と NSArray は toll-free bridge
+    let list: &NSArray<NSDictionary<NSString, AnyObject>> =
+        unsafe { &*CFRetained::as_ptr(&list).as_ptr().cast() };
+
+    list.iter()
+        .find(|window| number(window, ns_string!("kCGWindowLayer")) == Some(0))
+        .and_then(|window| {
+            let pid = number(&window, ns_string!("kCGWindowOwnerPID"))?;
+            let app = NSRunningApplication::runningApplicationWithProcessIdentifier(pid);
+            let app_name = app
+                .as_ref()
+                .and_then(|app| app.localizedName())
+                .map(|name| name.to_string())
+                .or_else(|| string(&window, ns_string!("kCGWindowOwnerName")))
+                .filter(|name| !name.is_empty())?;
+            Some(FrontWindow {
+                app,
+                app_name,
+                window_name: string(&window, ns_string!("kCGWindowName")).unwrap_or_default(),
+                pid,
+            })
+        })
+}
+
+/// 同じプロセス内で AppleScript を実行する（ブラウザのURL取得用。「オートメーション」の許可が必要）
+fn run_applescript(source: &str) -> Option<String> {
+    let script =
+        NSAppleScript::initWithSource(NSAppleScript::alloc(), &NSString::from_str(source))?;
+    let mut error = None;
+    let result = unsafe { script.executeAndReturnError(Some(&mut error)) };
+    if error.is_some() {
+        return None;
+    }
+    result.stringValue().map(|url| url.to_string())
+}
+
+/// フォアグラウンドのアプリ名・ウィンドウタイトル・ドメインを取得
+///
+/// ウィンドウタイトルの取得には「アクセシビリティ」の許可が必要（初回に許可を求める）。
+/// 許可がなければ画面収録の許可で取れるウィンドウ名を使い、それもなければタイトルは空になる。
+pub fn get_active_window_info() -> Option<(String, String, Option<String>)> {
+    autoreleasepool(|_| {
+        let front = front_window()?;
+
+        let title = accessibility_trusted()
+            .then(|| focused_window_title(front.pid))
+            .flatten()
+            .unwrap_or(front.window_name);
+
+        let domain = BROWSERS
+            .iter()
+            .find(|(name, _)| *name == front.app_name)
+            .and_then(|(_, script)| run_applescript(script))
+            .and_then(|url| extract_domain(&url))
+            .or_else(|| {
+                // スクリプトでURLを取得できないブラウザ（Firefox など）はタイトルから推定
+                browser::is_browser(&front.app_name)
+                    .then(|| browser::domain_from_title(&title))
+                    .flatten()
+            });
+
+        Some((front.app_name, title, domain))
+    })
+}
+
+pub fn get_foreground_process_metadata() -> Option<crate::ProcessMetadata> {
+    autoreleasepool(|_| {
+        let front = front_window()?;
+        let bundle_url = front.app.as_ref().and_then(|app| app.bundleURL());
+        let bundle = bundle_url.as_deref().and_then(NSBundle::bundleWithURL);
+
+        // Info.plist から値を読み取る
+        let read = |key: &NSString| {
+            bundle
+                .as_ref()
+                .and_then(|bundle| bundle.objectForInfoDictionaryKey(key))
+                .and_then(|value| value.downcast::<NSString>().ok())
+                .map(|value| value.to_string())
+                .filter(|value| !value.is_empty())
+        };
+
+        Some(crate::ProcessMetadata {
+            process_name: front.app_name,
+            company_name: None,
+            product_name: read(ns_string!("CFBundleName")),
+            file_version: read(ns_string!("CFBundleShortVersionString")),
+            exe_path: bundle_url
+                .and_then(|url| url.path())
+                .map(|path| path.to_string()),
+        })
+    })
+}
+
+/// 最後の入力からの経過秒数
+pub fn get_idle_seconds() -> u64 {
+    let seconds = CGEventSource::seconds_since_last_event_type(
+        CGEventSourceStateID::CombinedSessionState,
+        ANY_INPUT_EVENT,
+    );
+    if seconds.is_finite() && seconds > 0.0 {
+        seconds as u64
+    } else {
+        0
+    }
+}
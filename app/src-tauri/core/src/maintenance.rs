@@ -0,0 +1,835 @@
+use chrono::{DateTime, Duration, Local, NaiveDateTime};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::plugins::config::CompactionConfig;
+use crate::ActivityRecord;
+
+/// アクティビティ a が送信前の確認待ち（pending_sync の送信済み以外）か送信待ちのキュー（sync_queue）に含まれる
+///
+/// 候補・キューは activity_ids で記録を指すので、含まれる記録を消したり変えたりすると同じ時間を二重に送ることになる。
+pub(crate) const STAGED_CONDITION: &str = "(EXISTS (
+        SELECT 1 FROM pending_sync p, json_each(p.activity_ids) j
+        WHERE p.status != 'synced' AND j.value = a.id
+    )
+    OR EXISTS (
+        SELECT 1 FROM sync_queue q
+        WHERE q.status = 'pending'
+          AND (q.activity_id = a.id
+               OR EXISTS (SELECT 1 FROM json_each(q.activity_ids) j WHERE j.value = a.id))
+    ))";
+
+/// アクティビティ a が同期・アップロード済みか、送信候補・送信待ちに含まれる（整理の対象にしない）
+pub(crate) fn locked_condition() -> String {
+    format!(
+        "(a.uploaded_at IS NOT NULL
+          OR EXISTS (SELECT 1 FROM sync_log s WHERE s.activity_id = a.id)
+          OR {})",
+        STAGED_CONDITION
+    )
+}
+
+/// 重複として統合されるアクティビティのグループ
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateGroup {
+    /// 残すレコード（区間が最も広いもの）
+    pub kept_id: i64,
+    /// 削除されるレコード（同一区間またはkeptの区間に完全に含まれるもの）
+    pub removed_ids: Vec<i64>,
+    pub process_name: String,
+    pub window_title: String,
+}
+
+/// 重複排除の結果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DedupeReport {
+    pub dry_run: bool,
+    pub scanned: usize,
+    pub removed: usize,
+    pub groups: Vec<DuplicateGroup>,
+}
+
+/// 同じプロセス/タイトル/ドメインで、区間が同一または他のレコードに完全に含まれるものを統合する
+///
+/// dry_run の場合は削除せずに結果のみ返す。削除されるレコードの割り当ては残すレコードに引き継ぐ。
+/// 同期・アップロード済みや送信候補・送信待ちの記録は削除しない（同じ区間なら消さずに残す側にする）。
+pub fn dedupe_activities(
+    conn: &Connection,
+    start_date: &str,
+    end_date: &str,
+    dry_run: bool,
+) -> Result<DedupeReport, String> {
+    let range_start = format!("{}T00:00:00", start_date);
+    let range_end = format!("{}T23:59:59", end_date);
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT id, process_name, window_title, COALESCE(domain, ''), start_time, end_time, locked
+             FROM (SELECT a.*, {} AS locked FROM activities a)
+             WHERE start_time >= ?1 AND start_time <= ?2
+             ORDER BY process_name, window_title, COALESCE(domain, ''), start_time ASC, end_time DESC,
+                      locked DESC, id ASC",
+            locked_condition()
+        ))
+        .map_err(|e| e.to_string())?;
+
+    type Row = (i64, String, String, String, String, String, bool);
+    let rows: Vec<Row> = stmt
+        .query_map(params![range_start, range_end], |row| {
+            Ok((
+                row.get(0)?,
+                row.get(1)?,
+                row.get(2)?,
+                row.get(3)?,
+                row.get(4)?,
+                row.get(5)?,
+                row.get(6)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let scanned = rows.len();
+    let mut groups: Vec<DuplicateGroup> = Vec::new();
+    // (key, end_time, group) : 現在のコンテナ（同じキーの中で終了時刻が最大のレコード）
+    let mut container: Option<((String, String, String), String, DuplicateGroup)> = None;
+
+    for (id, process_name, window_title, domain, _start, end, locked) in rows {
+        let key = (process_name.clone(), window_title.clone(), domain);
+
+        if let Some((current_key, current_end, group)) = container.as_mut() {
+            // start_time 昇順に並んでいるため、終了時刻が収まっていれば区間ごと含まれる
+            if *current_key == key && end <= *current_end {
+                // 送信済みなどの記録は含まれていても消さない（割り当てや同期の記録が残せなくなる）
+                if !locked {
+                    group.removed_ids.push(id);
+                }
+                continue;
+            }
+        }
+
+        if let Some((_, _, group)) = container.take() {
+            if !group.removed_ids.is_empty() {
+                groups.push(group);
+            }
+        }
+
+        container = Some((
+            key,
+            end,
+            DuplicateGroup {
+                kept_id: id,
+                removed_ids: Vec::new(),
+                process_name,
+                window_title,
+            },
+        ));
+    }
+
+    if let Some((_, _, group)) = container {
+        if !group.removed_ids.is_empty() {
+            groups.push(group);
+        }
+    }
+
+    let removed = groups.iter().map(|g| g.removed_ids.len()).sum();
+
+    if !dry_run && removed > 0 {
+        let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+        for group in &groups {
+            for removed_id in &group.removed_ids {
+                // 残すレコードに割り当てがなければ引き継ぐ
+                tx.execute(
+                    "UPDATE OR IGNORE ticket_assignments SET activity_id = ?1 WHERE activity_id = ?2",
+                    params![group.kept_id, removed_id],
+                )
+                .map_err(|e| e.to_string())?;
+                tx.execute(
+                    "DELETE FROM ticket_assignments WHERE activity_id = ?1",
+                    params![removed_id],
+                )
+                .map_err(|e| e.to_string())?;
+                tx.execute("DELETE FROM activities WHERE id = ?1", params![removed_id])
+                    .map_err(|e| e.to_string())?;
+            }
+        }
+        tx.commit().map_err(|e| e.to_string())?;
+    }
+
+    Ok(DedupeReport {
+        dry_run,
+        scanned,
+        removed,
+        groups,
+    })
+}
+
+/// 断片統合の結果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompactionReport {
+    pub dry_run: bool,
+    /// この日時より前に開始したレコードが対象
+    pub cutoff: String,
+    pub scanned: usize,
+    /// 統合後に残ったレコード数（統合されたグループ数）
+    pub merged: usize,
+    pub removed: usize,
+}
+
+/// 統合中のブロック
+struct FragmentBlock {
+    kept_id: i64,
+    end: NaiveDateTime,
+    duration_seconds: i64,
+    removed_ids: Vec<i64>,
+    /// (タイトル, ドメイン, カテゴリ) ごとの合計時間（最も長いものを統合後の値にする）
+    labels: HashMap<(String, Option<String>, Option<String>), i64>,
+}
+
+impl FragmentBlock {
+    fn dominant_label(&self) -> (String, Option<String>, Option<String>) {
+        self.labels
+            .iter()
+            .max_by(|a, b| a.1.cmp(b.1).then_with(|| b.0.cmp(a.0)))
+            .map(|(label, _)| label.clone())
+            .unwrap_or_default()
+    }
+}
+
+/// 古いデータのうち、同じアプリ・同じ割り当て・同じ抽出チケットで短い間隔に連続する短時間の断片を1レコードに統合する
+///
+/// 統合後のタイトル/ドメイン/カテゴリは、ブロック内で最も長く使われたものを採用する。
+/// 同期・アップロード済みや送信候補・送信待ちの記録は変えず、間に他の記録（別のアプリや断片でない記録）を挟む断片はまとめない。
+pub fn compact_fragments(
+    conn: &Connection,
+    config: &CompactionConfig,
+    now: DateTime<Local>,
+    dry_run: bool,
+) -> Result<CompactionReport, String> {
+    let cutoff = (now - Duration::days(config.older_than_days as i64))
+        .format("%Y-%m-%dT%H:%M:%S")
+        .to_string();
+
+    /// (プラグイン, チケット, プロジェクト, 抽出したプラグイン, 抽出したチケット)
+    type Assignment = (String, String, String, String, String);
+    type Row = (
+        i64,
+        String,
+        String,
+        Option<String>,
+        Option<String>,
+        String,
+        String,
+        i64,
+        Assignment,
+        bool,
+    );
+
+    // 連続しているかを判定するため、断片でない記録も含めて時刻順に読む
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT a.id, a.process_name, a.window_title, a.domain, a.category,
+                    a.start_time, a.end_time, a.duration_seconds,
+                    COALESCE(t.plugin, ''), COALESCE(t.ticket_id, ''), COALESCE(t.project, ''),
+                    COALESCE(a.extracted_plugin, ''), COALESCE(a.extracted_ticket_id, ''),
+                    NOT {}
+             FROM activities a
+             LEFT JOIN ticket_assignments t ON t.activity_id = a.id
+             WHERE a.start_time < ?1
+             ORDER BY a.start_time ASC, a.id ASC",
+            locked_condition()
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map(params![cutoff], |row| -> rusqlite::Result<Row> {
+            Ok((
+                row.get(0)?,
+                row.get(1)?,
+                row.get(2)?,
+                row.get(3)?,
+                row.get(4)?,
+                row.get(5)?,
+                row.get(6)?,
+                row.get(7)?,
+                (
+                    row.get(8)?,
+                    row.get(9)?,
+                    row.get(10)?,
+                    row.get(11)?,
+                    row.get(12)?,
+                ),
+                row.get(13)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok());
+
+    let mut scanned = 0;
+    let mut blocks: Vec<FragmentBlock> = Vec::new();
+    // 直前の記録から続いているブロックと、その (プロセス名, 割り当て)
+    let mut current: Option<((String, Assignment), FragmentBlock)> = None;
+
+    for (id, process_name, title, domain, category, start, end, duration, assignment, editable) in
+        rows
+    {
+        let (Ok(start), Ok(end)) = (
+            NaiveDateTime::parse_from_str(&start, "%Y-%m-%dT%H:%M:%S"),
+            NaiveDateTime::parse_from_str(&end, "%Y-%m-%dT%H:%M:%S"),
+        ) else {
+            continue;
+        };
+        // 断片でない記録と同期・アップロード済み・送信待ちの記録はブロックを区切る
+        if !editable || duration >= config.max_fragment_seconds {
+            blocks.extend(current.take().map(|(_, block)| block));
+            continue;
+        }
+        scanned += 1;
+        let key = (process_name, assignment);
+        let label = (title, domain, category);
+
+        if let Some((current_key, block)) = current.as_mut() {
+            if *current_key == key && (start - block.end).num_seconds() <= config.merge_gap_seconds
+            {
+                block.removed_ids.push(id);
+                block.end = block.end.max(end);
+                block.duration_seconds += duration;
+                *block.labels.entry(label).or_insert(0) += duration;
+                continue;
+            }
+        }
+
+        let block = FragmentBlock {
+            kept_id: id,
+            end,
+            duration_seconds: duration,
+            removed_ids: Vec::new(),
+            labels: HashMap::from([(label, duration)]),
+        };
+        if let Some((_, previous)) = current.replace((key, block)) {
+            blocks.push(previous);
+        }
+    }
+    blocks.extend(current.map(|(_, block)| block));
+    blocks.retain(|block| !block.removed_ids.is_empty());
+
+    let merged = blocks.len();
+    let removed = blocks.iter().map(|b| b.removed_ids.len()).sum();
+
+    if !dry_run && merged > 0 {
+        let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+        for block in &blocks {
+            let (title, domain, category) = block.dominant_label();
+            // 代表のタイトルが変わることがあるので、チケットは抽出し直す
+            tx.execute(
+                "UPDATE activities
+                 SET window_title = ?1, domain = ?2, category = ?3, end_time = ?4, duration_seconds = ?5,
+                     extracted_plugin = NULL, extracted_ticket_id = NULL, extracted_at = NULL
+                 WHERE id = ?6",
+                params![
+                    title,
+                    domain,
+                    category,
+                    block.end.format("%Y-%m-%dT%H:%M:%S").to_string(),
+                    block.duration_seconds,
+                    block.kept_id,
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+
+            for removed_id in &block.removed_ids {
+                tx.execute(
+                    "DELETE FROM ticket_assignments WHERE activity_id = ?1",
+                    params![removed_id],
+                )
+                .map_err(|e| e.to_string())?;
+                tx.execute("DELETE FROM activities WHERE id = ?1", params![removed_id])
+                    .map_err(|e| e.to_string())?;
+            }
+        }
+        tx.commit().map_err(|e| e.to_string())?;
+    }
+
+    Ok(CompactionReport {
+        dry_run,
+        cutoff,
+        scanned,
+        merged,
+        removed,
+    })
+}
+
+fn load_record(conn: &Connection, id: i64) -> Result<ActivityRecord, String> {
+    conn.query_row(
+        "SELECT id, process_name, window_title, domain, start_time, end_time, duration_seconds, note
+         FROM activities WHERE id = ?1",
+        params![id],
+        |row| {
+            Ok(ActivityRecord {
+                id: row.get(0)?,
+                process_name: row.get(1)?,
+                window_title: row.get(2)?,
+                domain: row.get(3)?,
+                start_time: row.get(4)?,
+                end_time: row.get(5)?,
+                duration_seconds: row.get(6)?,
+                note: row.get(7)?,
+            })
+        },
+    )
+    .map_err(|_| format!("Activity not found: {}", id))
+}
+
+/// 同期・アップロード済みや送信候補・送信待ちの記録か確かめる（送信する内容と食い違うので手動の整理では変えない）
+fn ensure_unsent(conn: &Connection, id: i64) -> Result<(), String> {
+    let (synced, uploaded, staged): (bool, bool, bool) = conn
+        .query_row(
+            &format!(
+                "SELECT EXISTS (SELECT 1 FROM sync_log s WHERE s.activity_id = a.id),
+                        a.uploaded_at IS NOT NULL, {}
+                 FROM activities a WHERE a.id = ?1",
+                STAGED_CONDITION
+            ),
+            params![id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .map_err(|_| format!("Activity not found: {}", id))?;
+    if synced {
+        return Err(format!("Activity {} is already synced", id));
+    }
+    if uploaded {
+        return Err(format!("Activity {} is already uploaded", id));
+    }
+    if staged {
+        return Err(format!("Activity {} is waiting to be synced", id));
+    }
+    Ok(())
+}
+
+/// 指定したアクティビティを1レコードにまとめる（手動の整理用）
+///
+/// 間に他のアクティビティを挟まない連続した記録のみ対象（同期・アップロード済みや送信待ちの記録は不可）。最も早い記録を残し、
+/// アプリ/タイトル/ドメイン/カテゴリは最も長いもの、メモは連結、割り当てがなければ引き継ぐ。
+pub fn merge_activities(conn: &Connection, ids: &[i64]) -> Result<ActivityRecord, String> {
+    for &id in ids {
+        ensure_unsent(conn, id)?;
+    }
+    let mut records = ids
+        .iter()
+        .map(|&id| load_record(conn, id))
+        .collect::<Result<Vec<_>, _>>()?;
+    records.sort_by(|a, b| a.start_time.cmp(&b.start_time).then(a.id.cmp(&b.id)));
+    records.dedup_by_key(|r| r.id);
+    if records.len() < 2 {
+        return Err("At least two activities are required to merge".to_string());
+    }
+
+    let first = &records[0];
+    let last_start = &records[records.len() - 1].start_time;
+    let in_range: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM activities WHERE start_time >= ?1 AND start_time <= ?2",
+            params![first.start_time, last_start],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    if in_range != records.len() as i64 {
+        return Err("Only adjacent activities can be merged".to_string());
+    }
+
+    let kept_id = first.id;
+    let end_time = records
+        .iter()
+        .map(|r| r.end_time.clone())
+        .max()
+        .unwrap_or_default();
+    let duration_seconds: i64 = records.iter().map(|r| r.duration_seconds).sum();
+    let notes: Vec<&str> = records.iter().filter_map(|r| r.note.as_deref()).collect();
+    let note = (!notes.is_empty()).then(|| notes.join("; "));
+
+    // (アプリ, タイトル, ドメイン) ごとの合計時間で統合後の値を決める
+    let mut labels: HashMap<(&str, &str, Option<&str>), i64> = HashMap::new();
+    for r in &records {
+        *labels
+            .entry((&r.process_name, &r.window_title, r.domain.as_deref()))
+            .or_insert(0) += r.duration_seconds;
+    }
+    let (process_name, window_title, domain) = labels
+        .iter()
+        .max_by(|a, b| a.1.cmp(b.1).then_with(|| b.0.cmp(a.0)))
+        .map(|(label, _)| *label)
+        .unwrap_or_default();
+
+    // カテゴリと正規化前のタイトルは代表の記録のうち最も長いもの、入力回数は合計
+    let mut category: Option<String> = None;
+    let mut raw_window_title: Option<String> = None;
+    let mut category_seconds = -1;
+    let mut keystrokes: Option<i64> = None;
+    let mut clicks: Option<i64> = None;
+    for r in &records {
+        let (record_category, record_raw_title, record_keystrokes, record_clicks): (
+            Option<String>,
+            Option<String>,
+            Option<i64>,
+            Option<i64>,
+        ) = conn
+            .query_row(
+                "SELECT category, raw_window_title, keystrokes, clicks FROM activities WHERE id = ?1",
+                params![r.id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            )
+            .map_err(|e| e.to_string())?;
+        let representative = r.process_name == process_name
+            && r.window_title == window_title
+            && r.domain.as_deref() == domain;
+        if representative && r.duration_seconds > category_seconds {
+            category = record_category;
+            raw_window_title = record_raw_title;
+            category_seconds = r.duration_seconds;
+        }
+        if let Some(k) = record_keystrokes {
+            keystrokes = Some(keystrokes.unwrap_or(0) + k);
+        }
+        if let Some(c) = record_clicks {
+            clicks = Some(clicks.unwrap_or(0) + c);
+        }
+    }
+
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    // 代表のタイトルが変わることがあるので、チケットは抽出し直す
+    tx.execute(
+        "UPDATE activities
+         SET process_name = ?1, window_title = ?2, domain = ?3, category = ?4,
+             end_time = ?5, duration_seconds = ?6, note = ?7, keystrokes = ?8, clicks = ?9,
+             raw_window_title = ?10, extracted_plugin = NULL, extracted_ticket_id = NULL, extracted_at = NULL
+         WHERE id = ?11",
+        params![
+            process_name,
+            window_title,
+            domain,
+            category,
+            end_time,
+            duration_seconds,
+            note,
+            keystrokes,
+            clicks,
+            raw_window_title,
+            kept_id,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    for removed in &records[1..] {
+        // 残すレコードに割り当てがなければ引き継ぐ
+        tx.execute(
+            "UPDATE OR IGNORE ticket_assignments SET activity_id = ?1 WHERE activity_id = ?2",
+            params![kept_id, removed.id],
+        )
+        .map_err(|e| e.to_string())?;
+        tx.execute(
+            "DELETE FROM ticket_assignments WHERE activity_id = ?1",
+            params![removed.id],
+        )
+        .map_err(|e| e.to_string())?;
+        tx.execute("DELETE FROM activities WHERE id = ?1", params![removed.id])
+            .map_err(|e| e.to_string())?;
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+
+    load_record(conn, kept_id)
+}
+
+/// アクティビティを指定時刻で2つに分ける（手動の整理用）
+///
+/// 記録時間と入力回数は区間の長さで按分する。割り当て・メモ・カテゴリは両方に残す。
+/// 同期・アップロード済みや送信候補・送信待ちの記録は分けられない。
+pub fn split_activity(
+    conn: &Connection,
+    id: i64,
+    at_time: &str,
+) -> Result<(ActivityRecord, ActivityRecord), String> {
+    ensure_unsent(conn, id)?;
+    let record = load_record(conn, id)?;
+    let parse = |s: &str| {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S")
+            .map_err(|e| format!("Invalid time {}: {}", s, e))
+    };
+    let start = parse(&record.start_time)?;
+    let end = parse(&record.end_time)?;
+    let at = parse(at_time)?;
+    if at <= start || at >= end {
+        return Err(format!(
+            "Split time must be between {} and {}",
+            record.start_time, record.end_time
+        ));
+    }
+
+    let span = (end - start).num_seconds();
+    let first_span = (at - start).num_seconds();
+    let first_duration = record.duration_seconds * first_span / span;
+    let at_time = at.format("%Y-%m-%dT%H:%M:%S").to_string();
+
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    tx.execute(
+        "INSERT INTO activities
+            (process_name, window_title, domain, start_time, end_time, duration_seconds, category, note,
+             monitor, monitor_primary, virtual_desktop, keystrokes, clicks,
+             raw_window_title, extracted_plugin, extracted_ticket_id, extracted_at)
+         SELECT process_name, window_title, domain, ?2, end_time, duration_seconds - ?3, category, note,
+                monitor, monitor_primary, virtual_desktop,
+                keystrokes - keystrokes * ?4 / ?5, clicks - clicks * ?4 / ?5,
+                raw_window_title, extracted_plugin, extracted_ticket_id, extracted_at
+         FROM activities WHERE id = ?1",
+        params![id, at_time, first_duration, first_span, span],
+    )
+    .map_err(|e| e.to_string())?;
+    let second_id = tx.last_insert_rowid();
+
+    tx.execute(
+        "UPDATE activities
+         SET end_time = ?2, duration_seconds = ?3,
+             keystrokes = keystrokes * ?4 / ?5, clicks = clicks * ?4 / ?5
+         WHERE id = ?1",
+        params![id, at_time, first_duration, first_span, span],
+    )
+    .map_err(|e| e.to_string())?;
+
+    tx.execute(
+        "INSERT INTO ticket_assignments (activity_id, plugin, ticket_id, project, source, assigned_at)
+         SELECT ?2, plugin, ticket_id, project, source, assigned_at
+         FROM ticket_assignments WHERE activity_id = ?1",
+        params![id, second_id],
+    )
+    .map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok((load_record(conn, id)?, load_record(conn, second_id)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn open() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::create_schema(&conn).unwrap();
+        conn
+    }
+
+    fn insert(conn: &Connection, title: &str, start: &str, end: &str, duration: i64) -> i64 {
+        conn.execute(
+            "INSERT INTO activities (process_name, window_title, start_time, end_time, duration_seconds)
+             VALUES ('Code.exe', ?1, ?2, ?3, ?4)",
+            params![title, start, end, duration],
+        )
+        .unwrap();
+        conn.last_insert_rowid()
+    }
+
+    fn ids(conn: &Connection) -> Vec<i64> {
+        let mut stmt = conn
+            .prepare("SELECT id FROM activities ORDER BY id")
+            .unwrap();
+        stmt.query_map([], |row| row.get(0))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect()
+    }
+
+    fn mark_synced(conn: &Connection, id: i64) {
+        conn.execute(
+            "INSERT INTO sync_log (activity_id, plugin, ticket_id, synced_at)
+             VALUES (?1, 'redmine', '1', '2024-01-02T00:00:00')",
+            params![id],
+        )
+        .unwrap();
+    }
+
+    fn stage(conn: &Connection, id: i64) {
+        conn.execute(
+            "INSERT INTO pending_sync
+                (plugin, ticket_id, date, duration_seconds, comment, activity_ids, activity_json,
+                 status, created_at, updated_at)
+             VALUES ('redmine', '1', '2024-01-01', 60, '', ?1, '[]', 'pending',
+                     '2024-01-02T00:00:00', '2024-01-02T00:00:00')",
+            params![format!("[{}]", id)],
+        )
+        .unwrap();
+    }
+
+    fn compaction() -> CompactionConfig {
+        CompactionConfig {
+            enabled: true,
+            older_than_days: 7,
+            max_fragment_seconds: 60,
+            merge_gap_seconds: 30,
+        }
+    }
+
+    #[test]
+    fn dedupe_removes_contained_records_and_moves_assignments() {
+        let conn = open();
+        let kept = insert(
+            &conn,
+            "a",
+            "2024-01-01T09:00:00",
+            "2024-01-01T10:00:00",
+            3600,
+        );
+        let inner = insert(
+            &conn,
+            "a",
+            "2024-01-01T09:10:00",
+            "2024-01-01T09:20:00",
+            600,
+        );
+        let other = insert(
+            &conn,
+            "b",
+            "2024-01-01T09:10:00",
+            "2024-01-01T09:20:00",
+            600,
+        );
+        crate::assignments::assign(
+            &conn,
+            inner,
+            Some("redmine"),
+            Some("1"),
+            None,
+            crate::assignments::SOURCE_MANUAL,
+        )
+        .unwrap();
+
+        let preview = dedupe_activities(&conn, "2024-01-01", "2024-01-01", true).unwrap();
+        assert_eq!(preview.removed, 1);
+        assert_eq!(ids(&conn), [kept, inner, other]);
+
+        let report = dedupe_activities(&conn, "2024-01-01", "2024-01-01", false).unwrap();
+        assert_eq!(report.groups[0].kept_id, kept);
+        assert_eq!(report.groups[0].removed_ids, [inner]);
+        assert_eq!(ids(&conn), [kept, other]);
+        let assigned: i64 = conn
+            .query_row(
+                "SELECT activity_id FROM ticket_assignments WHERE ticket_id = '1'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(assigned, kept);
+    }
+
+    #[test]
+    fn dedupe_keeps_synced_and_staged_records() {
+        let conn = open();
+        let first = insert(
+            &conn,
+            "a",
+            "2024-01-01T09:00:00",
+            "2024-01-01T10:00:00",
+            3600,
+        );
+        let synced = insert(
+            &conn,
+            "a",
+            "2024-01-01T09:00:00",
+            "2024-01-01T10:00:00",
+            3600,
+        );
+        let staged = insert(
+            &conn,
+            "a",
+            "2024-01-01T09:10:00",
+            "2024-01-01T09:20:00",
+            600,
+        );
+        mark_synced(&conn, synced);
+        stage(&conn, staged);
+
+        // 同じ区間なら同期済みの記録を残す
+        let report = dedupe_activities(&conn, "2024-01-01", "2024-01-01", false).unwrap();
+        assert_eq!(report.groups.len(), 1);
+        assert_eq!(report.groups[0].kept_id, synced);
+        assert_eq!(report.groups[0].removed_ids, [first]);
+        assert_eq!(ids(&conn), [synced, staged]);
+    }
+
+    #[test]
+    fn compaction_merges_adjacent_fragments() {
+        let conn = open();
+        let kept = insert(&conn, "a", "2024-01-01T09:00:00", "2024-01-01T09:00:20", 20);
+        insert(&conn, "b", "2024-01-01T09:00:25", "2024-01-01T09:00:45", 20);
+        insert(&conn, "b", "2024-01-01T09:00:50", "2024-01-01T09:01:10", 20);
+        let long = insert(
+            &conn,
+            "a",
+            "2024-01-01T09:01:20",
+            "2024-01-01T09:11:20",
+            600,
+        );
+
+        let preview = compact_fragments(&conn, &compaction(), Local::now(), true).unwrap();
+        assert_eq!((preview.merged, preview.removed), (1, 2));
+        assert_eq!(ids(&conn).len(), 4);
+
+        compact_fragments(&conn, &compaction(), Local::now(), false).unwrap();
+        assert_eq!(ids(&conn), [kept, long]);
+        let (title, end, duration): (String, String, i64) = conn
+            .query_row(
+                "SELECT window_title, end_time, duration_seconds FROM activities WHERE id = ?1",
+                params![kept],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!(title, "b");
+        assert_eq!(end, "2024-01-01T09:01:10");
+        assert_eq!(duration, 60);
+    }
+
+    #[test]
+    fn compaction_skips_locked_records() {
+        let conn = open();
+        insert(&conn, "a", "2024-01-01T09:00:00", "2024-01-01T09:00:20", 20);
+        let staged = insert(&conn, "a", "2024-01-01T09:00:25", "2024-01-01T09:00:45", 20);
+        insert(&conn, "a", "2024-01-01T09:00:50", "2024-01-01T09:01:10", 20);
+        let synced = insert(&conn, "a", "2024-01-01T09:01:15", "2024-01-01T09:01:35", 20);
+        stage(&conn, staged);
+        mark_synced(&conn, synced);
+
+        // 送信待ち・同期済みの記録は変えず、前後の断片もまとめない
+        let report = compact_fragments(&conn, &compaction(), Local::now(), false).unwrap();
+        assert_eq!(report.merged, 0);
+        assert_eq!(ids(&conn).len(), 4);
+    }
+
+    #[test]
+    fn compaction_separates_extracted_tickets() {
+        let conn = open();
+        let first = insert(
+            &conn,
+            "#1",
+            "2024-01-01T09:00:00",
+            "2024-01-01T09:00:20",
+            20,
+        );
+        let second = insert(
+            &conn,
+            "#2",
+            "2024-01-01T09:00:25",
+            "2024-01-01T09:00:45",
+            20,
+        );
+        for (id, ticket) in [(first, "1"), (second, "2")] {
+            conn.execute(
+                "UPDATE activities SET extracted_plugin = 'redmine', extracted_ticket_id = ?2 WHERE id = ?1",
+                params![id, ticket],
+            )
+            .unwrap();
+        }
+
+        let report = compact_fragments(&conn, &compaction(), Local::now(), false).unwrap();
+        assert_eq!(report.merged, 0);
+        assert_eq!(ids(&conn), [first, second]);
+    }
+}
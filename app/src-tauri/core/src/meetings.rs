@@ -0,0 +1,208 @@
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use crate::focus;
+use crate::plugins::integrations::calendar::CalendarEvent;
+
+/// 会議中のアクティビティに付けるタグの接頭辞（"Meeting: <予定のタイトル>"）
+pub const MEETING_PREFIX: &str = "Meeting: ";
+
+const TIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+/// 取り込んだ会議ごとの予定時間と記録時間
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MeetingSummary {
+    pub calendar: String,
+    pub title: String,
+    pub start_time: String,
+    pub end_time: String,
+    pub scheduled_seconds: i64,
+    /// 会議中として記録された時間
+    pub tracked_seconds: i64,
+}
+
+/// 1日の会議と集中の比率
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MeetingReport {
+    pub date: String,
+    pub meetings: Vec<MeetingSummary>,
+    pub tracked_seconds: i64,
+    pub meeting_seconds: i64,
+    /// 会議中を除いた集中区間の時間
+    pub focus_seconds: i64,
+    /// 記録時間に対する割合（%）
+    pub meeting_percentage: f64,
+    pub focus_percentage: f64,
+}
+
+pub fn create_tables(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS calendar_events (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            calendar TEXT NOT NULL,
+            external_id TEXT NOT NULL,
+            title TEXT NOT NULL,
+            start_time TEXT NOT NULL,
+            end_time TEXT NOT NULL,
+            UNIQUE(calendar, external_id, start_time)
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_calendar_events_start ON calendar_events(start_time)",
+        [],
+    )?;
+    Ok(())
+}
+
+/// 指定日のカレンダーの予定を取得した内容で置き換える
+pub fn store_events(
+    conn: &Connection,
+    date: &str,
+    calendar: &str,
+    events: &[CalendarEvent],
+) -> Result<(), String> {
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    tx.execute(
+        "DELETE FROM calendar_events
+         WHERE calendar = ?1 AND start_time >= ?2 AND start_time <= ?3",
+        params![
+            calendar,
+            format!("{}T00:00:00", date),
+            format!("{}T23:59:59", date)
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    for event in events {
+        tx.execute(
+            "INSERT OR REPLACE INTO calendar_events (calendar, external_id, title, start_time, end_time)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                calendar,
+                event.external_id,
+                event.title,
+                event.start.format(TIME_FORMAT).to_string(),
+                event.end.format(TIME_FORMAT).to_string()
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    tx.commit().map_err(|e| e.to_string())
+}
+
+/// 指定日のアクティビティに会議のタグを付け直す（開始時刻が会議中のアクティビティ、重なる会議は先に始まったもの）
+///
+/// タグを付けたアクティビティの件数を返す。
+pub fn tag_activities(conn: &Connection, date: &str) -> Result<usize, String> {
+    let start_of_day = format!("{}T00:00:00", date);
+    let end_of_day = format!("{}T23:59:59", date);
+
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    tx.execute(
+        "UPDATE activities SET meeting = NULL WHERE start_time >= ?1 AND start_time <= ?2",
+        params![start_of_day, end_of_day],
+    )
+    .map_err(|e| e.to_string())?;
+    let tagged = tx
+        .execute(
+            "UPDATE activities SET meeting = (
+                 SELECT ?3 || e.title FROM calendar_events e
+                 WHERE activities.start_time >= e.start_time AND activities.start_time < e.end_time
+                 ORDER BY e.start_time LIMIT 1
+             )
+             WHERE start_time >= ?1 AND start_time <= ?2
+               AND EXISTS (
+                 SELECT 1 FROM calendar_events e
+                 WHERE activities.start_time >= e.start_time AND activities.start_time < e.end_time
+               )",
+            params![start_of_day, end_of_day, MEETING_PREFIX],
+        )
+        .map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(tagged)
+}
+
+/// 指定日の会議の時間と、会議以外の集中区間の時間
+pub fn report(conn: &Connection, date: &str) -> Result<MeetingReport, String> {
+    let start_of_day = format!("{}T00:00:00", date);
+    let end_of_day = format!("{}T23:59:59", date);
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT e.calendar, e.title, e.start_time, e.end_time,
+                    CAST((julianday(e.end_time) - julianday(e.start_time)) * 86400 AS INTEGER),
+                    (SELECT COALESCE(SUM(a.duration_seconds), 0) FROM activities a
+                     WHERE a.start_time >= e.start_time AND a.start_time < e.end_time
+                       AND a.meeting = ?3 || e.title)
+             FROM calendar_events e
+             WHERE e.start_time >= ?1 AND e.start_time <= ?2
+             ORDER BY e.start_time",
+        )
+        .map_err(|e| e.to_string())?;
+    let meetings: Vec<MeetingSummary> = stmt
+        .query_map(params![start_of_day, end_of_day, MEETING_PREFIX], |row| {
+            Ok(MeetingSummary {
+                calendar: row.get(0)?,
+                title: row.get(1)?,
+                start_time: row.get(2)?,
+                end_time: row.get(3)?,
+                scheduled_seconds: row.get(4)?,
+                tracked_seconds: row.get(5)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT id FROM activities
+             WHERE start_time >= ?1 AND start_time <= ?2 AND meeting IS NOT NULL",
+        )
+        .map_err(|e| e.to_string())?;
+    let in_meeting: HashSet<i64> = stmt
+        .query_map(params![start_of_day, end_of_day], |row| row.get(0))
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+    let meeting_seconds: i64 = conn
+        .query_row(
+            "SELECT COALESCE(SUM(duration_seconds), 0) FROM activities
+             WHERE start_time >= ?1 AND start_time <= ?2 AND meeting IS NOT NULL",
+            params![start_of_day, end_of_day],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+
+    // 会議アプリを使い続けた区間は集中に数えない
+    let focus = focus::sessions(conn, date, focus::DEFAULT_MIN_FOCUS_MINUTES)?;
+    let focus_seconds = focus
+        .sessions
+        .iter()
+        .filter(|session| {
+            !session
+                .activity_ids
+                .iter()
+                .any(|id| in_meeting.contains(id))
+        })
+        .map(|session| session.duration_seconds)
+        .sum();
+
+    let percentage = |part: i64| {
+        if focus.tracked_seconds > 0 {
+            part as f64 / focus.tracked_seconds as f64 * 100.0
+        } else {
+            0.0
+        }
+    };
+    Ok(MeetingReport {
+        date: date.to_string(),
+        meetings,
+        tracked_seconds: focus.tracked_seconds,
+        meeting_seconds,
+        focus_seconds,
+        meeting_percentage: percentage(meeting_seconds),
+        focus_percentage: percentage(focus_seconds),
+    })
+}
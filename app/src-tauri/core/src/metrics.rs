@@ -0,0 +1,159 @@
+use parking_lot::Mutex;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// アプリの稼働状況のカウンター（/metrics で Prometheus の形式で公開する）
+#[derive(Default)]
+pub struct Metrics {
+    watcher_loops: AtomicU64,
+    watcher_loop_micros_total: AtomicU64,
+    watcher_loop_micros_last: AtomicU64,
+    db_write_errors: AtomicU64,
+    sync_failures: AtomicU64,
+    /// 起動してから記録した秒数（アプリ, カテゴリ）
+    tracked_seconds: Mutex<BTreeMap<(String, String), u64>>,
+}
+
+impl Metrics {
+    /// ウォッチャーの1回のループにかかった時間（待機時間を除く）
+    pub fn observe_watcher_loop(&self, elapsed: Duration) {
+        let micros = elapsed.as_micros() as u64;
+        self.watcher_loops.fetch_add(1, Ordering::Relaxed);
+        self.watcher_loop_micros_total
+            .fetch_add(micros, Ordering::Relaxed);
+        self.watcher_loop_micros_last
+            .store(micros, Ordering::Relaxed);
+    }
+
+    pub fn db_write_error(&self) {
+        self.db_write_errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn sync_failure(&self) {
+        self.sync_failures.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// 記録したアクティビティの秒数（直前の記録にまとめた分を含む）
+    pub fn observe_tracked(&self, process_name: &str, category: Option<&str>, seconds: i64) {
+        let key = (process_name.to_string(), category.unwrap_or("").to_string());
+        *self.tracked_seconds.lock().entry(key).or_default() += seconds.max(0) as u64;
+    }
+}
+
+/// メトリクスの出力時点のアプリの状態
+pub struct Snapshot {
+    pub tracking: bool,
+    pub today_seconds: i64,
+}
+
+/// Prometheus のテキスト形式で出力する
+///
+/// timetracker_tracked_seconds_total は起動してから記録した秒数のアプリ・カテゴリ別の合計
+/// （DB を集計しないので、保持期間による削除や圧縮で減ることはない）。
+pub fn render(metrics: &Metrics, snapshot: &Snapshot) -> String {
+    let mut out = String::new();
+    header(
+        &mut out,
+        "timetracker_tracked_seconds_total",
+        "counter",
+        "Tracked seconds by app and category since the app started",
+    );
+    for ((app, category), seconds) in metrics.tracked_seconds.lock().iter() {
+        let _ = writeln!(
+            out,
+            "timetracker_tracked_seconds_total{{app=\"{}\",category=\"{}\"}} {}",
+            escape_label(app),
+            escape_label(category),
+            seconds
+        );
+    }
+
+    header(
+        &mut out,
+        "timetracker_today_tracked_seconds",
+        "gauge",
+        "Tracked seconds today including the current activity",
+    );
+    let _ = writeln!(
+        out,
+        "timetracker_today_tracked_seconds {}",
+        snapshot.today_seconds
+    );
+
+    header(
+        &mut out,
+        "timetracker_tracking",
+        "gauge",
+        "1 while tracking is active",
+    );
+    let _ = writeln!(out, "timetracker_tracking {}", snapshot.tracking as u8);
+
+    let micros = |value: &AtomicU64| value.load(Ordering::Relaxed) as f64 / 1_000_000.0;
+    header(
+        &mut out,
+        "timetracker_watcher_loop_duration_seconds",
+        "summary",
+        "Time spent in one watcher loop iteration, excluding the wait",
+    );
+    let _ = writeln!(
+        out,
+        "timetracker_watcher_loop_duration_seconds_sum {}",
+        micros(&metrics.watcher_loop_micros_total)
+    );
+    let _ = writeln!(
+        out,
+        "timetracker_watcher_loop_duration_seconds_count {}",
+        metrics.watcher_loops.load(Ordering::Relaxed)
+    );
+    header(
+        &mut out,
+        "timetracker_watcher_loop_last_duration_seconds",
+        "gauge",
+        "Duration of the latest watcher loop iteration",
+    );
+    let _ = writeln!(
+        out,
+        "timetracker_watcher_loop_last_duration_seconds {}",
+        micros(&metrics.watcher_loop_micros_last)
+    );
+
+    header(
+        &mut out,
+        "timetracker_db_write_errors_total",
+        "counter",
+        "Failed database writes from the watcher",
+    );
+    let _ = writeln!(
+        out,
+        "timetracker_db_write_errors_total {}",
+        metrics.db_write_errors.load(Ordering::Relaxed)
+    );
+
+    header(
+        &mut out,
+        "timetracker_sync_failures_total",
+        "counter",
+        "Time entries that failed to sync to an integration",
+    );
+    let _ = writeln!(
+        out,
+        "timetracker_sync_failures_total {}",
+        metrics.sync_failures.load(Ordering::Relaxed)
+    );
+
+    out
+}
+
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
@@ -0,0 +1,188 @@
+use chrono::Local;
+use rusqlite::{params, Connection};
+
+/// スキーマの変更（適用済みのものは schema_migrations に記録して二度と実行しない）
+///
+/// 既存の手順を書き換えず、変更は必ず末尾に新しいバージョンとして追加すること。
+struct Migration {
+    version: i64,
+    name: &'static str,
+    apply: fn(&Connection) -> rusqlite::Result<()>,
+}
+
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "activities_domain",
+        apply: |conn| {
+            add_column(conn, "activities", "domain", "TEXT")?;
+            conn.execute(
+                "CREATE INDEX IF NOT EXISTS idx_domain ON activities(domain)",
+                [],
+            )?;
+            Ok(())
+        },
+    },
+    Migration {
+        version: 2,
+        name: "activities_category",
+        apply: |conn| {
+            add_column(conn, "activities", "category", "TEXT")?;
+            conn.execute(
+                "CREATE INDEX IF NOT EXISTS idx_category ON activities(category)",
+                [],
+            )?;
+            Ok(())
+        },
+    },
+    Migration {
+        version: 3,
+        name: "activities_uploaded_at",
+        apply: |conn| {
+            // 列を追加する前の記録は送信済みとみなす（初回の自動アップロードで全履歴を送らない）
+            if !has_column(conn, "activities", "uploaded_at")? {
+                add_column(conn, "activities", "uploaded_at", "TEXT")?;
+                conn.execute(
+                    "UPDATE activities SET uploaded_at = ?1",
+                    params![Local::now().format("%Y-%m-%dT%H:%M:%S").to_string()],
+                )?;
+            }
+            Ok(())
+        },
+    },
+    Migration {
+        version: 4,
+        name: "sync_queue_next_attempt_at",
+        apply: |conn| add_column(conn, "sync_queue", "next_attempt_at", "TEXT"),
+    },
+    Migration {
+        version: 5,
+        name: "activities_note",
+        apply: |conn| add_column(conn, "activities", "note", "TEXT"),
+    },
+    Migration {
+        version: 6,
+        name: "activities_placement",
+        apply: |conn| {
+            add_column(conn, "activities", "monitor", "TEXT")?;
+            add_column(conn, "activities", "monitor_primary", "INTEGER")?;
+            add_column(conn, "activities", "virtual_desktop", "TEXT")?;
+            conn.execute(
+                "CREATE INDEX IF NOT EXISTS idx_virtual_desktop ON activities(virtual_desktop)",
+                [],
+            )?;
+            Ok(())
+        },
+    },
+    Migration {
+        version: 7,
+        name: "activities_input_counts",
+        apply: |conn| {
+            add_column(conn, "activities", "keystrokes", "INTEGER")?;
+            add_column(conn, "activities", "clicks", "INTEGER")?;
+            Ok(())
+        },
+    },
+    Migration {
+        version: 8,
+        name: "activities_meeting",
+        apply: |conn| add_column(conn, "activities", "meeting", "TEXT"),
+    },
+    Migration {
+        version: 9,
+        name: "sync_log_duration",
+        apply: |conn| add_column(conn, "sync_log", "duration_seconds", "INTEGER"),
+    },
+    Migration {
+        version: 10,
+        name: "sync_queue_activity_ids",
+        apply: |conn| add_column(conn, "sync_queue", "activity_ids", "TEXT"),
+    },
+    Migration {
+        version: 11,
+        name: "activities_extracted_ticket",
+        apply: |conn| {
+            add_column(conn, "activities", "extracted_plugin", "TEXT")?;
+            add_column(conn, "activities", "extracted_ticket_id", "TEXT")?;
+            add_column(conn, "activities", "extracted_at", "TEXT")?;
+            conn.execute(
+                "CREATE INDEX IF NOT EXISTS idx_extracted_ticket ON activities(extracted_plugin, extracted_ticket_id)",
+                [],
+            )?;
+            Ok(())
+        },
+    },
+    Migration {
+        version: 12,
+        name: "activities_raw_window_title",
+        apply: |conn| add_column(conn, "activities", "raw_window_title", "TEXT"),
+    },
+];
+
+/// 未適用のマイグレーションを順に適用する（テーブル作成後に呼ぶ）
+///
+/// 1件ずつトランザクションで実行し、失敗したらそれ以降は適用せずにエラーを返す。
+pub fn run(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS schema_migrations (
+            version INTEGER PRIMARY KEY,
+            name TEXT NOT NULL,
+            applied_at TEXT NOT NULL
+        )",
+        [],
+    )?;
+
+    let current = current_version(conn)?;
+    for migration in MIGRATIONS.iter().filter(|m| m.version > current) {
+        let tx = conn.unchecked_transaction()?;
+        (migration.apply)(&tx)?;
+        tx.execute(
+            "INSERT INTO schema_migrations (version, name, applied_at) VALUES (?1, ?2, ?3)",
+            params![
+                migration.version,
+                migration.name,
+                Local::now().format("%Y-%m-%dT%H:%M:%S").to_string()
+            ],
+        )?;
+        tx.commit()?;
+    }
+
+    Ok(())
+}
+
+/// 適用済みの最新バージョン（未適用なら 0）
+pub fn current_version(conn: &Connection) -> rusqlite::Result<i64> {
+    conn.query_row(
+        "SELECT COALESCE(MAX(version), 0) FROM schema_migrations",
+        [],
+        |row| row.get(0),
+    )
+}
+
+/// 列がなければ追加する（マイグレーション導入前に列を追加済みのデータベースもある）
+fn add_column(
+    conn: &Connection,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> rusqlite::Result<()> {
+    if has_column(conn, table, column)? {
+        return Ok(());
+    }
+    conn.execute(
+        &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition),
+        [],
+    )?;
+    Ok(())
+}
+
+fn has_column(conn: &Connection, table: &str, column: &str) -> rusqlite::Result<bool> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let columns = stmt.query_map([], |row| row.get::<_, String>(1))?;
+    for name in columns {
+        if name? == column {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
@@ -0,0 +1,182 @@
+use rumqttc::{AsyncClient, LastWill, MqttOptions, QoS, Transport};
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+use crate::live::LiveEvent;
+use crate::plugins::config::MqttConfig;
+use crate::secrets;
+use crate::AppState;
+
+/// {topic_prefix}/state に送る状態（接続が切れたらブローカーが "offline" を送る）
+pub const STATE_TRACKING: &str = "tracking";
+pub const STATE_PAUSED: &str = "paused";
+pub const STATE_IDLE: &str = "idle";
+pub const STATE_STOPPED: &str = "stopped";
+pub const STATE_OFFLINE: &str = "offline";
+
+/// {topic_prefix}/activity に送る現在のアクティビティ
+#[derive(Debug, Serialize)]
+struct ActivityPayload {
+    process_name: String,
+    category: Option<String>,
+    domain: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    window_title: Option<String>,
+    start_time: String,
+}
+
+/// 配信中の状態（変化したときだけ送る）
+struct Status {
+    tracking: bool,
+    paused: bool,
+    idle: bool,
+}
+
+impl Status {
+    fn state(&self) -> &'static str {
+        if self.paused {
+            STATE_PAUSED
+        } else if !self.tracking {
+            STATE_STOPPED
+        } else if self.idle {
+            STATE_IDLE
+        } else {
+            STATE_TRACKING
+        }
+    }
+}
+
+/// MQTT ブローカーに接続し、トラッキング状態と現在のアクティビティを retain つきで配信する
+pub fn start(state: Arc<AppState>, config: MqttConfig) -> Result<(), String> {
+    let state_topic = format!("{}/state", config.topic_prefix);
+    let activity_topic = format!("{}/activity", config.topic_prefix);
+
+    let mut options = MqttOptions::new(&config.client_id, &config.host, config.port);
+    options.set_keep_alive(Duration::from_secs(30));
+    options.set_last_will(LastWill::new(
+        &state_topic,
+        STATE_OFFLINE,
+        QoS::AtLeastOnce,
+        true,
+    ));
+    if let Some(username) = &config.username {
+        // キーリングのパスワードを優先する
+        let password = match &config.password_secret {
+            Some(alias) => secrets::get_integration(alias)?
+                .ok_or_else(|| format!("MQTT password not found in the OS keyring: {}", alias))?,
+            None => config.password.clone().unwrap_or_default(),
+        };
+        options.set_credentials(username, password);
+    }
+    if config.tls {
+        options.set_transport(match &config.ca_file {
+            Some(path) => {
+                let ca = std::fs::read(path)
+                    .map_err(|e| format!("Failed to read MQTT CA file {}: {}", path, e))?;
+                Transport::tls(ca, None, None)
+            }
+            None => Transport::tls_with_default_config(),
+        });
+    }
+    let (client, mut eventloop) = AsyncClient::new(options, 32);
+
+    // 接続を維持する（切断されたら poll が再接続する）
+    crate::runtime::spawn(async move {
+        loop {
+            if let Err(e) = eventloop.poll().await {
+                eprintln!("MQTT connection error: {}", e);
+                tokio::time::sleep(Duration::from_secs(5)).await;
+            }
+        }
+    });
+
+    let mut receiver = state.live.subscribe();
+    crate::runtime::spawn(async move {
+        let mut status = Status {
+            tracking: *state.is_tracking.lock(),
+            paused: state.pause.lock().is_some(),
+            idle: *state.is_idle.lock(),
+        };
+        publish(&client, &state_topic, status.state()).await;
+        if let Some(current) = state.live.current() {
+            let payload = activity_payload(
+                &state,
+                &config,
+                current.process_name,
+                current.window_title,
+                current.domain,
+                current.start_time,
+            );
+            publish(&client, &activity_topic, payload).await;
+        }
+
+        loop {
+            let event = match receiver.recv().await {
+                Ok(event) => event,
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            };
+            let previous = status.state();
+            match event {
+                LiveEvent::ActivityStart {
+                    process_name,
+                    window_title,
+                    domain,
+                    start_time,
+                    ..
+                } => {
+                    let payload = activity_payload(
+                        &state,
+                        &config,
+                        process_name,
+                        window_title,
+                        domain,
+                        start_time,
+                    );
+                    publish(&client, &activity_topic, payload).await;
+                }
+                LiveEvent::Idle { idle, .. } => status.idle = idle,
+                LiveEvent::TrackingState { tracking, paused } => {
+                    status.tracking = tracking;
+                    status.paused = paused;
+                }
+                _ => continue,
+            }
+            if status.state() != previous {
+                publish(&client, &state_topic, status.state()).await;
+            }
+        }
+    });
+    Ok(())
+}
+
+fn activity_payload(
+    state: &AppState,
+    config: &MqttConfig,
+    process_name: String,
+    window_title: String,
+    domain: Option<String>,
+    start_time: String,
+) -> String {
+    let payload = ActivityPayload {
+        category: state
+            .categorizer
+            .classify(&process_name, &window_title, domain.as_deref()),
+        process_name,
+        domain,
+        window_title: config.include_titles.then_some(window_title),
+        start_time,
+    };
+    serde_json::to_string(&payload).unwrap_or_default()
+}
+
+async fn publish(client: &AsyncClient, topic: &str, payload: impl Into<Vec<u8>>) {
+    if let Err(e) = client
+        .publish(topic, QoS::AtLeastOnce, true, payload.into())
+        .await
+    {
+        eprintln!("Failed to publish to {}: {}", topic, e);
+    }
+}
@@ -0,0 +1,162 @@
+use parking_lot::RwLock;
+use regex::Regex;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use crate::plugins::config::{IntegrationsConfig, TitleRule};
+
+struct CompiledRule {
+    regex: Regex,
+    replacement: String,
+    process: Option<Regex>,
+}
+
+/// 再正規化の結果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenormalizeReport {
+    pub scanned: usize,
+    pub changed: usize,
+    pub dry_run: bool,
+}
+
+/// ウィンドウタイトルの正規化
+pub struct TitleNormalizer {
+    rules: RwLock<Vec<CompiledRule>>,
+}
+
+impl TitleNormalizer {
+    pub fn new() -> Self {
+        Self {
+            rules: RwLock::new(Vec::new()),
+        }
+    }
+
+    /// 設定ファイルから正規化ルールを読み込む
+    pub fn load_from_config(&self) {
+        let config = IntegrationsConfig::load();
+        self.set_rules(&config.title_rules);
+    }
+
+    /// 正規化ルールを差し替える（コンパイルできないパターンは無視）
+    pub fn set_rules(&self, rules: &[TitleRule]) {
+        let compiled = rules
+            .iter()
+            .filter_map(|rule| {
+                let regex = match Regex::new(&rule.pattern) {
+                    Ok(regex) => regex,
+                    Err(e) => {
+                        eprintln!("Invalid title pattern {:?}: {}", rule.pattern, e);
+                        return None;
+                    }
+                };
+                let process = match rule.process_pattern.as_deref().map(Regex::new) {
+                    Some(Ok(process)) => Some(process),
+                    Some(Err(e)) => {
+                        eprintln!("Invalid process pattern {:?}: {}", rule.process_pattern, e);
+                        return None;
+                    }
+                    None => None,
+                };
+                Some(CompiledRule {
+                    regex,
+                    replacement: rule.replacement.clone(),
+                    process,
+                })
+            })
+            .collect();
+
+        *self.rules.write() = compiled;
+    }
+
+    /// ルールを順に適用したタイトルを返す（空になった場合は元のタイトル）
+    pub fn normalize(&self, process_name: &str, window_title: &str) -> String {
+        let mut title = window_title.to_string();
+        for rule in self.rules.read().iter() {
+            if rule
+                .process
+                .as_ref()
+                .is_some_and(|process| !process.is_match(process_name))
+            {
+                continue;
+            }
+            title = rule
+                .regex
+                .replace_all(&title, rule.replacement.as_str())
+                .trim()
+                .to_string();
+        }
+
+        if title.is_empty() {
+            window_title.to_string()
+        } else {
+            title
+        }
+    }
+
+    /// 記録済みのアクティビティに現在のルールを適用し直す（日付未指定なら全期間）
+    ///
+    /// 正規化前のタイトル（raw_window_title）から正規化し直すので、ルールを外せば元のタイトルに戻る。
+    pub fn renormalize(
+        &self,
+        conn: &Connection,
+        start_date: Option<&str>,
+        end_date: Option<&str>,
+        dry_run: bool,
+    ) -> Result<RenormalizeReport, String> {
+        let start = start_date.map(|d| format!("{}T00:00:00", d));
+        let end = end_date.map(|d| format!("{}T23:59:59", d));
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, process_name, window_title, COALESCE(raw_window_title, window_title)
+                 FROM activities
+                 WHERE (?1 IS NULL OR start_time >= ?1) AND (?2 IS NULL OR start_time <= ?2)",
+            )
+            .map_err(|e| e.to_string())?;
+
+        let rows: Vec<(i64, String, String, String)> = stmt
+            .query_map(params![start, end], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+            })
+            .map_err(|e| e.to_string())?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        let changes: Vec<(i64, String, Option<&str>)> = rows
+            .iter()
+            .filter_map(|(id, process_name, window_title, raw_title)| {
+                let normalized = self.normalize(process_name, raw_title);
+                let raw = (normalized != *raw_title).then_some(raw_title.as_str());
+                (normalized != *window_title).then_some((*id, normalized, raw))
+            })
+            .collect();
+
+        if !dry_run {
+            let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+            for (id, title, raw) in &changes {
+                tx.execute(
+                    // タイトルが変わるのでチケットは抽出し直す
+                    "UPDATE activities
+                     SET window_title = ?1, raw_window_title = ?2,
+                         extracted_plugin = NULL, extracted_ticket_id = NULL, extracted_at = NULL
+                     WHERE id = ?3",
+                    params![title, raw, id],
+                )
+                .map_err(|e| e.to_string())?;
+            }
+            tx.commit().map_err(|e| e.to_string())?;
+        }
+
+        Ok(RenormalizeReport {
+            scanned: rows.len(),
+            changed: changes.len(),
+            dry_run,
+        })
+    }
+}
+
+impl Default for TitleNormalizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
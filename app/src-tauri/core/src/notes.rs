@@ -0,0 +1,65 @@
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+/// メモつきのアクティビティ
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivityNote {
+    pub activity_id: i64,
+    pub process_name: String,
+    pub window_title: String,
+    pub start_time: String,
+    pub end_time: String,
+    pub duration_seconds: i64,
+    pub note: String,
+}
+
+/// アクティビティにメモを設定（空文字なら削除）
+pub fn set(conn: &Connection, activity_id: i64, text: &str) -> Result<(), String> {
+    let text = text.trim();
+    let note = if text.is_empty() { None } else { Some(text) };
+
+    let updated = conn
+        .execute(
+            "UPDATE activities SET note = ?1 WHERE id = ?2",
+            params![note, activity_id],
+        )
+        .map_err(|e| e.to_string())?;
+
+    if updated == 0 {
+        return Err(format!("Activity not found: {}", activity_id));
+    }
+    Ok(())
+}
+
+/// 指定日のメモつきのアクティビティ
+pub fn for_date(conn: &Connection, date: &str) -> Result<Vec<ActivityNote>, String> {
+    let start_of_day = format!("{}T00:00:00", date);
+    let end_of_day = format!("{}T23:59:59", date);
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, process_name, window_title, start_time, end_time, duration_seconds, note
+             FROM activities
+             WHERE start_time >= ?1 AND start_time <= ?2 AND note IS NOT NULL
+             ORDER BY start_time ASC",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let notes = stmt
+        .query_map(params![start_of_day, end_of_day], |row| {
+            Ok(ActivityNote {
+                activity_id: row.get(0)?,
+                process_name: row.get(1)?,
+                window_title: row.get(2)?,
+                start_time: row.get(3)?,
+                end_time: row.get(4)?,
+                duration_seconds: row.get(5)?,
+                note: row.get(6)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(notes)
+}
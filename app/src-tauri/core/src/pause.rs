@@ -0,0 +1,92 @@
+use chrono::{DateTime, Local};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+/// 一時停止できる最大時間（分）
+pub const MAX_PAUSE_MINUTES: u32 = 24 * 60;
+
+const TIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+/// 進行中の一時停止
+#[derive(Debug, Clone, Copy)]
+pub struct Pause {
+    pub started_at: DateTime<Local>,
+    /// この時刻に自動でトラッキングを再開する
+    pub resume_at: DateTime<Local>,
+}
+
+impl Pause {
+    pub fn new(duration_minutes: u32) -> Result<Self, String> {
+        if duration_minutes == 0 || duration_minutes > MAX_PAUSE_MINUTES {
+            return Err(format!(
+                "Pause duration must be between 1 and {} minutes",
+                MAX_PAUSE_MINUTES
+            ));
+        }
+        let started_at = Local::now();
+        Ok(Self {
+            started_at,
+            resume_at: started_at + chrono::Duration::minutes(duration_minutes as i64),
+        })
+    }
+
+    /// 再開までの残り秒数
+    pub fn remaining_seconds(&self) -> i64 {
+        (self.resume_at - Local::now()).num_seconds().max(0)
+    }
+
+    pub fn is_over(&self) -> bool {
+        Local::now() >= self.resume_at
+    }
+
+    pub fn status(&self) -> PauseStatus {
+        PauseStatus {
+            started_at: self.started_at.format(TIME_FORMAT).to_string(),
+            resume_at: self.resume_at.format(TIME_FORMAT).to_string(),
+            remaining_seconds: self.remaining_seconds(),
+        }
+    }
+}
+
+/// 一時停止の状態（フロントエンド向け）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PauseStatus {
+    pub started_at: String,
+    pub resume_at: String,
+    pub remaining_seconds: i64,
+}
+
+pub fn create_tables(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS paused_periods (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            start_time TEXT NOT NULL,
+            end_time TEXT NOT NULL
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_paused_periods_start ON paused_periods(start_time)",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// 一時停止していた区間を記録（自動再開の予定時刻より後には伸ばさない）
+pub fn record(conn: &Connection, pause: &Pause, ended_at: DateTime<Local>) -> Result<(), String> {
+    let end = ended_at.min(pause.resume_at);
+    if end <= pause.started_at {
+        return Ok(());
+    }
+    conn.execute(
+        "INSERT INTO paused_periods (start_time, end_time) VALUES (?1, ?2)",
+        params![
+            pause.started_at.format(TIME_FORMAT).to_string(),
+            end.format(TIME_FORMAT).to_string()
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
@@ -0,0 +1,396 @@
+use std::collections::HashSet;
+
+use chrono::{Duration, Local, NaiveDate, NaiveDateTime};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use crate::plugins::traits::{ActivityInfo, SyncResult};
+
+/// 確認待ち
+pub const STATUS_PENDING: &str = "pending";
+/// 承認済み（送信対象）
+pub const STATUS_APPROVED: &str = "approved";
+/// 却下（送信せず、再生成もしない）
+pub const STATUS_REJECTED: &str = "rejected";
+/// 送信済み
+pub const STATUS_SYNCED: &str = "synced";
+
+/// 送信前に確認するタイムエントリの候補
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingSync {
+    pub id: i64,
+    pub plugin: String,
+    pub ticket_id: String,
+    /// YYYY-MM-DD
+    pub date: String,
+    pub duration_seconds: i64,
+    pub comment: String,
+    pub activity_ids: Vec<i64>,
+    /// 候補の元になった（チケット単位に統合した）アクティビティ
+    pub activity: ActivityInfo,
+    pub status: String,
+    pub last_error: Option<String>,
+    pub external_id: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl PendingSync {
+    /// 編集内容を反映した送信用のアクティビティ（コメントはメモとして渡す）
+    pub fn to_activity(&self) -> ActivityInfo {
+        let time = self
+            .activity
+            .start_time
+            .split_once('T')
+            .map(|(_, time)| time)
+            .unwrap_or("00:00:00");
+        let start_time = format!("{}T{}", self.date, time);
+        let end_time = NaiveDateTime::parse_from_str(&start_time, "%Y-%m-%dT%H:%M:%S")
+            .map(|start| {
+                (start + Duration::seconds(self.duration_seconds))
+                    .format("%Y-%m-%dT%H:%M:%S")
+                    .to_string()
+            })
+            .unwrap_or_else(|_| self.activity.end_time.clone());
+
+        ActivityInfo {
+            start_time,
+            end_time,
+            duration_seconds: self.duration_seconds,
+            note: Some(self.comment.clone()).filter(|c| !c.trim().is_empty()),
+            ..self.activity.clone()
+        }
+    }
+}
+
+/// 承認済みエントリの送信結果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PushResult {
+    pub id: i64,
+    pub plugin: String,
+    pub ticket_id: String,
+    pub result: SyncResult,
+}
+
+pub fn create_tables(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS pending_sync (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            plugin TEXT NOT NULL,
+            ticket_id TEXT NOT NULL,
+            date TEXT NOT NULL,
+            duration_seconds INTEGER NOT NULL,
+            comment TEXT NOT NULL,
+            activity_ids TEXT NOT NULL,
+            activity_json TEXT NOT NULL,
+            status TEXT NOT NULL,
+            last_error TEXT,
+            external_id TEXT,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_pending_sync_date ON pending_sync(date, plugin)",
+        [],
+    )?;
+
+    Ok(())
+}
+
+fn now_string() -> String {
+    Local::now().format("%Y-%m-%dT%H:%M:%S").to_string()
+}
+
+fn json_error(index: usize, e: serde_json::Error) -> rusqlite::Error {
+    rusqlite::Error::FromSqlConversionFailure(index, rusqlite::types::Type::Text, Box::new(e))
+}
+
+fn from_row(row: &rusqlite::Row) -> rusqlite::Result<PendingSync> {
+    let activity_ids: String = row.get(6)?;
+    let activity_json: String = row.get(7)?;
+    Ok(PendingSync {
+        id: row.get(0)?,
+        plugin: row.get(1)?,
+        ticket_id: row.get(2)?,
+        date: row.get(3)?,
+        duration_seconds: row.get(4)?,
+        comment: row.get(5)?,
+        activity_ids: serde_json::from_str(&activity_ids).map_err(|e| json_error(6, e))?,
+        activity: serde_json::from_str(&activity_json).map_err(|e| json_error(7, e))?,
+        status: row.get(8)?,
+        last_error: row.get(9)?,
+        external_id: row.get(10)?,
+        created_at: row.get(11)?,
+        updated_at: row.get(12)?,
+    })
+}
+
+const COLUMNS: &str = "id, plugin, ticket_id, date, duration_seconds, comment, activity_ids, activity_json, status, last_error, external_id, created_at, updated_at";
+
+/// 候補に含まれているアクティビティID（送信済みは sync_log で判定するので除く）
+pub fn staged_activity_ids(conn: &Connection, plugin: &str) -> Result<HashSet<i64>, String> {
+    let ids = list(conn, Some(plugin), None, None)?
+        .into_iter()
+        .filter(|entry| entry.status != STATUS_SYNCED)
+        .flat_map(|entry| entry.activity_ids)
+        .collect();
+    Ok(ids)
+}
+
+/// 指定日の未送信の候補（却下したものを除く）の時間の合計
+pub fn staged_seconds(conn: &Connection, plugin: &str, date: &str) -> Result<i64, String> {
+    Ok(list(conn, Some(plugin), Some(date), None)?
+        .into_iter()
+        .filter(|entry| entry.status == STATUS_PENDING || entry.status == STATUS_APPROVED)
+        .map(|entry| entry.duration_seconds)
+        .sum())
+}
+
+/// 候補を追加（comment は送信するコメントの初期値）
+pub fn propose(
+    conn: &Connection,
+    plugin: &str,
+    ticket_id: &str,
+    activity: &ActivityInfo,
+    activity_ids: &[i64],
+    comment: &str,
+) -> Result<i64, String> {
+    let date = activity
+        .start_time
+        .split_once('T')
+        .map(|(date, _)| date)
+        .unwrap_or(&activity.start_time);
+    let activity_ids = serde_json::to_string(activity_ids).map_err(|e| e.to_string())?;
+    let activity_json = serde_json::to_string(activity).map_err(|e| e.to_string())?;
+    let now = now_string();
+
+    conn.execute(
+        "INSERT INTO pending_sync (plugin, ticket_id, date, duration_seconds, comment, activity_ids, activity_json, status, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?9)",
+        params![
+            plugin,
+            ticket_id,
+            date,
+            activity.duration_seconds,
+            comment,
+            activity_ids,
+            activity_json,
+            STATUS_PENDING,
+            now
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(conn.last_insert_rowid())
+}
+
+/// 候補を取得
+pub fn get(conn: &Connection, id: i64) -> Result<Option<PendingSync>, String> {
+    conn.query_row(
+        &format!("SELECT {} FROM pending_sync WHERE id = ?1", COLUMNS),
+        params![id],
+        from_row,
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+/// 未送信の候補を取得（送信済みは編集できない）
+fn get_editable(conn: &Connection, id: i64) -> Result<PendingSync, String> {
+    let entry = get(conn, id)?.ok_or_else(|| format!("Pending sync entry not found: {}", id))?;
+    if entry.status == STATUS_SYNCED {
+        return Err(format!("Pending sync entry {} is already synced", id));
+    }
+    Ok(entry)
+}
+
+/// 候補の一覧（未指定の条件は絞り込まない、日付順）
+pub fn list(
+    conn: &Connection,
+    plugin: Option<&str>,
+    date: Option<&str>,
+    status: Option<&str>,
+) -> Result<Vec<PendingSync>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM pending_sync
+             WHERE (?1 IS NULL OR plugin = ?1) AND (?2 IS NULL OR date = ?2) AND (?3 IS NULL OR status = ?3)
+             ORDER BY date ASC, id ASC",
+            COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let entries = stmt
+        .query_map(params![plugin, date, status], from_row)
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(entries)
+}
+
+/// 候補を編集（指定した項目だけ変更し、確認待ちに戻す）
+pub fn update(
+    conn: &Connection,
+    id: i64,
+    ticket_id: Option<&str>,
+    date: Option<&str>,
+    duration_seconds: Option<i64>,
+    comment: Option<&str>,
+) -> Result<PendingSync, String> {
+    let mut entry = get_editable(conn, id)?;
+
+    if let Some(ticket_id) = ticket_id {
+        let ticket_id = ticket_id.trim();
+        if ticket_id.is_empty() {
+            return Err("Ticket ID must not be empty".to_string());
+        }
+        entry.ticket_id = ticket_id.to_string();
+    }
+    if let Some(date) = date {
+        NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|e| format!("Invalid date {}: {}", date, e))?;
+        entry.date = date.to_string();
+    }
+    if let Some(duration_seconds) = duration_seconds {
+        if duration_seconds <= 0 {
+            return Err("Duration must be positive".to_string());
+        }
+        entry.duration_seconds = duration_seconds;
+    }
+    if let Some(comment) = comment {
+        entry.comment = comment.to_string();
+    }
+
+    conn.execute(
+        "UPDATE pending_sync
+         SET ticket_id = ?1, date = ?2, duration_seconds = ?3, comment = ?4, status = ?5, updated_at = ?6
+         WHERE id = ?7",
+        params![
+            entry.ticket_id,
+            entry.date,
+            entry.duration_seconds,
+            entry.comment,
+            STATUS_PENDING,
+            now_string(),
+            id
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    get_editable(conn, id)
+}
+
+/// 同じチケットの複数の候補を1件にまとめる（先頭の候補に時間とアクティビティを合算し、残りは削除）
+pub fn merge(conn: &Connection, ids: &[i64]) -> Result<PendingSync, String> {
+    if ids.len() < 2 {
+        return Err("Select at least two entries to merge".to_string());
+    }
+    let mut entries = ids
+        .iter()
+        .map(|&id| get_editable(conn, id))
+        .collect::<Result<Vec<_>, _>>()?;
+    entries
+        .sort_by(|a, b| (&a.date, &a.activity.start_time).cmp(&(&b.date, &b.activity.start_time)));
+
+    let first = &entries[0];
+    if let Some(other) = entries.iter().find(|e| e.plugin != first.plugin) {
+        return Err(format!(
+            "Cannot merge entries of different integrations: {} and {}",
+            first.plugin, other.plugin
+        ));
+    }
+    if let Some(other) = entries.iter().find(|e| e.ticket_id != first.ticket_id) {
+        return Err(format!(
+            "Cannot merge entries of different tickets: {} and {}",
+            first.ticket_id, other.ticket_id
+        ));
+    }
+
+    let mut activity_ids: Vec<i64> = Vec::new();
+    let mut comments: Vec<&str> = Vec::new();
+    for entry in &entries {
+        for id in &entry.activity_ids {
+            if !activity_ids.contains(id) {
+                activity_ids.push(*id);
+            }
+        }
+        let comment = entry.comment.trim();
+        if !comment.is_empty() && !comments.contains(&comment) {
+            comments.push(comment);
+        }
+    }
+    let duration_seconds: i64 = entries.iter().map(|e| e.duration_seconds).sum();
+    let activity_ids = serde_json::to_string(&activity_ids).map_err(|e| e.to_string())?;
+
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    tx.execute(
+        "UPDATE pending_sync
+         SET duration_seconds = ?1, comment = ?2, activity_ids = ?3, status = ?4, updated_at = ?5
+         WHERE id = ?6",
+        params![
+            duration_seconds,
+            comments.join("; "),
+            activity_ids,
+            STATUS_PENDING,
+            now_string(),
+            first.id
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    for entry in &entries[1..] {
+        tx.execute("DELETE FROM pending_sync WHERE id = ?1", params![entry.id])
+            .map_err(|e| e.to_string())?;
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+
+    get_editable(conn, first.id)
+}
+
+/// 候補を承認・却下・確認待ちに戻す
+pub fn set_status(conn: &Connection, ids: &[i64], status: &str) -> Result<(), String> {
+    if ![STATUS_PENDING, STATUS_APPROVED, STATUS_REJECTED].contains(&status) {
+        return Err(format!("Invalid status: {}", status));
+    }
+    for &id in ids {
+        get_editable(conn, id)?;
+        conn.execute(
+            "UPDATE pending_sync SET status = ?1, updated_at = ?2 WHERE id = ?3",
+            params![status, now_string(), id],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// 候補を削除（含まれていたアクティビティは次回の生成で再び候補になる）
+pub fn discard(conn: &Connection, id: i64) -> Result<(), String> {
+    get_editable(conn, id)?;
+    conn.execute("DELETE FROM pending_sync WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// 送信成功を記録
+pub fn mark_synced(conn: &Connection, id: i64, external_id: Option<&str>) -> Result<(), String> {
+    conn.execute(
+        "UPDATE pending_sync SET status = ?1, last_error = NULL, external_id = ?2, updated_at = ?3
+         WHERE id = ?4",
+        params![STATUS_SYNCED, external_id, now_string(), id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// 送信失敗を記録（承認済みのまま残し、次回の送信で再送する）
+pub fn mark_error(conn: &Connection, id: i64, error: &str) -> Result<(), String> {
+    conn.execute(
+        "UPDATE pending_sync SET last_error = ?1, updated_at = ?2 WHERE id = ?3",
+        params![error, now_string(), id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
@@ -0,0 +1,74 @@
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+/// フォーカスされたウィンドウが表示されていたモニターと仮想デスクトップ
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WindowPlacement {
+    /// モニターのデバイス名（"\\.\DISPLAY1" など）
+    pub monitor: Option<String>,
+    /// メインモニターかどうか
+    pub primary: Option<bool>,
+    /// 仮想デスクトップのID（Windows の GUID）
+    pub virtual_desktop: Option<String>,
+}
+
+/// モニター・仮想デスクトップごとの合計
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlacementSummary {
+    pub monitor: Option<String>,
+    pub primary: Option<bool>,
+    pub virtual_desktop: Option<String>,
+    pub total_seconds: i64,
+    pub percentage: f64,
+}
+
+/// 期間内の記録時間をモニターと仮想デスクトップの組み合わせごとに集計
+///
+/// 記録前のデータや取得できない環境では各項目が None になる。
+pub fn summary(
+    conn: &Connection,
+    start_date: &str,
+    end_date: &str,
+) -> Result<Vec<PlacementSummary>, String> {
+    type Row = (Option<String>, Option<bool>, Option<String>, i64);
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT monitor, monitor_primary, virtual_desktop, SUM(duration_seconds) AS total
+             FROM activities
+             WHERE start_time >= ?1 AND start_time <= ?2
+             GROUP BY monitor, monitor_primary, virtual_desktop
+             ORDER BY total DESC",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows: Vec<Row> = stmt
+        .query_map(
+            params![
+                format!("{}T00:00:00", start_date),
+                format!("{}T23:59:59", end_date)
+            ],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let total: i64 = rows.iter().map(|(_, _, _, seconds)| seconds).sum();
+    Ok(rows
+        .into_iter()
+        .map(
+            |(monitor, primary, virtual_desktop, total_seconds)| PlacementSummary {
+                monitor,
+                primary,
+                virtual_desktop,
+                total_seconds,
+                percentage: if total > 0 {
+                    total_seconds as f64 / total as f64 * 100.0
+                } else {
+                    0.0
+                },
+            },
+        )
+        .collect())
+}
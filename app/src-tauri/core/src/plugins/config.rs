@@ -0,0 +1,1729 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// チケットID抽出ルール
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtractionRule {
+    /// 正規表現パターン（キャプチャグループでIDを抽出）
+    pub pattern: String,
+    /// 抽出元: "window_title" | "process_name" | "domain"
+    #[serde(default = "default_rule_source")]
+    pub source: String,
+    /// 複数の抽出元を順に試す（指定時は source より優先）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sources: Vec<String>,
+    /// チケットIDの組み立て方（"$project-$issue" のように名前付きグループを参照。未指定時は1番目のグループ）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+}
+
+fn default_rule_source() -> String {
+    "window_title".to_string()
+}
+
+impl ExtractionRule {
+    /// ウィンドウタイトルから1番目のグループを抽出するルール
+    pub fn window_title(pattern: &str) -> Self {
+        Self {
+            pattern: pattern.to_string(),
+            source: default_rule_source(),
+            sources: Vec::new(),
+            template: None,
+        }
+    }
+
+    /// 試す抽出元（sources が空なら source のみ）
+    pub fn sources(&self) -> Vec<&str> {
+        if self.sources.is_empty() {
+            vec![self.source.as_str()]
+        } else {
+            self.sources.iter().map(String::as_str).collect()
+        }
+    }
+}
+
+/// Redmine固有設定
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedmineConfig {
+    pub url: String,
+    /// 平文のAPIキー（api_key_secret を使う場合は空）
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub api_key: String,
+    /// OSのキーリングに保存したAPIキーの名前（指定時は api_key より優先）
+    #[serde(default)]
+    pub api_key_secret: Option<String>,
+    #[serde(default)]
+    pub default_activity_id: Option<i64>,
+    #[serde(default)]
+    pub rules: Vec<ExtractionRule>,
+}
+
+/// Jira固有設定（Basic認証: メールアドレス + APIトークン）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JiraConfig {
+    /// e.g., "https://your-domain.atlassian.net"
+    pub url: String,
+    pub email: String,
+    pub api_token: String,
+    /// 未指定時はウィンドウタイトルから "PROJ-123" 形式の課題キーを抽出
+    #[serde(default = "default_jira_rules")]
+    pub rules: Vec<ExtractionRule>,
+}
+
+fn default_jira_rules() -> Vec<ExtractionRule> {
+    vec![ExtractionRule::window_title(r"\b([A-Z][A-Z0-9]+-\d+)\b")]
+}
+
+/// GitHub固有設定（Personal Access Token で認証）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitHubConfig {
+    pub token: String,
+    /// GitHub Enterprise の場合は "https://github.example.com/api/v3" など
+    #[serde(default = "default_github_api_url")]
+    pub api_url: String,
+    /// 番号のみ抽出できた場合に使うリポジトリ（"owner/repo"）
+    #[serde(default)]
+    pub default_repo: Option<String>,
+    /// Issue/PRに作業時間のコメントを追記する
+    #[serde(default = "default_enabled")]
+    pub post_comments: bool,
+    /// 指定時はプロジェクトの数値フィールド（時間）に作業時間を加算
+    #[serde(default)]
+    pub project: Option<GitHubProjectConfig>,
+    /// 名前付きグループ repo / number で "owner/repo#123" を組み立てる
+    #[serde(default = "default_github_rules")]
+    pub rules: Vec<ExtractionRule>,
+}
+
+/// GitHub Projects の更新先（GraphQL のノードID）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitHubProjectConfig {
+    pub project_id: String,
+    pub field_id: String,
+}
+
+fn default_github_api_url() -> String {
+    "https://api.github.com".to_string()
+}
+
+fn default_github_rules() -> Vec<ExtractionRule> {
+    vec![
+        ExtractionRule::window_title(
+            r"github\.com/(?P<repo>[\w.-]+/[\w.-]+)/(?:issues|pull)/(?P<number>\d+)",
+        ),
+        ExtractionRule::window_title(
+            r"(?:Issue|Pull Request) #(?P<number>\d+) · (?P<repo>[\w.-]+/[\w.-]+)",
+        ),
+        ExtractionRule::window_title(r"\b(?P<repo>[\w.-]+/[\w.-]+)#(?P<number>\d+)\b"),
+    ]
+}
+
+/// GitLab固有設定（プロジェクトごとに1エントリ）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitLabConfig {
+    #[serde(default = "default_gitlab_url")]
+    pub url: String,
+    /// Personal/Project Access Token（api スコープ）
+    pub token: String,
+    /// プロジェクトID または "group/project" 形式のパス
+    pub project: String,
+    /// "!45" はマージリクエスト、それ以外はIssueとして扱う
+    #[serde(default = "default_gitlab_rules")]
+    pub rules: Vec<ExtractionRule>,
+}
+
+fn default_gitlab_url() -> String {
+    "https://gitlab.com".to_string()
+}
+
+fn default_gitlab_rules() -> Vec<ExtractionRule> {
+    vec![
+        ExtractionRule::window_title(r"\((!\d+)\)"),
+        ExtractionRule::window_title(r"#(\d+)"),
+    ]
+}
+
+/// Toggl Track固有設定
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TogglConfig {
+    pub api_token: String,
+    pub workspace_id: i64,
+    /// マッピングにないチケットの登録先プロジェクト
+    #[serde(default)]
+    pub default_project_id: Option<i64>,
+    /// チケットID（またはプロジェクト名）→ TogglプロジェクトID
+    #[serde(default)]
+    pub projects: HashMap<String, i64>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub billable: bool,
+    #[serde(default)]
+    pub rules: Vec<ExtractionRule>,
+}
+
+/// Clockify固有設定
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClockifyConfig {
+    /// 平文のAPIキー（api_key_secret を使う場合は空）
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub api_key: String,
+    /// OSのキーリングに保存したAPIキーの名前（指定時は api_key より優先）
+    #[serde(default)]
+    pub api_key_secret: Option<String>,
+    pub workspace_id: String,
+    /// どのマッピングにも当てはまらないチケットの登録先プロジェクト
+    #[serde(default)]
+    pub default_project_id: Option<String>,
+    /// チケットIDのパターン → プロジェクト・タスク・タグ（最初にマッチしたもの）
+    #[serde(default)]
+    pub projects: Vec<ClockifyProjectMapping>,
+    /// すべてのタイムエントリに付けるタグのID
+    #[serde(default)]
+    pub tag_ids: Vec<String>,
+    #[serde(default)]
+    pub billable: bool,
+    #[serde(default)]
+    pub rules: Vec<ExtractionRule>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClockifyProjectMapping {
+    /// チケットIDに対する正規表現（例: "^PROJ-"）
+    pub pattern: String,
+    pub project_id: String,
+    #[serde(default)]
+    pub task_id: Option<String>,
+    /// tag_ids に追加するタグのID
+    #[serde(default)]
+    pub tag_ids: Vec<String>,
+}
+
+/// Harvest固有設定（Personal Access Token、または OAuth2 のリフレッシュトークン）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HarvestConfig {
+    pub account_id: String,
+    /// Personal Access Token（oauth を使う場合は空）
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub token: String,
+    /// OSのキーリングに保存したトークンの名前（指定時は token より優先）
+    #[serde(default)]
+    pub token_secret: Option<String>,
+    /// 指定時はリフレッシュトークンからアクセストークンを取得する
+    #[serde(default)]
+    pub oauth: Option<HarvestOAuthConfig>,
+    /// どのマッピングにも当てはまらないチケットの登録先
+    #[serde(default)]
+    pub default_project_id: Option<i64>,
+    #[serde(default)]
+    pub default_task_id: Option<i64>,
+    /// チケットIDのパターン → プロジェクト・タスク（最初にマッチしたもの）
+    #[serde(default)]
+    pub projects: Vec<HarvestProjectMapping>,
+    #[serde(default)]
+    pub rules: Vec<ExtractionRule>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HarvestOAuthConfig {
+    pub client_id: String,
+    /// OSのキーリングに保存したクライアントシークレットの名前
+    pub client_secret_secret: String,
+    /// OSのキーリングに保存したリフレッシュトークンの名前
+    pub refresh_token_secret: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HarvestProjectMapping {
+    /// チケットIDに対する正規表現（例: "^PROJ-"）
+    pub pattern: String,
+    pub project_id: i64,
+    pub task_id: i64,
+}
+
+/// Asana固有設定（Personal Access Token で認証）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AsanaConfig {
+    /// 平文のトークン（token_secret を使う場合は空）
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub token: String,
+    /// OSのキーリングに保存したトークンの名前（指定時は token より優先）
+    #[serde(default)]
+    pub token_secret: Option<String>,
+    /// タスクにコメントとして作業時間を追記する
+    #[serde(default = "default_enabled")]
+    pub post_comments: bool,
+    /// 指定時はタスクの数値カスタムフィールド（時間）に作業時間を加算
+    #[serde(default)]
+    pub hours_field_id: Option<String>,
+    /// 1番目のキャプチャグループでタスクの gid を抽出
+    #[serde(default = "default_asana_rules")]
+    pub rules: Vec<ExtractionRule>,
+}
+
+fn default_asana_rules() -> Vec<ExtractionRule> {
+    vec![
+        // https://app.asana.com/0/<project>/<task>（末尾に /f が付くこともある）
+        ExtractionRule::window_title(r"app\.asana\.com/0/\d+/(\d+)"),
+        // https://app.asana.com/1/<workspace>/project/<project>/task/<task>
+        ExtractionRule::window_title(r"app\.asana\.com/\S*/task/(\d+)"),
+    ]
+}
+
+/// Trello固有設定（APIキーとトークンで認証）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrelloConfig {
+    pub api_key: String,
+    /// 平文のトークン（token_secret を使う場合は空）
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub token: String,
+    /// OSのキーリングに保存したトークンの名前（指定時は token より優先）
+    #[serde(default)]
+    pub token_secret: Option<String>,
+    /// カードにコメントとして作業時間を追記する
+    #[serde(default = "default_enabled")]
+    pub post_comments: bool,
+    /// 指定時はカードの数値カスタムフィールド（時間）に作業時間を加算
+    #[serde(default)]
+    pub custom_field_id: Option<String>,
+    /// URLが取れなくても trello.com のタブなら "カード名 | ボード名 | Trello" のカード名で検索する
+    #[serde(default = "default_enabled")]
+    pub match_card_names: bool,
+    /// 1番目のキャプチャグループでカードの shortLink を抽出
+    #[serde(default = "default_trello_rules")]
+    pub rules: Vec<ExtractionRule>,
+}
+
+fn default_trello_rules() -> Vec<ExtractionRule> {
+    vec![ExtractionRule::window_title(
+        r"trello\.com/c/([A-Za-z0-9]{8})",
+    )]
+}
+
+/// Notion固有設定（インテグレーションのトークンで認証し、データベースに行を追加）
+///
+/// 抽出したチケットIDをプロジェクトとして記録する。1件ずつの同期ならアクティビティごと、
+/// 1日分の一括同期ならプロジェクトごとに1行になる。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotionConfig {
+    /// 平文のトークン（token_secret を使う場合は空）
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub token: String,
+    /// OSのキーリングに保存したトークンの名前（指定時は token より優先）
+    #[serde(default)]
+    pub token_secret: Option<String>,
+    pub database_id: String,
+    /// 指定時はルールにマッチしないアクティビティもこのプロジェクトとして記録する
+    #[serde(default)]
+    pub default_project: Option<String>,
+    #[serde(default)]
+    pub columns: NotionColumns,
+    #[serde(default)]
+    pub rules: Vec<ExtractionRule>,
+}
+
+/// 書き込み先のプロパティ名（空文字のプロパティは書き込まない）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotionColumns {
+    /// タイトル型のプロパティ（プロジェクト）
+    pub project: String,
+    /// 日付型
+    pub date: String,
+    /// テキスト型
+    pub app: String,
+    /// 数値型（時間）
+    pub duration: String,
+    /// テキスト型
+    pub notes: String,
+}
+
+impl Default for NotionColumns {
+    fn default() -> Self {
+        Self {
+            project: "Project".to_string(),
+            date: "Date".to_string(),
+            app: "App".to_string(),
+            duration: "Hours".to_string(),
+            notes: "Notes".to_string(),
+        }
+    }
+}
+
+/// 任意のHTTP APIに送る連携（URL・ヘッダー・本文はテンプレート）
+///
+/// `{{ticket_id}}` `{{title}}` `{{duration_hours}}` などのプレースホルダーを置き換える。
+/// 本文では JSON の文字列として、URLではパーセントエンコードして埋め込む。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomHttpConfig {
+    pub url: String,
+    #[serde(default = "default_custom_method")]
+    pub method: String,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// JSON本文のテンプレート（未指定なら本文なし）
+    #[serde(default)]
+    pub body: Option<String>,
+    /// OSのキーリングに保存したトークンの名前（URL・ヘッダーの `{{token}}` に入る）
+    #[serde(default)]
+    pub token_secret: Option<String>,
+    /// レスポンスのJSONから外部IDを取り出す JSON Pointer（例: "/id"）
+    #[serde(default)]
+    pub id_pointer: Option<String>,
+    /// 接続テストで GET するURL（未指定なら接続テストは未対応）
+    #[serde(default)]
+    pub test_url: Option<String>,
+    #[serde(default)]
+    pub rules: Vec<ExtractionRule>,
+}
+
+fn default_custom_method() -> String {
+    "POST".to_string()
+}
+
+/// 外部コマンドに任せる連携
+///
+/// 標準入力に `{"action": "sync", "ticket_id", "activity"}` または `{"action": "test"}` の JSON を渡し、
+/// 標準出力の JSON（sync は SyncResult、test は `{"success": true}`）を結果として受け取る。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecConfig {
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// 追加の環境変数
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// 指定時はOSのキーリングの値を環境変数 TIMETRACKER_TOKEN で渡す
+    #[serde(default)]
+    pub token_secret: Option<String>,
+    #[serde(default = "default_exec_timeout")]
+    pub timeout_seconds: u64,
+    #[serde(default)]
+    pub rules: Vec<ExtractionRule>,
+}
+
+fn default_exec_timeout() -> u64 {
+    30
+}
+
+/// Slack のステータス（絵文字は ":technologist:" 形式）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SlackStatus {
+    pub text: String,
+    #[serde(default)]
+    pub emoji: String,
+}
+
+/// Slack 連携（ユーザートークン: users.profile:write, dnd:write, chat:write）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlackConfig {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// 平文のトークン（token_secret を使う場合は空）
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub token: String,
+    /// OSのキーリングに保存したトークンの名前（指定時は token より優先）
+    #[serde(default)]
+    pub token_secret: Option<String>,
+    /// カテゴリ → ステータス（マッピングにないカテゴリでは default_status、なければステータスを消す）
+    #[serde(default)]
+    pub statuses: HashMap<String, SlackStatus>,
+    #[serde(default)]
+    pub default_status: Option<SlackStatus>,
+    /// 同じカテゴリが dnd_after_minutes 続いたらおやすみモードにする
+    #[serde(default)]
+    pub dnd_during_focus: bool,
+    /// おやすみモードにするカテゴリ（空ならカテゴリのあるすべてのアクティビティ）
+    #[serde(default)]
+    pub focus_categories: Vec<String>,
+    #[serde(default = "default_slack_dnd_after")]
+    pub dnd_after_minutes: i64,
+    /// 日次サマリーの投稿先チャンネル（ID または "#channel"）
+    #[serde(default)]
+    pub summary_channel: Option<String>,
+    /// 日次サマリーを投稿する時刻（"HH:MM"、未指定なら自動では投稿しない）
+    #[serde(default)]
+    pub summary_time: Option<String>,
+}
+
+fn default_slack_dnd_after() -> i64 {
+    25
+}
+
+/// 予定を取り込むカレンダーのサービス
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CalendarProvider {
+    /// Google Calendar API
+    Google,
+    /// Microsoft Graph（Outlook / Microsoft 365）
+    Outlook,
+}
+
+/// 会議の時間と記録を突き合わせるカレンダー（OAuth のアクセストークンで認証）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarConfig {
+    pub name: String,
+    pub provider: CalendarProvider,
+    /// 平文のアクセストークン（token_secret を使う場合は空）
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub token: String,
+    /// OSのキーリングに保存したトークンの名前（指定時は token より優先）
+    #[serde(default)]
+    pub token_secret: Option<String>,
+    /// Google のカレンダーID（既定は "primary"、Outlook では既定の予定表を使う）
+    #[serde(default)]
+    pub calendar_id: Option<String>,
+}
+
+/// プラグイン設定（汎用）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum IntegrationConfig {
+    #[serde(rename = "redmine")]
+    Redmine(RedmineConfig),
+    #[serde(rename = "jira")]
+    Jira(JiraConfig),
+    #[serde(rename = "github")]
+    GitHub(GitHubConfig),
+    #[serde(rename = "toggl")]
+    Toggl(TogglConfig),
+    #[serde(rename = "gitlab")]
+    GitLab(GitLabConfig),
+    #[serde(rename = "clockify")]
+    Clockify(ClockifyConfig),
+    #[serde(rename = "harvest")]
+    Harvest(HarvestConfig),
+    #[serde(rename = "asana")]
+    Asana(AsanaConfig),
+    #[serde(rename = "trello")]
+    Trello(TrelloConfig),
+    #[serde(rename = "notion")]
+    Notion(NotionConfig),
+    #[serde(rename = "custom")]
+    Custom(CustomHttpConfig),
+    #[serde(rename = "exec")]
+    Exec(ExecConfig),
+}
+
+impl IntegrationConfig {
+    /// チケットID抽出ルール
+    pub fn rules(&self) -> &Vec<ExtractionRule> {
+        match self {
+            IntegrationConfig::Redmine(config) => &config.rules,
+            IntegrationConfig::Jira(config) => &config.rules,
+            IntegrationConfig::GitHub(config) => &config.rules,
+            IntegrationConfig::Toggl(config) => &config.rules,
+            IntegrationConfig::GitLab(config) => &config.rules,
+            IntegrationConfig::Clockify(config) => &config.rules,
+            IntegrationConfig::Harvest(config) => &config.rules,
+            IntegrationConfig::Asana(config) => &config.rules,
+            IntegrationConfig::Trello(config) => &config.rules,
+            IntegrationConfig::Notion(config) => &config.rules,
+            IntegrationConfig::Custom(config) => &config.rules,
+            IntegrationConfig::Exec(config) => &config.rules,
+        }
+    }
+
+    pub fn rules_mut(&mut self) -> &mut Vec<ExtractionRule> {
+        match self {
+            IntegrationConfig::Redmine(config) => &mut config.rules,
+            IntegrationConfig::Jira(config) => &mut config.rules,
+            IntegrationConfig::GitHub(config) => &mut config.rules,
+            IntegrationConfig::Toggl(config) => &mut config.rules,
+            IntegrationConfig::GitLab(config) => &mut config.rules,
+            IntegrationConfig::Clockify(config) => &mut config.rules,
+            IntegrationConfig::Harvest(config) => &mut config.rules,
+            IntegrationConfig::Asana(config) => &mut config.rules,
+            IntegrationConfig::Trello(config) => &mut config.rules,
+            IntegrationConfig::Notion(config) => &mut config.rules,
+            IntegrationConfig::Custom(config) => &mut config.rules,
+            IntegrationConfig::Exec(config) => &mut config.rules,
+        }
+    }
+}
+
+/// 個別の連携設定
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntegrationEntry {
+    pub name: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(flatten)]
+    pub config: IntegrationConfig,
+    /// 同期する時間の丸め・最小時間・1日の上限
+    #[serde(default)]
+    pub rounding: TimeRoundingConfig,
+    /// 同期するタイムエントリのコメント
+    #[serde(default)]
+    pub comment: CommentConfig,
+}
+
+impl IntegrationEntry {
+    /// 丸め・コメントが既定値の連携エントリ
+    pub fn new(name: &str, enabled: bool, config: IntegrationConfig) -> Self {
+        Self {
+            name: name.to_string(),
+            enabled,
+            config,
+            rounding: TimeRoundingConfig::default(),
+            comment: CommentConfig::default(),
+        }
+    }
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// 丸め方
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum RoundingMode {
+    /// 最も近い単位に丸める
+    #[default]
+    Nearest,
+    /// 単位に切り上げる
+    Up,
+}
+
+/// 連携ごとの同期時間の調整（同期時に適用し、記録された時間は変えない）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TimeRoundingConfig {
+    /// 丸め単位（分, e.g., 5 / 15 / 30）。0 なら丸めない
+    #[serde(default)]
+    pub minutes: u32,
+    #[serde(default)]
+    pub mode: RoundingMode,
+    /// 1エントリの最小時間（分）。0 なら制限しない
+    #[serde(default)]
+    pub minimum_minutes: u32,
+    /// 1日に同期する合計の上限（時間）
+    #[serde(default)]
+    pub daily_cap_hours: Option<f64>,
+}
+
+/// タイムエントリのコメントの組み立て方
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CommentConfig {
+    /// テンプレート（{{title}}, {{process}}, {{domain}}, {{project}}, {{category}}, {{note}}, {{duration_hm}}）。
+    /// 未指定ならメモ、なければ "アプリ名 - タイトル"
+    #[serde(default)]
+    pub template: Option<String>,
+    /// ウィンドウタイトルを送信しない
+    #[serde(default)]
+    pub omit_titles: bool,
+}
+
+/// アップロード設定
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UploadConfig {
+    /// アップロード先サーバーURL (e.g., "https://timetracker.example.com/api")
+    pub server_url: String,
+    /// アップロードを有効にするかどうか
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// 自動アップロードを有効にするかどうか
+    #[serde(default)]
+    pub auto_upload: bool,
+    /// 自動アップロード間隔（分）
+    #[serde(default = "default_upload_interval")]
+    pub auto_upload_interval_minutes: u32,
+    /// アップロード対象の最小時間（秒）- この時間以上使用したアプリ/ドメインのみアップロード
+    #[serde(default = "default_min_duration")]
+    pub min_duration_seconds: u32,
+    /// アップロード時に適用するマスキングプロファイル名（未指定時は "full"）
+    #[serde(default)]
+    pub redaction_profile: Option<String>,
+}
+
+fn default_upload_interval() -> u32 {
+    60 // デフォルト1時間ごと
+}
+
+fn default_min_duration() -> u32 {
+    600 // デフォルト10分
+}
+
+impl Default for UploadConfig {
+    fn default() -> Self {
+        Self {
+            server_url: String::new(),
+            enabled: false,
+            auto_upload: false,
+            auto_upload_interval_minutes: default_upload_interval(),
+            min_duration_seconds: default_min_duration(),
+            redaction_profile: None,
+        }
+    }
+}
+
+/// カテゴリ分類ルール（上から順に評価し、最初にマッチしたカテゴリを採用）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryRule {
+    /// 割り当てるカテゴリ名 (e.g., "Development")
+    pub category: String,
+    /// 正規表現パターン
+    pub pattern: String,
+    /// 判定対象: "window_title" | "process_name" | "domain"
+    pub source: String,
+}
+
+/// ウィンドウタイトルの正規化ルール（上から順にすべて適用）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TitleRule {
+    /// 置換対象の正規表現 (e.g., r"^\(\d+\)\s*" で "(3) Inbox" の件数を除去)
+    pub pattern: String,
+    /// 置換文字列（"$1" などでキャプチャグループを参照可能）
+    #[serde(default)]
+    pub replacement: String,
+    /// 指定時はこのパターンにマッチするプロセスにのみ適用
+    #[serde(default)]
+    pub process_pattern: Option<String>,
+}
+
+/// 割り当てルール（マッチしたアクティビティに固定のチケット/プロジェクトを割り当てる）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AssignmentRule {
+    /// 正規表現パターン
+    pub pattern: String,
+    /// 判定対象: "window_title" | "process_name" | "domain"
+    pub source: String,
+    #[serde(default)]
+    pub plugin: Option<String>,
+    #[serde(default)]
+    pub ticket_id: Option<String>,
+    #[serde(default)]
+    pub project: Option<String>,
+}
+
+/// カテゴリごとの週間予算
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeeklyBudget {
+    pub category: String,
+    /// "max"（上限）または "min"（目標下限）
+    #[serde(default = "default_budget_kind")]
+    pub kind: String,
+    /// 1週間あたりの時間
+    pub hours: f64,
+    /// 上限予算でこの割合（%）を超えたら警告通知
+    #[serde(default = "default_warn_percent")]
+    pub warn_at_percent: f64,
+}
+
+fn default_budget_kind() -> String {
+    "max".to_string()
+}
+
+fn default_warn_percent() -> f64 {
+    80.0
+}
+
+/// フィールドごとのマスキング方法
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldPolicy {
+    /// そのまま出力
+    #[default]
+    Keep,
+    /// ソルト付きハッシュに置き換え
+    Hash,
+    /// 出力しない
+    Remove,
+}
+
+/// エクスポート/アップロード用のマスキングプロファイル
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedactionProfile {
+    pub name: String,
+    #[serde(default)]
+    pub process_name: FieldPolicy,
+    #[serde(default)]
+    pub window_title: FieldPolicy,
+    #[serde(default)]
+    pub domain: FieldPolicy,
+    /// ハッシュ化に使うソルト（共有先での辞書攻撃対策）
+    #[serde(default)]
+    pub salt: String,
+}
+
+/// 古いデータの断片を統合するメンテナンス設定（記録を削除するので明示的に有効にした場合のみ）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompactionConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// この日数より古いデータのみ統合する（直近のデータは正確なまま残す）
+    #[serde(default = "default_compaction_age_days")]
+    pub older_than_days: u32,
+    /// この秒数未満のレコードを断片として扱う
+    #[serde(default = "default_fragment_seconds")]
+    pub max_fragment_seconds: i64,
+    /// 同じアプリの断片同士の間隔がこの秒数以内なら統合する
+    #[serde(default = "default_merge_gap_seconds")]
+    pub merge_gap_seconds: i64,
+}
+
+fn default_compaction_age_days() -> u32 {
+    7
+}
+
+fn default_fragment_seconds() -> i64 {
+    60
+}
+
+fn default_merge_gap_seconds() -> i64 {
+    120
+}
+
+impl Default for CompactionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            older_than_days: default_compaction_age_days(),
+            max_fragment_seconds: default_fragment_seconds(),
+            merge_gap_seconds: default_merge_gap_seconds(),
+        }
+    }
+}
+
+/// 保存時に細切れの記録をまとめる設定
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoalesceConfig {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// 直前の記録と同じアプリ・タイトルで、間隔がこの秒数以内なら直前の記録に足す
+    #[serde(default = "default_coalesce_gap_seconds")]
+    pub merge_gap_seconds: i64,
+    /// この秒数未満で、直前の記録に足せない記録は保存しない
+    #[serde(default = "default_min_segment_seconds")]
+    pub min_segment_seconds: i64,
+}
+
+fn default_coalesce_gap_seconds() -> i64 {
+    5
+}
+
+fn default_min_segment_seconds() -> i64 {
+    1
+}
+
+impl Default for CoalesceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            merge_gap_seconds: default_coalesce_gap_seconds(),
+            min_segment_seconds: default_min_segment_seconds(),
+        }
+    }
+}
+
+/// データベースの保存方法
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DatabaseConfig {
+    /// SQLCipher で暗号化して保存する（鍵は OS のキーリング）
+    #[serde(default)]
+    pub encrypted: bool,
+}
+
+/// 古い生データを日別の集計にまとめて削除する保持ポリシー
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 生のアクティビティを残す日数（それより古い日は daily_summaries に集計して削除）
+    #[serde(default = "default_retention_raw_days")]
+    pub raw_days: u32,
+}
+
+fn default_retention_raw_days() -> u32 {
+    90
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            raw_days: default_retention_raw_days(),
+        }
+    }
+}
+
+/// ローカルWebSocketによるアクティビティのリアルタイム配信
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiveStreamConfig {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default = "default_live_bind_address")]
+    pub bind_address: String,
+    #[serde(default = "default_live_port")]
+    pub port: u16,
+    /// 接続時にAPIトークン（read スコープ以上）を要求する
+    #[serde(default = "default_enabled")]
+    pub require_token: bool,
+}
+
+fn default_live_bind_address() -> String {
+    "127.0.0.1".to_string()
+}
+
+fn default_live_port() -> u16 {
+    8765
+}
+
+/// 外部ツール向けのローカル REST API サーバー（常に API トークンが必要）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiServerConfig {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default = "default_live_bind_address")]
+    pub bind_address: String,
+    #[serde(default = "default_api_port")]
+    pub port: u16,
+    /// GET /metrics で Prometheus の形式のメトリクスを公開する
+    #[serde(default)]
+    pub metrics: bool,
+}
+
+fn default_api_port() -> u16 {
+    8766
+}
+
+/// 現在のアプリ・カテゴリとトラッキング状態を MQTT で配信する（在席ランプなどのホームオートメーション向け）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MqttConfig {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    pub host: String,
+    #[serde(default = "default_mqtt_port")]
+    pub port: u16,
+    #[serde(default = "default_mqtt_client_id")]
+    pub client_id: String,
+    #[serde(default)]
+    pub username: Option<String>,
+    /// 平文のパスワード（password_secret を使う場合は空）
+    #[serde(default)]
+    pub password: Option<String>,
+    /// OSのキーリングに保存したパスワードの名前（指定時は password より優先）
+    #[serde(default)]
+    pub password_secret: Option<String>,
+    /// TLS で接続する（ポートは通常 8883）
+    #[serde(default)]
+    pub tls: bool,
+    /// TLS で信頼する CA 証明書（PEM）のパス（未指定なら OS の証明書ストアを使う）
+    #[serde(default)]
+    pub ca_file: Option<String>,
+    /// {topic_prefix}/state と {topic_prefix}/activity に配信する
+    #[serde(default = "default_mqtt_topic_prefix")]
+    pub topic_prefix: String,
+    /// ウィンドウタイトルも送る（既定では送らない）
+    #[serde(default)]
+    pub include_titles: bool,
+}
+
+fn default_mqtt_port() -> u16 {
+    1883
+}
+
+fn default_mqtt_client_id() -> String {
+    "timetracker".to_string()
+}
+
+fn default_mqtt_topic_prefix() -> String {
+    "timetracker".to_string()
+}
+
+/// plugins/ ディレクトリの WASM プラグインの設定（キーはファイル名から拡張子を除いたもの）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WasmPluginConfig {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// HTTPリクエストを許可するホスト（空ならネットワークに接続できない）
+    #[serde(default)]
+    pub allowed_hosts: Vec<String>,
+    /// プラグインから host.setting で読める値
+    #[serde(default)]
+    pub settings: HashMap<String, String>,
+    /// OSのキーリングに保存した値の名前（プラグインからは settings の "token" として読める）
+    #[serde(default)]
+    pub token_secret: Option<String>,
+}
+
+impl Default for WasmPluginConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            allowed_hosts: Vec::new(),
+            settings: HashMap::new(),
+            token_secret: None,
+        }
+    }
+}
+
+/// イベント発生時に JSON を POST する Webhook
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhooksConfig {
+    #[serde(default)]
+    pub endpoints: Vec<WebhookEndpoint>,
+    /// 今日の合計がこの時間を超えたら daily.threshold を送る
+    #[serde(default)]
+    pub daily_thresholds_hours: Vec<f64>,
+    /// 接続エラー・5xx のときの再送回数
+    #[serde(default = "default_webhook_retries")]
+    pub max_retries: u32,
+}
+
+impl Default for WebhooksConfig {
+    fn default() -> Self {
+        Self {
+            endpoints: Vec::new(),
+            daily_thresholds_hours: Vec::new(),
+            max_retries: default_webhook_retries(),
+        }
+    }
+}
+
+fn default_webhook_retries() -> u32 {
+    3
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookEndpoint {
+    pub url: String,
+    /// 送るイベント（"activity.finished" | "goal.reached" | "daily.threshold"）。空なら全イベント
+    #[serde(default)]
+    pub events: Vec<String>,
+    /// 平文の署名キー（secret_secret を使う場合は空）。指定すると本文の HMAC-SHA256 を X-TimeTracker-Signature ヘッダーで送る
+    #[serde(default)]
+    pub secret: Option<String>,
+    /// OSのキーリングに保存した署名キーの名前（指定時は secret より優先）
+    #[serde(default)]
+    pub secret_secret: Option<String>,
+}
+
+/// アイドル（離席）検出の設定
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdleConfig {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// 入力がこの秒数続かなければアイドルとみなす
+    #[serde(default = "default_idle_threshold")]
+    pub threshold_seconds: u64,
+    /// 画面ロック・スリープ中の時間をタイムラインに "locked" として記録する
+    #[serde(default = "default_enabled")]
+    pub record_locked: bool,
+}
+
+fn default_idle_threshold() -> u64 {
+    300 // デフォルト5分
+}
+
+impl Default for IdleConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            threshold_seconds: default_idle_threshold(),
+            record_locked: true,
+        }
+    }
+}
+
+/// アクティブウィンドウを取得する間隔の設定
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PollingConfig {
+    /// 取得間隔（ミリ秒）
+    #[serde(default = "default_poll_interval_ms")]
+    pub interval_ms: u64,
+    /// 同じウィンドウが続く間は間隔を max_interval_ms まで倍々に延ばす
+    #[serde(default)]
+    pub adaptive: bool,
+    #[serde(default = "default_max_poll_interval_ms")]
+    pub max_interval_ms: u64,
+}
+
+/// 連携の API 呼び出しの設定（接続先ホストごとに制限する）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpConfig {
+    /// リクエスト全体のタイムアウト（秒）
+    #[serde(default = "default_http_timeout_seconds")]
+    pub timeout_seconds: u64,
+    /// 接続のタイムアウト（秒）
+    #[serde(default = "default_http_connect_timeout_seconds")]
+    pub connect_timeout_seconds: u64,
+    /// 同じホストへの同時リクエスト数の上限
+    #[serde(default = "default_http_max_concurrent")]
+    pub max_concurrent_per_host: usize,
+    /// 同じホストへの1秒あたりのリクエスト数の上限（0 なら制限しない）
+    #[serde(default = "default_http_requests_per_second")]
+    pub requests_per_second: f64,
+    /// 429 / 5xx の再試行回数
+    #[serde(default = "default_http_max_retries")]
+    pub max_retries: u32,
+    /// 再試行の待ち時間の初期値（ミリ秒）。再試行のたびに倍にする
+    #[serde(default = "default_http_retry_base_ms")]
+    pub retry_base_ms: u64,
+}
+
+fn default_http_timeout_seconds() -> u64 {
+    30
+}
+
+fn default_http_connect_timeout_seconds() -> u64 {
+    10
+}
+
+fn default_http_max_concurrent() -> usize {
+    4
+}
+
+fn default_http_requests_per_second() -> f64 {
+    5.0
+}
+
+fn default_http_max_retries() -> u32 {
+    3
+}
+
+fn default_http_retry_base_ms() -> u64 {
+    500
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            timeout_seconds: default_http_timeout_seconds(),
+            connect_timeout_seconds: default_http_connect_timeout_seconds(),
+            max_concurrent_per_host: default_http_max_concurrent(),
+            requests_per_second: default_http_requests_per_second(),
+            max_retries: default_http_max_retries(),
+            retry_base_ms: default_http_retry_base_ms(),
+        }
+    }
+}
+
+/// 短すぎる間隔で CPU を使い切らないための下限（ミリ秒）
+pub const MIN_POLL_INTERVAL_MS: u64 = 100;
+
+fn default_poll_interval_ms() -> u64 {
+    1000
+}
+
+fn default_max_poll_interval_ms() -> u64 {
+    5000
+}
+
+impl Default for PollingConfig {
+    fn default() -> Self {
+        Self {
+            interval_ms: default_poll_interval_ms(),
+            adaptive: false,
+            max_interval_ms: default_max_poll_interval_ms(),
+        }
+    }
+}
+
+impl PollingConfig {
+    /// 通常の取得間隔
+    pub fn interval(&self) -> Duration {
+        Duration::from_millis(self.interval_ms.max(MIN_POLL_INTERVAL_MS))
+    }
+
+    /// 前回から変化がなかったときの次の間隔（adaptive でなければ常に通常の間隔）
+    pub fn backoff(&self, current: Duration) -> Duration {
+        if !self.adaptive {
+            return self.interval();
+        }
+        let max = Duration::from_millis(self.max_interval_ms).max(self.interval());
+        (current * 2).min(max)
+    }
+}
+
+/// プロジェクトの定義（いずれかの条件にマッチしたアクティビティを自動で割り当てる）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProjectConfig {
+    pub name: String,
+    /// 請求先のクライアント
+    #[serde(default)]
+    pub client: Option<String>,
+    /// 時間単価（未設定なら請求対象外）
+    #[serde(default)]
+    pub hourly_rate: Option<f64>,
+    /// ウィンドウタイトルの正規表現
+    #[serde(default)]
+    pub title_patterns: Vec<String>,
+    /// ドメイン（サブドメインも対象）
+    #[serde(default)]
+    pub domains: Vec<String>,
+    /// リポジトリのフォルダ名（エディタのウィンドウタイトルに含まれるもの）
+    #[serde(default)]
+    pub repo_folders: Vec<String>,
+}
+
+/// 請求レポートの設定
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BillingConfig {
+    #[serde(default = "default_currency")]
+    pub currency: String,
+    /// 請求時間をこの分数単位に切り上げる（0 なら切り上げない）
+    #[serde(default)]
+    pub round_up_minutes: u32,
+}
+
+fn default_currency() -> String {
+    "JPY".to_string()
+}
+
+impl Default for BillingConfig {
+    fn default() -> Self {
+        Self {
+            currency: default_currency(),
+            round_up_minutes: 0,
+        }
+    }
+}
+
+/// キー入力・クリックの回数の計測（内容は記録しない）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct InputMeterConfig {
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+/// 生産性の区分
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Productivity {
+    Productive,
+    /// 区分のないアプリ・カテゴリもこの扱い
+    #[default]
+    Neutral,
+    Distracting,
+}
+
+/// カテゴリ・アプリの生産性の区分（アプリの区分がカテゴリより優先）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProductivityConfig {
+    /// カテゴリ名 → 区分
+    #[serde(default)]
+    pub categories: HashMap<String, Productivity>,
+    /// プロセス名 → 区分
+    #[serde(default)]
+    pub apps: HashMap<String, Productivity>,
+}
+
+/// 記録しないアプリ・タイトル・ドメイン（パスワードマネージャーやプライベートウィンドウなど）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExclusionConfig {
+    /// プロセス名（大文字小文字を区別しない完全一致）
+    #[serde(default)]
+    pub processes: Vec<String>,
+    /// ウィンドウタイトルの正規表現
+    #[serde(default)]
+    pub title_patterns: Vec<String>,
+    /// ドメイン（サブドメインも対象）
+    #[serde(default)]
+    pub domains: Vec<String>,
+}
+
+/// 全体設定ファイル
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct IntegrationsConfig {
+    #[serde(default)]
+    pub integrations: Vec<IntegrationEntry>,
+    /// データアップロード設定
+    #[serde(default)]
+    pub upload: Option<UploadConfig>,
+    /// カテゴリ分類ルール
+    #[serde(default)]
+    pub categories: Vec<CategoryRule>,
+    /// チケット/プロジェクトの割り当てルール
+    #[serde(default)]
+    pub assignment_rules: Vec<AssignmentRule>,
+    /// カテゴリ別の週間予算
+    #[serde(default)]
+    pub weekly_budgets: Vec<WeeklyBudget>,
+    /// ユーザー定義のマスキングプロファイル（組み込みプロファイルと同名なら上書き）
+    #[serde(default)]
+    pub redaction_profiles: Vec<RedactionProfile>,
+    /// 断片統合メンテナンス
+    #[serde(default)]
+    pub compaction: CompactionConfig,
+    /// 生データの保持期間
+    #[serde(default)]
+    pub retention: RetentionConfig,
+    /// データベースの暗号化
+    #[serde(default)]
+    pub database: DatabaseConfig,
+    /// キャプチャソースの優先度の上書き（ソース名 → 優先度。大きいほど優先）
+    #[serde(default)]
+    pub capture_priorities: HashMap<String, i32>,
+    /// 進行中のアクティビティにチケットを割り当てるクイック入力のホットキー
+    /// （未指定時は "CommandOrControl+Shift+T"）
+    #[serde(default)]
+    pub quick_entry_hotkey: Option<String>,
+    /// トレイのプロジェクト切り替えメニューに常に表示するプロジェクト
+    #[serde(default)]
+    pub pinned_projects: Vec<String>,
+    /// リアルタイム配信（未設定なら無効）
+    #[serde(default)]
+    pub live_stream: Option<LiveStreamConfig>,
+    /// ローカル REST API サーバー（未設定なら無効）
+    #[serde(default)]
+    pub api_server: Option<ApiServerConfig>,
+    /// MQTT での状態の配信（未設定なら無効）
+    #[serde(default)]
+    pub mqtt: Option<MqttConfig>,
+    /// Slack のステータス・日次サマリー（未設定なら無効）
+    #[serde(default)]
+    pub slack: Option<SlackConfig>,
+    /// 会議を取り込むカレンダー
+    #[serde(default)]
+    pub calendars: Vec<CalendarConfig>,
+    /// アイドル検出
+    #[serde(default)]
+    pub idle: IdleConfig,
+    /// アクティブウィンドウの取得間隔
+    #[serde(default)]
+    pub polling: PollingConfig,
+    /// 保存時に適用するウィンドウタイトルの正規化ルール
+    #[serde(default)]
+    pub title_rules: Vec<TitleRule>,
+    /// 記録から除外する対象
+    #[serde(default)]
+    pub exclusions: ExclusionConfig,
+    /// 自動割り当てするプロジェクト
+    #[serde(default)]
+    pub projects: Vec<ProjectConfig>,
+    /// 請求レポート
+    #[serde(default)]
+    pub billing: BillingConfig,
+    /// 入力の多さの計測（既定で無効）
+    #[serde(default)]
+    pub input_meter: InputMeterConfig,
+    /// 保存時の細切れの記録のまとめ方
+    #[serde(default)]
+    pub coalesce: CoalesceConfig,
+    /// 生産性スコアに使う区分
+    #[serde(default)]
+    pub productivity: ProductivityConfig,
+    /// イベントの Webhook 通知
+    #[serde(default)]
+    pub webhooks: WebhooksConfig,
+    /// WASM プラグインごとの設定（設定がないプラグインも既定値で読み込む）
+    #[serde(default)]
+    pub wasm_plugins: HashMap<String, WasmPluginConfig>,
+    /// 連携の API 呼び出し（全連携で共有）
+    #[serde(default)]
+    pub http: HttpConfig,
+}
+
+impl IntegrationsConfig {
+    /// 設定ファイルのパスを取得
+    pub fn config_path() -> PathBuf {
+        dirs::data_local_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("timetracker")
+            .join("integrations.toml")
+    }
+
+    /// 設定ファイルを読み込む（読めない場合は既定値。読み込んだ内容を保存し直す場合は try_load を使う）
+    pub fn load() -> Self {
+        Self::try_load().unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            Self::default()
+        })
+    }
+
+    /// 設定ファイルを読み込む（読めない・解析できない場合は既定値ではなくエラー）
+    pub fn try_load() -> Result<Self, String> {
+        let path = Self::config_path();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read integrations config: {}", e))?;
+        toml::from_str(&content).map_err(|e| format!("Failed to parse integrations config: {}", e))
+    }
+
+    /// 設定ファイルを保存（一時ファイルに書いてから置き換えるので、途中で失敗しても元の内容が残る）
+    pub fn save(&self) -> Result<(), String> {
+        let path = Self::config_path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let content = toml::to_string_pretty(self).map_err(|e| e.to_string())?;
+        let temp_path = path.with_extension("toml.tmp");
+        fs::write(&temp_path, content).map_err(|e| e.to_string())?;
+        fs::rename(&temp_path, &path).map_err(|e| {
+            let _ = fs::remove_file(&temp_path);
+            e.to_string()
+        })?;
+        Ok(())
+    }
+
+    /// サンプル設定を生成
+    pub fn create_sample() -> Self {
+        Self {
+            integrations: vec![
+                IntegrationEntry {
+                    // 15分単位に切り上げ、1日8時間まで
+                    rounding: TimeRoundingConfig {
+                        minutes: 15,
+                        mode: RoundingMode::Up,
+                        minimum_minutes: 15,
+                        daily_cap_hours: Some(8.0),
+                    },
+                    comment: CommentConfig {
+                        template: Some("{{note}} ({{duration_hm}})".to_string()),
+                        omit_titles: true,
+                    },
+                    ..IntegrationEntry::new(
+                        "my-redmine",
+                        false,
+                        IntegrationConfig::Redmine(RedmineConfig {
+                            url: "https://redmine.example.com".to_string(),
+                            api_key: String::new(),
+                            api_key_secret: Some("my-redmine".to_string()),
+                            default_activity_id: Some(9),
+                            rules: vec![
+                                ExtractionRule::window_title(r"#(\d+)"),
+                                ExtractionRule::window_title(r"Issue (\d+)"),
+                            ],
+                        }),
+                    )
+                },
+                IntegrationEntry::new(
+                    "my-jira",
+                    false,
+                    IntegrationConfig::Jira(JiraConfig {
+                        url: "https://your-domain.atlassian.net".to_string(),
+                        email: "you@example.com".to_string(),
+                        api_token: "your-api-token-here".to_string(),
+                        rules: vec![
+                            ExtractionRule::window_title(r"\b([A-Z][A-Z0-9]+-\d+)\b"),
+                            // "[PROJ] #123 ..." → "PROJ-123"
+                            ExtractionRule {
+                                template: Some("$project-$issue".to_string()),
+                                ..ExtractionRule::window_title(
+                                    r"\[(?P<project>[A-Z][A-Z0-9]+)\] #(?P<issue>\d+)",
+                                )
+                            },
+                        ],
+                    }),
+                ),
+                IntegrationEntry::new(
+                    "my-github",
+                    false,
+                    IntegrationConfig::GitHub(GitHubConfig {
+                        token: "your-personal-access-token".to_string(),
+                        api_url: default_github_api_url(),
+                        default_repo: Some("owner/repo".to_string()),
+                        post_comments: true,
+                        project: None,
+                        rules: default_github_rules(),
+                    }),
+                ),
+                IntegrationEntry::new(
+                    "my-gitlab",
+                    false,
+                    IntegrationConfig::GitLab(GitLabConfig {
+                        url: default_gitlab_url(),
+                        token: "your-access-token-here".to_string(),
+                        project: "group/project".to_string(),
+                        rules: default_gitlab_rules(),
+                    }),
+                ),
+                IntegrationEntry::new(
+                    "my-toggl",
+                    false,
+                    IntegrationConfig::Toggl(TogglConfig {
+                        api_token: "your-api-token-here".to_string(),
+                        workspace_id: 1234567,
+                        default_project_id: None,
+                        projects: HashMap::from([("PROJ".to_string(), 7654321)]),
+                        tags: vec!["timetracker".to_string()],
+                        billable: false,
+                        rules: vec![ExtractionRule::window_title(r"\b([A-Z][A-Z0-9]+)-\d+\b")],
+                    }),
+                ),
+                IntegrationEntry::new(
+                    "my-clockify",
+                    false,
+                    IntegrationConfig::Clockify(ClockifyConfig {
+                        api_key: String::new(),
+                        api_key_secret: Some("my-clockify".to_string()),
+                        workspace_id: "your-workspace-id".to_string(),
+                        default_project_id: None,
+                        projects: vec![ClockifyProjectMapping {
+                            pattern: "^PROJ-".to_string(),
+                            project_id: "your-project-id".to_string(),
+                            task_id: None,
+                            tag_ids: Vec::new(),
+                        }],
+                        tag_ids: Vec::new(),
+                        billable: false,
+                        rules: default_jira_rules(),
+                    }),
+                ),
+                IntegrationEntry::new(
+                    "my-harvest",
+                    false,
+                    IntegrationConfig::Harvest(HarvestConfig {
+                        account_id: "1234567".to_string(),
+                        token: String::new(),
+                        token_secret: Some("my-harvest".to_string()),
+                        oauth: None,
+                        default_project_id: None,
+                        default_task_id: None,
+                        projects: vec![HarvestProjectMapping {
+                            pattern: "^PROJ-".to_string(),
+                            project_id: 14307913,
+                            task_id: 8083365,
+                        }],
+                        rules: default_jira_rules(),
+                    }),
+                ),
+                IntegrationEntry::new(
+                    "my-asana",
+                    false,
+                    IntegrationConfig::Asana(AsanaConfig {
+                        token: String::new(),
+                        token_secret: Some("my-asana".to_string()),
+                        post_comments: true,
+                        hours_field_id: None,
+                        rules: default_asana_rules(),
+                    }),
+                ),
+                IntegrationEntry::new(
+                    "my-trello",
+                    false,
+                    IntegrationConfig::Trello(TrelloConfig {
+                        api_key: "your-trello-api-key".to_string(),
+                        token: String::new(),
+                        token_secret: Some("my-trello".to_string()),
+                        post_comments: true,
+                        custom_field_id: None,
+                        match_card_names: true,
+                        rules: default_trello_rules(),
+                    }),
+                ),
+                IntegrationEntry::new(
+                    "my-notion",
+                    false,
+                    IntegrationConfig::Notion(NotionConfig {
+                        token: String::new(),
+                        token_secret: Some("my-notion".to_string()),
+                        database_id: "your-database-id".to_string(),
+                        default_project: None,
+                        columns: NotionColumns::default(),
+                        rules: default_jira_rules(),
+                    }),
+                ),
+                IntegrationEntry::new(
+                    "my-webhook",
+                    false,
+                    IntegrationConfig::Custom(CustomHttpConfig {
+                        url: "https://example.com/api/worklogs".to_string(),
+                        method: default_custom_method(),
+                        headers: HashMap::from([(
+                            "Authorization".to_string(),
+                            "Bearer {{token}}".to_string(),
+                        )]),
+                        body: Some(
+                            r#"{"issue": "{{ticket_id}}", "hours": {{duration_hours}}, "comment": "{{title}}", "date": "{{date}}"}"#
+                                .to_string(),
+                        ),
+                        token_secret: Some("my-webhook".to_string()),
+                        id_pointer: Some("/id".to_string()),
+                        test_url: None,
+                        rules: default_jira_rules(),
+                    }),
+                ),
+                IntegrationEntry::new(
+                    "my-script",
+                    false,
+                    IntegrationConfig::Exec(ExecConfig {
+                        command: "python3".to_string(),
+                        args: vec!["/path/to/sync.py".to_string()],
+                        env: HashMap::new(),
+                        token_secret: None,
+                        timeout_seconds: default_exec_timeout(),
+                        rules: default_jira_rules(),
+                    }),
+                ),
+            ],
+            upload: Some(UploadConfig {
+                server_url: "https://timetracker.example.com/api/upload".to_string(),
+                enabled: false,
+                auto_upload: false,
+                auto_upload_interval_minutes: 60,
+                min_duration_seconds: 600, // 10分以上使用したアプリ/ドメインのみ
+                redaction_profile: Some("full".to_string()),
+            }),
+            categories: vec![
+                CategoryRule {
+                    category: "Development".to_string(),
+                    pattern: r"(?i)^(code|devenv|idea64|windowsterminal)\.exe$".to_string(),
+                    source: "process_name".to_string(),
+                },
+                CategoryRule {
+                    category: "Social".to_string(),
+                    pattern: r"(?i)(twitter\.com|x\.com|facebook\.com|youtube\.com)$".to_string(),
+                    source: "domain".to_string(),
+                },
+            ],
+            assignment_rules: vec![AssignmentRule {
+                pattern: r"(?i)standup|daily scrum".to_string(),
+                source: "window_title".to_string(),
+                plugin: None,
+                ticket_id: None,
+                project: Some("Meetings".to_string()),
+            }],
+            weekly_budgets: vec![
+                WeeklyBudget {
+                    category: "Social".to_string(),
+                    kind: "max".to_string(),
+                    hours: 5.0,
+                    warn_at_percent: default_warn_percent(),
+                },
+                WeeklyBudget {
+                    category: "Development".to_string(),
+                    kind: "min".to_string(),
+                    hours: 20.0,
+                    warn_at_percent: default_warn_percent(),
+                },
+            ],
+            redaction_profiles: vec![RedactionProfile {
+                name: "share-with-team".to_string(),
+                process_name: FieldPolicy::Keep,
+                window_title: FieldPolicy::Hash,
+                domain: FieldPolicy::Keep,
+                salt: "change-me".to_string(),
+            }],
+            compaction: CompactionConfig::default(),
+            retention: RetentionConfig::default(),
+            database: DatabaseConfig::default(),
+            capture_priorities: HashMap::new(),
+            quick_entry_hotkey: Some("CommandOrControl+Shift+T".to_string()),
+            pinned_projects: vec!["Meetings".to_string()],
+            live_stream: Some(LiveStreamConfig {
+                enabled: false,
+                bind_address: default_live_bind_address(),
+                port: default_live_port(),
+                require_token: true,
+            }),
+            api_server: Some(ApiServerConfig {
+                enabled: false,
+                bind_address: default_live_bind_address(),
+                port: default_api_port(),
+                metrics: false,
+            }),
+            mqtt: Some(MqttConfig {
+                enabled: false,
+                host: "localhost".to_string(),
+                port: default_mqtt_port(),
+                client_id: default_mqtt_client_id(),
+                username: None,
+                password: None,
+                password_secret: None,
+                tls: false,
+                ca_file: None,
+                topic_prefix: default_mqtt_topic_prefix(),
+                include_titles: false,
+            }),
+            slack: Some(SlackConfig {
+                enabled: false,
+                token: String::new(),
+                token_secret: Some("slack".to_string()),
+                statuses: HashMap::from([(
+                    "Development".to_string(),
+                    SlackStatus {
+                        text: "Focusing".to_string(),
+                        emoji: ":technologist:".to_string(),
+                    },
+                )]),
+                default_status: None,
+                dnd_during_focus: true,
+                focus_categories: vec!["Development".to_string()],
+                dnd_after_minutes: default_slack_dnd_after(),
+                summary_channel: Some("#daily-log".to_string()),
+                summary_time: Some("18:00".to_string()),
+            }),
+            calendars: vec![CalendarConfig {
+                name: "work".to_string(),
+                provider: CalendarProvider::Google,
+                token: String::new(),
+                token_secret: Some("google-calendar".to_string()),
+                calendar_id: None,
+            }],
+            idle: IdleConfig::default(),
+            polling: PollingConfig {
+                interval_ms: default_poll_interval_ms(),
+                adaptive: true,
+                max_interval_ms: default_max_poll_interval_ms(),
+            },
+            title_rules: vec![
+                TitleRule {
+                    // "(3) Inbox - Gmail" → "Inbox - Gmail"
+                    pattern: r"^\(\d+\+?\)\s*".to_string(),
+                    replacement: String::new(),
+                    process_pattern: None,
+                },
+                TitleRule {
+                    // "file.rs - myproject - Visual Studio Code" → "myproject - Visual Studio Code"
+                    pattern: r"^.*? - (.+ - Visual Studio Code)$".to_string(),
+                    replacement: "$1".to_string(),
+                    process_pattern: Some(r"(?i)^code(\.exe)?$".to_string()),
+                },
+            ],
+            exclusions: ExclusionConfig {
+                processes: vec!["KeePassXC.exe".to_string(), "1Password.exe".to_string()],
+                title_patterns: vec![r"(?i)\b(InPrivate|Incognito|Private Browsing)\b".to_string()],
+                domains: vec!["bank.example.com".to_string()],
+            },
+            projects: vec![ProjectConfig {
+                name: "Website Redesign".to_string(),
+                client: Some("Example Corp".to_string()),
+                hourly_rate: Some(8000.0),
+                title_patterns: vec![r"(?i)\bredesign\b".to_string()],
+                domains: vec!["staging.example.com".to_string()],
+                repo_folders: vec!["website".to_string()],
+            }],
+            billing: BillingConfig {
+                currency: default_currency(),
+                round_up_minutes: 15,
+            },
+            input_meter: InputMeterConfig::default(),
+            coalesce: CoalesceConfig {
+                min_segment_seconds: 3,
+                ..CoalesceConfig::default()
+            },
+            productivity: ProductivityConfig {
+                categories: HashMap::from([
+                    ("Development".to_string(), Productivity::Productive),
+                    ("Social".to_string(), Productivity::Distracting),
+                ]),
+                apps: HashMap::new(),
+            },
+            webhooks: WebhooksConfig::default(),
+            wasm_plugins: HashMap::from([(
+                "my-wasm-plugin".to_string(),
+                WasmPluginConfig {
+                    allowed_hosts: vec!["api.example.com".to_string()],
+                    ..WasmPluginConfig::default()
+                },
+            )]),
+            http: HttpConfig::default(),
+        }
+    }
+}
+
+/// WASM プラグインを置くディレクトリ（integrations.toml と同じ場所の plugins/）
+pub fn wasm_plugins_dir() -> PathBuf {
+    IntegrationsConfig::config_path()
+        .parent()
+        .map(|dir| dir.join("plugins"))
+        .unwrap_or_else(|| PathBuf::from("plugins"))
+}
@@ -0,0 +1,282 @@
+use parking_lot::{Mutex, RwLock};
+use reqwest::redirect::Policy;
+use reqwest::{Body, Method, Response, StatusCode};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::time::Instant;
+
+use super::config::HttpConfig;
+
+/// 再試行の待ち時間の上限
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+/// 接続先ホストごとの状態
+struct HostState {
+    /// 同時リクエスト数の上限
+    permits: Arc<Semaphore>,
+    /// 次にリクエストを送ってよい時刻
+    next_slot: Instant,
+}
+
+/// 全連携で共有するクライアントと制限
+struct Shared {
+    client: RwLock<reqwest::Client>,
+    /// リダイレクトをたどらないクライアント（接続先を制限する WASM プラグイン用）
+    no_redirect_client: RwLock<reqwest::Client>,
+    config: RwLock<HttpConfig>,
+    hosts: Mutex<HashMap<String, HostState>>,
+}
+
+static SHARED: OnceLock<Shared> = OnceLock::new();
+
+fn build_client(config: &HttpConfig, redirect: Policy) -> reqwest::Client {
+    reqwest::Client::builder()
+        .timeout(Duration::from_secs(config.timeout_seconds.max(1)))
+        .connect_timeout(Duration::from_secs(config.connect_timeout_seconds.max(1)))
+        .redirect(redirect)
+        .build()
+        .unwrap_or_default()
+}
+
+fn shared() -> &'static Shared {
+    SHARED.get_or_init(|| {
+        let config = HttpConfig::default();
+        Shared {
+            client: RwLock::new(build_client(&config, Policy::default())),
+            no_redirect_client: RwLock::new(build_client(&config, Policy::none())),
+            config: RwLock::new(config),
+            hosts: Mutex::new(HashMap::new()),
+        }
+    })
+}
+
+/// 設定を反映（タイムアウトは次のリクエストから、同時数の上限は新しく始まるリクエストから効く）
+pub fn configure(config: &HttpConfig) {
+    let shared = shared();
+    *shared.client.write() = build_client(config, Policy::default());
+    *shared.no_redirect_client.write() = build_client(config, Policy::none());
+    *shared.config.write() = config.clone();
+    shared.hosts.lock().clear();
+}
+
+/// URL の接続先（ホスト名とポート）
+fn host_key(url: &str) -> String {
+    url::Url::parse(url)
+        .ok()
+        .and_then(|url| {
+            let host = url.host_str()?.to_string();
+            Some(match url.port_or_known_default() {
+                Some(port) => format!("{}:{}", host, port),
+                None => host,
+            })
+        })
+        .unwrap_or_default()
+}
+
+/// 同時数の枠を取り、ホストごとの間隔を空けてから送る
+async fn acquire(host: &str) -> OwnedSemaphorePermit {
+    let shared = shared();
+    let config = shared.config.read().clone();
+    let new_state = || HostState {
+        permits: Arc::new(Semaphore::new(
+            config
+                .max_concurrent_per_host
+                .clamp(1, Semaphore::MAX_PERMITS),
+        )),
+        next_slot: Instant::now(),
+    };
+
+    let permits = shared
+        .hosts
+        .lock()
+        .entry(host.to_string())
+        .or_insert_with(new_state)
+        .permits
+        .clone();
+    let permit = permits
+        .acquire_owned()
+        .await
+        .expect("host semaphore is never closed");
+
+    if config.requests_per_second > 0.0 {
+        let interval = Duration::try_from_secs_f64(1.0 / config.requests_per_second)
+            .unwrap_or(MAX_RETRY_DELAY);
+        let slot = {
+            let mut hosts = shared.hosts.lock();
+            let state = hosts.entry(host.to_string()).or_insert_with(new_state);
+            let slot = state.next_slot.max(Instant::now());
+            state.next_slot = slot + interval;
+            slot
+        };
+        tokio::time::sleep_until(slot).await;
+    }
+
+    permit
+}
+
+/// 再試行する応答か（429 と 5xx）
+fn is_retryable(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// 再試行までの待ち時間（Retry-After があれば従う）
+fn retry_delay(config: &HttpConfig, attempt: u32, response: &Response) -> Duration {
+    let retry_after = response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .map(Duration::from_secs);
+    let backoff = Duration::from_millis(config.retry_base_ms.saturating_mul(1 << attempt.min(16)));
+    retry_after.unwrap_or(backoff).min(MAX_RETRY_DELAY)
+}
+
+/// 連携で共有する HTTP クライアント
+///
+/// 接続先ホストごとに同時リクエスト数と間隔を制限し、429 / 5xx は待って再試行する。
+#[derive(Debug, Clone)]
+pub struct HttpClient {
+    follow_redirects: bool,
+}
+
+impl Default for HttpClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HttpClient {
+    pub fn new() -> Self {
+        Self {
+            follow_redirects: true,
+        }
+    }
+
+    /// リダイレクトをたどらない（3xx はそのまま返す）
+    pub fn without_redirects() -> Self {
+        Self {
+            follow_redirects: false,
+        }
+    }
+
+    pub fn request(&self, method: Method, url: impl AsRef<str>) -> RequestBuilder {
+        let url = url.as_ref();
+        let shared = shared();
+        let client = if self.follow_redirects {
+            shared.client.read()
+        } else {
+            shared.no_redirect_client.read()
+        };
+        RequestBuilder {
+            inner: client.request(method, url),
+            host: host_key(url),
+        }
+    }
+
+    pub fn get(&self, url: impl AsRef<str>) -> RequestBuilder {
+        self.request(Method::GET, url)
+    }
+
+    pub fn post(&self, url: impl AsRef<str>) -> RequestBuilder {
+        self.request(Method::POST, url)
+    }
+
+    pub fn put(&self, url: impl AsRef<str>) -> RequestBuilder {
+        self.request(Method::PUT, url)
+    }
+
+    pub fn patch(&self, url: impl AsRef<str>) -> RequestBuilder {
+        self.request(Method::PATCH, url)
+    }
+
+    pub fn delete(&self, url: impl AsRef<str>) -> RequestBuilder {
+        self.request(Method::DELETE, url)
+    }
+}
+
+/// 送信時に制限と再試行を行う reqwest::RequestBuilder
+pub struct RequestBuilder {
+    inner: reqwest::RequestBuilder,
+    host: String,
+}
+
+impl RequestBuilder {
+    fn map(self, f: impl FnOnce(reqwest::RequestBuilder) -> reqwest::RequestBuilder) -> Self {
+        Self {
+            inner: f(self.inner),
+            host: self.host,
+        }
+    }
+
+    pub fn header(self, key: &str, value: impl AsRef<str>) -> Self {
+        self.map(|inner| inner.header(key, value.as_ref()))
+    }
+
+    pub fn basic_auth<U: Display, P: Display>(self, username: U, password: Option<P>) -> Self {
+        self.map(|inner| inner.basic_auth(username, password))
+    }
+
+    pub fn bearer_auth<T: Display>(self, token: T) -> Self {
+        self.map(|inner| inner.bearer_auth(token))
+    }
+
+    pub fn query<T: Serialize + ?Sized>(self, query: &T) -> Self {
+        self.map(|inner| inner.query(query))
+    }
+
+    pub fn form<T: Serialize + ?Sized>(self, form: &T) -> Self {
+        self.map(|inner| inner.form(form))
+    }
+
+    pub fn json<T: Serialize + ?Sized>(self, json: &T) -> Self {
+        self.map(|inner| inner.json(json))
+    }
+
+    pub fn body<T: Into<Body>>(self, body: T) -> Self {
+        self.map(|inner| inner.body(body))
+    }
+
+    pub fn timeout(self, timeout: Duration) -> Self {
+        self.map(|inner| inner.timeout(timeout))
+    }
+
+    /// 送信（429 / 5xx なら設定の回数まで再試行し、最後の応答を返す）
+    pub async fn send(self) -> reqwest::Result<Response> {
+        let config = shared().config.read().clone();
+        let mut attempt = 0;
+        loop {
+            // 複製できない（ストリームの）本文は再試行しない
+            let retry = if attempt < config.max_retries {
+                self.inner.try_clone()
+            } else {
+                None
+            };
+            let Some(request) = retry else {
+                let _permit = acquire(&self.host).await;
+                return self.inner.send().await;
+            };
+
+            let response = {
+                let _permit = acquire(&self.host).await;
+                request.send().await?
+            };
+            if !is_retryable(response.status()) {
+                return Ok(response);
+            }
+
+            let delay = retry_delay(&config, attempt, &response);
+            eprintln!(
+                "HTTP {} from {}, retrying in {:?}",
+                response.status(),
+                self.host,
+                delay
+            );
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+}
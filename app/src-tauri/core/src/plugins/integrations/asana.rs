@@ -0,0 +1,240 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use super::{compile_rules, extract_with_rules, spent_comment, CompiledRule};
+use crate::plugins::config::AsanaConfig;
+use crate::plugins::http::{HttpClient, RequestBuilder};
+use crate::plugins::traits::{ActivityInfo, ExternalIntegration, SyncError, SyncResult};
+use crate::secrets;
+
+const API_BASE: &str = "https://app.asana.com/api/1.0";
+
+/// Asana API: レスポンスは data で包まれる
+#[derive(Debug, Deserialize)]
+struct DataResponse<T> {
+    data: T,
+}
+
+/// Asana API: コメント（ストーリー）作成リクエスト
+#[derive(Debug, Serialize)]
+struct StoryRequest {
+    data: StoryData,
+}
+
+#[derive(Debug, Serialize)]
+struct StoryData {
+    text: String,
+}
+
+/// Asana API: 作成されたオブジェクト
+#[derive(Debug, Deserialize)]
+struct Created {
+    gid: String,
+}
+
+/// Asana API: タスクのカスタムフィールド
+#[derive(Debug, Deserialize)]
+struct TaskInfo {
+    #[serde(default)]
+    custom_fields: Vec<CustomFieldValue>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CustomFieldValue {
+    gid: String,
+    number_value: Option<f64>,
+}
+
+/// Asana API: ユーザー情報（接続テスト用）
+#[derive(Debug, Deserialize)]
+struct UserInfo {
+    gid: String,
+    email: String,
+}
+
+/// Asana連携プラグイン
+pub struct AsanaIntegration {
+    name: String,
+    enabled: bool,
+    config: AsanaConfig,
+    client: HttpClient,
+    rules: Vec<CompiledRule>,
+}
+
+impl AsanaIntegration {
+    pub fn new(name: String, enabled: bool, mut config: AsanaConfig) -> Result<Self, String> {
+        let client = HttpClient::new();
+
+        // キーリングのトークンを優先する
+        if let Some(alias) = &config.token_secret {
+            config.token = secrets::get_integration(alias)?
+                .ok_or_else(|| format!("Token not found in the OS keyring: {}", alias))?;
+        }
+
+        let rules = compile_rules(&config.rules);
+
+        Ok(Self {
+            name,
+            enabled,
+            config,
+            client,
+            rules,
+        })
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> RequestBuilder {
+        self.client
+            .request(method, format!("{}/{}", API_BASE, path))
+            .bearer_auth(&self.config.token)
+            .header("Accept", "application/json")
+    }
+
+    async fn parse<T: for<'de> Deserialize<'de>>(response: reqwest::Response) -> Result<T, String> {
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(format!("Asana API error ({}): {}", status, body));
+        }
+        let result: DataResponse<T> = response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse response: {}", e))?;
+        Ok(result.data)
+    }
+
+    /// タスクにコメントとして作業時間を追記
+    async fn post_comment(
+        &self,
+        task_id: &str,
+        activity: &ActivityInfo,
+    ) -> Result<String, SyncError> {
+        let request = StoryRequest {
+            data: StoryData {
+                text: spent_comment(activity),
+            },
+        };
+
+        let response = self
+            .request(reqwest::Method::POST, &format!("tasks/{}/stories", task_id))
+            .json(&request)
+            .send()
+            .await
+            .map_err(SyncError::network)?;
+        let story: Created = Self::parse(response).await?;
+        Ok(story.gid)
+    }
+
+    /// 数値カスタムフィールド（時間）に作業時間を加算し、更新後の値を返す
+    async fn add_to_hours_field(
+        &self,
+        task_id: &str,
+        field_id: &str,
+        hours: f64,
+    ) -> Result<f64, SyncError> {
+        let response = self
+            .request(reqwest::Method::GET, &format!("tasks/{}", task_id))
+            .query(&[("opt_fields", "custom_fields.gid,custom_fields.number_value")])
+            .send()
+            .await
+            .map_err(SyncError::network)?;
+        let task: TaskInfo = Self::parse(response).await?;
+
+        let field = task
+            .custom_fields
+            .iter()
+            .find(|field| field.gid == field_id)
+            .ok_or_else(|| format!("Task {} has no custom field {}", task_id, field_id))?;
+        let total = ((field.number_value.unwrap_or(0.0) + hours) * 100.0).round() / 100.0;
+
+        let response = self
+            .request(reqwest::Method::PUT, &format!("tasks/{}", task_id))
+            .json(&json!({ "data": { "custom_fields": { field_id: total } } }))
+            .send()
+            .await
+            .map_err(SyncError::network)?;
+        let _: serde_json::Value = Self::parse(response).await?;
+
+        Ok(total)
+    }
+}
+
+#[async_trait]
+impl ExternalIntegration for AsanaIntegration {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn display_name(&self) -> &str {
+        "Asana"
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn extract_ticket_id(&self, activity: &ActivityInfo) -> Option<String> {
+        extract_with_rules(&self.rules, activity)
+    }
+
+    async fn sync_time_entry(
+        &self,
+        activity: &ActivityInfo,
+        ticket_id: &str,
+    ) -> Result<SyncResult, SyncError> {
+        if ticket_id.is_empty() || !ticket_id.chars().all(|c| c.is_ascii_digit()) {
+            return Err(format!("Invalid task ID: {}", ticket_id).into());
+        }
+        let mut messages = Vec::new();
+        let mut external_id = None;
+
+        if self.config.post_comments {
+            let story_id = self.post_comment(ticket_id, activity).await?;
+            messages.push(format!("Added comment on task {}", ticket_id));
+            external_id = Some(story_id);
+        }
+
+        if let Some(field_id) = &self.config.hours_field_id {
+            let hours = activity.duration_seconds as f64 / 3600.0;
+            let total = self.add_to_hours_field(ticket_id, field_id, hours).await?;
+            messages.push(format!("Updated custom field to {}h", total));
+        }
+
+        if messages.is_empty() {
+            return Err(
+                "Asana integration has neither post_comments nor hours_field_id configured"
+                    .to_string()
+                    .into(),
+            );
+        }
+
+        Ok(SyncResult {
+            success: true,
+            message: messages.join(", "),
+            external_id,
+        })
+    }
+
+    async fn test_connection(&self) -> Result<bool, String> {
+        let response = self
+            .request(reqwest::Method::GET, "users/me")
+            .send()
+            .await
+            .map_err(|e| format!("Network error: {}", e))?;
+
+        if response.status().is_success() {
+            let result: DataResponse<UserInfo> = response
+                .json()
+                .await
+                .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+            println!(
+                "Connected to Asana as: {} (gid: {})",
+                result.data.email, result.data.gid
+            );
+            Ok(true)
+        } else {
+            Err(format!("Authentication failed: {}", response.status()))
+        }
+    }
+}
@@ -0,0 +1,244 @@
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use serde::Deserialize;
+
+use crate::plugins::config::{CalendarConfig, CalendarProvider};
+use crate::plugins::http::HttpClient;
+use crate::secrets;
+
+const GOOGLE_API_BASE: &str = "https://www.googleapis.com/calendar/v3";
+const GRAPH_API_BASE: &str = "https://graph.microsoft.com/v1.0";
+
+/// カレンダーの予定（時刻はローカル時刻）
+#[derive(Debug, Clone)]
+pub struct CalendarEvent {
+    pub external_id: String,
+    pub title: String,
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+}
+
+/// Google Calendar API: events.list
+#[derive(Debug, Deserialize)]
+struct GoogleEvents {
+    #[serde(default)]
+    items: Vec<GoogleEvent>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GoogleEvent {
+    id: String,
+    #[serde(default)]
+    summary: Option<String>,
+    #[serde(default)]
+    status: Option<String>,
+    /// "transparent" は空き時間として表示する予定
+    #[serde(default)]
+    transparency: Option<String>,
+    start: GoogleTime,
+    end: GoogleTime,
+}
+
+/// 終日の予定は date のみ
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GoogleTime {
+    #[serde(default)]
+    date_time: Option<String>,
+}
+
+/// Microsoft Graph: calendarView
+#[derive(Debug, Deserialize)]
+struct GraphEvents {
+    #[serde(default)]
+    value: Vec<GraphEvent>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GraphEvent {
+    id: String,
+    #[serde(default)]
+    subject: Option<String>,
+    #[serde(default)]
+    is_all_day: bool,
+    #[serde(default)]
+    is_cancelled: bool,
+    /// "free" は空き時間として表示する予定
+    #[serde(default)]
+    show_as: Option<String>,
+    start: GraphTime,
+    end: GraphTime,
+}
+
+/// Prefer: outlook.timezone="UTC" を指定するので UTC のタイムゾーンなしの日時
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GraphTime {
+    date_time: String,
+}
+
+/// Google Calendar / Outlook からその日の予定を取得する
+pub struct CalendarClient {
+    config: CalendarConfig,
+    client: HttpClient,
+}
+
+impl CalendarClient {
+    pub fn new(mut config: CalendarConfig) -> Result<Self, String> {
+        // キーリングのトークンを優先する
+        if let Some(alias) = &config.token_secret {
+            config.token = secrets::get_integration(alias)?
+                .ok_or_else(|| format!("Calendar token not found in the OS keyring: {}", alias))?;
+        }
+
+        Ok(Self {
+            config,
+            client: HttpClient::new(),
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.config.name
+    }
+
+    /// 指定日（ローカル時刻の0時〜24時）にかかる予定（終日・キャンセル・空き時間の予定は除く）
+    pub async fn fetch_events(&self, date: NaiveDate) -> Result<Vec<CalendarEvent>, String> {
+        let start = local_midnight(date)?;
+        let end = local_midnight(date.succ_opt().ok_or("Invalid date")?)?;
+
+        match self.config.provider {
+            CalendarProvider::Google => self.fetch_google(start, end).await,
+            CalendarProvider::Outlook => self.fetch_outlook(start, end).await,
+        }
+    }
+
+    async fn fetch_google(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<CalendarEvent>, String> {
+        let calendar_id = self.config.calendar_id.as_deref().unwrap_or("primary");
+        let url = format!(
+            "{}/calendars/{}/events",
+            GOOGLE_API_BASE,
+            urlencode(calendar_id)
+        );
+        let response = self
+            .client
+            .get(&url)
+            .bearer_auth(&self.config.token)
+            .query(&[
+                ("timeMin", start.to_rfc3339()),
+                ("timeMax", end.to_rfc3339()),
+                ("singleEvents", "true".to_string()),
+                ("orderBy", "startTime".to_string()),
+                ("maxResults", "250".to_string()),
+            ])
+            .send()
+            .await
+            .map_err(|e| format!("Network error: {}", e))?;
+        let events: GoogleEvents = parse(response, "Google Calendar").await?;
+
+        Ok(events
+            .items
+            .into_iter()
+            .filter(|event| event.status.as_deref() != Some("cancelled"))
+            .filter(|event| event.transparency.as_deref() != Some("transparent"))
+            .filter_map(|event| {
+                let start = parse_rfc3339(event.start.date_time.as_deref()?)?;
+                let end = parse_rfc3339(event.end.date_time.as_deref()?)?;
+                Some(CalendarEvent {
+                    external_id: event.id,
+                    title: event.summary.unwrap_or_default(),
+                    start,
+                    end,
+                })
+            })
+            .collect())
+    }
+
+    async fn fetch_outlook(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<CalendarEvent>, String> {
+        let url = format!("{}/me/calendarView", GRAPH_API_BASE);
+        let response = self
+            .client
+            .get(&url)
+            .bearer_auth(&self.config.token)
+            .header("Prefer", "outlook.timezone=\"UTC\"")
+            .query(&[
+                ("startDateTime", start.to_rfc3339()),
+                ("endDateTime", end.to_rfc3339()),
+                (
+                    "$select",
+                    "id,subject,start,end,isAllDay,isCancelled,showAs".to_string(),
+                ),
+                ("$top", "250".to_string()),
+            ])
+            .send()
+            .await
+            .map_err(|e| format!("Network error: {}", e))?;
+        let events: GraphEvents = parse(response, "Microsoft Graph").await?;
+
+        Ok(events
+            .value
+            .into_iter()
+            .filter(|event| !event.is_all_day && !event.is_cancelled)
+            .filter(|event| event.show_as.as_deref() != Some("free"))
+            .filter_map(|event| {
+                Some(CalendarEvent {
+                    external_id: event.id,
+                    title: event.subject.unwrap_or_default(),
+                    start: parse_graph_time(&event.start.date_time)?,
+                    end: parse_graph_time(&event.end.date_time)?,
+                })
+            })
+            .collect())
+    }
+}
+
+async fn parse<T: serde::de::DeserializeOwned>(
+    response: reqwest::Response,
+    service: &str,
+) -> Result<T, String> {
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(format!("{} API error ({}): {}", service, status, body));
+    }
+    response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse response: {}", e))
+}
+
+fn local_midnight(date: NaiveDate) -> Result<DateTime<Utc>, String> {
+    Local
+        .from_local_datetime(&date.and_hms_opt(0, 0, 0).expect("midnight is valid"))
+        .earliest()
+        .map(|t| t.with_timezone(&Utc))
+        .ok_or_else(|| format!("Invalid local date: {}", date))
+}
+
+fn parse_rfc3339(value: &str) -> Option<NaiveDateTime> {
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|t| t.with_timezone(&Local).naive_local())
+}
+
+/// "2024-01-15T09:00:00.0000000"（UTC）
+fn parse_graph_time(value: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f")
+        .ok()
+        .map(|t| {
+            Utc.from_utc_datetime(&t)
+                .with_timezone(&Local)
+                .naive_local()
+        })
+}
+
+fn urlencode(value: &str) -> String {
+    url::form_urlencoded::byte_serialize(value.as_bytes()).collect()
+}
@@ -0,0 +1,226 @@
+use async_trait::async_trait;
+use chrono::{Local, NaiveDateTime, SecondsFormat, TimeZone, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use super::{compile_rules, entry_comment, extract_with_rules, CompiledRule};
+use crate::plugins::config::{ClockifyConfig, ClockifyProjectMapping};
+use crate::plugins::http::HttpClient;
+use crate::plugins::traits::{ActivityInfo, ExternalIntegration, SyncError, SyncResult};
+use crate::secrets;
+
+const API_BASE: &str = "https://api.clockify.me/api/v1";
+
+/// Clockify API: タイムエントリ作成リクエスト
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TimeEntryRequest {
+    /// RFC3339 (UTC)
+    start: String,
+    end: String,
+    billable: bool,
+    description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    project_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    task_id: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tag_ids: Vec<String>,
+}
+
+/// Clockify API: タイムエントリ作成レスポンス
+#[derive(Debug, Deserialize)]
+struct TimeEntryResponse {
+    id: String,
+}
+
+/// Clockify API: ユーザー情報（接続テスト用）
+#[derive(Debug, Deserialize)]
+struct UserResponse {
+    id: String,
+    email: String,
+}
+
+/// Clockify連携プラグイン
+pub struct ClockifyIntegration {
+    name: String,
+    enabled: bool,
+    config: ClockifyConfig,
+    client: HttpClient,
+    rules: Vec<CompiledRule>,
+    /// チケットIDのパターン（コンパイルできないパターンは無視）
+    projects: Vec<(Regex, ClockifyProjectMapping)>,
+}
+
+impl ClockifyIntegration {
+    pub fn new(name: String, enabled: bool, mut config: ClockifyConfig) -> Result<Self, String> {
+        let client = HttpClient::new();
+
+        // キーリングのAPIキーを優先する
+        if let Some(alias) = &config.api_key_secret {
+            config.api_key = secrets::get_integration(alias)?
+                .ok_or_else(|| format!("API key not found in the OS keyring: {}", alias))?;
+        }
+
+        let rules = compile_rules(&config.rules);
+        let projects = config
+            .projects
+            .iter()
+            .filter_map(|mapping| {
+                Regex::new(&mapping.pattern)
+                    .ok()
+                    .map(|re| (re, mapping.clone()))
+            })
+            .collect();
+
+        Ok(Self {
+            name,
+            enabled,
+            config,
+            client,
+            rules,
+            projects,
+        })
+    }
+
+    /// チケットIDに対応するプロジェクト・タスク・タグ
+    ///
+    /// 最初にマッチしたマッピング → default_project_id の順。タグは共通のタグに追加する。
+    fn resolve(&self, ticket_id: &str) -> (Option<String>, Option<String>, Vec<String>) {
+        let mut tag_ids = self.config.tag_ids.clone();
+        match self
+            .projects
+            .iter()
+            .find(|(pattern, _)| pattern.is_match(ticket_id))
+        {
+            Some((_, mapping)) => {
+                for tag in &mapping.tag_ids {
+                    if !tag_ids.contains(tag) {
+                        tag_ids.push(tag.clone());
+                    }
+                }
+                (
+                    Some(mapping.project_id.clone()),
+                    mapping.task_id.clone(),
+                    tag_ids,
+                )
+            }
+            None => (self.config.default_project_id.clone(), None, tag_ids),
+        }
+    }
+
+    /// ローカル時刻をUTCのRFC3339に変換
+    fn to_utc(time: &str) -> Result<String, String> {
+        let naive = NaiveDateTime::parse_from_str(time, "%Y-%m-%dT%H:%M:%S")
+            .map_err(|e| format!("Invalid time {}: {}", time, e))?;
+        let local = Local
+            .from_local_datetime(&naive)
+            .earliest()
+            .ok_or_else(|| format!("Invalid local time: {}", time))?;
+        Ok(local
+            .with_timezone(&Utc)
+            .to_rfc3339_opts(SecondsFormat::Secs, true))
+    }
+}
+
+#[async_trait]
+impl ExternalIntegration for ClockifyIntegration {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn display_name(&self) -> &str {
+        "Clockify"
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn extract_ticket_id(&self, activity: &ActivityInfo) -> Option<String> {
+        extract_with_rules(&self.rules, activity)
+    }
+
+    async fn sync_time_entry(
+        &self,
+        activity: &ActivityInfo,
+        ticket_id: &str,
+    ) -> Result<SyncResult, SyncError> {
+        let (project_id, task_id, tag_ids) = self.resolve(ticket_id);
+
+        // 1日分の統合では期間が合計より長くなるので、終了は開始 + 合計時間にする
+        let start = NaiveDateTime::parse_from_str(&activity.start_time, "%Y-%m-%dT%H:%M:%S")
+            .map_err(|e| format!("Invalid start time {}: {}", activity.start_time, e))?;
+        let end = (start + chrono::Duration::seconds(activity.duration_seconds))
+            .format("%Y-%m-%dT%H:%M:%S")
+            .to_string();
+
+        let request = TimeEntryRequest {
+            start: Self::to_utc(&activity.start_time)?,
+            end: Self::to_utc(&end)?,
+            billable: self.config.billable,
+            description: format!("{} - {}", ticket_id, entry_comment(activity)),
+            project_id,
+            task_id,
+            tag_ids,
+        };
+
+        let url = format!(
+            "{}/workspaces/{}/time-entries",
+            API_BASE, self.config.workspace_id
+        );
+
+        let response = self
+            .client
+            .post(&url)
+            .header("X-Api-Key", &self.config.api_key)
+            .json(&request)
+            .send()
+            .await
+            .map_err(SyncError::network)?;
+
+        if response.status().is_success() {
+            let result: TimeEntryResponse = response
+                .json()
+                .await
+                .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+            Ok(SyncResult {
+                success: true,
+                message: format!("Created time entry {}", result.id),
+                external_id: Some(result.id),
+            })
+        } else {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            Err(format!("Clockify API error ({}): {}", status, body).into())
+        }
+    }
+
+    async fn test_connection(&self) -> Result<bool, String> {
+        let url = format!("{}/user", API_BASE);
+
+        let response = self
+            .client
+            .get(&url)
+            .header("X-Api-Key", &self.config.api_key)
+            .send()
+            .await
+            .map_err(|e| format!("Network error: {}", e))?;
+
+        if response.status().is_success() {
+            let result: UserResponse = response
+                .json()
+                .await
+                .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+            println!(
+                "Connected to Clockify as: {} (id: {})",
+                result.email, result.id
+            );
+            Ok(true)
+        } else {
+            Err(format!("Authentication failed: {}", response.status()))
+        }
+    }
+}
@@ -0,0 +1,182 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use super::{compile_rules, extract_with_rules, CompiledRule};
+use crate::plugins::config::ExecConfig;
+use crate::plugins::traits::{ActivityInfo, ExternalIntegration, SyncError, SyncResult};
+use crate::secrets;
+
+/// 外部コマンドに渡すリクエスト
+#[derive(Debug, Serialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+enum ExecRequest<'a> {
+    Sync {
+        ticket_id: &'a str,
+        activity: &'a ActivityInfo,
+    },
+    Test,
+}
+
+/// 接続テストの結果
+#[derive(Debug, Deserialize)]
+struct TestResponse {
+    success: bool,
+    #[serde(default)]
+    message: Option<String>,
+}
+
+/// 外部コマンド連携プラグイン
+pub struct ExecIntegration {
+    name: String,
+    enabled: bool,
+    config: ExecConfig,
+    rules: Vec<CompiledRule>,
+    token: Option<String>,
+}
+
+impl ExecIntegration {
+    pub fn new(name: String, enabled: bool, config: ExecConfig) -> Result<Self, String> {
+        let token = match &config.token_secret {
+            Some(alias) => Some(
+                secrets::get_integration(alias)?
+                    .ok_or_else(|| format!("Token not found in the OS keyring: {}", alias))?,
+            ),
+            None => None,
+        };
+
+        let rules = compile_rules(&config.rules);
+
+        Ok(Self {
+            name,
+            enabled,
+            config,
+            rules,
+            token,
+        })
+    }
+
+    /// コマンドを実行し、リクエストを標準入力に書き込んで標準出力を返す
+    ///
+    /// 終了コードが0以外ならエラー（標準エラー出力をメッセージにする）。
+    async fn run(&self, request: &ExecRequest<'_>) -> Result<String, String> {
+        let input = serde_json::to_vec(request).map_err(|e| e.to_string())?;
+
+        let mut command = Command::new(&self.config.command);
+        command
+            .args(&self.config.args)
+            .envs(&self.config.env)
+            .env("TIMETRACKER_INTEGRATION", &self.name)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        if let Some(token) = &self.token {
+            command.env("TIMETRACKER_TOKEN", token);
+        }
+        #[cfg(target_os = "windows")]
+        {
+            // コンソールウィンドウを開かない
+            const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+            command.creation_flags(CREATE_NO_WINDOW);
+        }
+
+        let mut child = command
+            .spawn()
+            .map_err(|e| format!("Failed to run {}: {}", self.config.command, e))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(&input)
+                .await
+                .map_err(|e| format!("Failed to write to {}: {}", self.config.command, e))?;
+            // 閉じて入力の終わりを伝える
+            drop(stdin);
+        }
+
+        let timeout = Duration::from_secs(self.config.timeout_seconds.max(1));
+        let output = tokio::time::timeout(timeout, child.wait_with_output())
+            .await
+            .map_err(|_| {
+                format!(
+                    "{} timed out after {}s",
+                    self.config.command,
+                    timeout.as_secs()
+                )
+            })?
+            .map_err(|e| format!("Failed to run {}: {}", self.config.command, e))?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!(
+                "{} exited with {}: {}",
+                self.config.command,
+                output.status,
+                stderr.trim()
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+#[async_trait]
+impl ExternalIntegration for ExecIntegration {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn display_name(&self) -> &str {
+        "External command"
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn extract_ticket_id(&self, activity: &ActivityInfo) -> Option<String> {
+        extract_with_rules(&self.rules, activity)
+    }
+
+    async fn sync_time_entry(
+        &self,
+        activity: &ActivityInfo,
+        ticket_id: &str,
+    ) -> Result<SyncResult, SyncError> {
+        let stdout = self
+            .run(&ExecRequest::Sync {
+                ticket_id,
+                activity,
+            })
+            .await?;
+        let result: SyncResult = serde_json::from_str(stdout.trim())
+            .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+        // 失敗は Err として返し、送信失敗として扱わせる
+        if result.success {
+            Ok(result)
+        } else {
+            Err(result.message.into())
+        }
+    }
+
+    async fn test_connection(&self) -> Result<bool, String> {
+        let stdout = self.run(&ExecRequest::Test).await?;
+        let result: TestResponse = serde_json::from_str(stdout.trim())
+            .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+        if result.success {
+            println!(
+                "Connected via {}: {}",
+                self.config.command,
+                result.message.unwrap_or_default()
+            );
+            Ok(true)
+        } else {
+            Err(result
+                .message
+                .unwrap_or_else(|| "Connection test failed".to_string()))
+        }
+    }
+}
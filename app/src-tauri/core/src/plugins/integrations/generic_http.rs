@@ -0,0 +1,235 @@
+use async_trait::async_trait;
+use reqwest::Method;
+
+use super::{compile_rules, entry_comment, extract_with_rules, substitute, CompiledRule};
+use crate::plugins::config::CustomHttpConfig;
+use crate::plugins::http::{HttpClient, RequestBuilder};
+use crate::plugins::traits::{ActivityInfo, ExternalIntegration, SyncError, SyncResult};
+use crate::secrets;
+
+/// プレースホルダーの埋め込み先（埋め込み先ごとにエスケープが異なる）
+#[derive(Clone, Copy)]
+enum Target {
+    Url,
+    Header,
+    /// JSON の文字列の中（数値のプレースホルダーはそのまま）
+    Json,
+}
+
+/// 任意のHTTP APIへの連携プラグイン
+pub struct GenericHttpIntegration {
+    name: String,
+    enabled: bool,
+    config: CustomHttpConfig,
+    client: HttpClient,
+    rules: Vec<CompiledRule>,
+    method: Method,
+    token: String,
+}
+
+impl GenericHttpIntegration {
+    pub fn new(name: String, enabled: bool, config: CustomHttpConfig) -> Result<Self, String> {
+        let client = HttpClient::new();
+
+        let method = Method::from_bytes(config.method.to_uppercase().as_bytes())
+            .map_err(|_| format!("Invalid HTTP method: {}", config.method))?;
+        let token = match &config.token_secret {
+            Some(alias) => secrets::get_integration(alias)?
+                .ok_or_else(|| format!("Token not found in the OS keyring: {}", alias))?,
+            None => String::new(),
+        };
+        if let Some(body) = &config.body {
+            // プレースホルダーを数値で仮に埋めて、JSONとして正しいか確かめる
+            let sample = ActivityInfo {
+                id: 0,
+                process_name: String::new(),
+                window_title: String::new(),
+                domain: None,
+                start_time: String::new(),
+                end_time: String::new(),
+                duration_seconds: 0,
+                note: None,
+                project: None,
+                category: None,
+            };
+            let rendered = render(body, &sample, "", "", Target::Json);
+            serde_json::from_str::<serde_json::Value>(&rendered)
+                .map_err(|e| format!("Invalid body template for {}: {}", name, e))?;
+        }
+
+        let rules = compile_rules(&config.rules);
+
+        Ok(Self {
+            name,
+            enabled,
+            config,
+            client,
+            rules,
+            method,
+            token,
+        })
+    }
+
+    fn request(
+        &self,
+        method: Method,
+        url: &str,
+        activity: Option<(&ActivityInfo, &str)>,
+    ) -> RequestBuilder {
+        let fill = |template: &str, target| match activity {
+            Some((activity, ticket_id)) => {
+                render(template, activity, ticket_id, &self.token, target)
+            }
+            None => substitute(template, |key| (key == "token").then(|| self.token.clone())),
+        };
+
+        let mut builder = self.client.request(method, fill(url, Target::Url));
+        for (name, value) in &self.config.headers {
+            builder = builder.header(name, fill(value, Target::Header));
+        }
+        builder
+    }
+}
+
+/// テンプレートのプレースホルダーを置き換える（未知のプレースホルダーはそのまま残す）
+///
+/// 1回の走査で置き換えるので、タイトルなどに含まれる {{token}} は展開されない。
+fn render(
+    template: &str,
+    activity: &ActivityInfo,
+    ticket_id: &str,
+    token: &str,
+    target: Target,
+) -> String {
+    let hours = (activity.duration_seconds as f64 / 3600.0 * 100.0).round() / 100.0;
+    let date = activity
+        .start_time
+        .split('T')
+        .next()
+        .unwrap_or(&activity.start_time);
+    let comment = entry_comment(activity);
+
+    // 数値はエスケープしない
+    let numbers = [
+        ("duration_hours", hours.to_string()),
+        (
+            "duration_minutes",
+            (activity.duration_seconds / 60).to_string(),
+        ),
+        ("duration_seconds", activity.duration_seconds.to_string()),
+        ("activity_id", activity.id.to_string()),
+    ];
+    let texts = [
+        ("ticket_id", ticket_id),
+        ("title", activity.window_title.as_str()),
+        ("process_name", activity.process_name.as_str()),
+        ("domain", activity.domain.as_deref().unwrap_or("")),
+        ("start_time", activity.start_time.as_str()),
+        ("end_time", activity.end_time.as_str()),
+        ("date", date),
+        ("comment", comment.as_str()),
+        ("token", token),
+    ];
+
+    substitute(template, |key| {
+        if let Some((_, value)) = numbers.iter().find(|(name, _)| *name == key) {
+            return Some(value.clone());
+        }
+        texts
+            .iter()
+            .find(|(name, _)| *name == key)
+            .map(|(_, value)| escape(value, target))
+    })
+}
+
+fn escape(value: &str, target: Target) -> String {
+    match target {
+        Target::Url => url::form_urlencoded::byte_serialize(value.as_bytes()).collect(),
+        // 改行はヘッダーに含められない
+        Target::Header => value.replace(['\r', '\n'], " "),
+        Target::Json => {
+            let quoted = serde_json::to_string(value).unwrap_or_default();
+            quoted[1..quoted.len() - 1].to_string()
+        }
+    }
+}
+
+#[async_trait]
+impl ExternalIntegration for GenericHttpIntegration {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn display_name(&self) -> &str {
+        "Custom HTTP"
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn extract_ticket_id(&self, activity: &ActivityInfo) -> Option<String> {
+        extract_with_rules(&self.rules, activity)
+    }
+
+    async fn sync_time_entry(
+        &self,
+        activity: &ActivityInfo,
+        ticket_id: &str,
+    ) -> Result<SyncResult, SyncError> {
+        let mut builder = self.request(
+            self.method.clone(),
+            &self.config.url,
+            Some((activity, ticket_id)),
+        );
+        if let Some(body) = &self.config.body {
+            builder = builder
+                .header("Content-Type", "application/json")
+                .body(render(body, activity, ticket_id, &self.token, Target::Json));
+        }
+
+        let response = builder.send().await.map_err(SyncError::network)?;
+
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        if !status.is_success() {
+            return Err(format!("HTTP error ({}): {}", status, text).into());
+        }
+
+        let external_id = self.config.id_pointer.as_deref().and_then(|pointer| {
+            let value: serde_json::Value = serde_json::from_str(&text).ok()?;
+            match value.pointer(pointer)? {
+                serde_json::Value::String(id) => Some(id.clone()),
+                serde_json::Value::Null => None,
+                id => Some(id.to_string()),
+            }
+        });
+
+        Ok(SyncResult {
+            success: true,
+            message: format!("Sent {} ({})", ticket_id, status),
+            external_id,
+        })
+    }
+
+    async fn test_connection(&self) -> Result<bool, String> {
+        let url = self
+            .config
+            .test_url
+            .as_deref()
+            .ok_or("test_url is not configured")?;
+
+        let response = self
+            .request(Method::GET, url, None)
+            .send()
+            .await
+            .map_err(|e| format!("Network error: {}", e))?;
+
+        if response.status().is_success() {
+            println!("Connected to {} ({})", url, response.status());
+            Ok(true)
+        } else {
+            Err(format!("Authentication failed: {}", response.status()))
+        }
+    }
+}
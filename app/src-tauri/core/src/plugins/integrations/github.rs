@@ -0,0 +1,461 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use super::{compile_rules, spent_comment, CompiledRule};
+use crate::plugins::config::GitHubConfig;
+use crate::plugins::http::{HttpClient, RequestBuilder};
+use crate::plugins::traits::{
+    ActivityInfo, ExternalIntegration, IssueSummary, SyncError, SyncResult,
+};
+
+const USER_AGENT: &str = "timetracker";
+
+const PROJECT_ITEMS_QUERY: &str = r#"
+query($owner: String!, $repo: String!, $number: Int!) {
+  repository(owner: $owner, name: $repo) {
+    issueOrPullRequest(number: $number) {
+      ... on Issue { projectItems(first: 20) { nodes { ...item } } }
+      ... on PullRequest { projectItems(first: 20) { nodes { ...item } } }
+    }
+  }
+}
+fragment item on ProjectV2Item {
+  id
+  project { id }
+  fieldValues(first: 50) {
+    nodes {
+      ... on ProjectV2ItemFieldNumberValue {
+        number
+        field { ... on ProjectV2Field { id } }
+      }
+    }
+  }
+}
+"#;
+
+const UPDATE_FIELD_MUTATION: &str = r#"
+mutation($project: ID!, $item: ID!, $field: ID!, $value: Float!) {
+  updateProjectV2ItemFieldValue(input: {
+    projectId: $project, itemId: $item, fieldId: $field, value: { number: $value }
+  }) {
+    projectV2Item { id }
+  }
+}
+"#;
+
+/// GitHub API: コメント作成リクエスト
+#[derive(Debug, Serialize)]
+struct CommentRequest {
+    body: String,
+}
+
+/// GitHub API: コメント作成レスポンス
+#[derive(Debug, Deserialize)]
+struct CommentResponse {
+    id: i64,
+}
+
+/// GitHub API: ユーザー情報（接続テスト用）
+#[derive(Debug, Deserialize)]
+struct UserResponse {
+    id: i64,
+    login: String,
+}
+
+/// GitHub API: 自分に割り当てられたIssue
+#[derive(Debug, Deserialize)]
+struct IssueListItem {
+    number: i64,
+    title: String,
+    state: String,
+    repository: Option<RepositoryRef>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RepositoryRef {
+    full_name: String,
+}
+
+/// GraphQL: Issue/PRが属するプロジェクトアイテム
+#[derive(Debug, Deserialize)]
+struct GraphQlResponse<T> {
+    data: Option<T>,
+    #[serde(default)]
+    errors: Vec<GraphQlError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphQlError {
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ProjectItemsData {
+    repository: Option<RepositoryNode>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RepositoryNode {
+    issue_or_pull_request: Option<IssueNode>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IssueNode {
+    project_items: Option<Nodes<ProjectItem>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Nodes<T> {
+    nodes: Vec<T>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProjectItem {
+    id: String,
+    project: IdRef,
+    field_values: Nodes<FieldValue>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FieldValue {
+    number: Option<f64>,
+    field: Option<IdRef>,
+}
+
+#[derive(Debug, Deserialize)]
+struct IdRef {
+    id: Option<String>,
+}
+
+/// "owner/repo#123" 形式のIssue参照
+struct IssueRef<'a> {
+    owner: &'a str,
+    repo: &'a str,
+    number: i64,
+}
+
+impl<'a> IssueRef<'a> {
+    fn parse(ticket_id: &'a str) -> Result<Self, String> {
+        let invalid = || format!("Invalid ticket ID: {} (expected owner/repo#123)", ticket_id);
+        let (full_name, number) = ticket_id.split_once('#').ok_or_else(invalid)?;
+        let (owner, repo) = full_name.split_once('/').ok_or_else(invalid)?;
+        let number = number.parse().map_err(|_| invalid())?;
+        Ok(Self {
+            owner,
+            repo,
+            number,
+        })
+    }
+}
+
+/// GitHub Issues/Projects連携プラグイン
+pub struct GitHubIntegration {
+    name: String,
+    enabled: bool,
+    config: GitHubConfig,
+    client: HttpClient,
+    rules: Vec<CompiledRule>,
+}
+
+impl GitHubIntegration {
+    pub fn new(name: String, enabled: bool, config: GitHubConfig) -> Result<Self, String> {
+        let client = HttpClient::new();
+        let rules = compile_rules(&config.rules);
+
+        Ok(Self {
+            name,
+            enabled,
+            config,
+            client,
+            rules,
+        })
+    }
+
+    fn api_url(&self, path: &str) -> String {
+        format!("{}/{}", self.config.api_url.trim_end_matches('/'), path)
+    }
+
+    fn request(&self, method: reqwest::Method, url: &str) -> RequestBuilder {
+        self.client
+            .request(method, url)
+            .bearer_auth(&self.config.token)
+            .header("Accept", "application/vnd.github+json")
+            .header("X-GitHub-Api-Version", "2022-11-28")
+            .header("User-Agent", USER_AGENT)
+    }
+
+    async fn graphql<T: for<'de> Deserialize<'de>>(
+        &self,
+        query: &str,
+        variables: serde_json::Value,
+    ) -> Result<T, SyncError> {
+        let response = self
+            .request(reqwest::Method::POST, &self.api_url("graphql"))
+            .json(&json!({ "query": query, "variables": variables }))
+            .send()
+            .await
+            .map_err(SyncError::network)?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(format!("GitHub API error ({}): {}", status, body).into());
+        }
+
+        let result: GraphQlResponse<T> = response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+        if let Some(error) = result.errors.first() {
+            return Err(format!("GitHub GraphQL error: {}", error.message).into());
+        }
+        result
+            .data
+            .ok_or_else(|| SyncError::Failed("GitHub GraphQL returned no data".to_string()))
+    }
+
+    /// Issueにコメントとして作業時間を追記
+    async fn post_comment(
+        &self,
+        issue: &IssueRef<'_>,
+        activity: &ActivityInfo,
+    ) -> Result<i64, SyncError> {
+        let url = self.api_url(&format!(
+            "repos/{}/{}/issues/{}/comments",
+            issue.owner, issue.repo, issue.number
+        ));
+        let request = CommentRequest {
+            body: spent_comment(activity),
+        };
+
+        let response = self
+            .request(reqwest::Method::POST, &url)
+            .json(&request)
+            .send()
+            .await
+            .map_err(SyncError::network)?;
+
+        if response.status().is_success() {
+            let result: CommentResponse = response
+                .json()
+                .await
+                .map_err(|e| format!("Failed to parse response: {}", e))?;
+            Ok(result.id)
+        } else {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            Err(format!("GitHub API error ({}): {}", status, body).into())
+        }
+    }
+
+    /// プロジェクトの数値フィールド（時間）に作業時間を加算し、更新後の値を返す
+    async fn add_to_project_field(
+        &self,
+        issue: &IssueRef<'_>,
+        project_id: &str,
+        field_id: &str,
+        hours: f64,
+    ) -> Result<f64, SyncError> {
+        let data: ProjectItemsData = self
+            .graphql(
+                PROJECT_ITEMS_QUERY,
+                json!({ "owner": issue.owner, "repo": issue.repo, "number": issue.number }),
+            )
+            .await?;
+
+        let item = data
+            .repository
+            .and_then(|r| r.issue_or_pull_request)
+            .and_then(|i| i.project_items)
+            .and_then(|items| {
+                items
+                    .nodes
+                    .into_iter()
+                    .find(|item| item.project.id.as_deref() == Some(project_id))
+            })
+            .ok_or_else(|| {
+                format!(
+                    "{}/{}#{} is not in project {}",
+                    issue.owner, issue.repo, issue.number, project_id
+                )
+            })?;
+
+        let current = item
+            .field_values
+            .nodes
+            .iter()
+            .find(|value| value.field.as_ref().and_then(|f| f.id.as_deref()) == Some(field_id))
+            .and_then(|value| value.number)
+            .unwrap_or(0.0);
+        let total = ((current + hours) * 100.0).round() / 100.0;
+
+        let _: serde_json::Value = self
+            .graphql(
+                UPDATE_FIELD_MUTATION,
+                json!({
+                    "project": project_id,
+                    "item": item.id,
+                    "field": field_id,
+                    "value": total,
+                }),
+            )
+            .await?;
+
+        Ok(total)
+    }
+}
+
+#[async_trait]
+impl ExternalIntegration for GitHubIntegration {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn display_name(&self) -> &str {
+        "GitHub"
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// 名前付きグループ repo / number があれば "owner/repo#123" に組み立てる。
+    /// 番号のみ取れた場合は default_repo を補う。template を指定したルールはその展開結果を使う。
+    fn extract_ticket_id(&self, activity: &ActivityInfo) -> Option<String> {
+        for rule in &self.rules {
+            let Some(captures) = rule.captures(activity) else {
+                continue;
+            };
+            if rule.template.is_some() {
+                match rule.ticket_id(&captures) {
+                    Some(id) => return Some(id),
+                    None => continue,
+                }
+            }
+
+            let number = captures
+                .name("number")
+                .or_else(|| captures.get(1))
+                .map(|m| m.as_str());
+            let repo = captures
+                .name("repo")
+                .map(|m| m.as_str())
+                .or(self.config.default_repo.as_deref());
+
+            match (repo, number) {
+                (_, Some(number)) if number.contains('#') => return Some(number.to_string()),
+                (Some(repo), Some(number)) => return Some(format!("{}#{}", repo, number)),
+                _ => continue,
+            }
+        }
+        None
+    }
+
+    async fn sync_time_entry(
+        &self,
+        activity: &ActivityInfo,
+        ticket_id: &str,
+    ) -> Result<SyncResult, SyncError> {
+        let issue = IssueRef::parse(ticket_id)?;
+        let mut messages = Vec::new();
+        let mut external_id = None;
+
+        if self.config.post_comments {
+            let comment_id = self.post_comment(&issue, activity).await?;
+            messages.push(format!("Added comment on {}", ticket_id));
+            external_id = Some(comment_id.to_string());
+        }
+
+        if let Some(project) = &self.config.project {
+            let hours = activity.duration_seconds as f64 / 3600.0;
+            match self
+                .add_to_project_field(&issue, &project.project_id, &project.field_id, hours)
+                .await
+            {
+                Ok(total) => messages.push(format!("Updated project field to {}h", total)),
+                // コメントを投稿済みなら同期済みとして扱う（再送でコメントが重複しないように）
+                Err(e) if external_id.is_some() => {
+                    messages.push(format!("Failed to update project field: {}", e))
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        if messages.is_empty() {
+            return Err(
+                "GitHub integration has neither post_comments nor project configured"
+                    .to_string()
+                    .into(),
+            );
+        }
+
+        Ok(SyncResult {
+            success: true,
+            message: messages.join(", "),
+            external_id,
+        })
+    }
+
+    async fn test_connection(&self) -> Result<bool, String> {
+        let response = self
+            .request(reqwest::Method::GET, &self.api_url("user"))
+            .send()
+            .await
+            .map_err(|e| format!("Network error: {}", e))?;
+
+        if response.status().is_success() {
+            let result: UserResponse = response
+                .json()
+                .await
+                .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+            println!(
+                "Connected to GitHub as: {} (id: {})",
+                result.login, result.id
+            );
+            Ok(true)
+        } else {
+            Err(format!("Authentication failed: {}", response.status()))
+        }
+    }
+
+    async fn list_my_issues(&self) -> Result<Vec<IssueSummary>, String> {
+        let response = self
+            .request(
+                reqwest::Method::GET,
+                &self.api_url("issues?filter=assigned&state=open&sort=updated&per_page=100"),
+            )
+            .send()
+            .await
+            .map_err(|e| format!("Network error: {}", e))?;
+
+        if response.status().is_success() {
+            let result: Vec<IssueListItem> = response
+                .json()
+                .await
+                .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+            Ok(result
+                .into_iter()
+                .filter_map(|issue| {
+                    let repo = issue.repository?.full_name;
+                    Some(IssueSummary {
+                        id: format!("{}#{}", repo, issue.number),
+                        subject: issue.title,
+                        project: Some(repo),
+                        status: Some(issue.state),
+                    })
+                })
+                .collect())
+        } else {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            Err(format!("GitHub API error ({}): {}", status, body))
+        }
+    }
+}
@@ -0,0 +1,247 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use super::{compile_rules, entry_comment, extract_with_rules, CompiledRule};
+use crate::plugins::config::GitLabConfig;
+use crate::plugins::http::HttpClient;
+use crate::plugins::traits::{
+    ActivityInfo, ExternalIntegration, IssueSummary, SyncError, SyncResult,
+};
+
+/// GitLab API: 作業時間
+#[derive(Debug, Deserialize)]
+struct TimeStats {
+    time_estimate: i64,
+    total_time_spent: i64,
+    human_total_time_spent: Option<String>,
+}
+
+/// GitLab API: Issue情報（見積もり取得用）
+#[derive(Debug, Deserialize)]
+struct IssueResponse {
+    time_stats: TimeStats,
+}
+
+/// GitLab API: ユーザー情報（接続テスト用）
+#[derive(Debug, Deserialize)]
+struct UserResponse {
+    id: i64,
+    username: String,
+}
+
+/// GitLab API: Issue一覧
+#[derive(Debug, Deserialize)]
+struct IssueListItem {
+    iid: i64,
+    title: String,
+    state: String,
+}
+
+/// 作業時間の記録先（Issue または マージリクエスト）
+enum Target {
+    Issue(i64),
+    MergeRequest(i64),
+}
+
+impl Target {
+    /// "123" / "#123" はIssue、"!45" はマージリクエスト
+    fn parse(ticket_id: &str) -> Result<Self, String> {
+        let invalid = || format!("Invalid ticket ID: {}", ticket_id);
+        match ticket_id.strip_prefix('!') {
+            Some(iid) => iid.parse().map(Target::MergeRequest).map_err(|_| invalid()),
+            None => ticket_id
+                .trim_start_matches('#')
+                .parse()
+                .map(Target::Issue)
+                .map_err(|_| invalid()),
+        }
+    }
+
+    fn path(&self) -> String {
+        match self {
+            Target::Issue(iid) => format!("issues/{}", iid),
+            Target::MergeRequest(iid) => format!("merge_requests/{}", iid),
+        }
+    }
+}
+
+/// GitLab連携プラグイン
+pub struct GitLabIntegration {
+    name: String,
+    enabled: bool,
+    config: GitLabConfig,
+    client: HttpClient,
+    rules: Vec<CompiledRule>,
+}
+
+impl GitLabIntegration {
+    pub fn new(name: String, enabled: bool, config: GitLabConfig) -> Result<Self, String> {
+        let client = HttpClient::new();
+        let rules = compile_rules(&config.rules);
+
+        Ok(Self {
+            name,
+            enabled,
+            config,
+            client,
+            rules,
+        })
+    }
+
+    fn api_url(&self, path: &str) -> String {
+        format!("{}/api/v4/{}", self.config.url.trim_end_matches('/'), path)
+    }
+
+    /// プロジェクト単位のAPI URL（"group/project" 形式はURLエンコードする）
+    fn project_url(&self, path: &str) -> String {
+        let project: String =
+            url::form_urlencoded::byte_serialize(self.config.project.as_bytes()).collect();
+        self.api_url(&format!("projects/{}/{}", project, path))
+    }
+}
+
+#[async_trait]
+impl ExternalIntegration for GitLabIntegration {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn display_name(&self) -> &str {
+        "GitLab"
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn extract_ticket_id(&self, activity: &ActivityInfo) -> Option<String> {
+        extract_with_rules(&self.rules, activity)
+    }
+
+    async fn sync_time_entry(
+        &self,
+        activity: &ActivityInfo,
+        ticket_id: &str,
+    ) -> Result<SyncResult, SyncError> {
+        let target = Target::parse(ticket_id)?;
+
+        // GitLab の duration は "1h30m" や "90s" 形式を受け付ける
+        let duration = format!("{}s", activity.duration_seconds.max(1));
+        let summary = entry_comment(activity);
+        let url = self.project_url(&format!("{}/add_spent_time", target.path()));
+
+        let response = self
+            .client
+            .post(&url)
+            .header("PRIVATE-TOKEN", &self.config.token)
+            .form(&[("duration", &duration), ("summary", &summary)])
+            .send()
+            .await
+            .map_err(SyncError::network)?;
+
+        if response.status().is_success() {
+            let result: TimeStats = response
+                .json()
+                .await
+                .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+            let total = result
+                .human_total_time_spent
+                .unwrap_or_else(|| format!("{}s", result.total_time_spent));
+            Ok(SyncResult {
+                success: true,
+                message: format!("Added spent time to {} (total {})", ticket_id, total),
+                external_id: None,
+            })
+        } else {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            Err(format!("GitLab API error ({}): {}", status, body).into())
+        }
+    }
+
+    async fn test_connection(&self) -> Result<bool, String> {
+        let response = self
+            .client
+            .get(self.api_url("user"))
+            .header("PRIVATE-TOKEN", &self.config.token)
+            .send()
+            .await
+            .map_err(|e| format!("Network error: {}", e))?;
+
+        if response.status().is_success() {
+            let result: UserResponse = response
+                .json()
+                .await
+                .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+            println!(
+                "Connected to GitLab as: {} (id: {})",
+                result.username, result.id
+            );
+            Ok(true)
+        } else {
+            Err(format!("Authentication failed: {}", response.status()))
+        }
+    }
+
+    async fn get_ticket_estimate(&self, ticket_id: &str) -> Result<Option<f64>, String> {
+        let target = Target::parse(ticket_id)?;
+
+        let response = self
+            .client
+            .get(self.project_url(&target.path()))
+            .header("PRIVATE-TOKEN", &self.config.token)
+            .send()
+            .await
+            .map_err(|e| format!("Network error: {}", e))?;
+
+        if response.status().is_success() {
+            let result: IssueResponse = response
+                .json()
+                .await
+                .map_err(|e| format!("Failed to parse response: {}", e))?;
+            let estimate = result.time_stats.time_estimate;
+            Ok((estimate > 0).then(|| estimate as f64 / 3600.0))
+        } else {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            Err(format!("GitLab API error ({}): {}", status, body))
+        }
+    }
+
+    async fn list_my_issues(&self) -> Result<Vec<IssueSummary>, String> {
+        let url = self.project_url(
+            "issues?scope=assigned_to_me&state=opened&order_by=updated_at&per_page=100",
+        );
+
+        let response = self
+            .client
+            .get(&url)
+            .header("PRIVATE-TOKEN", &self.config.token)
+            .send()
+            .await
+            .map_err(|e| format!("Network error: {}", e))?;
+
+        if response.status().is_success() {
+            let result: Vec<IssueListItem> = response
+                .json()
+                .await
+                .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+            Ok(result
+                .into_iter()
+                .map(|issue| IssueSummary {
+                    id: issue.iid.to_string(),
+                    subject: issue.title,
+                    project: Some(self.config.project.clone()),
+                    status: Some(issue.state),
+                })
+                .collect())
+        } else {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            Err(format!("GitLab API error ({}): {}", status, body))
+        }
+    }
+}
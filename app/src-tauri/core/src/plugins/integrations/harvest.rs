@@ -0,0 +1,280 @@
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+use super::{compile_rules, entry_comment, extract_with_rules, CompiledRule};
+use crate::plugins::config::{HarvestConfig, HarvestOAuthConfig, HarvestProjectMapping};
+use crate::plugins::http::{HttpClient, RequestBuilder};
+use crate::plugins::traits::{ActivityInfo, ExternalIntegration, SyncError, SyncResult};
+use crate::secrets;
+
+const API_BASE: &str = "https://api.harvestapp.com/v2";
+const OAUTH_TOKEN_URL: &str = "https://id.getharvest.com/api/v2/oauth2/token";
+/// Harvest API は User-Agent が必須
+const USER_AGENT: &str = "timetracker (https://github.com/omatztw/timetracker)";
+
+/// Harvest API: タイムエントリ作成リクエスト（時間指定）
+#[derive(Debug, Serialize)]
+struct TimeEntryRequest {
+    project_id: i64,
+    task_id: i64,
+    spent_date: String,
+    hours: f64,
+    notes: String,
+}
+
+/// Harvest API: タイムエントリ作成レスポンス
+#[derive(Debug, Deserialize)]
+struct TimeEntryResponse {
+    id: i64,
+}
+
+/// Harvest API: ユーザー情報（接続テスト用）
+#[derive(Debug, Deserialize)]
+struct UserResponse {
+    id: i64,
+    email: String,
+}
+
+/// Harvest OAuth2: トークンのレスポンス
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    #[serde(default)]
+    refresh_token: Option<String>,
+    expires_in: u64,
+}
+
+/// Harvest連携プラグイン
+pub struct HarvestIntegration {
+    name: String,
+    enabled: bool,
+    config: HarvestConfig,
+    client: HttpClient,
+    rules: Vec<CompiledRule>,
+    /// チケットIDのパターン（コンパイルできないパターンは無視）
+    projects: Vec<(Regex, HarvestProjectMapping)>,
+    /// OAuth2 で取得したアクセストークンと有効期限
+    access_token: Mutex<Option<(String, Instant)>>,
+}
+
+impl HarvestIntegration {
+    pub fn new(name: String, enabled: bool, mut config: HarvestConfig) -> Result<Self, String> {
+        let client = HttpClient::new();
+
+        // キーリングのトークンを優先する
+        if let Some(alias) = &config.token_secret {
+            config.token = secrets::get_integration(alias)?
+                .ok_or_else(|| format!("Token not found in the OS keyring: {}", alias))?;
+        }
+        if config.token.is_empty() && config.oauth.is_none() {
+            return Err("Harvest requires a token or oauth settings".to_string());
+        }
+
+        let rules = compile_rules(&config.rules);
+        let projects = config
+            .projects
+            .iter()
+            .filter_map(|mapping| {
+                Regex::new(&mapping.pattern)
+                    .ok()
+                    .map(|re| (re, mapping.clone()))
+            })
+            .collect();
+
+        Ok(Self {
+            name,
+            enabled,
+            config,
+            client,
+            rules,
+            projects,
+            access_token: Mutex::new(None),
+        })
+    }
+
+    /// チケットIDに対応するプロジェクトとタスク
+    ///
+    /// 最初にマッチしたマッピング → default_project_id / default_task_id の順。
+    fn resolve(&self, ticket_id: &str) -> Option<(i64, i64)> {
+        self.projects
+            .iter()
+            .find(|(pattern, _)| pattern.is_match(ticket_id))
+            .map(|(_, mapping)| (mapping.project_id, mapping.task_id))
+            .or_else(|| {
+                self.config
+                    .default_project_id
+                    .zip(self.config.default_task_id)
+            })
+    }
+
+    /// API のアクセストークン（OAuth2 なら期限切れの前にリフレッシュする）
+    async fn token(&self) -> Result<String, SyncError> {
+        let Some(oauth) = &self.config.oauth else {
+            return Ok(self.config.token.clone());
+        };
+        if let Some((token, expires_at)) = self.access_token.lock().clone() {
+            if Instant::now() < expires_at {
+                return Ok(token);
+            }
+        }
+
+        let token = self.refresh(oauth).await?;
+        // 期限の1分前には切れたものとして扱う
+        let expires_at = Instant::now() + Duration::from_secs(token.expires_in.saturating_sub(60));
+        *self.access_token.lock() = Some((token.access_token.clone(), expires_at));
+        Ok(token.access_token)
+    }
+
+    async fn refresh(&self, oauth: &HarvestOAuthConfig) -> Result<TokenResponse, SyncError> {
+        let keyring_value = |alias: &str| {
+            secrets::get_integration(alias)?
+                .ok_or_else(|| format!("Secret not found in the OS keyring: {}", alias))
+        };
+        let client_secret = keyring_value(&oauth.client_secret_secret)?;
+        let refresh_token = keyring_value(&oauth.refresh_token_secret)?;
+
+        let response = self
+            .client
+            .post(OAUTH_TOKEN_URL)
+            .header("User-Agent", USER_AGENT)
+            .form(&[
+                ("grant_type", "refresh_token"),
+                ("client_id", oauth.client_id.as_str()),
+                ("client_secret", client_secret.as_str()),
+                ("refresh_token", refresh_token.as_str()),
+            ])
+            .send()
+            .await
+            .map_err(SyncError::network)?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(format!("Harvest OAuth error ({}): {}", status, body).into());
+        }
+        let token: TokenResponse = response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+        // リフレッシュトークンが更新されたら保存し直す
+        if let Some(new_token) = token.refresh_token.as_deref() {
+            if new_token != refresh_token {
+                secrets::set_integration(&oauth.refresh_token_secret, new_token)?;
+            }
+        }
+        Ok(token)
+    }
+
+    async fn request(&self, builder: RequestBuilder) -> Result<RequestBuilder, String> {
+        Ok(builder
+            .bearer_auth(self.token().await?)
+            .header("Harvest-Account-Id", &self.config.account_id)
+            .header("User-Agent", USER_AGENT))
+    }
+}
+
+#[async_trait]
+impl ExternalIntegration for HarvestIntegration {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn display_name(&self) -> &str {
+        "Harvest"
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn extract_ticket_id(&self, activity: &ActivityInfo) -> Option<String> {
+        extract_with_rules(&self.rules, activity)
+    }
+
+    async fn sync_time_entry(
+        &self,
+        activity: &ActivityInfo,
+        ticket_id: &str,
+    ) -> Result<SyncResult, SyncError> {
+        let (project_id, task_id) = self
+            .resolve(ticket_id)
+            .ok_or_else(|| format!("No Harvest project/task mapping for {}", ticket_id))?;
+
+        // 時間（小数、2桁に丸める）
+        let hours = (activity.duration_seconds as f64 / 3600.0 * 100.0).round() / 100.0;
+        let spent_date = activity
+            .start_time
+            .split('T')
+            .next()
+            .unwrap_or(&activity.start_time)
+            .to_string();
+
+        let request = TimeEntryRequest {
+            project_id,
+            task_id,
+            spent_date,
+            hours,
+            notes: format!("{} - {}", ticket_id, entry_comment(activity)),
+        };
+
+        let url = format!("{}/time_entries", API_BASE);
+        let response = self
+            .request(self.client.post(&url))
+            .await?
+            .json(&request)
+            .send()
+            .await
+            .map_err(SyncError::network)?;
+
+        if response.status().is_success() {
+            let result: TimeEntryResponse = response
+                .json()
+                .await
+                .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+            Ok(SyncResult {
+                success: true,
+                message: format!("Created time entry #{} ({:.2}h)", result.id, hours),
+                external_id: Some(result.id.to_string()),
+            })
+        } else {
+            let status = response.status();
+            if status == reqwest::StatusCode::UNAUTHORIZED {
+                // 失効したアクセストークンは次回取得し直す
+                *self.access_token.lock() = None;
+            }
+            let body = response.text().await.unwrap_or_default();
+            Err(format!("Harvest API error ({}): {}", status, body).into())
+        }
+    }
+
+    async fn test_connection(&self) -> Result<bool, String> {
+        let url = format!("{}/users/me", API_BASE);
+
+        let response = self
+            .request(self.client.get(&url))
+            .await?
+            .send()
+            .await
+            .map_err(|e| format!("Network error: {}", e))?;
+
+        if response.status().is_success() {
+            let result: UserResponse = response
+                .json()
+                .await
+                .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+            println!(
+                "Connected to Harvest as: {} (id: {})",
+                result.email, result.id
+            );
+            Ok(true)
+        } else {
+            Err(format!("Authentication failed: {}", response.status()))
+        }
+    }
+}
@@ -0,0 +1,301 @@
+use async_trait::async_trait;
+use chrono::{Local, NaiveDateTime, TimeZone};
+use serde::{Deserialize, Serialize};
+
+use super::{compile_rules, entry_comment, extract_with_rules, CompiledRule};
+use crate::plugins::config::JiraConfig;
+use crate::plugins::http::{HttpClient, RequestBuilder};
+use crate::plugins::traits::{
+    ActivityInfo, ExternalIntegration, IssueSummary, SyncError, SyncResult,
+};
+
+/// Jira API: ワークログ作成リクエスト
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct WorklogRequest {
+    time_spent_seconds: i64,
+    /// e.g., "2024-01-15T09:30:00.000+0900"
+    started: String,
+    comment: String,
+}
+
+/// Jira API: ワークログ作成レスポンス
+#[derive(Debug, Deserialize)]
+struct WorklogResponse {
+    id: String,
+}
+
+/// Jira API: ユーザー情報（接続テスト用）
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MyselfResponse {
+    account_id: Option<String>,
+    display_name: String,
+}
+
+/// Jira API: 課題（見積もり取得用）
+#[derive(Debug, Deserialize)]
+struct IssueResponse {
+    fields: IssueEstimateFields,
+}
+
+#[derive(Debug, Deserialize)]
+struct IssueEstimateFields {
+    /// 初期見積もり（秒）
+    timeoriginalestimate: Option<i64>,
+}
+
+/// Jira API: 課題検索
+#[derive(Debug, Deserialize)]
+struct SearchResponse {
+    issues: Vec<SearchIssue>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchIssue {
+    key: String,
+    fields: SearchIssueFields,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchIssueFields {
+    summary: String,
+    project: Option<NamedRef>,
+    status: Option<NamedRef>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NamedRef {
+    name: String,
+}
+
+/// Jira連携プラグイン
+pub struct JiraIntegration {
+    name: String,
+    enabled: bool,
+    config: JiraConfig,
+    client: HttpClient,
+    rules: Vec<CompiledRule>,
+}
+
+impl JiraIntegration {
+    pub fn new(name: String, enabled: bool, config: JiraConfig) -> Result<Self, String> {
+        let client = HttpClient::new();
+        let rules = compile_rules(&config.rules);
+
+        Ok(Self {
+            name,
+            enabled,
+            config,
+            client,
+            rules,
+        })
+    }
+
+    fn api_url(&self, path: &str) -> String {
+        format!(
+            "{}/rest/api/2/{}",
+            self.config.url.trim_end_matches('/'),
+            path
+        )
+    }
+
+    fn get(&self, url: &str) -> RequestBuilder {
+        self.client
+            .get(url)
+            .basic_auth(&self.config.email, Some(&self.config.api_token))
+            .header("Accept", "application/json")
+    }
+
+    /// 記録の開始時刻をJiraの形式（タイムゾーン付き）に変換
+    fn started(activity: &ActivityInfo) -> Result<String, String> {
+        let naive = NaiveDateTime::parse_from_str(&activity.start_time, "%Y-%m-%dT%H:%M:%S")
+            .map_err(|e| format!("Invalid start time {}: {}", activity.start_time, e))?;
+        let local = Local
+            .from_local_datetime(&naive)
+            .earliest()
+            .ok_or_else(|| format!("Invalid local time: {}", activity.start_time))?;
+        Ok(local.format("%Y-%m-%dT%H:%M:%S%.3f%z").to_string())
+    }
+}
+
+#[async_trait]
+impl ExternalIntegration for JiraIntegration {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn display_name(&self) -> &str {
+        "Jira"
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn extract_ticket_id(&self, activity: &ActivityInfo) -> Option<String> {
+        extract_with_rules(&self.rules, activity)
+    }
+
+    async fn sync_time_entry(
+        &self,
+        activity: &ActivityInfo,
+        ticket_id: &str,
+    ) -> Result<SyncResult, SyncError> {
+        let request = WorklogRequest {
+            // Jira は1分未満のワークログを受け付けない
+            time_spent_seconds: activity.duration_seconds.max(60),
+            started: Self::started(activity)?,
+            comment: entry_comment(activity),
+        };
+
+        let url = self.api_url(&format!("issue/{}/worklog", ticket_id));
+
+        let response = self
+            .client
+            .post(&url)
+            .basic_auth(&self.config.email, Some(&self.config.api_token))
+            .header("Content-Type", "application/json")
+            .json(&request)
+            .send()
+            .await
+            .map_err(SyncError::network)?;
+
+        if response.status().is_success() {
+            let result: WorklogResponse = response
+                .json()
+                .await
+                .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+            Ok(SyncResult {
+                success: true,
+                message: format!("Created worklog {} on {}", result.id, ticket_id),
+                external_id: Some(result.id),
+            })
+        } else {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            Err(format!("Jira API error ({}): {}", status, body).into())
+        }
+    }
+
+    async fn test_connection(&self) -> Result<bool, String> {
+        let response = self
+            .get(&self.api_url("myself"))
+            .send()
+            .await
+            .map_err(|e| format!("Network error: {}", e))?;
+
+        if response.status().is_success() {
+            let result: MyselfResponse = response
+                .json()
+                .await
+                .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+            println!(
+                "Connected to Jira as: {} (account: {})",
+                result.display_name,
+                result.account_id.unwrap_or_default()
+            );
+            Ok(true)
+        } else {
+            Err(format!("Authentication failed: {}", response.status()))
+        }
+    }
+
+    async fn get_ticket_estimate(&self, ticket_id: &str) -> Result<Option<f64>, String> {
+        let url = self.api_url(&format!("issue/{}?fields=timeoriginalestimate", ticket_id));
+
+        let response = self
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| format!("Network error: {}", e))?;
+
+        if response.status().is_success() {
+            let result: IssueResponse = response
+                .json()
+                .await
+                .map_err(|e| format!("Failed to parse response: {}", e))?;
+            Ok(result
+                .fields
+                .timeoriginalestimate
+                .map(|seconds| seconds as f64 / 3600.0))
+        } else {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            Err(format!("Jira API error ({}): {}", status, body))
+        }
+    }
+
+    async fn get_ticket_info(&self, ticket_id: &str) -> Result<Option<IssueSummary>, String> {
+        let url = self.api_url(&format!(
+            "issue/{}?fields=summary,project,status",
+            ticket_id
+        ));
+
+        let response = self
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| format!("Network error: {}", e))?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if response.status().is_success() {
+            let issue: SearchIssue = response
+                .json()
+                .await
+                .map_err(|e| format!("Failed to parse response: {}", e))?;
+            Ok(Some(IssueSummary {
+                id: issue.key,
+                subject: issue.fields.summary,
+                project: issue.fields.project.map(|p| p.name),
+                status: issue.fields.status.map(|s| s.name),
+            }))
+        } else {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            Err(format!("Jira API error ({}): {}", status, body))
+        }
+    }
+
+    async fn list_my_issues(&self) -> Result<Vec<IssueSummary>, String> {
+        let response = self
+            .get(&self.api_url("search"))
+            .query(&[
+                (
+                    "jql",
+                    "assignee = currentUser() AND resolution = Unresolved ORDER BY updated DESC",
+                ),
+                ("fields", "summary,project,status"),
+                ("maxResults", "100"),
+            ])
+            .send()
+            .await
+            .map_err(|e| format!("Network error: {}", e))?;
+
+        if response.status().is_success() {
+            let result: SearchResponse = response
+                .json()
+                .await
+                .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+            Ok(result
+                .issues
+                .into_iter()
+                .map(|issue| IssueSummary {
+                    id: issue.key,
+                    subject: issue.fields.summary,
+                    project: issue.fields.project.map(|p| p.name),
+                    status: issue.fields.status.map(|s| s.name),
+                })
+                .collect())
+        } else {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            Err(format!("Jira API error ({}): {}", status, body))
+        }
+    }
+}
@@ -0,0 +1,204 @@
+pub mod asana;
+pub mod calendar;
+pub mod clockify;
+pub mod exec;
+pub mod generic_http;
+pub mod github;
+pub mod gitlab;
+pub mod harvest;
+pub mod jira;
+pub mod notion;
+pub mod redmine;
+pub mod slack;
+pub mod toggl;
+pub mod trello;
+
+pub use asana::AsanaIntegration;
+pub use clockify::ClockifyIntegration;
+pub use exec::ExecIntegration;
+pub use generic_http::GenericHttpIntegration;
+pub use github::GitHubIntegration;
+pub use gitlab::GitLabIntegration;
+pub use harvest::HarvestIntegration;
+pub use jira::JiraIntegration;
+pub use notion::NotionIntegration;
+pub use redmine::RedmineIntegration;
+pub use slack::SlackIntegration;
+pub use toggl::TogglIntegration;
+pub use trello::TrelloIntegration;
+
+use regex::{Captures, Regex};
+
+use crate::plugins::config::{CommentConfig, ExtractionRule};
+use crate::plugins::traits::ActivityInfo;
+
+/// コンパイル済みの抽出ルール
+pub(crate) struct CompiledRule {
+    pub regex: Regex,
+    pub sources: Vec<String>,
+    pub template: Option<String>,
+}
+
+impl CompiledRule {
+    pub fn new(rule: &ExtractionRule) -> Result<Self, regex::Error> {
+        Ok(Self {
+            regex: Regex::new(&rule.pattern)?,
+            sources: rule.sources().into_iter().map(String::from).collect(),
+            template: rule.template.clone(),
+        })
+    }
+
+    /// 抽出元を順に試し、最初にマッチした抽出元とキャプチャを返す
+    pub fn find<'a>(&self, activity: &'a ActivityInfo) -> Option<(&str, Captures<'a>)> {
+        self.sources.iter().find_map(|source| {
+            self.regex
+                .captures(source_text(activity, source))
+                .map(|captures| (source.as_str(), captures))
+        })
+    }
+
+    /// 抽出元を順に試し、最初にマッチしたもののキャプチャを返す
+    pub fn captures<'a>(&self, activity: &'a ActivityInfo) -> Option<Captures<'a>> {
+        self.find(activity).map(|(_, captures)| captures)
+    }
+
+    /// キャプチャからチケットIDを組み立てる（template があれば展開、なければ1番目のグループ）
+    pub fn ticket_id(&self, captures: &Captures) -> Option<String> {
+        let id = match &self.template {
+            Some(template) => {
+                let mut id = String::new();
+                captures.expand(template, &mut id);
+                id
+            }
+            None => captures.get(1)?.as_str().to_string(),
+        };
+        let id = id.trim();
+        (!id.is_empty()).then(|| id.to_string())
+    }
+}
+
+/// 抽出ルールをコンパイル（コンパイルできないパターンは無視）
+pub(crate) fn compile_rules(rules: &[ExtractionRule]) -> Vec<CompiledRule> {
+    rules
+        .iter()
+        .filter_map(|rule| CompiledRule::new(rule).ok())
+        .collect()
+}
+
+/// 抽出元のテキストを取得
+pub(crate) fn source_text<'a>(activity: &'a ActivityInfo, source: &str) -> &'a str {
+    match source {
+        "window_title" => &activity.window_title,
+        "process_name" => &activity.process_name,
+        "domain" => activity.domain.as_deref().unwrap_or(""),
+        _ => &activity.window_title,
+    }
+}
+
+/// タイムエントリのコメント（メモがあればメモ、なければアプリ名とタイトル）
+pub(crate) fn entry_comment(activity: &ActivityInfo) -> String {
+    match activity.note.as_deref() {
+        Some(note) if !note.trim().is_empty() => note.to_string(),
+        _ if activity.window_title.is_empty() => activity.process_name.clone(),
+        _ => format!("{} - {}", activity.process_name, activity.window_title),
+    }
+}
+
+/// 連携の設定に沿ってコメントを組み立てる
+///
+/// {{note}} はアクティビティのメモのみ（なければ空）。組み立てた結果が空なら既定のコメント（アプリ名とタイトル）にする。
+/// omit_titles ならタイトルは空として扱う。埋め込んだ値の中の {{...}} は展開しない。
+pub fn render_comment(config: &CommentConfig, activity: &ActivityInfo) -> String {
+    let title = if config.omit_titles {
+        ""
+    } else {
+        activity.window_title.as_str()
+    };
+    let fallback = entry_comment(&ActivityInfo {
+        window_title: title.to_string(),
+        ..activity.clone()
+    });
+
+    let Some(template) = config.template.as_deref() else {
+        return fallback;
+    };
+    let rendered = substitute(template, |key| {
+        let value = match key {
+            "title" => title,
+            "process" => activity.process_name.as_str(),
+            "domain" => activity.domain.as_deref().unwrap_or(""),
+            "project" => activity.project.as_deref().unwrap_or(""),
+            "category" => activity.category.as_deref().unwrap_or(""),
+            "note" => activity.note.as_deref().unwrap_or(""),
+            "duration_hm" => return Some(format_duration(activity.duration_seconds)),
+            _ => return None,
+        };
+        Some(value.to_string())
+    });
+
+    let rendered = rendered.trim();
+    if rendered.is_empty() {
+        fallback
+    } else {
+        rendered.to_string()
+    }
+}
+
+/// テンプレートの {{key}} を1回の走査で置き換える（lookup が None のものはそのまま残す）
+///
+/// 埋め込んだ値に含まれる {{...}} は展開しないので、タイトルなどから秘密の値を引き出されない。
+pub(crate) fn substitute(template: &str, lookup: impl Fn(&str) -> Option<String>) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        rendered.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let replaced = after
+            .find("}}")
+            .and_then(|end| Some((end, lookup(&after[..end])?)));
+        match replaced {
+            Some((end, value)) => {
+                rendered.push_str(&value);
+                rest = &after[end + 2..];
+            }
+            None => {
+                rendered.push_str("{{");
+                rest = after;
+            }
+        }
+    }
+    rendered.push_str(rest);
+    rendered
+}
+
+/// 秒数を "1h 23m" 形式に整形
+pub(crate) fn format_duration(seconds: i64) -> String {
+    let minutes = (seconds + 30) / 60;
+    match (minutes / 60, minutes % 60) {
+        (0, m) => format!("{}m", m),
+        (h, 0) => format!("{}h", h),
+        (h, m) => format!("{}h {}m", h, m),
+    }
+}
+
+/// 作業時間を追記するコメントの本文
+pub(crate) fn spent_comment(activity: &ActivityInfo) -> String {
+    format!(
+        "Spent {} ({})\n\n{} → {}",
+        format_duration(activity.duration_seconds),
+        entry_comment(activity),
+        activity.start_time,
+        activity.end_time
+    )
+}
+
+/// 最初にマッチしたルールでチケットIDを組み立てる
+pub(crate) fn extract_with_rules(
+    rules: &[CompiledRule],
+    activity: &ActivityInfo,
+) -> Option<String> {
+    rules.iter().find_map(|rule| {
+        let captures = rule.captures(activity)?;
+        rule.ticket_id(&captures)
+    })
+}
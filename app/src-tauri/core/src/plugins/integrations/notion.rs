@@ -0,0 +1,189 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+
+use super::{compile_rules, entry_comment, extract_with_rules, CompiledRule};
+use crate::plugins::config::NotionConfig;
+use crate::plugins::http::{HttpClient, RequestBuilder};
+use crate::plugins::traits::{ActivityInfo, ExternalIntegration, SyncError, SyncResult};
+use crate::secrets;
+
+const API_BASE: &str = "https://api.notion.com/v1";
+const NOTION_VERSION: &str = "2022-06-28";
+/// テキスト型のプロパティの1要素の上限
+const MAX_TEXT_LENGTH: usize = 2000;
+
+/// Notion API: 作成したページ
+#[derive(Debug, Deserialize)]
+struct PageResponse {
+    id: String,
+}
+
+/// Notion API: データベース（接続テスト用）
+#[derive(Debug, Deserialize)]
+struct DatabaseResponse {
+    id: String,
+    #[serde(default)]
+    title: Vec<RichText>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RichText {
+    #[serde(default)]
+    plain_text: String,
+}
+
+/// Notion連携プラグイン
+pub struct NotionIntegration {
+    name: String,
+    enabled: bool,
+    config: NotionConfig,
+    client: HttpClient,
+    rules: Vec<CompiledRule>,
+}
+
+impl NotionIntegration {
+    pub fn new(name: String, enabled: bool, mut config: NotionConfig) -> Result<Self, String> {
+        let client = HttpClient::new();
+
+        // キーリングのトークンを優先する
+        if let Some(alias) = &config.token_secret {
+            config.token = secrets::get_integration(alias)?
+                .ok_or_else(|| format!("Token not found in the OS keyring: {}", alias))?;
+        }
+
+        let rules = compile_rules(&config.rules);
+
+        Ok(Self {
+            name,
+            enabled,
+            config,
+            client,
+            rules,
+        })
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> RequestBuilder {
+        self.client
+            .request(method, format!("{}/{}", API_BASE, path))
+            .bearer_auth(&self.config.token)
+            .header("Notion-Version", NOTION_VERSION)
+    }
+
+    /// データベースの行のプロパティ（列名が空のものは含めない）
+    fn properties(&self, activity: &ActivityInfo, project: &str) -> Map<String, Value> {
+        let columns = &self.config.columns;
+        let date = activity
+            .start_time
+            .split('T')
+            .next()
+            .unwrap_or(&activity.start_time);
+        let hours = (activity.duration_seconds as f64 / 3600.0 * 100.0).round() / 100.0;
+
+        let mut properties = Map::new();
+        let mut insert = |column: &str, value: Value| {
+            if !column.is_empty() {
+                properties.insert(column.to_string(), value);
+            }
+        };
+        insert(&columns.project, json!({ "title": text(project) }));
+        insert(&columns.date, json!({ "date": { "start": date } }));
+        insert(
+            &columns.app,
+            json!({ "rich_text": text(&activity.process_name) }),
+        );
+        insert(&columns.duration, json!({ "number": hours }));
+        insert(
+            &columns.notes,
+            json!({ "rich_text": text(&entry_comment(activity)) }),
+        );
+        properties
+    }
+}
+
+/// リッチテキストの値（上限を超える分は切り捨てる）
+fn text(content: &str) -> Value {
+    let content: String = content.chars().take(MAX_TEXT_LENGTH).collect();
+    json!([{ "type": "text", "text": { "content": content } }])
+}
+
+#[async_trait]
+impl ExternalIntegration for NotionIntegration {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn display_name(&self) -> &str {
+        "Notion"
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn extract_ticket_id(&self, activity: &ActivityInfo) -> Option<String> {
+        extract_with_rules(&self.rules, activity).or_else(|| self.config.default_project.clone())
+    }
+
+    async fn sync_time_entry(
+        &self,
+        activity: &ActivityInfo,
+        ticket_id: &str,
+    ) -> Result<SyncResult, SyncError> {
+        let body = json!({
+            "parent": { "database_id": self.config.database_id },
+            "properties": self.properties(activity, ticket_id),
+        });
+
+        let response = self
+            .request(reqwest::Method::POST, "pages")
+            .json(&body)
+            .send()
+            .await
+            .map_err(SyncError::network)?;
+
+        if response.status().is_success() {
+            let result: PageResponse = response
+                .json()
+                .await
+                .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+            Ok(SyncResult {
+                success: true,
+                message: format!("Added row to Notion database for {}", ticket_id),
+                external_id: Some(result.id),
+            })
+        } else {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            Err(format!("Notion API error ({}): {}", status, body).into())
+        }
+    }
+
+    async fn test_connection(&self) -> Result<bool, String> {
+        let response = self
+            .request(
+                reqwest::Method::GET,
+                &format!("databases/{}", self.config.database_id),
+            )
+            .send()
+            .await
+            .map_err(|e| format!("Network error: {}", e))?;
+
+        if response.status().is_success() {
+            let result: DatabaseResponse = response
+                .json()
+                .await
+                .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+            let title: String = result.title.iter().map(|t| t.plain_text.as_str()).collect();
+            println!(
+                "Connected to Notion database: {} (id: {})",
+                title, result.id
+            );
+            Ok(true)
+        } else {
+            Err(format!("Authentication failed: {}", response.status()))
+        }
+    }
+}
@@ -0,0 +1,319 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use super::{compile_rules, entry_comment, extract_with_rules, CompiledRule};
+use crate::plugins::config::RedmineConfig;
+use crate::plugins::http::HttpClient;
+use crate::plugins::traits::{
+    ActivityInfo, ExternalIntegration, IssueSummary, SyncError, SyncResult,
+};
+use crate::secrets;
+
+/// Redmine API: タイムエントリ作成リクエスト
+#[derive(Debug, Serialize)]
+struct TimeEntryRequest {
+    time_entry: TimeEntryData,
+}
+
+#[derive(Debug, Serialize)]
+struct TimeEntryData {
+    issue_id: i64,
+    hours: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    activity_id: Option<i64>,
+    comments: String,
+    spent_on: String,
+}
+
+/// Redmine API: タイムエントリ作成レスポンス
+#[derive(Debug, Deserialize)]
+struct TimeEntryResponse {
+    time_entry: TimeEntryInfo,
+}
+
+#[derive(Debug, Deserialize)]
+struct TimeEntryInfo {
+    id: i64,
+}
+
+/// Redmine API: ユーザー情報（接続テスト用）
+#[derive(Debug, Deserialize)]
+struct CurrentUserResponse {
+    user: UserInfo,
+}
+
+#[derive(Debug, Deserialize)]
+struct UserInfo {
+    id: i64,
+    login: String,
+}
+
+/// Redmine API: チケット情報
+#[derive(Debug, Deserialize)]
+struct IssueResponse {
+    issue: IssueInfo,
+}
+
+#[derive(Debug, Deserialize)]
+struct IssueInfo {
+    id: i64,
+    subject: String,
+    project: Option<NamedRef>,
+    status: Option<NamedRef>,
+    estimated_hours: Option<f64>,
+}
+
+/// Redmine API: チケット一覧
+#[derive(Debug, Deserialize)]
+struct IssuesResponse {
+    issues: Vec<IssueListItem>,
+}
+
+#[derive(Debug, Deserialize)]
+struct IssueListItem {
+    id: i64,
+    subject: String,
+    project: Option<NamedRef>,
+    status: Option<NamedRef>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NamedRef {
+    name: String,
+}
+
+/// Redmine連携プラグイン
+pub struct RedmineIntegration {
+    name: String,
+    enabled: bool,
+    config: RedmineConfig,
+    client: HttpClient,
+    rules: Vec<CompiledRule>,
+}
+
+impl RedmineIntegration {
+    pub fn new(name: String, enabled: bool, mut config: RedmineConfig) -> Result<Self, String> {
+        let client = HttpClient::new();
+
+        // キーリングのAPIキーを優先する
+        if let Some(alias) = &config.api_key_secret {
+            config.api_key = secrets::get_integration(alias)?
+                .ok_or_else(|| format!("API key not found in the OS keyring: {}", alias))?;
+        }
+
+        // 抽出ルールをコンパイル
+        let rules = compile_rules(&config.rules);
+
+        Ok(Self {
+            name,
+            enabled,
+            config,
+            client,
+            rules,
+        })
+    }
+
+    /// チケットを取得（存在しなければ None）
+    async fn fetch_issue(&self, ticket_id: &str) -> Result<Option<IssueInfo>, String> {
+        let issue_id: i64 = ticket_id
+            .parse()
+            .map_err(|_| format!("Invalid ticket ID: {}", ticket_id))?;
+
+        let url = format!(
+            "{}/issues/{}.json",
+            self.config.url.trim_end_matches('/'),
+            issue_id
+        );
+
+        let response = self
+            .client
+            .get(&url)
+            .header("X-Redmine-API-Key", &self.config.api_key)
+            .send()
+            .await
+            .map_err(|e| format!("Network error: {}", e))?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if response.status().is_success() {
+            let result: IssueResponse = response
+                .json()
+                .await
+                .map_err(|e| format!("Failed to parse response: {}", e))?;
+            Ok(Some(result.issue))
+        } else {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            Err(format!("Redmine API error ({}): {}", status, body))
+        }
+    }
+}
+
+#[async_trait]
+impl ExternalIntegration for RedmineIntegration {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn display_name(&self) -> &str {
+        "Redmine"
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn extract_ticket_id(&self, activity: &ActivityInfo) -> Option<String> {
+        extract_with_rules(&self.rules, activity)
+    }
+
+    async fn sync_time_entry(
+        &self,
+        activity: &ActivityInfo,
+        ticket_id: &str,
+    ) -> Result<SyncResult, SyncError> {
+        let issue_id: i64 = ticket_id
+            .parse()
+            .map_err(|_| format!("Invalid ticket ID: {}", ticket_id))?;
+
+        // 時間を時間単位に変換（秒 → 時）
+        let hours = activity.duration_seconds as f64 / 3600.0;
+
+        // 日付を抽出（YYYY-MM-DD形式）
+        let spent_on = activity
+            .start_time
+            .split('T')
+            .next()
+            .unwrap_or("")
+            .to_string();
+
+        let request = TimeEntryRequest {
+            time_entry: TimeEntryData {
+                issue_id,
+                hours,
+                activity_id: self.config.default_activity_id,
+                comments: entry_comment(activity),
+                spent_on,
+            },
+        };
+
+        let url = format!(
+            "{}/time_entries.json",
+            self.config.url.trim_end_matches('/')
+        );
+
+        let response = self
+            .client
+            .post(&url)
+            .header("X-Redmine-API-Key", &self.config.api_key)
+            .header("Content-Type", "application/json")
+            .json(&request)
+            .send()
+            .await
+            .map_err(SyncError::network)?;
+
+        if response.status().is_success() {
+            let result: TimeEntryResponse = response
+                .json()
+                .await
+                .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+            Ok(SyncResult {
+                success: true,
+                message: format!("Created time entry #{}", result.time_entry.id),
+                external_id: Some(result.time_entry.id.to_string()),
+            })
+        } else {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            Err(format!("Redmine API error ({}): {}", status, body).into())
+        }
+    }
+
+    async fn test_connection(&self) -> Result<bool, String> {
+        let url = format!(
+            "{}/users/current.json",
+            self.config.url.trim_end_matches('/')
+        );
+
+        let response = self
+            .client
+            .get(&url)
+            .header("X-Redmine-API-Key", &self.config.api_key)
+            .send()
+            .await
+            .map_err(|e| format!("Network error: {}", e))?;
+
+        if response.status().is_success() {
+            let result: CurrentUserResponse = response
+                .json()
+                .await
+                .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+            println!(
+                "Connected to Redmine as: {} (id: {})",
+                result.user.login, result.user.id
+            );
+            Ok(true)
+        } else {
+            Err(format!("Authentication failed: {}", response.status()))
+        }
+    }
+
+    async fn get_ticket_estimate(&self, ticket_id: &str) -> Result<Option<f64>, String> {
+        Ok(self
+            .fetch_issue(ticket_id)
+            .await?
+            .and_then(|issue| issue.estimated_hours))
+    }
+
+    async fn get_ticket_info(&self, ticket_id: &str) -> Result<Option<IssueSummary>, String> {
+        Ok(self
+            .fetch_issue(ticket_id)
+            .await?
+            .map(|issue| IssueSummary {
+                id: issue.id.to_string(),
+                subject: issue.subject,
+                project: issue.project.map(|p| p.name),
+                status: issue.status.map(|s| s.name),
+            }))
+    }
+
+    async fn list_my_issues(&self) -> Result<Vec<IssueSummary>, String> {
+        let url = format!(
+            "{}/issues.json?assigned_to_id=me&status_id=open&sort=updated_on:desc&limit=100",
+            self.config.url.trim_end_matches('/')
+        );
+
+        let response = self
+            .client
+            .get(&url)
+            .header("X-Redmine-API-Key", &self.config.api_key)
+            .send()
+            .await
+            .map_err(|e| format!("Network error: {}", e))?;
+
+        if response.status().is_success() {
+            let result: IssuesResponse = response
+                .json()
+                .await
+                .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+            Ok(result
+                .issues
+                .into_iter()
+                .map(|issue| IssueSummary {
+                    id: issue.id.to_string(),
+                    subject: issue.subject,
+                    project: issue.project.map(|p| p.name),
+                    status: issue.status.map(|s| s.name),
+                })
+                .collect())
+        } else {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            Err(format!("Redmine API error ({}): {}", status, body))
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use serde::{Deserialize, Serialize};
+
+use crate::plugins::config::{SlackConfig, SlackStatus};
+use crate::plugins::http::HttpClient;
+use crate::secrets;
+
+const API_BASE: &str = "https://slack.com/api";
+
+/// Slack API: users.profile.set リクエスト
+#[derive(Debug, Serialize)]
+struct ProfileRequest<'a> {
+    profile: Profile<'a>,
+}
+
+#[derive(Debug, Serialize)]
+struct Profile<'a> {
+    status_text: &'a str,
+    status_emoji: &'a str,
+    status_expiration: i64,
+}
+
+/// Slack API: chat.postMessage リクエスト
+#[derive(Debug, Serialize)]
+struct MessageRequest<'a> {
+    channel: &'a str,
+    text: &'a str,
+}
+
+/// Slack API の共通レスポンス（HTTP 200 でも ok=false ならエラー）
+#[derive(Debug, Deserialize)]
+struct ApiResponse {
+    ok: bool,
+    #[serde(default)]
+    error: Option<String>,
+    /// auth.test のみ
+    #[serde(default)]
+    user: Option<String>,
+    #[serde(default)]
+    team: Option<String>,
+}
+
+/// Slack連携（ステータス・おやすみモード・メッセージの投稿）
+///
+/// タイムエントリの同期先ではないので ExternalIntegration は実装しない。
+pub struct SlackIntegration {
+    config: SlackConfig,
+    client: HttpClient,
+}
+
+impl SlackIntegration {
+    pub fn new(mut config: SlackConfig) -> Result<Self, String> {
+        // キーリングのトークンを優先する
+        if let Some(alias) = &config.token_secret {
+            config.token = secrets::get_integration(alias)?
+                .ok_or_else(|| format!("Slack token not found in the OS keyring: {}", alias))?;
+        }
+
+        Ok(Self {
+            config,
+            client: HttpClient::new(),
+        })
+    }
+
+    pub fn config(&self) -> &SlackConfig {
+        &self.config
+    }
+
+    /// カテゴリに対応するステータス（マッピングにないカテゴリは default_status）
+    pub fn status_for(&self, category: Option<&str>) -> Option<&SlackStatus> {
+        category
+            .and_then(|category| self.config.statuses.get(category))
+            .or(self.config.default_status.as_ref())
+    }
+
+    /// ステータスを設定（None ならステータスを消す）
+    pub async fn set_status(&self, status: Option<&SlackStatus>) -> Result<(), String> {
+        let request = ProfileRequest {
+            profile: Profile {
+                status_text: status.map_or("", |s| s.text.as_str()),
+                status_emoji: status.map_or("", |s| s.emoji.as_str()),
+                status_expiration: 0,
+            },
+        };
+        self.call("users.profile.set", &request).await.map(|_| ())
+    }
+
+    /// おやすみモードを minutes 分間オンにする
+    pub async fn set_snooze(&self, minutes: i64) -> Result<(), String> {
+        self.call_form("dnd.setSnooze", &[("num_minutes", minutes.to_string())])
+            .await
+    }
+
+    /// おやすみモードを終了する
+    pub async fn end_snooze(&self) -> Result<(), String> {
+        self.call_form("dnd.endSnooze", &[]).await
+    }
+
+    /// メッセージを投稿
+    pub async fn post_message(&self, channel: &str, text: &str) -> Result<(), String> {
+        self.call("chat.postMessage", &MessageRequest { channel, text })
+            .await
+            .map(|_| ())
+    }
+
+    /// 接続テスト（ワークスペースとユーザー名を返す）
+    pub async fn test_connection(&self) -> Result<String, String> {
+        let response = self.call("auth.test", &serde_json::json!({})).await?;
+        Ok(format!(
+            "{} ({})",
+            response.user.unwrap_or_default(),
+            response.team.unwrap_or_default()
+        ))
+    }
+
+    async fn call<T: Serialize>(&self, method: &str, body: &T) -> Result<ApiResponse, String> {
+        let response = self
+            .client
+            .post(format!("{}/{}", API_BASE, method))
+            .bearer_auth(&self.config.token)
+            .json(body)
+            .send()
+            .await
+            .map_err(|e| format!("Network error: {}", e))?;
+        Self::parse(method, response).await
+    }
+
+    /// dnd.* は JSON の本文を受け付けないのでフォームで送る
+    async fn call_form(&self, method: &str, form: &[(&str, String)]) -> Result<(), String> {
+        let response = self
+            .client
+            .post(format!("{}/{}", API_BASE, method))
+            .bearer_auth(&self.config.token)
+            .form(form)
+            .send()
+            .await
+            .map_err(|e| format!("Network error: {}", e))?;
+        Self::parse(method, response).await.map(|_| ())
+    }
+
+    async fn parse(method: &str, response: reqwest::Response) -> Result<ApiResponse, String> {
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(format!("Slack API error ({}): {}", status, body));
+        }
+        let result: ApiResponse = response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse response: {}", e))?;
+        if !result.ok {
+            return Err(format!(
+                "Slack {} failed: {}",
+                method,
+                result.error.as_deref().unwrap_or("unknown error")
+            ));
+        }
+        Ok(result)
+    }
+}
@@ -0,0 +1,187 @@
+use async_trait::async_trait;
+use chrono::{Local, NaiveDateTime, SecondsFormat, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{compile_rules, extract_with_rules, CompiledRule};
+use crate::plugins::config::TogglConfig;
+use crate::plugins::http::HttpClient;
+use crate::plugins::traits::{ActivityInfo, ExternalIntegration, SyncError, SyncResult};
+
+const API_BASE: &str = "https://api.track.toggl.com/api/v9";
+
+/// Toggl API: タイムエントリ作成リクエスト
+#[derive(Debug, Serialize)]
+struct TimeEntryRequest {
+    created_with: &'static str,
+    description: String,
+    workspace_id: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    project_id: Option<i64>,
+    tags: Vec<String>,
+    billable: bool,
+    /// RFC3339 (UTC)
+    start: String,
+    duration: i64,
+}
+
+/// Toggl API: タイムエントリ作成レスポンス
+#[derive(Debug, Deserialize)]
+struct TimeEntryResponse {
+    id: i64,
+}
+
+/// Toggl API: ユーザー情報（接続テスト用）
+#[derive(Debug, Deserialize)]
+struct MeResponse {
+    id: i64,
+    email: String,
+}
+
+/// Toggl Track連携プラグイン
+pub struct TogglIntegration {
+    name: String,
+    enabled: bool,
+    config: TogglConfig,
+    client: HttpClient,
+    rules: Vec<CompiledRule>,
+}
+
+impl TogglIntegration {
+    pub fn new(name: String, enabled: bool, config: TogglConfig) -> Result<Self, String> {
+        let client = HttpClient::new();
+        let rules = compile_rules(&config.rules);
+
+        Ok(Self {
+            name,
+            enabled,
+            config,
+            client,
+            rules,
+        })
+    }
+
+    /// チケットIDからTogglのプロジェクトIDを解決
+    ///
+    /// projects のマッピング → 数値ならそのままプロジェクトID → default_project_id の順。
+    fn resolve_project(&self, ticket_id: &str) -> Option<i64> {
+        self.config
+            .projects
+            .get(ticket_id)
+            .copied()
+            .or_else(|| ticket_id.parse().ok())
+            .or(self.config.default_project_id)
+    }
+
+    /// ローカル時刻の記録開始時刻をUTCのRFC3339に変換
+    fn start_utc(activity: &ActivityInfo) -> Result<String, String> {
+        let naive = NaiveDateTime::parse_from_str(&activity.start_time, "%Y-%m-%dT%H:%M:%S")
+            .map_err(|e| format!("Invalid start time {}: {}", activity.start_time, e))?;
+        let local = Local
+            .from_local_datetime(&naive)
+            .earliest()
+            .ok_or_else(|| format!("Invalid local time: {}", activity.start_time))?;
+        Ok(local
+            .with_timezone(&Utc)
+            .to_rfc3339_opts(SecondsFormat::Secs, true))
+    }
+}
+
+#[async_trait]
+impl ExternalIntegration for TogglIntegration {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn display_name(&self) -> &str {
+        "Toggl Track"
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn extract_ticket_id(&self, activity: &ActivityInfo) -> Option<String> {
+        extract_with_rules(&self.rules, activity)
+    }
+
+    async fn sync_time_entry(
+        &self,
+        activity: &ActivityInfo,
+        ticket_id: &str,
+    ) -> Result<SyncResult, SyncError> {
+        let project_id = self.resolve_project(ticket_id);
+
+        let request = TimeEntryRequest {
+            created_with: "timetracker",
+            description: format!(
+                "{} - {} - {}",
+                ticket_id, activity.process_name, activity.window_title
+            ),
+            workspace_id: self.config.workspace_id,
+            project_id,
+            tags: self.config.tags.clone(),
+            billable: self.config.billable,
+            start: Self::start_utc(activity)?,
+            duration: activity.duration_seconds,
+        };
+
+        let url = format!(
+            "{}/workspaces/{}/time_entries",
+            API_BASE, self.config.workspace_id
+        );
+
+        let response = self
+            .client
+            .post(&url)
+            .basic_auth(&self.config.api_token, Some("api_token"))
+            .header("Content-Type", "application/json")
+            .json(&request)
+            .send()
+            .await
+            .map_err(SyncError::network)?;
+
+        if response.status().is_success() {
+            let result: TimeEntryResponse = response
+                .json()
+                .await
+                .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+            Ok(SyncResult {
+                success: true,
+                message: format!("Created time entry #{}", result.id),
+                external_id: Some(result.id.to_string()),
+            })
+        } else {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            Err(format!("Toggl API error ({}): {}", status, body).into())
+        }
+    }
+
+    async fn test_connection(&self) -> Result<bool, String> {
+        let url = format!("{}/me", API_BASE);
+
+        let response = self
+            .client
+            .get(&url)
+            .basic_auth(&self.config.api_token, Some("api_token"))
+            .send()
+            .await
+            .map_err(|e| format!("Network error: {}", e))?;
+
+        if response.status().is_success() {
+            let result: MeResponse = response
+                .json()
+                .await
+                .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+            println!(
+                "Connected to Toggl as: {} (id: {})",
+                result.email, result.id
+            );
+            Ok(true)
+        } else {
+            Err(format!("Authentication failed: {}", response.status()))
+        }
+    }
+}
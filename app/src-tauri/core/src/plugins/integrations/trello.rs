@@ -0,0 +1,301 @@
+use async_trait::async_trait;
+use regex::Regex;
+use serde::Deserialize;
+use serde_json::json;
+
+use super::{compile_rules, extract_with_rules, spent_comment, CompiledRule};
+use crate::plugins::config::TrelloConfig;
+use crate::plugins::http::{HttpClient, RequestBuilder};
+use crate::plugins::traits::{ActivityInfo, ExternalIntegration, SyncError, SyncResult};
+use crate::secrets;
+
+const API_BASE: &str = "https://api.trello.com/1";
+/// カード名で検索するチケットIDの接頭辞（"name:<カード名>"）
+const NAME_PREFIX: &str = "name:";
+
+/// Trello API: 作成されたアクション（コメント）
+#[derive(Debug, Deserialize)]
+struct ActionResponse {
+    id: String,
+}
+
+/// Trello API: カードの検索結果
+#[derive(Debug, Deserialize)]
+struct SearchResponse {
+    #[serde(default)]
+    cards: Vec<CardInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CardInfo {
+    name: String,
+    short_link: String,
+}
+
+/// Trello API: カードのカスタムフィールドの値（数値も文字列で返る）
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CustomFieldItem {
+    id_custom_field: String,
+    #[serde(default)]
+    value: Option<CustomFieldValue>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CustomFieldValue {
+    #[serde(default)]
+    number: Option<String>,
+}
+
+/// Trello API: ユーザー情報（接続テスト用）
+#[derive(Debug, Deserialize)]
+struct MemberResponse {
+    id: String,
+    username: String,
+}
+
+/// Trello連携プラグイン
+pub struct TrelloIntegration {
+    name: String,
+    enabled: bool,
+    config: TrelloConfig,
+    client: HttpClient,
+    rules: Vec<CompiledRule>,
+    /// ブラウザのタブのタイトル "カード名 | ボード名 | Trello"
+    title_pattern: Regex,
+}
+
+impl TrelloIntegration {
+    pub fn new(name: String, enabled: bool, mut config: TrelloConfig) -> Result<Self, String> {
+        let client = HttpClient::new();
+
+        // キーリングのトークンを優先する
+        if let Some(alias) = &config.token_secret {
+            config.token = secrets::get_integration(alias)?
+                .ok_or_else(|| format!("Token not found in the OS keyring: {}", alias))?;
+        }
+
+        let rules = compile_rules(&config.rules);
+        let title_pattern =
+            Regex::new(r"^(.+?) \| [^|]+ \| Trello").expect("Trello title pattern is valid");
+
+        Ok(Self {
+            name,
+            enabled,
+            config,
+            client,
+            rules,
+            title_pattern,
+        })
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> RequestBuilder {
+        self.client
+            .request(method, format!("{}/{}", API_BASE, path))
+            .header(
+                "Authorization",
+                format!(
+                    "OAuth oauth_consumer_key=\"{}\", oauth_token=\"{}\"",
+                    self.config.api_key, self.config.token
+                ),
+            )
+    }
+
+    async fn parse<T: for<'de> Deserialize<'de>>(response: reqwest::Response) -> Result<T, String> {
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(format!("Trello API error ({}): {}", status, body));
+        }
+        response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse response: {}", e))
+    }
+
+    /// チケットIDからカードのIDを取得（カード名は名前が一致するカードを検索）
+    async fn resolve_card(&self, ticket_id: &str) -> Result<String, SyncError> {
+        let Some(card_name) = ticket_id.strip_prefix(NAME_PREFIX) else {
+            return Ok(ticket_id.to_string());
+        };
+
+        let response = self
+            .request(reqwest::Method::GET, "search")
+            .query(&[
+                ("query", format!("name:\"{}\"", card_name)),
+                ("modelTypes", "cards".to_string()),
+                ("card_fields", "name,shortLink".to_string()),
+                ("cards_limit", "10".to_string()),
+            ])
+            .send()
+            .await
+            .map_err(SyncError::network)?;
+        let result: SearchResponse = Self::parse(response).await?;
+
+        let mut matches = result
+            .cards
+            .into_iter()
+            .filter(|card| card.name == card_name);
+        match (matches.next(), matches.next()) {
+            (Some(card), None) => Ok(card.short_link),
+            (Some(_), Some(_)) => Err(format!("Multiple Trello cards named {}", card_name).into()),
+            (None, _) => Err(format!("Trello card not found: {}", card_name).into()),
+        }
+    }
+
+    /// カードにコメントとして作業時間を追記
+    async fn post_comment(
+        &self,
+        card_id: &str,
+        activity: &ActivityInfo,
+    ) -> Result<String, SyncError> {
+        let response = self
+            .request(
+                reqwest::Method::POST,
+                &format!("cards/{}/actions/comments", card_id),
+            )
+            .query(&[("text", spent_comment(activity))])
+            .send()
+            .await
+            .map_err(SyncError::network)?;
+        let action: ActionResponse = Self::parse(response).await?;
+        Ok(action.id)
+    }
+
+    /// 数値カスタムフィールド（時間）に作業時間を加算し、更新後の値を返す
+    async fn add_to_custom_field(
+        &self,
+        card_id: &str,
+        field_id: &str,
+        hours: f64,
+    ) -> Result<f64, SyncError> {
+        let response = self
+            .request(
+                reqwest::Method::GET,
+                &format!("cards/{}/customFieldItems", card_id),
+            )
+            .send()
+            .await
+            .map_err(SyncError::network)?;
+        let items: Vec<CustomFieldItem> = Self::parse(response).await?;
+
+        // 未入力のフィールドは一覧に含まれない
+        let current = items
+            .iter()
+            .find(|item| item.id_custom_field == field_id)
+            .and_then(|item| item.value.as_ref())
+            .and_then(|value| value.number.as_deref())
+            .and_then(|number| number.parse::<f64>().ok())
+            .unwrap_or(0.0);
+        let total = ((current + hours) * 100.0).round() / 100.0;
+
+        let response = self
+            .request(
+                reqwest::Method::PUT,
+                &format!("cards/{}/customField/{}/item", card_id, field_id),
+            )
+            .json(&json!({ "value": { "number": total.to_string() } }))
+            .send()
+            .await
+            .map_err(SyncError::network)?;
+        let _: serde_json::Value = Self::parse(response).await?;
+
+        Ok(total)
+    }
+}
+
+#[async_trait]
+impl ExternalIntegration for TrelloIntegration {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn display_name(&self) -> &str {
+        "Trello"
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// URLの shortLink を優先し、trello.com のタブならタイトルのカード名を "name:<カード名>" として返す
+    fn extract_ticket_id(&self, activity: &ActivityInfo) -> Option<String> {
+        if let Some(id) = extract_with_rules(&self.rules, activity) {
+            return Some(id);
+        }
+        if !self.config.match_card_names {
+            return None;
+        }
+        let on_trello = activity
+            .domain
+            .as_deref()
+            .is_some_and(|domain| domain == "trello.com" || domain.ends_with(".trello.com"));
+        if !on_trello {
+            return None;
+        }
+        self.title_pattern
+            .captures(&activity.window_title)
+            .and_then(|captures| captures.get(1))
+            .map(|name| format!("{}{}", NAME_PREFIX, name.as_str()))
+    }
+
+    async fn sync_time_entry(
+        &self,
+        activity: &ActivityInfo,
+        ticket_id: &str,
+    ) -> Result<SyncResult, SyncError> {
+        let card_id = self.resolve_card(ticket_id).await?;
+        let mut messages = Vec::new();
+        let mut external_id = None;
+
+        if self.config.post_comments {
+            let action_id = self.post_comment(&card_id, activity).await?;
+            messages.push(format!("Added comment on card {}", card_id));
+            external_id = Some(action_id);
+        }
+
+        if let Some(field_id) = &self.config.custom_field_id {
+            let hours = activity.duration_seconds as f64 / 3600.0;
+            let total = self.add_to_custom_field(&card_id, field_id, hours).await?;
+            messages.push(format!("Updated custom field to {}h", total));
+        }
+
+        if messages.is_empty() {
+            return Err(
+                "Trello integration has neither post_comments nor custom_field_id configured"
+                    .to_string()
+                    .into(),
+            );
+        }
+
+        Ok(SyncResult {
+            success: true,
+            message: messages.join(", "),
+            external_id,
+        })
+    }
+
+    async fn test_connection(&self) -> Result<bool, String> {
+        let response = self
+            .request(reqwest::Method::GET, "members/me")
+            .send()
+            .await
+            .map_err(|e| format!("Network error: {}", e))?;
+
+        if response.status().is_success() {
+            let result: MemberResponse = response
+                .json()
+                .await
+                .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+            println!(
+                "Connected to Trello as: {} (id: {})",
+                result.username, result.id
+            );
+            Ok(true)
+        } else {
+            Err(format!("Authentication failed: {}", response.status()))
+        }
+    }
+}
@@ -0,0 +1,484 @@
+pub mod config;
+pub mod http;
+pub mod integrations;
+pub mod rounding;
+pub mod traits;
+#[cfg(feature = "wasm-plugins")]
+pub mod wasm;
+
+use parking_lot::RwLock;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use config::{CommentConfig, IntegrationConfig, IntegrationsConfig, TimeRoundingConfig};
+use integrations::{
+    AsanaIntegration, ClockifyIntegration, ExecIntegration, GenericHttpIntegration,
+    GitHubIntegration, GitLabIntegration, HarvestIntegration, JiraIntegration, NotionIntegration,
+    RedmineIntegration, TogglIntegration, TrelloIntegration,
+};
+use traits::{
+    ActivityInfo, ExternalIntegration, IssueSummary, SyncError, SyncResult, TicketSyncResult,
+};
+
+pub use config::UploadConfig;
+
+/// チケット一覧のキャッシュ有効期間
+const ISSUE_CACHE_TTL: Duration = Duration::from_secs(300);
+
+/// プラグインマネージャー
+pub struct PluginManager {
+    plugins: RwLock<Vec<Arc<dyn ExternalIntegration>>>,
+    /// プラグイン名 → (取得時刻, 自分のチケット一覧)
+    issue_cache: RwLock<HashMap<String, (Instant, Vec<IssueSummary>)>>,
+    /// プラグイン名 → 同期時間の調整
+    rounding: RwLock<HashMap<String, TimeRoundingConfig>>,
+    /// プラグイン名 → コメントの組み立て方
+    comments: RwLock<HashMap<String, CommentConfig>>,
+    /// 前回読み込んだ抽出ルール（有効な連携のルールと WASM プラグインの名前）
+    extraction_key: RwLock<Option<String>>,
+    /// 読み直しで抽出ルールが変わった（保存済みの抽出結果を作り直す）
+    extraction_changed: AtomicBool,
+}
+
+impl PluginManager {
+    /// 新しいプラグインマネージャーを作成
+    pub fn new() -> Self {
+        Self {
+            plugins: RwLock::new(Vec::new()),
+            issue_cache: RwLock::new(HashMap::new()),
+            rounding: RwLock::new(HashMap::new()),
+            comments: RwLock::new(HashMap::new()),
+            extraction_key: RwLock::new(None),
+            extraction_changed: AtomicBool::new(false),
+        }
+    }
+
+    /// 設定ファイルからプラグインを読み込む（作成に失敗した連携はログに出して読み飛ばす）
+    pub fn load_from_config(&self) -> Result<(), String> {
+        let config = IntegrationsConfig::load();
+        http::configure(&config.http);
+
+        let rules: Vec<_> = config
+            .integrations
+            .iter()
+            .filter(|entry| entry.enabled)
+            .map(|entry| (&entry.name, entry.config.rules()))
+            .collect();
+        let wasm_plugins: BTreeSet<&String> = config.wasm_plugins.keys().collect();
+        let key = serde_json::to_string(&(rules, wasm_plugins)).unwrap_or_default();
+        let previous = self.extraction_key.write().replace(key.clone());
+        if previous.is_some_and(|previous| previous != key) {
+            self.extraction_changed.store(true, Ordering::SeqCst);
+        }
+
+        let mut plugins = self.plugins.write();
+        plugins.clear();
+        self.issue_cache.write().clear();
+        let mut rounding = self.rounding.write();
+        rounding.clear();
+        let mut comments = self.comments.write();
+        comments.clear();
+
+        for entry in config.integrations {
+            if !entry.enabled {
+                continue;
+            }
+            rounding.insert(entry.name.clone(), entry.rounding);
+            comments.insert(entry.name.clone(), entry.comment);
+
+            // 作成できない連携は読み飛ばし、残りの連携は使えるようにする
+            let name = entry.name.clone();
+            match build_plugin(entry.name, entry.enabled, entry.config) {
+                Ok(plugin) => plugins.push(plugin),
+                Err(e) => eprintln!("Skipping integration {}: {}", name, e),
+            }
+        }
+
+        // 設定ファイルの連携と名前が重なるプラグインは読み込まない
+        #[cfg(feature = "wasm-plugins")]
+        for plugin in wasm::load_plugins(&config.wasm_plugins) {
+            if plugins.iter().any(|p| p.name() == plugin.name()) {
+                eprintln!(
+                    "Skipping WASM plugin {}: name already in use",
+                    plugin.name()
+                );
+                continue;
+            }
+            plugins.push(plugin);
+        }
+
+        Ok(())
+    }
+
+    /// 有効なプラグイン一覧を取得
+    pub fn list_plugins(&self) -> Vec<String> {
+        self.plugins
+            .read()
+            .iter()
+            .map(|p| p.name().to_string())
+            .collect()
+    }
+
+    /// 指定したプラグインを取得
+    pub fn get_plugin(&self, name: &str) -> Option<Arc<dyn ExternalIntegration>> {
+        self.plugins
+            .read()
+            .iter()
+            .find(|p| p.name() == name)
+            .cloned()
+    }
+
+    /// 前回の確認以降に読み直しで抽出ルールが変わったか（確認したら false に戻す）
+    pub fn take_extraction_changed(&self) -> bool {
+        self.extraction_changed.swap(false, Ordering::SeqCst)
+    }
+
+    /// アクティビティからチケットIDを抽出（最初にマッチしたプラグインの結果を返す）
+    pub fn extract_ticket_id(&self, activity: &ActivityInfo) -> Option<(String, String)> {
+        for plugin in self.plugins.read().iter() {
+            if let Some(ticket_id) = plugin.extract_ticket_id(activity) {
+                return Some((plugin.name().to_string(), ticket_id));
+            }
+        }
+        None
+    }
+
+    /// 全プラグインで抽出を試行し、結果を返す
+    pub fn extract_all_ticket_ids(&self, activity: &ActivityInfo) -> Vec<(String, String)> {
+        let mut results = Vec::new();
+        for plugin in self.plugins.read().iter() {
+            if let Some(ticket_id) = plugin.extract_ticket_id(activity) {
+                results.push((plugin.name().to_string(), ticket_id));
+            }
+        }
+        results
+    }
+
+    /// 同期時間の調整（設定がなければ何もしない）
+    pub fn rounding(&self, plugin_name: &str) -> TimeRoundingConfig {
+        self.rounding
+            .read()
+            .get(plugin_name)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// 同じ日のエントリに丸め・最小時間・上限を適用（used_seconds はその日に送信済み・送信予定の時間）
+    pub fn apply_rounding(
+        &self,
+        plugin_name: &str,
+        entries: &mut [ActivityInfo],
+        used_seconds: i64,
+    ) {
+        rounding::apply_day(&self.rounding(plugin_name), entries, used_seconds);
+    }
+
+    /// 同じ日のエントリを送信する形に整える（丸め・上限の適用と、コメントをメモとして組み立て）
+    ///
+    /// omit_titles ならウィンドウタイトルは送信しない。
+    pub fn prepare_entries(
+        &self,
+        plugin_name: &str,
+        entries: &mut [ActivityInfo],
+        used_seconds: i64,
+    ) {
+        self.apply_rounding(plugin_name, entries, used_seconds);
+        let comment = self
+            .comments
+            .read()
+            .get(plugin_name)
+            .cloned()
+            .unwrap_or_default();
+        for entry in entries.iter_mut() {
+            entry.note = Some(integrations::render_comment(&comment, entry));
+            if comment.omit_titles {
+                entry.window_title.clear();
+            }
+        }
+    }
+
+    /// 作業時間を渡された時間・コメントのまま同期
+    ///
+    /// 丸めやコメントの組み立ては行わないので、記録から送る場合は先に prepare_entries を通す。
+    pub async fn sync_time_entry(
+        &self,
+        plugin_name: &str,
+        activity: &ActivityInfo,
+        ticket_id: &str,
+    ) -> Result<SyncResult, SyncError> {
+        let plugin = self
+            .get_plugin(plugin_name)
+            .ok_or_else(|| format!("Plugin not found: {}", plugin_name))?;
+
+        plugin.sync_time_entry(activity, ticket_id).await
+    }
+
+    /// 1日分のアクティビティをチケットごとにまとめて同期
+    ///
+    /// assigned（アクティビティID → チケットID）にないアクティビティは送信しない。
+    /// チケットごとに1件のタイムエントリを作成し、失敗しても残りのチケットの同期は続ける。
+    /// 送信する時間とコメントは prepare_entries で整える（used_seconds はその日に送信済み・送信予定の時間）。
+    pub async fn sync_day(
+        &self,
+        plugin_name: &str,
+        activities: &[ActivityInfo],
+        assigned: &HashMap<i64, String>,
+        used_seconds: i64,
+    ) -> Result<Vec<TicketSyncResult>, String> {
+        let plugin = self
+            .get_plugin(plugin_name)
+            .ok_or_else(|| format!("Plugin not found: {}", plugin_name))?;
+        let groups = group_by_ticket(activities, assigned);
+
+        let mut merged: Vec<ActivityInfo> = groups
+            .iter()
+            .map(|(_, members)| merge_activities(members))
+            .collect();
+        self.prepare_entries(plugin_name, &mut merged, used_seconds);
+        let has_cap = self.rounding(plugin_name).daily_cap_hours.is_some();
+
+        let mut results = Vec::new();
+        for ((ticket_id, members), activity) in groups.into_iter().zip(merged) {
+            let capped = has_cap && activity.duration_seconds == 0;
+            let mut network_error = false;
+            let result = if capped {
+                SyncResult {
+                    success: false,
+                    message: "Skipped: daily cap reached".to_string(),
+                    external_id: None,
+                }
+            } else {
+                plugin
+                    .sync_time_entry(&activity, &ticket_id)
+                    .await
+                    .unwrap_or_else(|e| {
+                        network_error = e.is_network();
+                        SyncResult {
+                            success: false,
+                            message: e.into(),
+                            external_id: None,
+                        }
+                    })
+            };
+
+            results.push(TicketSyncResult {
+                ticket_id,
+                activity_ids: members.iter().map(|a| a.id).collect(),
+                duration_seconds: activity.duration_seconds,
+                activity,
+                result,
+                capped,
+                network_error,
+            });
+        }
+
+        Ok(results)
+    }
+
+    /// アクティビティを assigned のチケットごとにまとめる
+    pub fn group_by_ticket<'a>(
+        &self,
+        plugin_name: &str,
+        activities: &'a [ActivityInfo],
+        assigned: &HashMap<i64, String>,
+    ) -> Result<Vec<(String, Vec<&'a ActivityInfo>)>, String> {
+        if self.get_plugin(plugin_name).is_none() {
+            return Err(format!("Plugin not found: {}", plugin_name));
+        }
+        Ok(group_by_ticket(activities, assigned))
+    }
+
+    /// 接続テスト
+    pub async fn test_connection(&self, plugin_name: &str) -> Result<bool, String> {
+        let plugin = self
+            .get_plugin(plugin_name)
+            .ok_or_else(|| format!("Plugin not found: {}", plugin_name))?;
+
+        plugin.test_connection().await
+    }
+
+    /// 自分に割り当てられたチケット一覧を取得（refresh=false なら一定時間キャッシュを返す）
+    pub async fn list_my_issues(
+        &self,
+        plugin_name: &str,
+        refresh: bool,
+    ) -> Result<Vec<IssueSummary>, String> {
+        if !refresh {
+            if let Some(issues) = self.cached_issues(plugin_name) {
+                return Ok(issues);
+            }
+        }
+
+        let plugin = self
+            .get_plugin(plugin_name)
+            .ok_or_else(|| format!("Plugin not found: {}", plugin_name))?;
+
+        let issues = plugin.list_my_issues().await?;
+        self.issue_cache
+            .write()
+            .insert(plugin_name.to_string(), (Instant::now(), issues.clone()));
+        Ok(issues)
+    }
+
+    fn cached_issues(&self, plugin_name: &str) -> Option<Vec<IssueSummary>> {
+        self.issue_cache
+            .read()
+            .get(plugin_name)
+            .filter(|(fetched_at, _)| fetched_at.elapsed() < ISSUE_CACHE_TTL)
+            .map(|(_, issues)| issues.clone())
+    }
+
+    /// チケットの件名・状態を取得
+    pub async fn get_ticket_info(
+        &self,
+        plugin_name: &str,
+        ticket_id: &str,
+    ) -> Result<Option<IssueSummary>, String> {
+        let plugin = self
+            .get_plugin(plugin_name)
+            .ok_or_else(|| format!("Plugin not found: {}", plugin_name))?;
+
+        plugin.get_ticket_info(ticket_id).await
+    }
+
+    /// チケットの見積もり時間（時間）を取得
+    pub async fn get_ticket_estimate(
+        &self,
+        plugin_name: &str,
+        ticket_id: &str,
+    ) -> Result<Option<f64>, String> {
+        let plugin = self
+            .get_plugin(plugin_name)
+            .ok_or_else(|| format!("Plugin not found: {}", plugin_name))?;
+
+        plugin.get_ticket_estimate(ticket_id).await
+    }
+}
+
+fn group_by_ticket<'a>(
+    activities: &'a [ActivityInfo],
+    assigned: &HashMap<i64, String>,
+) -> Vec<(String, Vec<&'a ActivityInfo>)> {
+    let mut groups: Vec<(String, Vec<&ActivityInfo>)> = Vec::new();
+    for activity in activities {
+        let Some(ticket_id) = assigned.get(&activity.id) else {
+            continue;
+        };
+        match groups.iter_mut().find(|(id, _)| id == ticket_id) {
+            Some((_, members)) => members.push(activity),
+            None => groups.push((ticket_id.clone(), vec![activity])),
+        }
+    }
+    groups
+}
+
+/// 同じチケットのアクティビティを1件に統合
+///
+/// 時間は合計、期間は最初の開始から最後の終了まで。タイトルは最も長く使ったものを代表とする。
+pub fn merge_activities(members: &[&ActivityInfo]) -> ActivityInfo {
+    let longest = members
+        .iter()
+        .max_by_key(|a| a.duration_seconds)
+        .expect("merged group is never empty");
+
+    let mut process_names: Vec<&str> = Vec::new();
+    let mut notes: Vec<&str> = Vec::new();
+    for activity in members {
+        if !process_names.contains(&activity.process_name.as_str()) {
+            process_names.push(&activity.process_name);
+        }
+        if let Some(note) = activity.note.as_deref() {
+            if !notes.contains(&note) {
+                notes.push(note);
+            }
+        }
+    }
+
+    ActivityInfo {
+        id: members[0].id,
+        process_name: process_names.join(", "),
+        window_title: longest.window_title.clone(),
+        domain: longest.domain.clone(),
+        start_time: members
+            .iter()
+            .map(|a| a.start_time.clone())
+            .min()
+            .unwrap_or_default(),
+        end_time: members
+            .iter()
+            .map(|a| a.end_time.clone())
+            .max()
+            .unwrap_or_default(),
+        duration_seconds: members.iter().map(|a| a.duration_seconds).sum(),
+        note: Some(notes.join("; ")).filter(|n| !n.is_empty()),
+        project: members.iter().find_map(|a| a.project.clone()),
+        category: longest.category.clone(),
+    }
+}
+
+/// 設定の連携エントリからプラグインを作成
+fn build_plugin(
+    name: String,
+    enabled: bool,
+    config: IntegrationConfig,
+) -> Result<Arc<dyn ExternalIntegration>, String> {
+    let plugin: Arc<dyn ExternalIntegration> = match config {
+        IntegrationConfig::Redmine(redmine_config) => {
+            Arc::new(RedmineIntegration::new(name, enabled, redmine_config)?)
+        }
+        IntegrationConfig::Jira(jira_config) => {
+            Arc::new(JiraIntegration::new(name, enabled, jira_config)?)
+        }
+        IntegrationConfig::GitHub(github_config) => {
+            Arc::new(GitHubIntegration::new(name, enabled, github_config)?)
+        }
+        IntegrationConfig::Toggl(toggl_config) => {
+            Arc::new(TogglIntegration::new(name, enabled, toggl_config)?)
+        }
+        IntegrationConfig::GitLab(gitlab_config) => {
+            Arc::new(GitLabIntegration::new(name, enabled, gitlab_config)?)
+        }
+        IntegrationConfig::Clockify(clockify_config) => {
+            Arc::new(ClockifyIntegration::new(name, enabled, clockify_config)?)
+        }
+        IntegrationConfig::Harvest(harvest_config) => {
+            Arc::new(HarvestIntegration::new(name, enabled, harvest_config)?)
+        }
+        IntegrationConfig::Asana(asana_config) => {
+            Arc::new(AsanaIntegration::new(name, enabled, asana_config)?)
+        }
+        IntegrationConfig::Trello(trello_config) => {
+            Arc::new(TrelloIntegration::new(name, enabled, trello_config)?)
+        }
+        IntegrationConfig::Notion(notion_config) => {
+            Arc::new(NotionIntegration::new(name, enabled, notion_config)?)
+        }
+        IntegrationConfig::Custom(custom_config) => {
+            Arc::new(GenericHttpIntegration::new(name, enabled, custom_config)?)
+        }
+        IntegrationConfig::Exec(exec_config) => {
+            Arc::new(ExecIntegration::new(name, enabled, exec_config)?)
+        }
+    };
+    Ok(plugin)
+}
+
+impl Default for PluginManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 設定ファイルのサンプルを作成
+pub fn create_sample_config() -> Result<(), String> {
+    let config = IntegrationsConfig::create_sample();
+    config.save()
+}
+
+/// アップロード設定を取得
+pub fn get_upload_config() -> Option<UploadConfig> {
+    let config = IntegrationsConfig::load();
+    config.upload
+}
@@ -0,0 +1,208 @@
+use chrono::{Duration, NaiveDateTime};
+
+use super::config::{RoundingMode, TimeRoundingConfig};
+use super::traits::ActivityInfo;
+
+/// 1エントリの時間を丸め、最小時間を適用（0秒はそのまま）
+pub fn round_seconds(config: &TimeRoundingConfig, seconds: i64) -> i64 {
+    if seconds <= 0 {
+        return 0;
+    }
+
+    let unit = i64::from(config.minutes) * 60;
+    let rounded = if unit == 0 {
+        seconds
+    } else {
+        let units = match config.mode {
+            RoundingMode::Nearest => (seconds + unit / 2) / unit,
+            RoundingMode::Up => (seconds + unit - 1) / unit,
+        };
+        // 短い作業も0にはしない
+        units.max(1) * unit
+    };
+
+    rounded.max(i64::from(config.minimum_minutes) * 60)
+}
+
+/// 1日分のエントリに丸め・最小時間・上限を適用
+///
+/// used_seconds はその日に送信済み・送信予定の時間で、上限から差し引く。
+/// 合計が残りを超える場合は各エントリを比率で縮め、丸め単位に切り捨てる（0秒になったエントリは送信しない）。
+pub fn apply_day(config: &TimeRoundingConfig, entries: &mut [ActivityInfo], used_seconds: i64) {
+    let mut durations: Vec<i64> = entries
+        .iter()
+        .map(|entry| round_seconds(config, entry.duration_seconds))
+        .collect();
+
+    if let Some(cap_hours) = config.daily_cap_hours {
+        let cap = ((cap_hours * 3600.0) as i64 - used_seconds).max(0);
+        let total: i64 = durations.iter().sum();
+        if total > cap {
+            let unit = i64::from(config.minutes) * 60;
+            for duration in durations.iter_mut() {
+                let scaled = (*duration as i128 * cap as i128 / total as i128) as i64;
+                *duration = if unit == 0 {
+                    scaled
+                } else {
+                    scaled / unit * unit
+                };
+            }
+        }
+    }
+
+    for (entry, duration) in entries.iter_mut().zip(durations) {
+        set_duration(entry, duration);
+    }
+}
+
+/// 時間を変え、終了時刻を合わせる
+fn set_duration(entry: &mut ActivityInfo, duration_seconds: i64) {
+    if entry.duration_seconds == duration_seconds {
+        return;
+    }
+    entry.duration_seconds = duration_seconds;
+    if let Ok(start) = NaiveDateTime::parse_from_str(&entry.start_time, "%Y-%m-%dT%H:%M:%S") {
+        entry.end_time = (start + Duration::seconds(duration_seconds))
+            .format("%Y-%m-%dT%H:%M:%S")
+            .to_string();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(minutes: u32, mode: RoundingMode, minimum_minutes: u32) -> TimeRoundingConfig {
+        TimeRoundingConfig {
+            minutes,
+            mode,
+            minimum_minutes,
+            daily_cap_hours: None,
+        }
+    }
+
+    fn entry(id: i64, start_time: &str, duration_seconds: i64) -> ActivityInfo {
+        ActivityInfo {
+            id,
+            process_name: "Code.exe".to_string(),
+            window_title: String::new(),
+            domain: None,
+            start_time: start_time.to_string(),
+            end_time: String::new(),
+            duration_seconds,
+            note: None,
+            project: None,
+            category: None,
+        }
+    }
+
+    #[test]
+    fn round_seconds_rounds_to_the_unit() {
+        let nearest = config(15, RoundingMode::Nearest, 0);
+        assert_eq!(round_seconds(&nearest, 22 * 60), 15 * 60);
+        assert_eq!(round_seconds(&nearest, 23 * 60), 30 * 60);
+
+        let up = config(15, RoundingMode::Up, 0);
+        assert_eq!(round_seconds(&up, 15 * 60), 15 * 60);
+        assert_eq!(round_seconds(&up, 15 * 60 + 1), 30 * 60);
+    }
+
+    #[test]
+    fn round_seconds_keeps_short_entries() {
+        // 単位の半分未満でも 0 にしない
+        assert_eq!(
+            round_seconds(&config(15, RoundingMode::Nearest, 0), 60),
+            15 * 60
+        );
+        assert_eq!(round_seconds(&config(15, RoundingMode::Nearest, 0), 0), 0);
+        assert_eq!(
+            round_seconds(&config(0, RoundingMode::Nearest, 0), 125),
+            125
+        );
+    }
+
+    #[test]
+    fn round_seconds_applies_minimum() {
+        assert_eq!(
+            round_seconds(&config(0, RoundingMode::Nearest, 30), 10 * 60),
+            30 * 60
+        );
+        assert_eq!(
+            round_seconds(&config(15, RoundingMode::Up, 30), 16 * 60),
+            30 * 60
+        );
+        assert_eq!(
+            round_seconds(&config(15, RoundingMode::Up, 30), 31 * 60),
+            45 * 60
+        );
+    }
+
+    #[test]
+    fn apply_day_rounds_without_cap() {
+        let mut entries = vec![entry(1, "2024-01-01T09:00:00", 20 * 60)];
+        apply_day(&config(15, RoundingMode::Up, 0), &mut entries, 0);
+        assert_eq!(entries[0].duration_seconds, 30 * 60);
+        assert_eq!(entries[0].end_time, "2024-01-01T09:30:00");
+    }
+
+    #[test]
+    fn apply_day_scales_down_to_the_cap() {
+        let mut rounding = config(15, RoundingMode::Nearest, 0);
+        rounding.daily_cap_hours = Some(1.0);
+        let mut entries = vec![
+            entry(1, "2024-01-01T09:00:00", 45 * 60),
+            entry(2, "2024-01-01T10:00:00", 45 * 60),
+        ];
+        apply_day(&rounding, &mut entries, 0);
+
+        assert_eq!(entries[0].duration_seconds, 30 * 60);
+        assert_eq!(entries[0].end_time, "2024-01-01T09:30:00");
+        assert_eq!(entries[1].duration_seconds, 30 * 60);
+        assert_eq!(entries[1].end_time, "2024-01-01T10:30:00");
+    }
+
+    #[test]
+    fn apply_day_truncates_scaled_entries_to_the_unit() {
+        let mut rounding = config(15, RoundingMode::Nearest, 0);
+        rounding.daily_cap_hours = Some(1.0);
+        let mut entries = vec![
+            entry(1, "2024-01-01T09:00:00", 60 * 60),
+            entry(2, "2024-01-01T10:00:00", 30 * 60),
+        ];
+        apply_day(&rounding, &mut entries, 0);
+
+        // 40分と20分に縮め、15分単位に切り捨てる
+        assert_eq!(entries[0].duration_seconds, 30 * 60);
+        assert_eq!(entries[1].duration_seconds, 15 * 60);
+    }
+
+    #[test]
+    fn apply_day_subtracts_used_seconds() {
+        let mut rounding = config(15, RoundingMode::Nearest, 0);
+        rounding.daily_cap_hours = Some(2.0);
+        let mut entries = vec![entry(1, "2024-01-01T09:00:00", 60 * 60)];
+        apply_day(&rounding, &mut entries, 90 * 60);
+        assert_eq!(entries[0].duration_seconds, 30 * 60);
+
+        // 送信済みで上限に達していれば 0 になる
+        let mut entries = vec![entry(1, "2024-01-01T09:00:00", 60 * 60)];
+        apply_day(&rounding, &mut entries, 3 * 60 * 60);
+        assert_eq!(entries[0].duration_seconds, 0);
+        assert_eq!(entries[0].end_time, "2024-01-01T09:00:00");
+    }
+
+    #[test]
+    fn apply_day_drops_entries_below_one_unit() {
+        let mut rounding = config(15, RoundingMode::Nearest, 0);
+        rounding.daily_cap_hours = Some(0.25);
+        let mut entries = vec![
+            entry(1, "2024-01-01T09:00:00", 15 * 60),
+            entry(2, "2024-01-01T10:00:00", 15 * 60),
+        ];
+        apply_day(&rounding, &mut entries, 0);
+
+        // 7分半ずつに縮めると丸め単位に満たない
+        assert_eq!(entries[0].duration_seconds, 0);
+        assert_eq!(entries[1].duration_seconds, 0);
+    }
+}
@@ -0,0 +1,148 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// アクティビティ記録（プラグインに渡すデータ）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivityInfo {
+    pub id: i64,
+    pub process_name: String,
+    pub window_title: String,
+    pub domain: Option<String>,
+    pub start_time: String,
+    pub end_time: String,
+    pub duration_seconds: i64,
+    /// ユーザーが付けたメモ（あれば同期時のコメントに使う）
+    #[serde(default)]
+    pub note: Option<String>,
+    /// 割り当て済みのプロジェクト（コメントのテンプレート用）
+    #[serde(default)]
+    pub project: Option<String>,
+    /// カテゴリ（コメントのテンプレート用）
+    #[serde(default)]
+    pub category: Option<String>,
+}
+
+/// 同期結果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncResult {
+    pub success: bool,
+    pub message: String,
+    pub external_id: Option<String>,
+}
+
+/// 同期の失敗
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyncError {
+    /// 連携先に接続できなかった（オフライン時などはキューに保留して再送する）
+    Network(String),
+    /// 連携先に拒否された・応答を解釈できなかったなど、再送しても成功しない失敗
+    Failed(String),
+}
+
+impl SyncError {
+    /// reqwest などの接続エラーから作る
+    pub fn network(error: impl fmt::Display) -> Self {
+        Self::Network(format!("Network error: {}", error))
+    }
+
+    pub fn is_network(&self) -> bool {
+        matches!(self, Self::Network(_))
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            Self::Network(message) | Self::Failed(message) => message,
+        }
+    }
+}
+
+impl fmt::Display for SyncError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl From<String> for SyncError {
+    fn from(message: String) -> Self {
+        Self::Failed(message)
+    }
+}
+
+impl From<SyncError> for String {
+    fn from(error: SyncError) -> Self {
+        match error {
+            SyncError::Network(message) | SyncError::Failed(message) => message,
+        }
+    }
+}
+
+/// 1日分の一括同期におけるチケット単位の結果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TicketSyncResult {
+    pub ticket_id: String,
+    pub activity_ids: Vec<i64>,
+    pub duration_seconds: i64,
+    /// 送信した（チケット単位に統合した）アクティビティ
+    pub activity: ActivityInfo,
+    pub result: SyncResult,
+    /// 1日の上限に達したため送信しなかった
+    #[serde(default)]
+    pub capped: bool,
+    /// 接続できずに失敗した（キューに保留して再送できる）
+    #[serde(default)]
+    pub network_error: bool,
+}
+
+/// 自分に割り当てられたチケットの概要（チケット選択用）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IssueSummary {
+    pub id: String,
+    pub subject: String,
+    pub project: Option<String>,
+    pub status: Option<String>,
+}
+
+/// 外部連携プラグインのトレイト
+#[async_trait]
+pub trait ExternalIntegration: Send + Sync {
+    /// プラグイン名を返す
+    fn name(&self) -> &str;
+
+    /// プラグインの表示名を返す
+    fn display_name(&self) -> &str;
+
+    /// プラグインが有効かどうか
+    fn is_enabled(&self) -> bool;
+
+    /// アクティビティからチケット/タスクIDを抽出する
+    fn extract_ticket_id(&self, activity: &ActivityInfo) -> Option<String>;
+
+    /// 作業時間を外部サービスに同期する
+    async fn sync_time_entry(
+        &self,
+        activity: &ActivityInfo,
+        ticket_id: &str,
+    ) -> Result<SyncResult, SyncError>;
+
+    /// 接続テスト
+    async fn test_connection(&self) -> Result<bool, String>;
+
+    /// チケットの見積もり時間（時間）を取得（見積もりに対応しない連携は None）
+    async fn get_ticket_estimate(&self, _ticket_id: &str) -> Result<Option<f64>, String> {
+        Ok(None)
+    }
+
+    /// チケットの件名・状態を取得（対応しない連携・存在しないチケットは None）
+    async fn get_ticket_info(&self, _ticket_id: &str) -> Result<Option<IssueSummary>, String> {
+        Ok(None)
+    }
+
+    /// 自分に割り当てられた未完了のチケット一覧
+    async fn list_my_issues(&self) -> Result<Vec<IssueSummary>, String> {
+        Err(format!(
+            "{} does not support issue lists",
+            self.display_name()
+        ))
+    }
+}
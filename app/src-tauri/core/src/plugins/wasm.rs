@@ -0,0 +1,310 @@
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use wasmtime::component::{Component, Linker};
+use wasmtime::{Config, Engine, Store, StoreLimits, StoreLimitsBuilder};
+
+use super::config::{wasm_plugins_dir, WasmPluginConfig};
+use super::http::HttpClient;
+use super::traits::{ActivityInfo, ExternalIntegration, SyncError, SyncResult};
+use crate::secrets;
+
+/// wit/integration.wit から生成したバインディング
+mod bindings {
+    wasmtime::component::bindgen!({
+        world: "integration",
+        path: "wit",
+    });
+}
+
+use bindings::timetracker::plugin::host::{self, HttpResponse};
+use bindings::timetracker::plugin::types;
+use bindings::Integration;
+
+/// 1回の呼び出しで使える燃料（無限ループで止まらないようにする）
+const FUEL_PER_CALL: u64 = 5_000_000_000;
+/// プラグインが使えるメモリの上限
+const MAX_MEMORY_BYTES: usize = 64 * 1024 * 1024;
+const HTTP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// プラグインのインスタンスに持たせるホスト側の状態
+struct HostState {
+    name: String,
+    allowed_hosts: Vec<String>,
+    settings: HashMap<String, String>,
+    /// None なら HTTP リクエストを許可しない（チケットIDの抽出用のインスタンス）
+    ///
+    /// リダイレクト先は allowed_hosts で確かめられないので、たどらないクライアントを使う。
+    client: Option<HttpClient>,
+    limits: StoreLimits,
+}
+
+impl types::Host for HostState {}
+
+impl host::Host for HostState {
+    fn log(&mut self, message: String) {
+        println!("[{}] {}", self.name, message);
+    }
+
+    fn setting(&mut self, key: String) -> Option<String> {
+        self.settings.get(&key).cloned()
+    }
+
+    fn http_request(
+        &mut self,
+        method: String,
+        url: String,
+        headers: Vec<(String, String)>,
+        body: Option<String>,
+    ) -> Result<HttpResponse, String> {
+        let client = self
+            .client
+            .clone()
+            .ok_or("HTTP requests are not available in extract-ticket-id")?;
+
+        let parsed = url::Url::parse(&url).map_err(|e| format!("Invalid URL {}: {}", url, e))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(format!("Unsupported URL scheme: {}", parsed.scheme()));
+        }
+        let host = parsed.host_str().unwrap_or("");
+        if !self.allowed_hosts.iter().any(|allowed| allowed == host) {
+            return Err(format!("Host is not in allowed_hosts: {}", host));
+        }
+        let method = reqwest::Method::from_bytes(method.to_uppercase().as_bytes())
+            .map_err(|_| format!("Invalid HTTP method: {}", method))?;
+
+        let mut request = client
+            .request(method, parsed.as_str())
+            .timeout(HTTP_TIMEOUT);
+        for (name, value) in headers {
+            request = request.header(&name, value);
+        }
+        if let Some(body) = body {
+            request = request.body(body);
+        }
+
+        // sync_time_entry / test_connection は spawn_blocking のスレッドから呼ぶのでブロックしてよい
+        crate::runtime::block_on(async move {
+            let response = request
+                .send()
+                .await
+                .map_err(|e| format!("Network error: {}", e))?;
+            let status = response.status().as_u16();
+            let body = response.text().await.unwrap_or_default();
+            Ok(HttpResponse { status, body })
+        })
+    }
+}
+
+/// 1つのインスタンス（呼び出しは排他）
+struct Runtime {
+    store: Store<HostState>,
+    bindings: Integration,
+}
+
+impl Runtime {
+    fn new(
+        engine: &Engine,
+        linker: &Linker<HostState>,
+        component: &Component,
+        state: HostState,
+    ) -> Result<Self, String> {
+        let mut store = Store::new(engine, state);
+        store.limiter(|state| &mut state.limits);
+        store.set_fuel(FUEL_PER_CALL).map_err(|e| e.to_string())?;
+        let bindings =
+            Integration::instantiate(&mut store, component, linker).map_err(|e| e.to_string())?;
+        Ok(Self { store, bindings })
+    }
+
+    /// 燃料を補充してから呼び出す
+    fn call<T>(
+        &mut self,
+        f: impl FnOnce(&Integration, &mut Store<HostState>) -> wasmtime::Result<T>,
+    ) -> Result<T, String> {
+        self.store
+            .set_fuel(FUEL_PER_CALL)
+            .map_err(|e| e.to_string())?;
+        f(&self.bindings, &mut self.store).map_err(|e| e.to_string())
+    }
+}
+
+fn to_wasm(activity: &ActivityInfo) -> types::ActivityInfo {
+    types::ActivityInfo {
+        id: activity.id,
+        process_name: activity.process_name.clone(),
+        window_title: activity.window_title.clone(),
+        domain: activity.domain.clone(),
+        start_time: activity.start_time.clone(),
+        end_time: activity.end_time.clone(),
+        duration_seconds: activity.duration_seconds,
+        note: activity.note.clone(),
+    }
+}
+
+/// WASM コンポーネントのプラグイン（wit/integration.wit の integration ワールド）
+pub struct WasmIntegration {
+    name: String,
+    display_name: String,
+    /// チケットIDの抽出用（ネットワークなし。同期の実行中も待たずに使える）
+    extractor: Mutex<Runtime>,
+    /// 同期・接続テスト用
+    worker: Arc<Mutex<Runtime>>,
+}
+
+impl WasmIntegration {
+    fn load(
+        engine: &Engine,
+        linker: &Linker<HostState>,
+        path: &Path,
+        name: String,
+        config: &WasmPluginConfig,
+    ) -> Result<Self, String> {
+        let component = Component::from_file(engine, path).map_err(|e| e.to_string())?;
+
+        let mut settings = config.settings.clone();
+        if let Some(alias) = &config.token_secret {
+            let token = secrets::get_integration(alias)?
+                .ok_or_else(|| format!("Token not found in the OS keyring: {}", alias))?;
+            settings.insert("token".to_string(), token);
+        }
+        let state = |client: Option<HttpClient>| HostState {
+            name: name.clone(),
+            allowed_hosts: config.allowed_hosts.clone(),
+            settings: settings.clone(),
+            client,
+            limits: StoreLimitsBuilder::new()
+                .memory_size(MAX_MEMORY_BYTES)
+                .build(),
+        };
+
+        let mut extractor = Runtime::new(engine, linker, &component, state(None))?;
+        let worker = Runtime::new(
+            engine,
+            linker,
+            &component,
+            state(Some(HttpClient::without_redirects())),
+        )?;
+        let display_name = extractor.call(|bindings, store| bindings.call_display_name(store))?;
+
+        Ok(Self {
+            name,
+            display_name,
+            extractor: Mutex::new(extractor),
+            worker: Arc::new(Mutex::new(worker)),
+        })
+    }
+
+    /// 同期・接続テストはブロックするので専用のスレッドで呼ぶ
+    async fn call_worker<T: Send + 'static>(
+        &self,
+        f: impl FnOnce(&Integration, &mut Store<HostState>) -> wasmtime::Result<T> + Send + 'static,
+    ) -> Result<T, String> {
+        let worker = self.worker.clone();
+        crate::runtime::spawn_blocking(move || worker.lock().call(f))
+            .await
+            .map_err(|e| e.to_string())?
+    }
+}
+
+#[async_trait]
+impl ExternalIntegration for WasmIntegration {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn display_name(&self) -> &str {
+        &self.display_name
+    }
+
+    fn is_enabled(&self) -> bool {
+        true
+    }
+
+    fn extract_ticket_id(&self, activity: &ActivityInfo) -> Option<String> {
+        let activity = to_wasm(activity);
+        self.extractor
+            .lock()
+            .call(|bindings, store| bindings.call_extract_ticket_id(store, &activity))
+            .unwrap_or_else(|e| {
+                eprintln!("WASM plugin {} failed to extract ticket: {}", self.name, e);
+                None
+            })
+    }
+
+    async fn sync_time_entry(
+        &self,
+        activity: &ActivityInfo,
+        ticket_id: &str,
+    ) -> Result<SyncResult, SyncError> {
+        let activity = to_wasm(activity);
+        let ticket_id = ticket_id.to_string();
+        let result = self
+            .call_worker(move |bindings, store| {
+                bindings.call_sync_time_entry(store, &activity, &ticket_id)
+            })
+            .await??;
+
+        Ok(SyncResult {
+            success: result.success,
+            message: result.message,
+            external_id: result.external_id,
+        })
+    }
+
+    async fn test_connection(&self) -> Result<bool, String> {
+        self.call_worker(|bindings, store| bindings.call_test_connection(store))
+            .await?
+    }
+}
+
+/// plugins/ ディレクトリの *.wasm を読み込む（読み込めないプラグインはログに出して飛ばす）
+pub fn load_plugins(
+    configs: &HashMap<String, WasmPluginConfig>,
+) -> Vec<Arc<dyn ExternalIntegration>> {
+    let dir = wasm_plugins_dir();
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return Vec::new();
+    };
+
+    let mut engine_config = Config::new();
+    engine_config.wasm_component_model(true).consume_fuel(true);
+    let engine = match Engine::new(&engine_config) {
+        Ok(engine) => engine,
+        Err(e) => {
+            eprintln!("Failed to start the WASM runtime: {}", e);
+            return Vec::new();
+        }
+    };
+    let mut linker = Linker::new(&engine);
+    if let Err(e) = Integration::add_to_linker(&mut linker, |state: &mut HostState| state) {
+        eprintln!("Failed to link WASM host functions: {}", e);
+        return Vec::new();
+    }
+
+    let mut paths: Vec<_> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "wasm"))
+        .collect();
+    paths.sort();
+
+    let mut plugins: Vec<Arc<dyn ExternalIntegration>> = Vec::new();
+    for path in paths {
+        let Some(name) = path.file_stem().map(|s| s.to_string_lossy().into_owned()) else {
+            continue;
+        };
+        let config = configs.get(&name).cloned().unwrap_or_default();
+        if !config.enabled {
+            continue;
+        }
+        match WasmIntegration::load(&engine, &linker, &path, name.clone(), &config) {
+            Ok(plugin) => plugins.push(Arc::new(plugin)),
+            Err(e) => eprintln!("Failed to load WASM plugin {}: {}", path.display(), e),
+        }
+    }
+    plugins
+}
@@ -0,0 +1,151 @@
+use chrono::NaiveDate;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::plugins::config::{Productivity, ProductivityConfig};
+
+/// 区分別の合計時間とスコア
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProductivityTotals {
+    pub productive_seconds: i64,
+    pub neutral_seconds: i64,
+    pub distracting_seconds: i64,
+    /// 0〜100（すべて productive なら 100、すべて distracting なら 0）
+    pub score: f64,
+}
+
+/// 1日分の生産性（トレンドグラフ用）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProductivityDay {
+    pub date: String,
+    #[serde(flatten)]
+    pub totals: ProductivityTotals,
+}
+
+/// 期間の生産性スコア
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProductivityReport {
+    pub start_date: String,
+    pub end_date: String,
+    #[serde(flatten)]
+    pub totals: ProductivityTotals,
+    /// 記録のない日も 0 で含む
+    pub daily: Vec<ProductivityDay>,
+}
+
+impl Productivity {
+    /// スコアの重み（productive 1.0 / neutral 0.5 / distracting 0.0）
+    pub fn weight(self) -> f64 {
+        match self {
+            Productivity::Productive => 1.0,
+            Productivity::Neutral => 0.5,
+            Productivity::Distracting => 0.0,
+        }
+    }
+}
+
+impl ProductivityConfig {
+    /// アプリ・カテゴリの区分（アプリの区分を優先）
+    pub fn classify(&self, process_name: &str, category: Option<&str>) -> Productivity {
+        if let Some(level) = self.apps.get(process_name) {
+            return *level;
+        }
+        category
+            .and_then(|category| self.categories.get(category))
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+impl ProductivityTotals {
+    fn add(&mut self, level: Productivity, seconds: i64) {
+        match level {
+            Productivity::Productive => self.productive_seconds += seconds,
+            Productivity::Neutral => self.neutral_seconds += seconds,
+            Productivity::Distracting => self.distracting_seconds += seconds,
+        }
+    }
+
+    fn with_score(mut self) -> Self {
+        let total = self.productive_seconds + self.neutral_seconds + self.distracting_seconds;
+        self.score = if total > 0 {
+            (self.productive_seconds as f64 * Productivity::Productive.weight()
+                + self.neutral_seconds as f64 * Productivity::Neutral.weight()
+                + self.distracting_seconds as f64 * Productivity::Distracting.weight())
+                / total as f64
+                * 100.0
+        } else {
+            0.0
+        };
+        self
+    }
+}
+
+/// start〜end（両端を含む）の生産性スコアと日別の推移
+pub fn score(
+    conn: &Connection,
+    config: &ProductivityConfig,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<ProductivityReport, String> {
+    if end < start {
+        return Err("end must not be before start".to_string());
+    }
+    let start_date = start.format("%Y-%m-%d").to_string();
+    let end_date = end.format("%Y-%m-%d").to_string();
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT substr(start_time, 1, 10) AS day, process_name, category, SUM(duration_seconds)
+             FROM activities
+             WHERE start_time >= ?1 AND start_time <= ?2
+             GROUP BY day, process_name, category",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows: Vec<(String, String, Option<String>, i64)> = stmt
+        .query_map(
+            params![
+                format!("{}T00:00:00", start_date),
+                format!("{}T23:59:59", end_date)
+            ],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let mut overall = ProductivityTotals::default();
+    let mut daily: BTreeMap<String, ProductivityTotals> = start
+        .iter_days()
+        .take_while(|date| *date <= end)
+        .map(|date| {
+            (
+                date.format("%Y-%m-%d").to_string(),
+                ProductivityTotals::default(),
+            )
+        })
+        .collect();
+
+    for (day, process_name, category, seconds) in rows {
+        let level = config.classify(&process_name, category.as_deref());
+        overall.add(level, seconds);
+        if let Some(totals) = daily.get_mut(&day) {
+            totals.add(level, seconds);
+        }
+    }
+
+    Ok(ProductivityReport {
+        start_date,
+        end_date,
+        totals: overall.with_score(),
+        daily: daily
+            .into_iter()
+            .map(|(date, totals)| ProductivityDay {
+                date,
+                totals: totals.with_score(),
+            })
+            .collect(),
+    })
+}
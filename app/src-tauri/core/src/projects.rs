@@ -0,0 +1,157 @@
+use parking_lot::RwLock;
+use regex::Regex;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use crate::plugins::config::{IntegrationsConfig, ProjectConfig};
+
+/// プロジェクト別の集計
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectSummary {
+    pub project: String,
+    pub total_seconds: i64,
+    pub activity_count: i64,
+    /// 期間内の記録時間全体に対する割合
+    pub percentage: f64,
+}
+
+struct CompiledProject {
+    name: String,
+    titles: Vec<Regex>,
+    domains: Vec<String>,
+    repo_folders: Vec<Regex>,
+}
+
+/// ウィンドウタイトル・ドメイン・リポジトリのフォルダ名からプロジェクトを判定する
+///
+/// 定義順に評価し、最初にマッチしたプロジェクトを採用する。
+pub struct ProjectMatcher {
+    projects: RwLock<Vec<CompiledProject>>,
+}
+
+impl ProjectMatcher {
+    pub fn new() -> Self {
+        Self {
+            projects: RwLock::new(Vec::new()),
+        }
+    }
+
+    /// 設定ファイルからプロジェクト定義を読み込む
+    pub fn load_from_config(&self) {
+        self.set_projects(&IntegrationsConfig::load().projects);
+    }
+
+    /// プロジェクト定義を差し替える（コンパイルできないパターンは無視）
+    pub fn set_projects(&self, projects: &[ProjectConfig]) {
+        let compiled = projects
+            .iter()
+            .map(|project| CompiledProject {
+                name: project.name.clone(),
+                titles: project
+                    .title_patterns
+                    .iter()
+                    .filter_map(|pattern| match Regex::new(pattern) {
+                        Ok(regex) => Some(regex),
+                        Err(e) => {
+                            eprintln!("Invalid project pattern {:?}: {}", pattern, e);
+                            None
+                        }
+                    })
+                    .collect(),
+                domains: project.domains.iter().map(|d| d.to_lowercase()).collect(),
+                // エディタのタイトルに含まれるフォルダ名を単語単位で探す
+                repo_folders: project
+                    .repo_folders
+                    .iter()
+                    .filter_map(|folder| {
+                        Regex::new(&format!(
+                            r"(?i)(^|[\s/\\\[(]){}($|[\s/\\\])])",
+                            regex::escape(folder)
+                        ))
+                        .ok()
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        *self.projects.write() = compiled;
+    }
+
+    /// マッチしたプロジェクト名を返す
+    pub fn detect(&self, window_title: &str, domain: Option<&str>) -> Option<String> {
+        let domain = domain.map(str::to_lowercase);
+        self.projects
+            .read()
+            .iter()
+            .find(|project| {
+                project.titles.iter().any(|re| re.is_match(window_title))
+                    || project
+                        .repo_folders
+                        .iter()
+                        .any(|re| re.is_match(window_title))
+                    || domain.as_deref().is_some_and(|domain| {
+                        project
+                            .domains
+                            .iter()
+                            .any(|d| domain == d || domain.ends_with(&format!(".{}", d)))
+                    })
+            })
+            .map(|project| project.name.clone())
+    }
+}
+
+impl Default for ProjectMatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 期間内のプロジェクト別の作業時間（多い順）
+pub fn summary(
+    conn: &Connection,
+    start_date: &str,
+    end_date: &str,
+) -> Result<Vec<ProjectSummary>, String> {
+    let range_start = format!("{}T00:00:00", start_date);
+    let range_end = format!("{}T23:59:59", end_date);
+
+    let total: i64 = conn
+        .query_row(
+            "SELECT COALESCE(SUM(duration_seconds), 0) FROM activities
+             WHERE start_time >= ?1 AND start_time <= ?2",
+            params![range_start, range_end],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT t.project, SUM(a.duration_seconds) AS total, COUNT(*)
+             FROM activities a
+             JOIN ticket_assignments t ON t.activity_id = a.id
+             WHERE a.start_time >= ?1 AND a.start_time <= ?2 AND t.project IS NOT NULL
+             GROUP BY t.project
+             ORDER BY total DESC",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let summaries = stmt
+        .query_map(params![range_start, range_end], |row| {
+            let total_seconds: i64 = row.get(1)?;
+            Ok(ProjectSummary {
+                project: row.get(0)?,
+                total_seconds,
+                activity_count: row.get(2)?,
+                percentage: if total > 0 {
+                    (total_seconds as f64 / total as f64) * 100.0
+                } else {
+                    0.0
+                },
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(summaries)
+}
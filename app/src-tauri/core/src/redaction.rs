@@ -0,0 +1,82 @@
+use sha2::{Digest, Sha256};
+
+use crate::plugins::config::{FieldPolicy, IntegrationsConfig, RedactionProfile};
+
+/// 組み込みプロファイル: 全項目をそのまま出力
+pub const PROFILE_FULL: &str = "full";
+/// 組み込みプロファイル: ウィンドウタイトルをハッシュ化
+pub const PROFILE_TITLES_HASHED: &str = "titles_hashed";
+/// 組み込みプロファイル: アプリ名のみ（タイトル・ドメインを除去）
+pub const PROFILE_APPS_ONLY: &str = "apps_only";
+
+fn builtin_profiles() -> Vec<RedactionProfile> {
+    let profile = |name: &str, window_title: FieldPolicy, domain: FieldPolicy| RedactionProfile {
+        name: name.to_string(),
+        process_name: FieldPolicy::Keep,
+        window_title,
+        domain,
+        salt: String::new(),
+    };
+
+    vec![
+        profile(PROFILE_FULL, FieldPolicy::Keep, FieldPolicy::Keep),
+        profile(PROFILE_TITLES_HASHED, FieldPolicy::Hash, FieldPolicy::Keep),
+        profile(PROFILE_APPS_ONLY, FieldPolicy::Remove, FieldPolicy::Remove),
+    ]
+}
+
+/// 利用可能なプロファイル一覧（設定ファイルの定義で組み込みを上書き）
+pub fn list_profiles(config: &IntegrationsConfig) -> Vec<RedactionProfile> {
+    let mut profiles: Vec<RedactionProfile> = builtin_profiles()
+        .into_iter()
+        .filter(|p| !config.redaction_profiles.iter().any(|c| c.name == p.name))
+        .collect();
+    profiles.extend(config.redaction_profiles.iter().cloned());
+    profiles
+}
+
+/// 名前からプロファイルを解決（未指定時は "full"）
+pub fn resolve_profile(
+    config: &IntegrationsConfig,
+    name: Option<&str>,
+) -> Result<RedactionProfile, String> {
+    let name = name.unwrap_or(PROFILE_FULL);
+    list_profiles(config)
+        .into_iter()
+        .find(|p| p.name == name)
+        .ok_or_else(|| format!("Unknown redaction profile: {}", name))
+}
+
+impl RedactionProfile {
+    /// ポリシーに従って値を変換（Remove の場合は None）
+    pub fn apply(&self, policy: FieldPolicy, value: &str) -> Option<String> {
+        match policy {
+            FieldPolicy::Keep => Some(value.to_string()),
+            FieldPolicy::Hash if value.is_empty() => Some(String::new()),
+            FieldPolicy::Hash => Some(self.hash(value)),
+            FieldPolicy::Remove => None,
+        }
+    }
+
+    pub fn process_name(&self, value: &str) -> Option<String> {
+        self.apply(self.process_name, value)
+    }
+
+    pub fn window_title(&self, value: &str) -> Option<String> {
+        self.apply(self.window_title, value)
+    }
+
+    pub fn domain(&self, value: Option<&str>) -> Option<String> {
+        value.and_then(|v| self.apply(self.domain, v))
+    }
+
+    /// ソルト付きSHA-256の先頭12桁
+    fn hash(&self, value: &str) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.salt.as_bytes());
+        hasher.update(value.as_bytes());
+        let digest = hasher.finalize();
+        let hex: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+        format!("h:{}", &hex[..12])
+    }
+}
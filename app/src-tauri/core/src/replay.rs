@@ -0,0 +1,419 @@
+use parking_lot::Mutex;
+use serde::Deserialize;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// リプレイ用フィクスチャのパスを指定する環境変数
+pub const REPLAY_FILE_ENV: &str = "TIMETRACKER_REPLAY";
+/// "0" を指定するとフィクスチャの最後で停止する（デフォルトはループ再生）
+pub const REPLAY_LOOP_ENV: &str = "TIMETRACKER_REPLAY_LOOP";
+
+/// リプレイする1件のウィンドウイベント
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReplayEvent {
+    pub process_name: String,
+    pub window_title: String,
+    #[serde(default)]
+    pub domain: Option<String>,
+    /// このウィンドウがフォアグラウンドに留まるポーリング回数（秒）
+    #[serde(default = "default_event_duration")]
+    pub duration_seconds: u64,
+    #[serde(default)]
+    pub company_name: Option<String>,
+    #[serde(default)]
+    pub product_name: Option<String>,
+    #[serde(default)]
+    pub file_version: Option<String>,
+    /// このイベントの間は入力がない（アイドル）ものとして扱う
+    #[serde(default)]
+    pub idle: bool,
+}
+
+fn default_event_duration() -> u64 {
+    1
+}
+
+/// フィクスチャを順に再生するウィンドウソース
+struct ReplaySource {
+    events: Vec<ReplayEvent>,
+    index: usize,
+    elapsed: u64,
+    looping: bool,
+    /// 直近に返したイベントの位置
+    current: Option<usize>,
+    /// 連続してアイドルのイベントを再生した秒数
+    idle_elapsed: u64,
+}
+
+impl ReplaySource {
+    fn next(&mut self) -> Option<&ReplayEvent> {
+        if self.events.is_empty() {
+            return None;
+        }
+
+        if self.index >= self.events.len() {
+            if !self.looping {
+                return None;
+            }
+            self.index = 0;
+        }
+
+        let index = self.index;
+        self.current = Some(index);
+        if self.events[index].idle {
+            self.idle_elapsed += 1;
+        } else {
+            self.idle_elapsed = 0;
+        }
+        self.elapsed += 1;
+        if self.elapsed >= self.events[index].duration_seconds.max(1) {
+            self.elapsed = 0;
+            self.index += 1;
+        }
+
+        self.events.get(index)
+    }
+}
+
+static SOURCE: OnceLock<Mutex<ReplaySource>> = OnceLock::new();
+
+fn source() -> &'static Mutex<ReplaySource> {
+    SOURCE.get_or_init(|| {
+        let events = match std::env::var(REPLAY_FILE_ENV) {
+            Ok(path) => match load_fixture(Path::new(&path)) {
+                Ok(events) => {
+                    eprintln!("Replaying {} window events from {}", events.len(), path);
+                    events
+                }
+                Err(e) => {
+                    eprintln!("Failed to load replay fixture {}: {}", path, e);
+                    demo_events()
+                }
+            },
+            Err(_) => demo_events(),
+        };
+
+        let looping = std::env::var(REPLAY_LOOP_ENV)
+            .map(|v| v != "0")
+            .unwrap_or(true);
+
+        Mutex::new(ReplaySource {
+            events,
+            index: 0,
+            elapsed: 0,
+            looping,
+            current: None,
+            idle_elapsed: 0,
+        })
+    })
+}
+
+/// フィクスチャが指定されているか（ネイティブ取得のあるプラットフォームではこのときだけリプレイする）
+pub fn is_enabled() -> bool {
+    std::env::var(REPLAY_FILE_ENV).is_ok()
+}
+
+/// 次のポーリングで返すウィンドウ情報を取得（ウォッチャーから1秒ごとに呼ばれる）
+pub fn next_window() -> Option<(String, String, Option<String>)> {
+    let mut source = source().lock();
+    source.next().map(|event| {
+        (
+            event.process_name.clone(),
+            event.window_title.clone(),
+            event.domain.clone().filter(|d| !d.is_empty()),
+        )
+    })
+}
+
+/// 最後の入力からの経過秒数（idle なイベントが続いた秒数）
+pub fn idle_seconds() -> u64 {
+    source().lock().idle_elapsed
+}
+
+/// 直近に返したイベントのプロセスメタデータを取得
+pub fn current_metadata() -> Option<crate::ProcessMetadata> {
+    let source = source().lock();
+    let event = source.events.get(source.current?)?;
+    Some(crate::ProcessMetadata {
+        process_name: event.process_name.clone(),
+        exe_path: None,
+        company_name: event.company_name.clone(),
+        product_name: event.product_name.clone(),
+        file_version: event.file_version.clone(),
+    })
+}
+
+/// フィクスチャファイルを読み込む（拡張子 .csv はCSV、それ以外はJSON配列として解釈）
+pub fn load_fixture(path: &Path) -> Result<Vec<ReplayEvent>, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+
+    let is_csv = path
+        .extension()
+        .map(|ext| ext.eq_ignore_ascii_case("csv"))
+        .unwrap_or(false);
+
+    if is_csv {
+        parse_csv_fixture(&content)
+    } else {
+        serde_json::from_str(&content).map_err(|e| e.to_string())
+    }
+}
+
+/// CSVフィクスチャを解析する
+///
+/// ヘッダー行で列を判別するため、export_timeline_csv の出力をそのまま再生できる。
+fn parse_csv_fixture(content: &str) -> Result<Vec<ReplayEvent>, String> {
+    let mut rows = parse_csv(content).into_iter();
+    let header = rows
+        .next()
+        .ok_or_else(|| "CSV fixture is empty".to_string())?;
+
+    let column = |name: &str| header.iter().position(|h| h.trim() == name);
+    let process_col =
+        column("process_name").ok_or_else(|| "Missing process_name column".to_string())?;
+    let title_col =
+        column("window_title").ok_or_else(|| "Missing window_title column".to_string())?;
+    let domain_col = column("domain");
+    let duration_col = column("duration_seconds");
+    let company_col = column("company_name");
+    let product_col = column("product_name");
+    let version_col = column("file_version");
+    let idle_col = column("idle");
+
+    let events = rows
+        .filter(|row| row.iter().any(|field| !field.is_empty()))
+        .map(|row| {
+            let field = |col: usize| row.get(col).cloned().unwrap_or_default();
+            ReplayEvent {
+                process_name: field(process_col),
+                window_title: field(title_col),
+                domain: domain_col.map(field).filter(|d| !d.is_empty()),
+                duration_seconds: duration_col
+                    .and_then(|col| field(col).trim().parse().ok())
+                    .unwrap_or_else(default_event_duration),
+                company_name: company_col.map(field).filter(|v| !v.is_empty()),
+                product_name: product_col.map(field).filter(|v| !v.is_empty()),
+                file_version: version_col.map(field).filter(|v| !v.is_empty()),
+                idle: idle_col
+                    .map(|col| matches!(field(col).trim(), "1" | "true"))
+                    .unwrap_or(false),
+            }
+        })
+        .collect();
+
+    Ok(events)
+}
+
+/// ダブルクォートによるエスケープに対応した最小限のCSVパーサー
+fn parse_csv(content: &str) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = content.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                '"' => in_quotes = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+
+        match c {
+            '"' => in_quotes = true,
+            ',' => row.push(std::mem::take(&mut field)),
+            '\r' => {}
+            '\n' => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            _ => field.push(c),
+        }
+    }
+
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+
+    rows
+}
+
+/// フィクスチャ未指定時のデモ用シーケンス
+fn demo_events() -> Vec<ReplayEvent> {
+    vec![
+        ReplayEvent {
+            process_name: String::from("chrome.exe"),
+            window_title: String::from("GitHub - Demo Page"),
+            domain: Some(String::from("github.com")),
+            duration_seconds: 1,
+            company_name: Some(String::from("Google LLC")),
+            product_name: Some(String::from("Google Chrome")),
+            file_version: None,
+            idle: false,
+        },
+        ReplayEvent {
+            process_name: String::from("chrome.exe"),
+            window_title: String::from("Google Search"),
+            domain: Some(String::from("google.com")),
+            duration_seconds: 1,
+            company_name: Some(String::from("Google LLC")),
+            product_name: Some(String::from("Google Chrome")),
+            file_version: None,
+            idle: false,
+        },
+        ReplayEvent {
+            process_name: String::from("Code.exe"),
+            window_title: String::from("lib.rs - timetracker"),
+            domain: None,
+            duration_seconds: 1,
+            company_name: Some(String::from("Microsoft Corporation")),
+            product_name: Some(String::from("Visual Studio Code")),
+            file_version: None,
+            idle: false,
+        },
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(process_name: &str, duration_seconds: u64, idle: bool) -> ReplayEvent {
+        ReplayEvent {
+            process_name: process_name.to_string(),
+            window_title: String::new(),
+            domain: None,
+            duration_seconds,
+            company_name: None,
+            product_name: None,
+            file_version: None,
+            idle,
+        }
+    }
+
+    fn replay(events: Vec<ReplayEvent>, looping: bool) -> ReplaySource {
+        ReplaySource {
+            events,
+            index: 0,
+            elapsed: 0,
+            looping,
+            current: None,
+            idle_elapsed: 0,
+        }
+    }
+
+    fn write_fixture(name: &str, content: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "timetracker-replay-{}-{}",
+            std::process::id(),
+            name
+        ));
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn parse_csv_handles_quotes_and_crlf() {
+        let rows = parse_csv("a,b\r\n\"x, \"\"y\"\"\",\"line\nbreak\"\nlast,\n");
+        assert_eq!(
+            rows,
+            vec![
+                vec!["a", "b"],
+                vec!["x, \"y\"", "line\nbreak"],
+                vec!["last", ""],
+            ]
+        );
+    }
+
+    #[test]
+    fn csv_fixture_maps_columns_by_header() {
+        let events = parse_csv_fixture(
+            "start_time,domain,window_title,process_name,duration_seconds,idle\n\
+             2024-01-01T09:00:00,github.com,PR,chrome.exe,5,\n\
+             ,,,,,\n\
+             2024-01-01T09:05:00,,,Code.exe,abc,true\n",
+        )
+        .unwrap();
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].process_name, "chrome.exe");
+        assert_eq!(events[0].window_title, "PR");
+        assert_eq!(events[0].domain.as_deref(), Some("github.com"));
+        assert_eq!(events[0].duration_seconds, 5);
+        assert!(!events[0].idle);
+        assert_eq!(events[1].domain, None);
+        assert_eq!(events[1].duration_seconds, 1);
+        assert!(events[1].idle);
+    }
+
+    #[test]
+    fn csv_fixture_requires_process_and_title() {
+        assert!(parse_csv_fixture("").is_err());
+        assert!(parse_csv_fixture("window_title\nfoo\n").is_err());
+        assert!(parse_csv_fixture("process_name\nfoo\n").is_err());
+    }
+
+    #[test]
+    fn load_fixture_picks_format_by_extension() {
+        let json = write_fixture(
+            "events.json",
+            r#"[{"process_name": "Slack", "window_title": "general", "idle": true}]"#,
+        );
+        let csv = write_fixture("events.CSV", "process_name,window_title\nSlack,general\n");
+
+        let from_json = load_fixture(&json).unwrap();
+        let from_csv = load_fixture(&csv).unwrap();
+        std::fs::remove_file(json).unwrap();
+        std::fs::remove_file(csv).unwrap();
+
+        assert_eq!(from_json.len(), 1);
+        assert_eq!(from_json[0].duration_seconds, 1);
+        assert!(from_json[0].idle);
+        assert_eq!(from_csv.len(), 1);
+        assert_eq!(from_csv[0].window_title, "general");
+    }
+
+    #[test]
+    fn source_holds_each_event_for_its_duration() {
+        let mut source = replay(vec![event("a", 2, false), event("b", 0, false)], false);
+        let played: Vec<_> =
+            std::iter::from_fn(|| source.next().map(|e| e.process_name.clone())).collect();
+        assert_eq!(played, ["a", "a", "b"]);
+        assert_eq!(source.current, Some(1));
+    }
+
+    #[test]
+    fn source_loops_back_to_the_start() {
+        let mut source = replay(vec![event("a", 1, false), event("b", 1, false)], true);
+        let played: Vec<_> = (0..5)
+            .map(|_| source.next().unwrap().process_name.clone())
+            .collect();
+        assert_eq!(played, ["a", "b", "a", "b", "a"]);
+    }
+
+    #[test]
+    fn source_counts_consecutive_idle_seconds() {
+        let mut source = replay(vec![event("a", 2, true), event("b", 1, false)], true);
+        source.next();
+        source.next();
+        assert_eq!(source.idle_elapsed, 2);
+        source.next();
+        assert_eq!(source.idle_elapsed, 0);
+        source.next();
+        assert_eq!(source.idle_elapsed, 1);
+    }
+
+    #[test]
+    fn empty_source_returns_nothing() {
+        let mut source = replay(Vec::new(), true);
+        assert!(source.next().is_none());
+        assert!(source.current.is_none());
+    }
+}
@@ -0,0 +1,265 @@
+use chrono::{Duration, Local, NaiveDate};
+use rusqlite::Connection;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::budgets::week_start;
+use crate::escape_html;
+use crate::summary::{self, GroupBy, RangeSummary};
+use crate::{categories, goals, timeline};
+
+/// レポートの HTML テンプレート
+const TEMPLATE: &str = include_str!("../templates/report.html");
+
+/// 上位アプリとして表示する件数
+const TOP_APPS: usize = 10;
+
+/// カテゴリに色が設定されていないときの配色
+const PALETTE: &[&str] = &[
+    "#e94560", "#4ade80", "#60a5fa", "#fbbf24", "#a78bfa", "#f472b6", "#34d399", "#fb923c",
+];
+
+/// レポートの対象期間
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportPeriod {
+    Daily,
+    Weekly,
+}
+
+impl ReportPeriod {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s {
+            "daily" => Ok(ReportPeriod::Daily),
+            "weekly" => Ok(ReportPeriod::Weekly),
+            _ => Err(format!(
+                "Unknown report period: {} (expected daily or weekly)",
+                s
+            )),
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            ReportPeriod::Daily => "daily",
+            ReportPeriod::Weekly => "weekly",
+        }
+    }
+
+    /// 対象日を含む期間（週は月曜始まり）
+    fn range(self, date: NaiveDate) -> (NaiveDate, NaiveDate) {
+        match self {
+            ReportPeriod::Daily => (date, date),
+            ReportPeriod::Weekly => {
+                let start = week_start(date);
+                (start, start + Duration::days(6))
+            }
+        }
+    }
+}
+
+/// レポートの保存先
+pub fn reports_dir() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("timetracker")
+        .join("reports")
+}
+
+fn format_duration(seconds: i64) -> String {
+    let hours = seconds / 3600;
+    let minutes = (seconds % 3600) / 60;
+    if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+/// "2024-01-15T09:30:00" → "09:30"
+fn format_time(s: &str) -> &str {
+    s.get(11..16).unwrap_or(s)
+}
+
+/// 単体で開ける HTML のレポートを作成（PDF が必要な場合はブラウザから印刷する）
+pub fn render(conn: &Connection, period: ReportPeriod, date: NaiveDate) -> Result<String, String> {
+    let (start, end) = period.range(date);
+    let group_by = match period {
+        ReportPeriod::Daily => GroupBy::Day,
+        ReportPeriod::Weekly => GroupBy::Week,
+    };
+    let range = summary::range_summary(conn, start, end, group_by)?;
+
+    let title = match period {
+        ReportPeriod::Daily => format!("Daily report {}", start),
+        ReportPeriod::Weekly => format!("Weekly report {} - {}", start, end),
+    };
+
+    // 日次は区間ごとのタイムライン、週次は日ごとの合計
+    let (timeline_heading, timeline) = match period {
+        ReportPeriod::Daily => ("Timeline", render_timeline(conn, &start.to_string())?),
+        ReportPeriod::Weekly => ("Daily totals", render_daily_totals(&range)),
+    };
+    let goals = render_goals(conn, end.min(Local::now().date_naive()))?;
+
+    // 差し込む値は各 render_* でエスケープ済み
+    let html = TEMPLATE
+        .replace("{{title}}", &escape_html(&title))
+        .replace("{{total}}", &format_duration(range.total_seconds))
+        .replace("{{apps}}", &render_apps(&range))
+        .replace("{{categories}}", &render_categories(conn, &range)?)
+        .replace("{{timeline_heading}}", timeline_heading)
+        .replace("{{timeline}}", &timeline)
+        .replace("{{goals}}", &goals)
+        .replace(
+            "{{generated_at}}",
+            &Local::now().format("%Y-%m-%d %H:%M").to_string(),
+        );
+    Ok(html)
+}
+
+/// レポートを reports フォルダに書き出してパスを返す
+pub fn write(conn: &Connection, period: ReportPeriod, date: NaiveDate) -> Result<PathBuf, String> {
+    let html = render(conn, period, date)?;
+    let (start, _) = period.range(date);
+
+    let dir = reports_dir();
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let path = dir.join(format!("{}-{}.html", period.as_str(), start));
+    std::fs::write(&path, html).map_err(|e| e.to_string())?;
+    Ok(path)
+}
+
+fn render_apps(range: &RangeSummary) -> String {
+    if range.apps.is_empty() {
+        return "<p class=\"muted\">No activity recorded</p>".to_string();
+    }
+    let rows: String = range
+        .apps
+        .iter()
+        .take(TOP_APPS)
+        .map(|app| {
+            format!(
+                "<tr><td>{}</td><td style=\"width:40%\"><div class=\"bar\" style=\"width:{:.1}%\"></div></td><td class=\"num\">{}</td><td class=\"num\">{:.1}%</td></tr>\n",
+                escape_html(&app.process_name),
+                app.percentage,
+                format_duration(app.total_seconds),
+                app.percentage
+            )
+        })
+        .collect();
+    format!("<table>\n{}</table>", rows)
+}
+
+/// カテゴリの割合を円グラフ（conic-gradient）と凡例で表示
+fn render_categories(conn: &Connection, range: &RangeSummary) -> Result<String, String> {
+    if range.categories.is_empty() {
+        return Ok("<p class=\"muted\">No categorized activity</p>".to_string());
+    }
+
+    let colors: HashMap<String, String> = categories::list(conn)?
+        .into_iter()
+        .filter_map(|c| c.color.map(|color| (c.name, color)))
+        .collect();
+
+    let mut stops = Vec::new();
+    let mut legend = String::new();
+    let mut offset = 0.0;
+    for (i, category) in range.categories.iter().enumerate() {
+        let color = colors
+            .get(&category.category)
+            .map(String::as_str)
+            .unwrap_or(PALETTE[i % PALETTE.len()]);
+        let next = offset + category.percentage;
+        stops.push(format!("{} {:.2}% {:.2}%", color, offset, next));
+        offset = next;
+
+        legend.push_str(&format!(
+            "<tr><td><span class=\"swatch\" style=\"background:{}\"></span>{}</td><td class=\"num\">{}</td><td class=\"num\">{:.1}%</td></tr>\n",
+            escape_html(color),
+            escape_html(&category.category),
+            format_duration(category.total_seconds),
+            category.percentage
+        ));
+    }
+
+    Ok(format!(
+        "<div class=\"categories\"><div class=\"pie\" style=\"background: conic-gradient({})\"></div><table>\n{}</table></div>",
+        escape_html(&stops.join(", ")),
+        legend
+    ))
+}
+
+fn render_timeline(conn: &Connection, date: &str) -> Result<String, String> {
+    let segments = timeline::build(conn, date, timeline::DEFAULT_MERGE_THRESHOLD_SECONDS)?;
+    if segments.is_empty() {
+        return Ok("<p class=\"muted\">No activity recorded</p>".to_string());
+    }
+
+    let rows: String = segments
+        .iter()
+        .map(|segment| {
+            let label = match segment.kind.as_str() {
+                timeline::KIND_ACTIVITY => escape_html(segment.process_name.as_deref().unwrap_or("")),
+                kind => format!("<span class=\"muted\">{}</span>", kind),
+            };
+            format!(
+                "<tr><td class=\"num\">{} - {}</td><td>{}</td><td>{}</td><td class=\"num\">{}</td></tr>\n",
+                format_time(&segment.start_time),
+                format_time(&segment.end_time),
+                label,
+                escape_html(segment.window_title.as_deref().unwrap_or("")),
+                format_duration(segment.duration_seconds)
+            )
+        })
+        .collect();
+    Ok(format!("<table>\n{}</table>", rows))
+}
+
+fn render_daily_totals(range: &RangeSummary) -> String {
+    let max = range
+        .daily_totals
+        .iter()
+        .map(|d| d.total_seconds)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let rows: String = range
+        .daily_totals
+        .iter()
+        .map(|day| {
+            format!(
+                "<tr><td>{}</td><td style=\"width:60%\"><div class=\"bar\" style=\"width:{:.1}%\"></div></td><td class=\"num\">{}</td></tr>\n",
+                escape_html(&day.date),
+                day.total_seconds as f64 / max as f64 * 100.0,
+                format_duration(day.total_seconds)
+            )
+        })
+        .collect();
+    format!("<table>\n{}</table>", rows)
+}
+
+fn render_goals(conn: &Connection, date: NaiveDate) -> Result<String, String> {
+    let progress = goals::progress(conn, date)?;
+    if progress.is_empty() {
+        return Ok("<p class=\"muted\">No goals configured</p>".to_string());
+    }
+
+    let rows: String = progress
+        .iter()
+        .map(|p| {
+            format!(
+                "<tr><td>{}</td><td>{} {}</td><td class=\"num\">{} / {}</td><td>{}</td></tr>\n",
+                escape_html(&p.goal.category),
+                escape_html(&p.goal.period),
+                escape_html(&p.goal.kind),
+                format_duration(p.tracked_seconds),
+                format_duration(p.target_seconds),
+                escape_html(&p.status)
+            )
+        })
+        .collect();
+    Ok(format!(
+        "<table>\n<tr><th>Category</th><th>Goal</th><th class=\"num\">Tracked / target</th><th>Status</th></tr>\n{}</table>",
+        rows
+    ))
+}
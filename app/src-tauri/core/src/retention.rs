@@ -0,0 +1,151 @@
+use chrono::{DateTime, Duration, Local};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use crate::plugins::config::RetentionConfig;
+
+/// 保持期間を過ぎた生データの集計・削除結果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionReport {
+    pub dry_run: bool,
+    /// この日付より前の生データが対象
+    pub cutoff_date: String,
+    /// 集計した日数
+    pub days: usize,
+    /// 削除した（dry_run なら削除される）アクティビティ件数
+    pub removed_activities: usize,
+}
+
+/// 日別の集計（生データ削除後も残る）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailySummary {
+    pub date: String,
+    pub process_name: String,
+    pub domain: Option<String>,
+    pub category: Option<String>,
+    pub project: Option<String>,
+    pub total_seconds: i64,
+    pub activity_count: i64,
+}
+
+pub fn create_tables(conn: &Connection) -> rusqlite::Result<()> {
+    // 主キーに NULL を含められないため、未設定の列は空文字で保存する
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS daily_summaries (
+            date TEXT NOT NULL,
+            process_name TEXT NOT NULL,
+            domain TEXT NOT NULL DEFAULT '',
+            category TEXT NOT NULL DEFAULT '',
+            project TEXT NOT NULL DEFAULT '',
+            total_seconds INTEGER NOT NULL,
+            activity_count INTEGER NOT NULL,
+            PRIMARY KEY (date, process_name, domain, category, project)
+        )",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// 保持期間より古い日のアクティビティを daily_summaries に集計して削除（dry_run で件数のみ）
+///
+/// 日の途中で区切らないよう、判定は開始日単位で行う。割り当ても一緒に削除する。
+pub fn prune(
+    conn: &Connection,
+    config: &RetentionConfig,
+    now: DateTime<Local>,
+    dry_run: bool,
+) -> Result<RetentionReport, String> {
+    let cutoff_date = (now - Duration::days(config.raw_days as i64))
+        .format("%Y-%m-%d")
+        .to_string();
+    let cutoff = format!("{}T00:00:00", cutoff_date);
+
+    let (removed, days): (i64, i64) = conn
+        .query_row(
+            "SELECT COUNT(*), COUNT(DISTINCT substr(start_time, 1, 10))
+             FROM activities WHERE start_time < ?1",
+            params![cutoff],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .map_err(|e| e.to_string())?;
+
+    if !dry_run && removed > 0 {
+        let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+
+        // 以前の実行で集計済みの日に遅れて入ったデータがあっても加算する
+        tx.execute(
+            "INSERT INTO daily_summaries
+                (date, process_name, domain, category, project, total_seconds, activity_count)
+             SELECT substr(a.start_time, 1, 10), a.process_name, COALESCE(a.domain, ''),
+                    COALESCE(a.category, ''), COALESCE(t.project, ''),
+                    SUM(a.duration_seconds), COUNT(*)
+             FROM activities a
+             LEFT JOIN ticket_assignments t ON t.activity_id = a.id
+             WHERE a.start_time < ?1
+             GROUP BY 1, 2, 3, 4, 5
+             ON CONFLICT(date, process_name, domain, category, project) DO UPDATE SET
+                total_seconds = total_seconds + excluded.total_seconds,
+                activity_count = activity_count + excluded.activity_count",
+            params![cutoff],
+        )
+        .map_err(|e| e.to_string())?;
+
+        tx.execute(
+            "DELETE FROM ticket_assignments
+             WHERE activity_id IN (SELECT id FROM activities WHERE start_time < ?1)",
+            params![cutoff],
+        )
+        .map_err(|e| e.to_string())?;
+
+        tx.execute(
+            "DELETE FROM activities WHERE start_time < ?1",
+            params![cutoff],
+        )
+        .map_err(|e| e.to_string())?;
+
+        tx.commit().map_err(|e| e.to_string())?;
+    }
+
+    Ok(RetentionReport {
+        dry_run,
+        cutoff_date,
+        days: days as usize,
+        removed_activities: removed as usize,
+    })
+}
+
+/// 期間内の日別集計を取得
+pub fn daily_summaries(
+    conn: &Connection,
+    start_date: &str,
+    end_date: &str,
+) -> Result<Vec<DailySummary>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT date, process_name, domain, category, project, total_seconds, activity_count
+             FROM daily_summaries
+             WHERE date >= ?1 AND date <= ?2
+             ORDER BY date ASC, total_seconds DESC",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let non_empty = |s: String| Some(s).filter(|s| !s.is_empty());
+    let summaries = stmt
+        .query_map(params![start_date, end_date], |row| {
+            Ok(DailySummary {
+                date: row.get(0)?,
+                process_name: row.get(1)?,
+                domain: non_empty(row.get(2)?),
+                category: non_empty(row.get(3)?),
+                project: non_empty(row.get(4)?),
+                total_seconds: row.get(5)?,
+                activity_count: row.get(6)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(summaries)
+}
@@ -0,0 +1,435 @@
+use regex::Regex;
+use rusqlite::{params, Connection};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::assignments::{self, RuleAssigner, SOURCE_PROJECT, SOURCE_RULE};
+use crate::categories::Categorizer;
+use crate::normalize::TitleNormalizer;
+use crate::plugins::config::{
+    AssignmentRule, CategoryRule, ExtractionRule, IntegrationConfig, IntegrationsConfig,
+};
+use crate::plugins::integrations::CompiledRule;
+use crate::plugins::traits::ActivityInfo;
+use crate::plugins::PluginManager;
+use crate::projects::ProjectMatcher;
+
+/// 正規表現パターンを持つルール
+trait PatternRule: Serialize + DeserializeOwned {
+    fn pattern(&self) -> &str;
+}
+
+impl PatternRule for CategoryRule {
+    fn pattern(&self) -> &str {
+        &self.pattern
+    }
+}
+
+impl PatternRule for AssignmentRule {
+    fn pattern(&self) -> &str {
+        &self.pattern
+    }
+}
+
+impl PatternRule for ExtractionRule {
+    fn pattern(&self) -> &str {
+        &self.pattern
+    }
+}
+
+/// ルールの種類
+///
+/// - "category": カテゴリ分類ルール
+/// - "assignment": チケット/プロジェクトの割り当てルール
+/// - "extraction": 連携プラグインのチケットID抽出ルール（integration で対象を指定）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleKind {
+    Category,
+    Assignment,
+    Extraction,
+}
+
+/// ルールに対する操作
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum RuleOp {
+    /// position を省略すると末尾に追加
+    Create {
+        rule: Value,
+        #[serde(default)]
+        position: Option<usize>,
+    },
+    Update {
+        index: usize,
+        rule: Value,
+    },
+    Delete {
+        index: usize,
+    },
+    /// order は現在のインデックスを新しい並び順で列挙したもの
+    Reorder {
+        order: Vec<usize>,
+    },
+}
+
+fn parse_rule<T: PatternRule>(rule: Value) -> Result<T, String> {
+    let rule: T = serde_json::from_value(rule).map_err(|e| format!("Invalid rule: {}", e))?;
+    Regex::new(rule.pattern()).map_err(|e| format!("Invalid pattern: {}", e))?;
+    Ok(rule)
+}
+
+fn apply_op<T: PatternRule>(rules: &mut Vec<T>, op: RuleOp) -> Result<(), String> {
+    let out_of_range = |index: usize| format!("Rule index out of range: {}", index);
+
+    match op {
+        RuleOp::Create { rule, position } => {
+            let rule = parse_rule(rule)?;
+            let position = position.unwrap_or(rules.len());
+            if position > rules.len() {
+                return Err(out_of_range(position));
+            }
+            rules.insert(position, rule);
+        }
+        RuleOp::Update { index, rule } => {
+            let rule = parse_rule(rule)?;
+            let slot = rules.get_mut(index).ok_or_else(|| out_of_range(index))?;
+            *slot = rule;
+        }
+        RuleOp::Delete { index } => {
+            if index >= rules.len() {
+                return Err(out_of_range(index));
+            }
+            rules.remove(index);
+        }
+        RuleOp::Reorder { order } => {
+            let mut sorted = order.clone();
+            sorted.sort_unstable();
+            if sorted != (0..rules.len()).collect::<Vec<_>>() {
+                return Err("Reorder must list every rule index exactly once".to_string());
+            }
+
+            let mut taken: Vec<Option<T>> = rules.drain(..).map(Some).collect();
+            for index in order {
+                if let Some(rule) = taken[index].take() {
+                    rules.push(rule);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn find_integration<'a>(
+    config: &'a IntegrationsConfig,
+    integration: Option<&str>,
+) -> Result<&'a IntegrationConfig, String> {
+    let name = integration.ok_or_else(|| "Integration name is required".to_string())?;
+    config
+        .integrations
+        .iter()
+        .find(|e| e.name == name)
+        .map(|e| &e.config)
+        .ok_or_else(|| format!("Integration not found: {}", name))
+}
+
+fn extraction_rules_mut<'a>(
+    config: &'a mut IntegrationsConfig,
+    integration: Option<&str>,
+) -> Result<&'a mut Vec<ExtractionRule>, String> {
+    let name = integration.ok_or_else(|| "Integration name is required".to_string())?;
+    let entry = config
+        .integrations
+        .iter_mut()
+        .find(|e| e.name == name)
+        .ok_or_else(|| format!("Integration not found: {}", name))?;
+
+    Ok(entry.config.rules_mut())
+}
+
+/// ルール一覧を取得
+pub fn list(
+    config: &IntegrationsConfig,
+    kind: RuleKind,
+    integration: Option<&str>,
+) -> Result<Value, String> {
+    let value = match kind {
+        RuleKind::Category => serde_json::to_value(&config.categories),
+        RuleKind::Assignment => serde_json::to_value(&config.assignment_rules),
+        RuleKind::Extraction => {
+            serde_json::to_value(find_integration(config, integration)?.rules())
+        }
+    };
+    value.map_err(|e| e.to_string())
+}
+
+/// ルールを編集（呼び出し側で設定ファイルの保存と再読み込みを行う）
+pub fn apply(
+    config: &mut IntegrationsConfig,
+    kind: RuleKind,
+    integration: Option<&str>,
+    op: RuleOp,
+) -> Result<(), String> {
+    match kind {
+        RuleKind::Category => apply_op(&mut config.categories, op),
+        RuleKind::Assignment => apply_op(&mut config.assignment_rules, op),
+        RuleKind::Extraction => apply_op(extraction_rules_mut(config, integration)?, op),
+    }
+}
+
+/// ルール再適用の結果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ApplyRulesReport {
+    /// 対象期間のアクティビティ件数
+    pub scanned: usize,
+    /// タイトルの正規化結果が変わった件数
+    pub renormalized: usize,
+    /// カテゴリが変わった件数
+    pub recategorized: usize,
+    /// ルール・プロジェクト検出による割り当てが変わった件数（手動割り当ては変更しない）
+    pub reassigned: usize,
+}
+
+/// 再適用に使うルール一式
+pub struct RuleSet<'a> {
+    pub normalizer: &'a TitleNormalizer,
+    pub categorizer: &'a Categorizer,
+    pub assigner: &'a RuleAssigner,
+    pub projects: &'a ProjectMatcher,
+}
+
+/// 既存のアクティビティにタイトル正規化・カテゴリ分類・割り当てルール・プロジェクト定義を再適用する
+///
+/// 記録時と同じ順に適用する。タイトルは正規化前のタイトル（raw_window_title）から正規化し直す。
+pub fn reapply(
+    conn: &Connection,
+    rules: &RuleSet,
+    start_date: &str,
+    end_date: &str,
+) -> Result<ApplyRulesReport, String> {
+    let range_start = format!("{}T00:00:00", start_date);
+    let range_end = format!("{}T23:59:59", end_date);
+
+    type Row = (
+        i64,
+        String,
+        String,
+        String,
+        Option<String>,
+        Option<String>,
+        Option<String>,
+        Option<String>,
+        Option<String>,
+        Option<String>,
+    );
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT a.id, a.process_name, a.window_title,
+                    COALESCE(a.raw_window_title, a.window_title), a.domain, a.category,
+                    t.source, t.plugin, t.ticket_id, t.project
+             FROM activities a
+             LEFT JOIN ticket_assignments t ON t.activity_id = a.id
+             WHERE a.start_time >= ?1 AND a.start_time <= ?2",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows: Vec<Row> = stmt
+        .query_map(params![range_start, range_end], |row| {
+            Ok((
+                row.get(0)?,
+                row.get(1)?,
+                row.get(2)?,
+                row.get(3)?,
+                row.get(4)?,
+                row.get(5)?,
+                row.get(6)?,
+                row.get(7)?,
+                row.get(8)?,
+                row.get(9)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let mut report = ApplyRulesReport {
+        scanned: rows.len(),
+        ..Default::default()
+    };
+
+    // 途中で失敗した場合は変更を破棄する（Transaction のドロップ時にロールバック）
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+
+    for (
+        id,
+        process_name,
+        window_title,
+        raw_title,
+        domain,
+        category,
+        source,
+        plugin,
+        ticket_id,
+        project,
+    ) in rows
+    {
+        let domain = domain.as_deref();
+
+        let normalized = rules.normalizer.normalize(&process_name, &raw_title);
+        if normalized != window_title {
+            let raw = (normalized != raw_title).then_some(raw_title.as_str());
+            tx.execute(
+                // タイトルが変わるのでチケットは抽出し直す
+                "UPDATE activities
+                 SET window_title = ?1, raw_window_title = ?2,
+                     extracted_plugin = NULL, extracted_ticket_id = NULL, extracted_at = NULL
+                 WHERE id = ?3",
+                params![normalized, raw, id],
+            )
+            .map_err(|e| e.to_string())?;
+            report.renormalized += 1;
+        }
+        let window_title = normalized;
+
+        let new_category = rules
+            .categorizer
+            .classify(&process_name, &window_title, domain);
+        if new_category != category {
+            tx.execute(
+                "UPDATE activities SET category = ?1 WHERE id = ?2",
+                params![new_category, id],
+            )
+            .map_err(|e| e.to_string())?;
+            report.recategorized += 1;
+        }
+
+        // 手動・上書きなど自動判定以外の割り当ては尊重する
+        if source
+            .as_deref()
+            .is_some_and(|s| s != SOURCE_RULE && s != SOURCE_PROJECT)
+        {
+            continue;
+        }
+
+        // 記録時と同じく、割り当てルール > プロジェクトの検出の順に適用する
+        let current = source.map(|source| (plugin, ticket_id, project, source));
+        let detected_project = rules.projects.detect(&window_title, domain);
+        let matched = rules
+            .assigner
+            .find_match(&process_name, &window_title, domain)
+            .map(|rule| {
+                (
+                    rule.plugin,
+                    rule.ticket_id,
+                    rule.project.or_else(|| detected_project.clone()),
+                    SOURCE_RULE.to_string(),
+                )
+            })
+            .or_else(|| {
+                detected_project
+                    .clone()
+                    .map(|project| (None, None, Some(project), SOURCE_PROJECT.to_string()))
+            });
+
+        if matched == current {
+            continue;
+        }
+
+        match &matched {
+            Some((plugin, ticket_id, project, source)) => {
+                assignments::assign(
+                    &tx,
+                    id,
+                    plugin.as_deref(),
+                    ticket_id.as_deref(),
+                    project.as_deref(),
+                    source,
+                )?;
+            }
+            None => assignments::unassign(&tx, id)?,
+        }
+        report.reassigned += 1;
+    }
+
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(report)
+}
+
+/// 抽出ルール1件の試行結果
+#[derive(Debug, Clone, Serialize)]
+pub struct ExtractionRuleTest {
+    pub integration: String,
+    pub enabled: bool,
+    /// 連携の rules の中での位置
+    pub rule_index: usize,
+    pub pattern: String,
+    /// マッチした抽出元（マッチしなければ None）
+    pub matched_source: Option<String>,
+    /// 組み立てたチケットID（マッチしてもIDが空なら None）
+    pub ticket_id: Option<String>,
+    /// パターンをコンパイルできない場合のエラー
+    pub error: Option<String>,
+}
+
+/// 抽出ルールの試行結果
+#[derive(Debug, Clone, Serialize)]
+pub struct ExtractionTestReport {
+    /// 正規化ルールを当てた後のタイトル（抽出ルールはこれに当てる）
+    pub normalized_title: String,
+    /// 設定されたすべての抽出ルール（無効な連携のルールも含む）
+    pub rules: Vec<ExtractionRuleTest>,
+    /// 読み込み済みのプラグインごとの抽出結果 (plugin, ticket_id)。GitHub のリポジトリ補完や WASM プラグインも含む
+    pub plugins: Vec<(String, String)>,
+    /// 記録時に採用される結果（最初にマッチしたプラグイン）
+    pub selected: Option<(String, String)>,
+}
+
+/// サンプルのタイトル・プロセス名・ドメインに抽出ルールを当てる（設定は変更しない）
+///
+/// 記録時と同じく、タイトルは正規化してから抽出する。
+pub fn test_extraction(
+    config: &IntegrationsConfig,
+    plugin_manager: &PluginManager,
+    normalizer: &TitleNormalizer,
+    activity: &ActivityInfo,
+) -> ExtractionTestReport {
+    let activity = &ActivityInfo {
+        window_title: normalizer.normalize(&activity.process_name, &activity.window_title),
+        ..activity.clone()
+    };
+    let mut rules = Vec::new();
+    for entry in &config.integrations {
+        for (i, rule) in entry.config.rules().iter().enumerate() {
+            let mut result = ExtractionRuleTest {
+                integration: entry.name.clone(),
+                enabled: entry.enabled,
+                rule_index: i,
+                pattern: rule.pattern.clone(),
+                matched_source: None,
+                ticket_id: None,
+                error: None,
+            };
+            match CompiledRule::new(rule) {
+                Ok(compiled) => {
+                    if let Some((source, captures)) = compiled.find(activity) {
+                        result.matched_source = Some(source.to_string());
+                        result.ticket_id = compiled.ticket_id(&captures);
+                    }
+                }
+                Err(e) => result.error = Some(e.to_string()),
+            }
+            rules.push(result);
+        }
+    }
+
+    let plugins = plugin_manager.extract_all_ticket_ids(activity);
+    ExtractionTestReport {
+        normalized_title: activity.window_title.clone(),
+        rules,
+        selected: plugins.first().cloned(),
+        plugins,
+    }
+}
@@ -0,0 +1,36 @@
+use std::future::Future;
+use std::sync::OnceLock;
+use tokio::runtime::{Handle, Runtime};
+use tokio::task::JoinHandle;
+
+static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+
+/// 同期・アップロード・Webhook などの非同期処理を動かす tokio ランタイム
+///
+/// GUI は同じランタイムを Tauri にも使わせる（crate::runtime::set）。
+pub fn handle() -> &'static Handle {
+    RUNTIME
+        .get_or_init(|| Runtime::new().expect("Failed to start the tokio runtime"))
+        .handle()
+}
+
+pub fn spawn<F>(future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    handle().spawn(future)
+}
+
+pub fn spawn_blocking<F, R>(f: F) -> JoinHandle<R>
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    handle().spawn_blocking(f)
+}
+
+/// 非同期処理の完了を待つ（ランタイムのワーカーからは呼ばない）
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    handle().block_on(future)
+}
@@ -0,0 +1,166 @@
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use crate::ActivityRecord;
+
+/// 1ページの既定の件数
+pub const DEFAULT_LIMIT: u32 = 100;
+/// 1ページの最大件数
+pub const MAX_LIMIT: u32 = 1000;
+
+/// 並び順
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchSort {
+    /// 新しい順
+    #[default]
+    Newest,
+    Oldest,
+    /// 長い順
+    Longest,
+    Shortest,
+}
+
+impl SearchSort {
+    fn order_by(self) -> &'static str {
+        match self {
+            SearchSort::Newest => "a.start_time DESC, a.id DESC",
+            SearchSort::Oldest => "a.start_time ASC, a.id ASC",
+            SearchSort::Longest => "a.duration_seconds DESC, a.start_time DESC",
+            SearchSort::Shortest => "a.duration_seconds ASC, a.start_time DESC",
+        }
+    }
+}
+
+/// 検索条件（指定した条件をすべて満たすアクティビティ）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SearchQuery {
+    /// ウィンドウタイトルまたはメモに含まれる文字列（大文字小文字を区別しない）
+    pub text: Option<String>,
+    pub process_name: Option<String>,
+    pub category: Option<String>,
+    /// 割り当てたプロジェクト
+    pub project: Option<String>,
+    pub domain: Option<String>,
+    /// "2024-01-15"（この日を含む）
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+    pub min_duration_seconds: Option<i64>,
+    pub sort: SearchSort,
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+/// 検索結果の1ページ
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchPage {
+    /// 条件に一致した全件数
+    pub total: i64,
+    pub limit: u32,
+    pub offset: u32,
+    pub activities: Vec<ActivityRecord>,
+}
+
+/// LIKE 用にワイルドカードをエスケープして部分一致のパターンにする
+fn like_pattern(text: &str) -> String {
+    let escaped = text
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{}%", escaped)
+}
+
+const FILTER: &str = "FROM activities a
+     LEFT JOIN ticket_assignments t ON t.activity_id = a.id
+     WHERE (?1 IS NULL OR a.window_title LIKE ?1 ESCAPE '\\' OR a.note LIKE ?1 ESCAPE '\\')
+       AND (?2 IS NULL OR a.process_name = ?2)
+       AND (?3 IS NULL OR a.category = ?3)
+       AND (?4 IS NULL OR t.project = ?4)
+       AND (?5 IS NULL OR a.domain = ?5)
+       AND (?6 IS NULL OR a.start_time >= ?6)
+       AND (?7 IS NULL OR a.start_time <= ?7)
+       AND a.duration_seconds >= ?8";
+
+pub fn search(conn: &Connection, query: &SearchQuery) -> Result<SearchPage, String> {
+    let text = query
+        .text
+        .as_deref()
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(like_pattern);
+    let start = query.start_date.as_ref().map(|d| format!("{}T00:00:00", d));
+    let end = query.end_date.as_ref().map(|d| format!("{}T23:59:59", d));
+    let min_duration = query.min_duration_seconds.unwrap_or(0);
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let offset = query.offset.unwrap_or(0);
+
+    let filter_params = params![
+        text,
+        query.process_name,
+        query.category,
+        query.project,
+        query.domain,
+        start,
+        end,
+        min_duration
+    ];
+
+    let total: i64 = conn
+        .query_row(
+            &format!("SELECT COUNT(*) {}", FILTER),
+            filter_params,
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT a.id, a.process_name, a.window_title, a.domain, a.start_time, a.end_time,
+                    a.duration_seconds, a.note
+             {}
+             ORDER BY {}
+             LIMIT ?9 OFFSET ?10",
+            FILTER,
+            query.sort.order_by()
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let activities = stmt
+        .query_map(
+            params![
+                text,
+                query.process_name,
+                query.category,
+                query.project,
+                query.domain,
+                start,
+                end,
+                min_duration,
+                limit,
+                offset
+            ],
+            |row| {
+                Ok(ActivityRecord {
+                    id: row.get(0)?,
+                    process_name: row.get(1)?,
+                    window_title: row.get(2)?,
+                    domain: row.get(3)?,
+                    start_time: row.get(4)?,
+                    end_time: row.get(5)?,
+                    duration_seconds: row.get(6)?,
+                    note: row.get(7)?,
+                })
+            },
+        )
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(SearchPage {
+        total,
+        limit,
+        offset,
+        activities,
+    })
+}
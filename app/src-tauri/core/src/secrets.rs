@@ -0,0 +1,50 @@
+use keyring::Entry;
+
+/// OS のキーリングに保存するときのサービス名
+const SERVICE: &str = "timetracker";
+
+fn entry(name: &str) -> Result<Entry, String> {
+    Entry::new(SERVICE, name).map_err(|e| format!("Keyring error: {}", e))
+}
+
+/// 連携の認証情報のキーリング上の名前
+fn integration_key(alias: &str) -> String {
+    format!("integration:{}", alias)
+}
+
+/// 連携の認証情報を取得（未登録なら None）
+pub fn get_integration(alias: &str) -> Result<Option<String>, String> {
+    get(&integration_key(alias))
+}
+
+pub fn set_integration(alias: &str, value: &str) -> Result<(), String> {
+    set(&integration_key(alias), value)
+}
+
+pub fn delete_integration(alias: &str) -> Result<(), String> {
+    delete(&integration_key(alias))
+}
+
+/// シークレットを取得（未登録なら None）
+pub fn get(name: &str) -> Result<Option<String>, String> {
+    match entry(name)?.get_password() {
+        Ok(value) => Ok(Some(value)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(format!("Keyring error: {}", e)),
+    }
+}
+
+/// シークレットを保存（既にあれば上書き）
+pub fn set(name: &str, value: &str) -> Result<(), String> {
+    entry(name)?
+        .set_password(value)
+        .map_err(|e| format!("Keyring error: {}", e))
+}
+
+/// シークレットを削除（未登録なら何もしない）
+pub fn delete(name: &str) -> Result<(), String> {
+    match entry(name)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(format!("Keyring error: {}", e)),
+    }
+}
@@ -0,0 +1,487 @@
+use chrono::NaiveTime;
+use regex::Regex;
+use serde::Serialize;
+use std::fs;
+
+use crate::plugins::config::{
+    ExtractionRule, IntegrationConfig, IntegrationEntry, IntegrationsConfig, UploadConfig,
+    MIN_POLL_INTERVAL_MS,
+};
+use crate::redaction;
+use crate::webhooks::{EVENT_ACTIVITY_FINISHED, EVENT_DAILY_THRESHOLD, EVENT_GOAL_REACHED};
+
+/// 抽出ルール・カテゴリ分類ルールなどの判定対象として使える値
+const RULE_SOURCES: [&str; 3] = ["window_title", "process_name", "domain"];
+
+/// コメントのテンプレートで使えるプレースホルダー
+const COMMENT_PLACEHOLDERS: [&str; 7] = [
+    "title",
+    "process",
+    "domain",
+    "project",
+    "category",
+    "note",
+    "duration_hm",
+];
+
+/// 設定の問題点
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigIssue {
+    /// 問題のある項目 (e.g., "integrations[0].rules[1].pattern")。ファイル全体の問題なら空
+    pub path: String,
+    pub message: String,
+    /// TOML として解析できないときの位置（1始まり）
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+}
+
+/// 問題点を集める
+#[derive(Default)]
+struct Validator {
+    issues: Vec<ConfigIssue>,
+}
+
+impl Validator {
+    fn push(&mut self, path: &str, message: impl Into<String>) {
+        self.issues.push(ConfigIssue {
+            path: path.to_string(),
+            message: message.into(),
+            line: None,
+            column: None,
+        });
+    }
+
+    fn require(&mut self, path: &str, value: &str) -> bool {
+        if value.trim().is_empty() {
+            self.push(path, "is required");
+            return false;
+        }
+        true
+    }
+
+    fn url(&mut self, path: &str, value: &str) {
+        if !self.require(path, value) {
+            return;
+        }
+        match url::Url::parse(value) {
+            Ok(url) if matches!(url.scheme(), "http" | "https") => {}
+            Ok(url) => self.push(path, format!("unsupported URL scheme: {}", url.scheme())),
+            Err(e) => self.push(path, format!("invalid URL: {}", e)),
+        }
+    }
+
+    fn pattern(&mut self, path: &str, pattern: &str) {
+        if let Err(e) = Regex::new(pattern) {
+            self.push(path, format!("invalid regex: {}", e));
+        }
+    }
+
+    fn source(&mut self, path: &str, source: &str) {
+        if !RULE_SOURCES.contains(&source) {
+            self.push(
+                path,
+                format!(
+                    "unknown source \"{}\" (expected one of {})",
+                    source,
+                    RULE_SOURCES.join(", ")
+                ),
+            );
+        }
+    }
+
+    fn at_least(&mut self, path: &str, value: i64, min: i64) {
+        if value < min {
+            self.push(path, format!("must be at least {}", min));
+        }
+    }
+
+    fn extraction_rules(&mut self, path: &str, rules: &[ExtractionRule]) {
+        for (i, rule) in rules.iter().enumerate() {
+            let rule_path = format!("{}[{}]", path, i);
+            self.pattern(&format!("{}.pattern", rule_path), &rule.pattern);
+            if rule.sources.is_empty() {
+                self.source(&format!("{}.source", rule_path), &rule.source);
+            }
+            for (j, source) in rule.sources.iter().enumerate() {
+                self.source(&format!("{}.sources[{}]", rule_path, j), source);
+            }
+            if rule
+                .template
+                .as_deref()
+                .is_some_and(|t| t.trim().is_empty())
+            {
+                self.push(&format!("{}.template", rule_path), "must not be empty");
+            }
+        }
+    }
+
+    fn integration(&mut self, path: &str, entry: &IntegrationEntry) {
+        self.require(&format!("{}.name", path), &entry.name);
+        let field = |name: &str| format!("{}.{}", path, name);
+
+        match &entry.config {
+            IntegrationConfig::Redmine(c) => self.url(&field("url"), &c.url),
+            IntegrationConfig::Jira(c) => {
+                self.url(&field("url"), &c.url);
+                self.require(&field("email"), &c.email);
+            }
+            IntegrationConfig::GitHub(c) => self.url(&field("api_url"), &c.api_url),
+            IntegrationConfig::GitLab(c) => {
+                self.url(&field("url"), &c.url);
+                self.require(&field("project"), &c.project);
+            }
+            IntegrationConfig::Toggl(_) | IntegrationConfig::Asana(_) => {}
+            IntegrationConfig::Clockify(c) => {
+                self.require(&field("workspace_id"), &c.workspace_id);
+                for (i, mapping) in c.projects.iter().enumerate() {
+                    self.pattern(
+                        &field(&format!("projects[{}].pattern", i)),
+                        &mapping.pattern,
+                    );
+                }
+            }
+            IntegrationConfig::Harvest(c) => {
+                self.require(&field("account_id"), &c.account_id);
+                for (i, mapping) in c.projects.iter().enumerate() {
+                    self.pattern(
+                        &field(&format!("projects[{}].pattern", i)),
+                        &mapping.pattern,
+                    );
+                }
+            }
+            IntegrationConfig::Trello(c) => {
+                self.require(&field("api_key"), &c.api_key);
+            }
+            IntegrationConfig::Notion(c) => {
+                self.require(&field("database_id"), &c.database_id);
+            }
+            IntegrationConfig::Custom(c) => {
+                // プレースホルダーを含むURLも解析できるよう、仮の値で埋めて確かめる
+                self.url(&field("url"), &fill_placeholders(&c.url));
+                if let Some(test_url) = &c.test_url {
+                    self.url(&field("test_url"), &fill_placeholders(test_url));
+                }
+                if reqwest::Method::from_bytes(c.method.to_uppercase().as_bytes()).is_err() {
+                    self.push(
+                        &field("method"),
+                        format!("invalid HTTP method: {}", c.method),
+                    );
+                }
+            }
+            IntegrationConfig::Exec(c) => {
+                self.require(&field("command"), &c.command);
+                self.at_least(&field("timeout_seconds"), c.timeout_seconds as i64, 1);
+            }
+        }
+
+        self.extraction_rules(&field("rules"), entry.config.rules());
+
+        let rounding = &entry.rounding;
+        if rounding.minutes > 24 * 60 {
+            self.push(&field("rounding.minutes"), "must be at most 1440");
+        }
+        if rounding.minimum_minutes > 24 * 60 {
+            self.push(&field("rounding.minimum_minutes"), "must be at most 1440");
+        }
+        if let Some(cap) = rounding.daily_cap_hours {
+            if !(cap > 0.0 && cap <= 24.0) {
+                self.push(
+                    &field("rounding.daily_cap_hours"),
+                    "must be greater than 0 and at most 24",
+                );
+            }
+        }
+
+        if let Some(template) = entry.comment.template.as_deref() {
+            self.comment_template(&field("comment.template"), template);
+        }
+    }
+
+    fn comment_template(&mut self, path: &str, template: &str) {
+        if !self.require(path, template) {
+            return;
+        }
+        let placeholder = Regex::new(r"\{\{([^}]*)\}\}").expect("valid regex");
+        for captures in placeholder.captures_iter(template) {
+            let name = &captures[1];
+            if !COMMENT_PLACEHOLDERS.contains(&name) {
+                self.push(path, format!("unknown placeholder: {{{{{}}}}}", name));
+            }
+        }
+    }
+
+    fn upload(&mut self, config: &IntegrationsConfig, upload: &UploadConfig) {
+        if upload.enabled || !upload.server_url.is_empty() {
+            self.url("upload.server_url", &upload.server_url);
+        }
+        self.at_least(
+            "upload.auto_upload_interval_minutes",
+            upload.auto_upload_interval_minutes as i64,
+            1,
+        );
+        if let Err(e) = redaction::resolve_profile(config, upload.redaction_profile.as_deref()) {
+            self.push("upload.redaction_profile", e);
+        }
+    }
+
+    fn config(&mut self, config: &IntegrationsConfig) {
+        for (i, entry) in config.integrations.iter().enumerate() {
+            let path = format!("integrations[{}]", i);
+            self.integration(&path, entry);
+            if config.integrations[..i]
+                .iter()
+                .any(|e| e.name == entry.name)
+            {
+                self.push(
+                    &format!("{}.name", path),
+                    format!("duplicate integration name: {}", entry.name),
+                );
+            }
+        }
+        if let Some(upload) = &config.upload {
+            self.upload(config, upload);
+        }
+
+        for (i, rule) in config.categories.iter().enumerate() {
+            self.require(&format!("categories[{}].category", i), &rule.category);
+            self.pattern(&format!("categories[{}].pattern", i), &rule.pattern);
+            self.source(&format!("categories[{}].source", i), &rule.source);
+        }
+        for (i, rule) in config.assignment_rules.iter().enumerate() {
+            self.pattern(&format!("assignment_rules[{}].pattern", i), &rule.pattern);
+            self.source(&format!("assignment_rules[{}].source", i), &rule.source);
+        }
+        for (i, rule) in config.title_rules.iter().enumerate() {
+            self.pattern(&format!("title_rules[{}].pattern", i), &rule.pattern);
+            if let Some(pattern) = &rule.process_pattern {
+                self.pattern(&format!("title_rules[{}].process_pattern", i), pattern);
+            }
+        }
+        for (i, pattern) in config.exclusions.title_patterns.iter().enumerate() {
+            self.pattern(&format!("exclusions.title_patterns[{}]", i), pattern);
+        }
+        for (i, project) in config.projects.iter().enumerate() {
+            for (j, pattern) in project.title_patterns.iter().enumerate() {
+                self.pattern(&format!("projects[{}].title_patterns[{}]", i, j), pattern);
+            }
+        }
+
+        self.at_least(
+            "polling.interval_ms",
+            config.polling.interval_ms as i64,
+            MIN_POLL_INTERVAL_MS as i64,
+        );
+        if config.polling.adaptive && config.polling.max_interval_ms < config.polling.interval_ms {
+            self.push(
+                "polling.max_interval_ms",
+                "must not be less than polling.interval_ms",
+            );
+        }
+        self.at_least(
+            "http.timeout_seconds",
+            config.http.timeout_seconds as i64,
+            1,
+        );
+        self.at_least(
+            "http.connect_timeout_seconds",
+            config.http.connect_timeout_seconds as i64,
+            1,
+        );
+        self.at_least(
+            "http.max_concurrent_per_host",
+            config.http.max_concurrent_per_host as i64,
+            1,
+        );
+        if config.http.requests_per_second.is_nan() || config.http.requests_per_second < 0.0 {
+            self.push("http.requests_per_second", "must be at least 0");
+        }
+        if config.idle.enabled {
+            self.at_least(
+                "idle.threshold_seconds",
+                config.idle.threshold_seconds as i64,
+                1,
+            );
+        }
+        self.at_least(
+            "coalesce.merge_gap_seconds",
+            config.coalesce.merge_gap_seconds,
+            0,
+        );
+        self.at_least(
+            "coalesce.min_segment_seconds",
+            config.coalesce.min_segment_seconds,
+            0,
+        );
+
+        if let Some(slack) = &config.slack {
+            self.at_least("slack.dnd_after_minutes", slack.dnd_after_minutes, 1);
+            if let Some(time) = &slack.summary_time {
+                if NaiveTime::parse_from_str(time, "%H:%M").is_err() {
+                    self.push("slack.summary_time", "expected \"HH:MM\"");
+                }
+            }
+        }
+        for (i, calendar) in config.calendars.iter().enumerate() {
+            self.require(&format!("calendars[{}].name", i), &calendar.name);
+        }
+        for (i, endpoint) in config.webhooks.endpoints.iter().enumerate() {
+            self.url(&format!("webhooks.endpoints[{}].url", i), &endpoint.url);
+            for (j, event) in endpoint.events.iter().enumerate() {
+                let known = [
+                    EVENT_ACTIVITY_FINISHED,
+                    EVENT_GOAL_REACHED,
+                    EVENT_DAILY_THRESHOLD,
+                ];
+                if !known.contains(&event.as_str()) {
+                    self.push(
+                        &format!("webhooks.endpoints[{}].events[{}]", i, j),
+                        format!("unknown event: {}", event),
+                    );
+                }
+            }
+        }
+    }
+
+    /// 問題があれば "path: message" を並べたエラーにする
+    fn into_result(self) -> Result<(), String> {
+        if self.issues.is_empty() {
+            return Ok(());
+        }
+        Err(format_issues(&self.issues))
+    }
+}
+
+/// 設定全体を確認する
+pub fn validate(config: &IntegrationsConfig) -> Vec<ConfigIssue> {
+    let mut validator = Validator::default();
+    validator.config(config);
+    validator.issues
+}
+
+/// 設定ファイルを読んで確認する（TOML として解析できなければその位置を返す）
+pub fn validate_file() -> Result<Vec<ConfigIssue>, String> {
+    let path = IntegrationsConfig::config_path();
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read integrations config: {}", e))?;
+
+    match toml::from_str::<IntegrationsConfig>(&content) {
+        Ok(config) => Ok(validate(&config)),
+        Err(e) => {
+            let (line, column) = match e.span() {
+                Some(span) => {
+                    let (line, column) = line_column(&content, span.start);
+                    (Some(line), Some(column))
+                }
+                None => (None, None),
+            };
+            Ok(vec![ConfigIssue {
+                path: String::new(),
+                message: e.message().to_string(),
+                line,
+                column,
+            }])
+        }
+    }
+}
+
+/// 問題点を1行ずつの文字列にする
+pub fn format_issues(issues: &[ConfigIssue]) -> String {
+    issues
+        .iter()
+        .map(|issue| match (issue.path.is_empty(), issue.line) {
+            (_, Some(line)) => format!(
+                "line {}, column {}: {}",
+                line,
+                issue.column.unwrap_or(1),
+                issue.message
+            ),
+            (true, None) => issue.message.clone(),
+            (false, None) => format!("{}: {}", issue.path, issue.message),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn line_column(content: &str, offset: usize) -> (usize, usize) {
+    let before = &content[..offset.min(content.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before
+        .rsplit('\n')
+        .next()
+        .map_or(0, |last| last.chars().count())
+        + 1;
+    (line, column)
+}
+
+/// 連携を追加・更新する
+///
+/// original_name があればその連携を置き換える（名前の変更）。なければ同名の連携を置き換え、
+/// どちらもなければ末尾に追加する。
+pub fn upsert_integration(
+    config: &mut IntegrationsConfig,
+    entry: IntegrationEntry,
+    original_name: Option<&str>,
+) -> Result<(), String> {
+    validate_integration(&entry)?;
+
+    let target = original_name.unwrap_or(&entry.name);
+    let position = config.integrations.iter().position(|e| e.name == target);
+    if original_name.is_some() && position.is_none() {
+        return Err(format!("Integration not found: {}", target));
+    }
+    let duplicate = config
+        .integrations
+        .iter()
+        .enumerate()
+        .any(|(i, e)| e.name == entry.name && Some(i) != position);
+    if duplicate {
+        return Err(format!("Integration already exists: {}", entry.name));
+    }
+
+    match position {
+        Some(i) => config.integrations[i] = entry,
+        None => config.integrations.push(entry),
+    }
+    Ok(())
+}
+
+/// 連携を削除する
+pub fn remove_integration(config: &mut IntegrationsConfig, name: &str) -> Result<(), String> {
+    let position = config
+        .integrations
+        .iter()
+        .position(|e| e.name == name)
+        .ok_or_else(|| format!("Integration not found: {}", name))?;
+    config.integrations.remove(position);
+    Ok(())
+}
+
+/// アップロード設定を置き換える（None で削除）
+pub fn set_upload(
+    config: &mut IntegrationsConfig,
+    upload: Option<UploadConfig>,
+) -> Result<(), String> {
+    if let Some(upload) = &upload {
+        let mut validator = Validator::default();
+        validator.upload(config, upload);
+        validator.into_result()?;
+    }
+    config.upload = upload;
+    Ok(())
+}
+
+/// 連携設定の必須項目・URL・抽出ルールを確認する
+pub fn validate_integration(entry: &IntegrationEntry) -> Result<(), String> {
+    let mut validator = Validator::default();
+    validator.integration("integration", entry);
+    validator.into_result()
+}
+
+fn fill_placeholders(template: &str) -> String {
+    Regex::new(r"\{\{\w+\}\}")
+        .map(|re| re.replace_all(template, "0").into_owned())
+        .unwrap_or_else(|_| template.to_string())
+}
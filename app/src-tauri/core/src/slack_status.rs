@@ -0,0 +1,179 @@
+use chrono::{Local, NaiveDate, NaiveTime};
+use rusqlite::Connection;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+
+use crate::format_hours_minutes;
+use crate::live::LiveEvent;
+use crate::plugins::config::SlackStatus;
+use crate::plugins::integrations::SlackIntegration;
+use crate::summary::{self, GroupBy};
+use crate::AppState;
+
+/// 1回のおやすみモードの長さ（集中が続いていれば切れる前に延長する）
+const SNOOZE_MINUTES: i64 = 60;
+/// 日次サマリーに載せるカテゴリ・アプリの数
+const SUMMARY_TOP: usize = 5;
+
+/// 現在のカテゴリに合わせてステータス・おやすみモードを切り替え、日次サマリーを投稿する
+pub fn start(state: Arc<AppState>, slack: Arc<SlackIntegration>) {
+    start_status_updater(state.clone(), slack.clone());
+    if slack.config().summary_channel.is_some() {
+        if let Some(time) = slack.config().summary_time.as_deref() {
+            match NaiveTime::parse_from_str(time, "%H:%M") {
+                Ok(time) => start_summary_poster(state, slack, time),
+                Err(e) => eprintln!("Invalid Slack summary_time {}: {}", time, e),
+            }
+        }
+    }
+}
+
+fn start_status_updater(state: Arc<AppState>, slack: Arc<SlackIntegration>) {
+    let mut receiver = state.live.subscribe();
+    crate::runtime::spawn(async move {
+        let config = slack.config();
+        let dnd_after = Duration::from_secs(config.dnd_after_minutes.max(1) as u64 * 60);
+        // 最後に設定したステータス（同じステータスは送り直さない）
+        let mut applied: Option<Option<SlackStatus>> = None;
+        // 集中中のカテゴリと開始時刻
+        let mut focus: Option<(String, Instant)> = None;
+        let mut snoozed_at: Option<Instant> = None;
+        let mut tick = tokio::time::interval(Duration::from_secs(30));
+
+        loop {
+            tokio::select! {
+                event = receiver.recv() => {
+                    let category = match event {
+                        Ok(LiveEvent::ActivityStart {
+                            process_name,
+                            window_title,
+                            domain,
+                            ..
+                        }) => state
+                            .categorizer
+                            .classify(&process_name, &window_title, domain.as_deref()),
+                        // アイドル・停止中はステータスを消して集中を終える
+                        Ok(LiveEvent::Idle { idle: true, .. })
+                        | Ok(LiveEvent::TrackingState { tracking: false, .. }) => None,
+                        Ok(_) => continue,
+                        Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    };
+
+                    let status = slack.status_for(category.as_deref()).cloned();
+                    if applied.as_ref() != Some(&status) {
+                        match slack.set_status(status.as_ref()).await {
+                            Ok(()) => applied = Some(status),
+                            Err(e) => eprintln!("Failed to set Slack status: {}", e),
+                        }
+                    }
+
+                    let focus_category = category.filter(|c| {
+                        config.focus_categories.is_empty() || config.focus_categories.contains(c)
+                    });
+                    if focus.as_ref().map(|(c, _)| c) != focus_category.as_ref() {
+                        focus = focus_category.map(|c| (c, Instant::now()));
+                        if snoozed_at.take().is_some() {
+                            if let Err(e) = slack.end_snooze().await {
+                                eprintln!("Failed to end Slack snooze: {}", e);
+                            }
+                        }
+                    }
+                }
+                _ = tick.tick() => {
+                    if !config.dnd_during_focus {
+                        continue;
+                    }
+                    let Some((_, since)) = &focus else {
+                        continue;
+                    };
+                    let due = match snoozed_at {
+                        Some(at) => at.elapsed() >= Duration::from_secs((SNOOZE_MINUTES as u64 - 5) * 60),
+                        None => since.elapsed() >= dnd_after,
+                    };
+                    if due {
+                        match slack.set_snooze(SNOOZE_MINUTES).await {
+                            Ok(()) => snoozed_at = Some(Instant::now()),
+                            Err(e) => eprintln!("Failed to set Slack snooze: {}", e),
+                        }
+                    }
+                }
+            }
+        }
+    });
+}
+
+fn start_summary_poster(state: Arc<AppState>, slack: Arc<SlackIntegration>, time: NaiveTime) {
+    crate::runtime::spawn(async move {
+        // 投稿時刻を過ぎてから起動した日は投稿しない
+        let now = Local::now();
+        let mut last_posted = (now.time() >= time).then(|| now.date_naive());
+
+        loop {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+
+            let now = Local::now();
+            let today = now.date_naive();
+            if now.time() < time || last_posted == Some(today) {
+                continue;
+            }
+            last_posted = Some(today);
+            if let Err(e) = post_summary(&state, &slack, today).await {
+                eprintln!("Failed to post Slack summary: {}", e);
+            }
+        }
+    });
+}
+
+/// 指定日のサマリーを summary_channel に投稿
+pub async fn post_summary(
+    state: &AppState,
+    slack: &SlackIntegration,
+    date: NaiveDate,
+) -> Result<(), String> {
+    let channel = slack
+        .config()
+        .summary_channel
+        .clone()
+        .ok_or("Slack summary_channel is not configured")?;
+    let text = {
+        let db = state.db.lock();
+        summary_text(&db, date)?
+    };
+    slack.post_message(&channel, &text).await
+}
+
+/// 日次サマリーのメッセージ（合計と上位のカテゴリ・アプリ）
+pub fn summary_text(conn: &Connection, date: NaiveDate) -> Result<String, String> {
+    let summary = summary::range_summary(conn, date, date, GroupBy::Day)?;
+
+    let mut lines = vec![format!(
+        "*{}* - {} tracked",
+        date.format("%Y-%m-%d"),
+        format_hours_minutes(summary.total_seconds)
+    )];
+    if !summary.categories.is_empty() {
+        lines.push("*Categories*".to_string());
+        for category in summary.categories.iter().take(SUMMARY_TOP) {
+            lines.push(format!(
+                "• {}: {} ({:.0}%)",
+                category.category,
+                format_hours_minutes(category.total_seconds),
+                category.percentage
+            ));
+        }
+    }
+    if !summary.apps.is_empty() {
+        lines.push("*Apps*".to_string());
+        for app in summary.apps.iter().take(SUMMARY_TOP) {
+            lines.push(format!(
+                "• {}: {} ({:.0}%)",
+                app.process_name,
+                format_hours_minutes(app.total_seconds),
+                app.percentage
+            ));
+        }
+    }
+    Ok(lines.join("\n"))
+}
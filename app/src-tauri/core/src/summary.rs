@@ -0,0 +1,391 @@
+use chrono::{Datelike, Duration, NaiveDate};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::budgets::week_start;
+use crate::categories::UNCATEGORIZED;
+use crate::AppSummary;
+
+/// カテゴリ別の合計
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryTotal {
+    pub category: String,
+    pub total_seconds: i64,
+    pub percentage: f64,
+}
+
+/// 日別の合計（トレンドグラフ用）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyTotal {
+    pub date: String,
+    pub total_seconds: i64,
+}
+
+/// 1期間（日/週/月）の集計
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SummaryBucket {
+    /// "2024-01-15" / "2024-W03" / "2024-01"
+    pub key: String,
+    pub start_date: String,
+    pub end_date: String,
+    pub total_seconds: i64,
+    pub apps: Vec<AppSummary>,
+    pub categories: Vec<CategoryTotal>,
+}
+
+/// 期間集計の結果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RangeSummary {
+    pub group_by: String,
+    pub total_seconds: i64,
+    pub buckets: Vec<SummaryBucket>,
+    /// 期間全体のアプリ別・カテゴリ別合計
+    pub apps: Vec<AppSummary>,
+    pub categories: Vec<CategoryTotal>,
+    /// 記録のない日も 0 で含む
+    pub daily_totals: Vec<DailyTotal>,
+}
+
+/// アプリ内のウィンドウタイトル別の合計
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TitleSummary {
+    pub window_title: String,
+    pub total_seconds: i64,
+    /// アプリの合計に対する割合
+    pub percentage: f64,
+    pub activity_count: i64,
+}
+
+/// 2期間の1項目（アプリ/カテゴリ）の差
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeriodDelta {
+    pub name: String,
+    pub a_seconds: i64,
+    pub b_seconds: i64,
+    /// a - b
+    pub delta_seconds: i64,
+    /// b に対する増減率（%、b が 0 なら None）
+    pub delta_percentage: Option<f64>,
+}
+
+/// 期間 a と基準の期間 b の比較（「今週 vs 先週」など）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeriodComparison {
+    pub a_start_date: String,
+    pub a_end_date: String,
+    pub b_start_date: String,
+    pub b_end_date: String,
+    pub total: PeriodDelta,
+    /// 差の大きい順
+    pub apps: Vec<PeriodDelta>,
+    pub categories: Vec<PeriodDelta>,
+}
+
+/// 集計単位
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupBy {
+    Day,
+    Week,
+    Month,
+}
+
+impl GroupBy {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s {
+            "day" => Ok(GroupBy::Day),
+            "week" => Ok(GroupBy::Week),
+            "month" => Ok(GroupBy::Month),
+            _ => Err(format!(
+                "Unknown group_by: {} (expected day, week or month)",
+                s
+            )),
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            GroupBy::Day => "day",
+            GroupBy::Week => "week",
+            GroupBy::Month => "month",
+        }
+    }
+
+    /// 日付が属する期間の (キー, 初日, 最終日)
+    fn bucket(self, date: NaiveDate) -> (String, NaiveDate, NaiveDate) {
+        match self {
+            GroupBy::Day => (date.format("%Y-%m-%d").to_string(), date, date),
+            GroupBy::Week => {
+                let start = week_start(date);
+                (
+                    date.format("%G-W%V").to_string(),
+                    start,
+                    start + Duration::days(6),
+                )
+            }
+            GroupBy::Month => {
+                let start = date.with_day(1).unwrap_or(date);
+                let next = if start.month() == 12 {
+                    NaiveDate::from_ymd_opt(start.year() + 1, 1, 1)
+                } else {
+                    NaiveDate::from_ymd_opt(start.year(), start.month() + 1, 1)
+                };
+                let end = next.map(|n| n - Duration::days(1)).unwrap_or(start);
+                (date.format("%Y-%m").to_string(), start, end)
+            }
+        }
+    }
+}
+
+fn percentage(part: i64, total: i64) -> f64 {
+    if total > 0 {
+        (part as f64 / total as f64) * 100.0
+    } else {
+        0.0
+    }
+}
+
+fn app_summaries(totals: HashMap<String, i64>, total: i64) -> Vec<AppSummary> {
+    let mut apps: Vec<AppSummary> = totals
+        .into_iter()
+        .map(|(process_name, total_seconds)| AppSummary {
+            percentage: percentage(total_seconds, total),
+            process_name,
+            total_seconds,
+        })
+        .collect();
+    apps.sort_by_key(|app| Reverse(app.total_seconds));
+    apps
+}
+
+fn category_totals(totals: HashMap<String, i64>, total: i64) -> Vec<CategoryTotal> {
+    let mut categories: Vec<CategoryTotal> = totals
+        .into_iter()
+        .map(|(category, total_seconds)| CategoryTotal {
+            percentage: percentage(total_seconds, total),
+            category,
+            total_seconds,
+        })
+        .collect();
+    categories.sort_by_key(|category| Reverse(category.total_seconds));
+    categories
+}
+
+#[derive(Default)]
+struct Totals {
+    total: i64,
+    apps: HashMap<String, i64>,
+    categories: HashMap<String, i64>,
+}
+
+impl Totals {
+    fn add(&mut self, process_name: &str, category: &str, seconds: i64) {
+        self.total += seconds;
+        *self.apps.entry(process_name.to_string()).or_insert(0) += seconds;
+        *self.categories.entry(category.to_string()).or_insert(0) += seconds;
+    }
+}
+
+/// start〜end（両端を含む）の使用時間を期間ごとに集計
+pub fn range_summary(
+    conn: &Connection,
+    start: NaiveDate,
+    end: NaiveDate,
+    group_by: GroupBy,
+) -> Result<RangeSummary, String> {
+    if end < start {
+        return Err("end must not be before start".to_string());
+    }
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT substr(start_time, 1, 10) AS day, process_name, COALESCE(category, ?3), SUM(duration_seconds)
+             FROM activities
+             WHERE start_time >= ?1 AND start_time <= ?2
+             GROUP BY day, process_name, COALESCE(category, ?3)",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows: Vec<(String, String, String, i64)> = stmt
+        .query_map(
+            params![
+                format!("{}T00:00:00", start.format("%Y-%m-%d")),
+                format!("{}T23:59:59", end.format("%Y-%m-%d")),
+                UNCATEGORIZED
+            ],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let mut overall = Totals::default();
+    let mut daily: HashMap<String, i64> = HashMap::new();
+    // キーの順（日付順）に並べるため BTreeMap
+    let mut buckets: BTreeMap<String, (NaiveDate, NaiveDate, Totals)> = BTreeMap::new();
+
+    for (day, process_name, category, seconds) in &rows {
+        let Ok(date) = NaiveDate::parse_from_str(day, "%Y-%m-%d") else {
+            continue;
+        };
+        let (key, bucket_start, bucket_end) = group_by.bucket(date);
+
+        overall.add(process_name, category, *seconds);
+        *daily.entry(day.clone()).or_insert(0) += seconds;
+        buckets
+            .entry(key)
+            .or_insert_with(|| (bucket_start, bucket_end, Totals::default()))
+            .2
+            .add(process_name, category, *seconds);
+    }
+
+    let daily_totals = start
+        .iter_days()
+        .take_while(|date| *date <= end)
+        .map(|date| {
+            let date = date.format("%Y-%m-%d").to_string();
+            DailyTotal {
+                total_seconds: daily.get(&date).copied().unwrap_or(0),
+                date,
+            }
+        })
+        .collect();
+
+    let buckets = buckets
+        .into_iter()
+        .map(|(key, (bucket_start, bucket_end, totals))| SummaryBucket {
+            key,
+            // 範囲の端で切れる期間は範囲内に収める
+            start_date: bucket_start.max(start).format("%Y-%m-%d").to_string(),
+            end_date: bucket_end.min(end).format("%Y-%m-%d").to_string(),
+            total_seconds: totals.total,
+            apps: app_summaries(totals.apps, totals.total),
+            categories: category_totals(totals.categories, totals.total),
+        })
+        .collect();
+
+    Ok(RangeSummary {
+        group_by: group_by.as_str().to_string(),
+        total_seconds: overall.total,
+        buckets,
+        apps: app_summaries(overall.apps, overall.total),
+        categories: category_totals(overall.categories, overall.total),
+        daily_totals,
+    })
+}
+
+/// start〜end（両端を含む）の1アプリの使用時間をタイトル別に集計（タイトルは保存時に正規化済み）
+pub fn title_summary(
+    conn: &Connection,
+    process_name: &str,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<Vec<TitleSummary>, String> {
+    if end < start {
+        return Err("end must not be before start".to_string());
+    }
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT window_title, SUM(duration_seconds) AS total, COUNT(*)
+             FROM activities
+             WHERE process_name = ?1 AND start_time >= ?2 AND start_time <= ?3
+             GROUP BY window_title
+             ORDER BY total DESC",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows: Vec<(String, i64, i64)> = stmt
+        .query_map(
+            params![
+                process_name,
+                format!("{}T00:00:00", start.format("%Y-%m-%d")),
+                format!("{}T23:59:59", end.format("%Y-%m-%d"))
+            ],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let total: i64 = rows.iter().map(|(_, seconds, _)| seconds).sum();
+    Ok(rows
+        .into_iter()
+        .map(
+            |(window_title, total_seconds, activity_count)| TitleSummary {
+                window_title,
+                total_seconds,
+                percentage: percentage(total_seconds, total),
+                activity_count,
+            },
+        )
+        .collect())
+}
+
+fn delta(name: String, a_seconds: i64, b_seconds: i64) -> PeriodDelta {
+    PeriodDelta {
+        name,
+        a_seconds,
+        b_seconds,
+        delta_seconds: a_seconds - b_seconds,
+        delta_percentage: (b_seconds > 0)
+            .then(|| (a_seconds - b_seconds) as f64 / b_seconds as f64 * 100.0),
+    }
+}
+
+fn deltas(a: Vec<(String, i64)>, b: Vec<(String, i64)>) -> Vec<PeriodDelta> {
+    let mut totals: HashMap<String, (i64, i64)> = HashMap::new();
+    for (name, seconds) in a {
+        totals.entry(name).or_insert((0, 0)).0 += seconds;
+    }
+    for (name, seconds) in b {
+        totals.entry(name).or_insert((0, 0)).1 += seconds;
+    }
+    let mut deltas: Vec<PeriodDelta> = totals
+        .into_iter()
+        .map(|(name, (a_seconds, b_seconds))| delta(name, a_seconds, b_seconds))
+        .collect();
+    deltas.sort_by(|x, y| {
+        y.delta_seconds
+            .abs()
+            .cmp(&x.delta_seconds.abs())
+            .then_with(|| x.name.cmp(&y.name))
+    });
+    deltas
+}
+
+/// 期間 a（a_start〜a_end）を基準の期間 b と比べたアプリ別・カテゴリ別の増減
+pub fn compare_periods(
+    conn: &Connection,
+    (a_start, a_end): (NaiveDate, NaiveDate),
+    (b_start, b_end): (NaiveDate, NaiveDate),
+) -> Result<PeriodComparison, String> {
+    let a = range_summary(conn, a_start, a_end, GroupBy::Day)?;
+    let b = range_summary(conn, b_start, b_end, GroupBy::Day)?;
+
+    let apps = |summary: &RangeSummary| {
+        summary
+            .apps
+            .iter()
+            .map(|app| (app.process_name.clone(), app.total_seconds))
+            .collect::<Vec<_>>()
+    };
+    let categories = |summary: &RangeSummary| {
+        summary
+            .categories
+            .iter()
+            .map(|category| (category.category.clone(), category.total_seconds))
+            .collect::<Vec<_>>()
+    };
+
+    Ok(PeriodComparison {
+        a_start_date: a_start.format("%Y-%m-%d").to_string(),
+        a_end_date: a_end.format("%Y-%m-%d").to_string(),
+        b_start_date: b_start.format("%Y-%m-%d").to_string(),
+        b_end_date: b_end.format("%Y-%m-%d").to_string(),
+        total: delta("total".to_string(), a.total_seconds, b.total_seconds),
+        apps: deltas(apps(&a), apps(&b)),
+        categories: deltas(categories(&a), categories(&b)),
+    })
+}
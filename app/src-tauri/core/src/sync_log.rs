@@ -0,0 +1,153 @@
+use chrono::Local;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// 外部サービスへの同期履歴
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncLogEntry {
+    pub id: i64,
+    pub activity_id: i64,
+    pub plugin: String,
+    pub ticket_id: String,
+    pub external_id: Option<String>,
+    pub synced_at: String,
+}
+
+pub fn create_tables(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS sync_log (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            activity_id INTEGER NOT NULL,
+            plugin TEXT NOT NULL,
+            ticket_id TEXT NOT NULL,
+            external_id TEXT,
+            synced_at TEXT NOT NULL
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_sync_log_activity ON sync_log(activity_id, plugin)",
+        [],
+    )?;
+
+    Ok(())
+}
+
+fn row_to_entry(row: &rusqlite::Row) -> rusqlite::Result<SyncLogEntry> {
+    Ok(SyncLogEntry {
+        id: row.get(0)?,
+        activity_id: row.get(1)?,
+        plugin: row.get(2)?,
+        ticket_id: row.get(3)?,
+        external_id: row.get(4)?,
+        synced_at: row.get(5)?,
+    })
+}
+
+/// 同期成功を記録（まとめて送信した場合は全アクティビティ分）
+///
+/// 送信した時間は先頭のアクティビティの行にだけ記録し、残りは0にする。
+pub fn record(
+    conn: &Connection,
+    activity_ids: &[i64],
+    plugin: &str,
+    ticket_id: &str,
+    external_id: Option<&str>,
+    duration_seconds: i64,
+) -> Result<(), String> {
+    let synced_at = Local::now().format("%Y-%m-%dT%H:%M:%S").to_string();
+    for (index, activity_id) in activity_ids.iter().enumerate() {
+        let duration = if index == 0 { duration_seconds } else { 0 };
+        conn.execute(
+            "INSERT INTO sync_log (activity_id, plugin, ticket_id, external_id, synced_at, duration_seconds)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![activity_id, plugin, ticket_id, external_id, synced_at, duration],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// 期間内に開始したアクティビティについて、指定プラグインへ送信した時間の合計
+pub fn synced_seconds(
+    conn: &Connection,
+    plugin: &str,
+    start: &str,
+    end: &str,
+) -> Result<i64, String> {
+    conn.query_row(
+        "SELECT COALESCE(SUM(s.duration_seconds), 0)
+         FROM sync_log s
+         JOIN activities a ON a.id = s.activity_id
+         WHERE s.plugin = ?1 AND a.start_time >= ?2 AND a.start_time <= ?3",
+        params![plugin, start, end],
+        |row| row.get(0),
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// アクティビティが指定プラグインに同期済みなら最新の履歴を返す
+pub fn find(
+    conn: &Connection,
+    activity_id: i64,
+    plugin: &str,
+) -> Result<Option<SyncLogEntry>, String> {
+    conn.query_row(
+        "SELECT id, activity_id, plugin, ticket_id, external_id, synced_at
+         FROM sync_log
+         WHERE activity_id = ?1 AND plugin = ?2
+         ORDER BY id DESC
+         LIMIT 1",
+        params![activity_id, plugin],
+        row_to_entry,
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+/// いずれかのアクティビティが指定プラグインに同期済みか
+pub fn any_synced(conn: &Connection, activity_ids: &[i64], plugin: &str) -> Result<bool, String> {
+    for &activity_id in activity_ids {
+        if find(conn, activity_id, plugin)?.is_some() {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// 期間内に開始したアクティビティの同期履歴
+pub fn for_range(conn: &Connection, start: &str, end: &str) -> Result<Vec<SyncLogEntry>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT s.id, s.activity_id, s.plugin, s.ticket_id, s.external_id, s.synced_at
+             FROM sync_log s
+             JOIN activities a ON a.id = s.activity_id
+             WHERE a.start_time >= ?1 AND a.start_time <= ?2
+             ORDER BY s.activity_id ASC, s.id ASC",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let entries = stmt
+        .query_map(params![start, end], row_to_entry)
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(entries)
+}
+
+/// 期間内で指定プラグインに同期済みのアクティビティID
+pub fn synced_activity_ids(
+    conn: &Connection,
+    plugin: &str,
+    start: &str,
+    end: &str,
+) -> Result<HashSet<i64>, String> {
+    Ok(for_range(conn, start, end)?
+        .into_iter()
+        .filter(|entry| entry.plugin == plugin)
+        .map(|entry| entry.activity_id)
+        .collect())
+}
@@ -0,0 +1,316 @@
+use chrono::{Duration, Local};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use crate::plugins::traits::{ActivityInfo, SyncError};
+
+/// 送信待ち
+pub const STATUS_PENDING: &str = "pending";
+/// 送信済み
+pub const STATUS_SYNCED: &str = "synced";
+/// 接続以外の理由で失敗（自動では再送しない）
+pub const STATUS_FAILED: &str = "failed";
+
+/// 再送間隔の初期値（秒）。失敗するたびに倍にする
+const RETRY_BASE_SECONDS: i64 = 60;
+/// 再送間隔の上限（秒）
+const RETRY_MAX_SECONDS: i64 = 3600;
+
+/// オフライン時に保留された同期
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedSync {
+    pub id: i64,
+    pub plugin: String,
+    pub ticket_id: String,
+    /// 保留時点のアクティビティ（後から統合・削除されても内容を保持する）
+    pub activity: ActivityInfo,
+    /// まとめて送信するアクティビティのID（送信できたら全件を同期済みにする）
+    pub activity_ids: Vec<i64>,
+    pub status: String,
+    pub attempts: i64,
+    pub last_error: Option<String>,
+    pub external_id: Option<String>,
+    pub created_at: String,
+    pub synced_at: Option<String>,
+    /// 次に自動再送する時刻（未設定なら次回の送信で対象になる）
+    pub next_attempt_at: Option<String>,
+}
+
+/// キュー送信の結果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlushReport {
+    pub synced: usize,
+    pub failed: usize,
+    /// 既に同じ内容が送信済みのためスキップした件数
+    pub skipped: usize,
+    /// まだ接続できず保留のままの件数
+    pub remaining: usize,
+}
+
+pub fn create_tables(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS sync_queue (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            plugin TEXT NOT NULL,
+            ticket_id TEXT NOT NULL,
+            activity_id INTEGER NOT NULL,
+            activity_json TEXT NOT NULL,
+            status TEXT NOT NULL,
+            attempts INTEGER NOT NULL DEFAULT 0,
+            last_error TEXT,
+            external_id TEXT,
+            created_at TEXT NOT NULL,
+            synced_at TEXT
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_sync_queue_status ON sync_queue(status)",
+        [],
+    )?;
+
+    Ok(())
+}
+
+fn now_string() -> String {
+    Local::now().format("%Y-%m-%dT%H:%M:%S").to_string()
+}
+
+/// attempts 回失敗した後の再送間隔（指数バックオフ）
+fn retry_delay_seconds(attempts: i64) -> i64 {
+    let exponent = attempts.saturating_sub(1).clamp(0, 16) as u32;
+    (RETRY_BASE_SECONDS << exponent).min(RETRY_MAX_SECONDS)
+}
+
+/// 同期をキューに保留（同じアクティビティ・チケットが送信待ち/送信済みなら追加しない）
+///
+/// activity_ids はチケット単位にまとめたアクティビティのID。追加した場合は Some(id) を返す。
+pub fn enqueue(
+    conn: &Connection,
+    plugin: &str,
+    ticket_id: &str,
+    activity: &ActivityInfo,
+    activity_ids: &[i64],
+    error: &str,
+) -> Result<Option<i64>, String> {
+    let existing: Option<i64> = conn
+        .query_row(
+            "SELECT id FROM sync_queue
+             WHERE plugin = ?1 AND ticket_id = ?2 AND activity_id = ?3 AND status IN (?4, ?5)",
+            params![
+                plugin,
+                ticket_id,
+                activity.id,
+                STATUS_PENDING,
+                STATUS_SYNCED
+            ],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+
+    if existing.is_some() {
+        return Ok(None);
+    }
+
+    let activity_json = serde_json::to_string(activity).map_err(|e| e.to_string())?;
+    let activity_ids = serde_json::to_string(activity_ids).map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO sync_queue (plugin, ticket_id, activity_id, activity_json, activity_ids, status, last_error, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            plugin,
+            ticket_id,
+            activity.id,
+            activity_json,
+            activity_ids,
+            STATUS_PENDING,
+            error,
+            now_string()
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(Some(conn.last_insert_rowid()))
+}
+
+/// キューの内容を取得（status 未指定なら全件、新しい順）
+pub fn list(conn: &Connection, status: Option<&str>) -> Result<Vec<QueuedSync>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, plugin, ticket_id, activity_json, status, attempts, last_error, external_id, created_at, synced_at, next_attempt_at,
+                    activity_ids
+             FROM sync_queue
+             WHERE ?1 IS NULL OR status = ?1
+             ORDER BY id DESC",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let entries = stmt
+        .query_map(params![status], |row| {
+            let activity_json: String = row.get(3)?;
+            let activity: ActivityInfo = serde_json::from_str(&activity_json).map_err(|e| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    3,
+                    rusqlite::types::Type::Text,
+                    Box::new(e),
+                )
+            })?;
+            // activity_ids がない古いエントリは保留したアクティビティ1件分
+            let activity_ids = match row.get::<_, Option<String>>(11)? {
+                Some(json) => serde_json::from_str(&json).map_err(|e| {
+                    rusqlite::Error::FromSqlConversionFailure(
+                        11,
+                        rusqlite::types::Type::Text,
+                        Box::new(e),
+                    )
+                })?,
+                None => vec![activity.id],
+            };
+            Ok(QueuedSync {
+                id: row.get(0)?,
+                plugin: row.get(1)?,
+                ticket_id: row.get(2)?,
+                activity,
+                activity_ids,
+                status: row.get(4)?,
+                attempts: row.get(5)?,
+                last_error: row.get(6)?,
+                external_id: row.get(7)?,
+                created_at: row.get(8)?,
+                synced_at: row.get(9)?,
+                next_attempt_at: row.get(10)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(entries)
+}
+
+/// 送信待ちのエントリ（古い順）
+pub fn pending(conn: &Connection) -> Result<Vec<QueuedSync>, String> {
+    let mut entries = list(conn, Some(STATUS_PENDING))?;
+    entries.reverse();
+    Ok(entries)
+}
+
+/// 再送時刻を過ぎた送信待ちのエントリ（古い順）
+pub fn due(conn: &Connection) -> Result<Vec<QueuedSync>, String> {
+    let now = now_string();
+    Ok(pending(conn)?
+        .into_iter()
+        .filter(|entry| match entry.next_attempt_at.as_deref() {
+            Some(at) => at <= now.as_str(),
+            None => true,
+        })
+        .collect())
+}
+
+/// 指定プラグインの送信待ちに含まれるアクティビティID
+pub fn queued_activity_ids(conn: &Connection, plugin: &str) -> Result<HashSet<i64>, String> {
+    Ok(pending(conn)?
+        .into_iter()
+        .filter(|entry| entry.plugin == plugin)
+        .flat_map(|entry| entry.activity_ids)
+        .collect())
+}
+
+/// 指定日に開始したアクティビティについて、指定プラグインの送信待ちの時間の合計
+pub fn queued_seconds(conn: &Connection, plugin: &str, date: &str) -> Result<i64, String> {
+    Ok(pending(conn)?
+        .into_iter()
+        .filter(|entry| entry.plugin == plugin && entry.activity.start_time.starts_with(date))
+        .map(|entry| entry.activity.duration_seconds)
+        .sum())
+}
+
+/// 同じアクティビティ・チケットが既に送信済みか
+pub fn already_synced(conn: &Connection, entry: &QueuedSync) -> Result<bool, String> {
+    let count: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM sync_queue
+             WHERE plugin = ?1 AND ticket_id = ?2 AND activity_id = ?3 AND status = ?4 AND id != ?5",
+            params![
+                entry.plugin,
+                entry.ticket_id,
+                entry.activity.id,
+                STATUS_SYNCED,
+                entry.id
+            ],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    Ok(count > 0)
+}
+
+/// 送信成功を記録
+pub fn mark_synced(conn: &Connection, id: i64, external_id: Option<&str>) -> Result<(), String> {
+    conn.execute(
+        "UPDATE sync_queue
+         SET status = ?1, attempts = attempts + 1, last_error = NULL, external_id = ?2, synced_at = ?3,
+             next_attempt_at = NULL
+         WHERE id = ?4",
+        params![STATUS_SYNCED, external_id, now_string(), id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// 送信失敗を記録（接続エラーなら送信待ちのまま、失敗回数に応じて再送を遅らせる）
+pub fn mark_error(conn: &Connection, id: i64, error: &SyncError) -> Result<(), String> {
+    let attempts: i64 = conn
+        .query_row(
+            "SELECT attempts FROM sync_queue WHERE id = ?1",
+            params![id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    let attempts = attempts + 1;
+
+    let (status, next_attempt_at) = if error.is_network() {
+        let next = Local::now() + Duration::seconds(retry_delay_seconds(attempts));
+        (
+            STATUS_PENDING,
+            Some(next.format("%Y-%m-%dT%H:%M:%S").to_string()),
+        )
+    } else {
+        (STATUS_FAILED, None)
+    };
+
+    conn.execute(
+        "UPDATE sync_queue SET status = ?1, attempts = ?2, last_error = ?3, next_attempt_at = ?4
+         WHERE id = ?5",
+        params![status, attempts, error.message(), next_attempt_at, id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// エントリを削除
+pub fn discard(conn: &Connection, id: i64) -> Result<(), String> {
+    let deleted = conn
+        .execute("DELETE FROM sync_queue WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+    if deleted == 0 {
+        return Err(format!("Queue entry not found: {}", id));
+    }
+    Ok(())
+}
+
+/// 失敗したエントリを送信待ちに戻す
+pub fn retry(conn: &Connection, id: i64) -> Result<(), String> {
+    let updated = conn
+        .execute(
+            "UPDATE sync_queue SET status = ?1, next_attempt_at = NULL WHERE id = ?2 AND status = ?3",
+            params![STATUS_PENDING, id, STATUS_FAILED],
+        )
+        .map_err(|e| e.to_string())?;
+    if updated == 0 {
+        return Err(format!("Failed queue entry not found: {}", id));
+    }
+    Ok(())
+}
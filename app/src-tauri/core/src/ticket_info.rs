@@ -0,0 +1,117 @@
+use chrono::{Duration, Local, NaiveDateTime};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use crate::plugins::traits::IssueSummary;
+
+/// 取得したチケット情報を使い回す期間
+pub const TTL_HOURS: i64 = 6;
+
+const TIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+/// キャッシュしたチケットの件名・状態
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TicketInfo {
+    pub plugin: String,
+    pub ticket_id: String,
+    pub subject: String,
+    pub project: Option<String>,
+    pub status: Option<String>,
+    pub fetched_at: String,
+    /// 取得に失敗したため、期限切れのキャッシュを返した
+    #[serde(default)]
+    pub stale: bool,
+}
+
+impl TicketInfo {
+    /// TTL 内に取得したものか
+    pub fn is_fresh(&self) -> bool {
+        NaiveDateTime::parse_from_str(&self.fetched_at, TIME_FORMAT).is_ok_and(|fetched_at| {
+            Local::now().naive_local() - fetched_at < Duration::hours(TTL_HOURS)
+        })
+    }
+}
+
+pub fn create_tables(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS ticket_info (
+            plugin TEXT NOT NULL,
+            ticket_id TEXT NOT NULL,
+            subject TEXT NOT NULL,
+            project TEXT,
+            status TEXT,
+            fetched_at TEXT NOT NULL,
+            PRIMARY KEY (plugin, ticket_id)
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+/// キャッシュを取得（期限切れでも返す）
+pub fn cached(
+    conn: &Connection,
+    plugin: &str,
+    ticket_id: &str,
+) -> Result<Option<TicketInfo>, String> {
+    conn.query_row(
+        "SELECT subject, project, status, fetched_at FROM ticket_info
+         WHERE plugin = ?1 AND ticket_id = ?2",
+        params![plugin, ticket_id],
+        |row| {
+            Ok(TicketInfo {
+                plugin: plugin.to_string(),
+                ticket_id: ticket_id.to_string(),
+                subject: row.get(0)?,
+                project: row.get(1)?,
+                status: row.get(2)?,
+                fetched_at: row.get(3)?,
+                stale: false,
+            })
+        },
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+/// 取得したチケット情報を保存
+pub fn store(
+    conn: &Connection,
+    plugin: &str,
+    ticket_id: &str,
+    issue: &IssueSummary,
+) -> Result<TicketInfo, String> {
+    let info = TicketInfo {
+        plugin: plugin.to_string(),
+        ticket_id: ticket_id.to_string(),
+        subject: issue.subject.clone(),
+        project: issue.project.clone(),
+        status: issue.status.clone(),
+        fetched_at: Local::now().format(TIME_FORMAT).to_string(),
+        stale: false,
+    };
+    conn.execute(
+        "INSERT OR REPLACE INTO ticket_info (plugin, ticket_id, subject, project, status, fetched_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            info.plugin,
+            info.ticket_id,
+            info.subject,
+            info.project,
+            info.status,
+            info.fetched_at
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(info)
+}
+
+/// チケットが見つからなくなったらキャッシュから消す
+pub fn remove(conn: &Connection, plugin: &str, ticket_id: &str) -> Result<(), String> {
+    conn.execute(
+        "DELETE FROM ticket_info WHERE plugin = ?1 AND ticket_id = ?2",
+        params![plugin, ticket_id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
@@ -0,0 +1,312 @@
+use chrono::NaiveDateTime;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+/// 記録されたアクティビティ
+pub const KIND_ACTIVITY: &str = "activity";
+/// 入力がなかった時間
+pub const KIND_IDLE: &str = "idle";
+/// 一時停止していた時間
+pub const KIND_PAUSED: &str = "paused";
+/// 画面ロック・スリープ中の時間
+pub const KIND_LOCKED: &str = "locked";
+/// 記録のない時間（トラッキング停止・アプリ未起動など）
+pub const KIND_GAP: &str = "gap";
+
+/// 同じアプリの記録をまとめる既定の間隔（秒）
+pub const DEFAULT_MERGE_THRESHOLD_SECONDS: i64 = 60;
+
+/// タイムライン上の1区間
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimelineSegment {
+    /// "activity" | "idle" | "paused" | "locked" | "gap"
+    pub kind: String,
+    pub process_name: Option<String>,
+    pub domain: Option<String>,
+    pub category: Option<String>,
+    /// 区間内で最も長く表示されていたタイトル
+    pub window_title: Option<String>,
+    pub start_time: String,
+    pub end_time: String,
+    /// activity は記録時間の合計、それ以外は区間の長さ
+    pub duration_seconds: i64,
+    pub activity_ids: Vec<i64>,
+}
+
+struct Row {
+    id: i64,
+    process_name: String,
+    window_title: String,
+    domain: Option<String>,
+    category: Option<String>,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    duration_seconds: i64,
+}
+
+struct Merged {
+    rows: Vec<Row>,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+}
+
+const TIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+pub fn create_tables(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS idle_periods (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            start_time TEXT NOT NULL,
+            end_time TEXT NOT NULL
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_idle_periods_start ON idle_periods(start_time)",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS locked_periods (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            start_time TEXT NOT NULL,
+            end_time TEXT NOT NULL
+        )",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// アイドル時間を記録
+pub fn record_idle(conn: &Connection, start_time: &str, end_time: &str) -> Result<(), String> {
+    if end_time <= start_time {
+        return Ok(());
+    }
+    conn.execute(
+        "INSERT INTO idle_periods (start_time, end_time) VALUES (?1, ?2)",
+        params![start_time, end_time],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// 画面ロック・スリープの時間を記録
+pub fn record_locked(conn: &Connection, start_time: &str, end_time: &str) -> Result<(), String> {
+    if end_time <= start_time {
+        return Ok(());
+    }
+    conn.execute(
+        "INSERT INTO locked_periods (start_time, end_time) VALUES (?1, ?2)",
+        params![start_time, end_time],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn parse(s: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(s, TIME_FORMAT).ok()
+}
+
+fn format(t: NaiveDateTime) -> String {
+    t.format(TIME_FORMAT).to_string()
+}
+
+fn load_activities(conn: &Connection, start: &str, end: &str) -> Result<Vec<Row>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, process_name, window_title, domain, category, start_time, end_time, duration_seconds
+             FROM activities
+             WHERE start_time >= ?1 AND start_time <= ?2
+             ORDER BY start_time ASC",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map(params![start, end], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, Option<String>>(4)?,
+                row.get::<_, String>(5)?,
+                row.get::<_, String>(6)?,
+                row.get::<_, i64>(7)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .filter_map(
+            |(id, process_name, window_title, domain, category, start, end, duration_seconds)| {
+                Some(Row {
+                    id,
+                    process_name,
+                    window_title,
+                    domain,
+                    category,
+                    start: parse(&start)?,
+                    end: parse(&end)?,
+                    duration_seconds,
+                })
+            },
+        )
+        .collect();
+
+    Ok(rows)
+}
+
+/// 区間のテーブル（idle_periods / paused_periods / locked_periods）から読み込む
+fn load_periods(
+    conn: &Connection,
+    table: &str,
+    start: &str,
+    end: &str,
+) -> Result<Vec<(NaiveDateTime, NaiveDateTime)>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT start_time, end_time FROM {}
+             WHERE end_time >= ?1 AND start_time <= ?2
+             ORDER BY start_time ASC",
+            table
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let periods = stmt
+        .query_map(params![start, end], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .filter_map(|(start, end)| Some((parse(&start)?, parse(&end)?)))
+        .collect();
+
+    Ok(periods)
+}
+
+fn activity_segment(merged: Merged) -> TimelineSegment {
+    // タイトルごとの合計時間で代表タイトルを決める
+    let mut titles: Vec<(&str, i64)> = Vec::new();
+    for row in &merged.rows {
+        match titles.iter_mut().find(|(t, _)| *t == row.window_title) {
+            Some((_, seconds)) => *seconds += row.duration_seconds,
+            None => titles.push((&row.window_title, row.duration_seconds)),
+        }
+    }
+    let window_title = titles
+        .iter()
+        .max_by_key(|(_, seconds)| *seconds)
+        .map(|(t, _)| t.to_string());
+
+    let first = &merged.rows[0];
+    TimelineSegment {
+        kind: KIND_ACTIVITY.to_string(),
+        process_name: Some(first.process_name.clone()),
+        domain: first.domain.clone(),
+        category: first.category.clone(),
+        window_title,
+        start_time: format(merged.start),
+        end_time: format(merged.end),
+        duration_seconds: merged.rows.iter().map(|r| r.duration_seconds).sum(),
+        activity_ids: merged.rows.iter().map(|r| r.id).collect(),
+    }
+}
+
+fn empty_segment(kind: &str, start: NaiveDateTime, end: NaiveDateTime) -> TimelineSegment {
+    TimelineSegment {
+        kind: kind.to_string(),
+        process_name: None,
+        domain: None,
+        category: None,
+        window_title: None,
+        start_time: format(start),
+        end_time: format(end),
+        duration_seconds: (end - start).num_seconds(),
+        activity_ids: Vec::new(),
+    }
+}
+
+/// 記録の間の時間をアイドル/一時停止/ロックの区間と記録なし区間に分ける
+///
+/// periods は開始時刻順に並んだ (開始, 終了, 種類)。
+fn fill_gap(
+    out: &mut Vec<TimelineSegment>,
+    from: NaiveDateTime,
+    to: NaiveDateTime,
+    periods: &[(NaiveDateTime, NaiveDateTime, &str)],
+) {
+    let mut cursor = from;
+    for &(period_start, period_end, kind) in periods {
+        let start = period_start.max(cursor);
+        let end = period_end.min(to);
+        if end <= start {
+            continue;
+        }
+        if start > cursor {
+            out.push(empty_segment(KIND_GAP, cursor, start));
+        }
+        out.push(empty_segment(kind, start, end));
+        cursor = end;
+    }
+    if to > cursor {
+        out.push(empty_segment(KIND_GAP, cursor, to));
+    }
+}
+
+/// 指定日のタイムラインを作成（最初の記録から最後の記録まで）
+///
+/// 同じアプリ・ドメインの記録は merge_threshold_seconds 以内の途切れならまとめる。
+/// それより長い途切れはアイドル/一時停止/ロック/記録なしの区間として明示する。
+pub fn build(
+    conn: &Connection,
+    date: &str,
+    merge_threshold_seconds: i64,
+) -> Result<Vec<TimelineSegment>, String> {
+    let start_of_day = format!("{}T00:00:00", date);
+    let end_of_day = format!("{}T23:59:59", date);
+    let rows = load_activities(conn, &start_of_day, &end_of_day)?;
+    let mut periods: Vec<(NaiveDateTime, NaiveDateTime, &str)> = Vec::new();
+    for (start, end) in load_periods(conn, "idle_periods", &start_of_day, &end_of_day)? {
+        periods.push((start, end, KIND_IDLE));
+    }
+    for (start, end) in load_periods(conn, "paused_periods", &start_of_day, &end_of_day)? {
+        periods.push((start, end, KIND_PAUSED));
+    }
+    for (start, end) in load_periods(conn, "locked_periods", &start_of_day, &end_of_day)? {
+        periods.push((start, end, KIND_LOCKED));
+    }
+    periods.sort_by_key(|(start, _, _)| *start);
+
+    let mut merged: Vec<Merged> = Vec::new();
+    for row in rows {
+        if let Some(last) = merged.last_mut() {
+            let prev = &last.rows[0];
+            let same = prev.process_name == row.process_name && prev.domain == row.domain;
+            if same && (row.start - last.end).num_seconds() <= merge_threshold_seconds {
+                last.end = last.end.max(row.end);
+                last.rows.push(row);
+                continue;
+            }
+        }
+        merged.push(Merged {
+            start: row.start,
+            end: row.end,
+            rows: vec![row],
+        });
+    }
+
+    let mut segments = Vec::new();
+    let mut previous_end: Option<NaiveDateTime> = None;
+    for block in merged {
+        if let Some(previous_end) = previous_end {
+            if (block.start - previous_end).num_seconds() > merge_threshold_seconds {
+                fill_gap(&mut segments, previous_end, block.start, &periods);
+            }
+        }
+        previous_end = Some(previous_end.map_or(block.end, |end| end.max(block.end)));
+        segments.push(activity_segment(block));
+    }
+
+    Ok(segments)
+}
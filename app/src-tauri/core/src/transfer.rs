@@ -0,0 +1,245 @@
+use chrono::Local;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use crate::assignments;
+use crate::categories::{self, CategoryInfo};
+
+/// エクスポート形式のバージョン
+pub const EXPORT_VERSION: u32 = 1;
+
+/// エクスポートするアクティビティ（割り当て・カテゴリを含む）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedActivity {
+    pub process_name: String,
+    pub window_title: String,
+    pub domain: Option<String>,
+    pub start_time: String,
+    pub end_time: String,
+    pub duration_seconds: i64,
+    #[serde(default)]
+    pub category: Option<String>,
+    #[serde(default)]
+    pub plugin: Option<String>,
+    #[serde(default)]
+    pub ticket_id: Option<String>,
+    #[serde(default)]
+    pub project: Option<String>,
+    #[serde(default)]
+    pub assignment_source: Option<String>,
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+/// JSON エクスポートの全体
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportBundle {
+    pub version: u32,
+    pub exported_at: String,
+    #[serde(default)]
+    pub categories: Vec<CategoryInfo>,
+    pub activities: Vec<ExportedActivity>,
+}
+
+/// JSONL の1行（ヘッダー・カテゴリ・アクティビティのいずれか）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum JsonlLine {
+    Header { version: u32, exported_at: String },
+    Category(CategoryInfo),
+    Activity(ExportedActivity),
+}
+
+/// インポート結果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub imported: usize,
+    /// (process_name, window_title, start_time) が一致したためスキップした件数
+    pub skipped_duplicates: usize,
+    pub categories_added: usize,
+}
+
+/// 全アクティビティとカテゴリをエクスポート
+pub fn export(conn: &Connection) -> Result<ExportBundle, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT a.process_name, a.window_title, a.domain, a.start_time, a.end_time, a.duration_seconds,
+                    a.category, t.plugin, t.ticket_id, t.project, t.source, a.note
+             FROM activities a
+             LEFT JOIN ticket_assignments t ON t.activity_id = a.id
+             ORDER BY a.start_time ASC",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let activities = stmt
+        .query_map([], |row| {
+            Ok(ExportedActivity {
+                process_name: row.get(0)?,
+                window_title: row.get(1)?,
+                domain: row.get(2)?,
+                start_time: row.get(3)?,
+                end_time: row.get(4)?,
+                duration_seconds: row.get(5)?,
+                category: row.get(6)?,
+                plugin: row.get(7)?,
+                ticket_id: row.get(8)?,
+                project: row.get(9)?,
+                assignment_source: row.get(10)?,
+                note: row.get(11)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(ExportBundle {
+        version: EXPORT_VERSION,
+        exported_at: Local::now().format("%Y-%m-%dT%H:%M:%S").to_string(),
+        categories: categories::list(conn)?,
+        activities,
+    })
+}
+
+/// JSONL 形式に変換（1行目はヘッダー）
+pub fn to_jsonl(bundle: &ExportBundle) -> Result<String, String> {
+    let header = JsonlLine::Header {
+        version: bundle.version,
+        exported_at: bundle.exported_at.clone(),
+    };
+    let lines = std::iter::once(header)
+        .chain(bundle.categories.iter().cloned().map(JsonlLine::Category))
+        .chain(bundle.activities.iter().cloned().map(JsonlLine::Activity));
+
+    let mut out = String::new();
+    for line in lines {
+        out.push_str(&serde_json::to_string(&line).map_err(|e| e.to_string())?);
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// JSON または JSONL を読み込む
+pub fn parse(content: &str) -> Result<ExportBundle, String> {
+    if let Ok(bundle) = serde_json::from_str::<ExportBundle>(content) {
+        return check_version(bundle);
+    }
+
+    let mut bundle = ExportBundle {
+        version: EXPORT_VERSION,
+        exported_at: String::new(),
+        categories: Vec::new(),
+        activities: Vec::new(),
+    };
+
+    for (index, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let line: JsonlLine =
+            serde_json::from_str(line).map_err(|e| format!("Invalid line {}: {}", index + 1, e))?;
+        match line {
+            JsonlLine::Header {
+                version,
+                exported_at,
+            } => {
+                bundle.version = version;
+                bundle.exported_at = exported_at;
+            }
+            JsonlLine::Category(category) => bundle.categories.push(category),
+            JsonlLine::Activity(activity) => bundle.activities.push(activity),
+        }
+    }
+
+    check_version(bundle)
+}
+
+fn check_version(bundle: ExportBundle) -> Result<ExportBundle, String> {
+    if bundle.version > EXPORT_VERSION {
+        return Err(format!(
+            "Unsupported export version: {} (expected {} or lower)",
+            bundle.version, EXPORT_VERSION
+        ));
+    }
+    Ok(bundle)
+}
+
+/// インポート（既存と同じ process_name・window_title・start_time のアクティビティは取り込まない）
+pub fn import(conn: &Connection, bundle: &ExportBundle) -> Result<ImportReport, String> {
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+
+    let mut report = ImportReport {
+        imported: 0,
+        skipped_duplicates: 0,
+        categories_added: 0,
+    };
+
+    for category in &bundle.categories {
+        report.categories_added += tx
+            .execute(
+                "INSERT OR IGNORE INTO categories (name, color, description, created_at)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![
+                    category.name,
+                    category.color,
+                    category.description,
+                    category.created_at
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+    }
+
+    for activity in &bundle.activities {
+        let exists: bool = tx
+            .query_row(
+                "SELECT EXISTS(SELECT 1 FROM activities
+                 WHERE process_name = ?1 AND window_title = ?2 AND start_time = ?3)",
+                params![
+                    activity.process_name,
+                    activity.window_title,
+                    activity.start_time
+                ],
+                |row| row.get(0),
+            )
+            .map_err(|e| e.to_string())?;
+        if exists {
+            report.skipped_duplicates += 1;
+            continue;
+        }
+
+        tx.execute(
+            "INSERT INTO activities
+                (process_name, window_title, domain, start_time, end_time, duration_seconds, category, note)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                activity.process_name,
+                activity.window_title,
+                activity.domain,
+                activity.start_time,
+                activity.end_time,
+                activity.duration_seconds,
+                activity.category,
+                activity.note
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+        let activity_id = tx.last_insert_rowid();
+
+        if activity.ticket_id.is_some() || activity.project.is_some() {
+            assignments::assign(
+                &tx,
+                activity_id,
+                activity.plugin.as_deref(),
+                activity.ticket_id.as_deref(),
+                activity.project.as_deref(),
+                activity
+                    .assignment_source
+                    .as_deref()
+                    .unwrap_or(assignments::SOURCE_MANUAL),
+            )?;
+        }
+        report.imported += 1;
+    }
+
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(report)
+}
@@ -0,0 +1,137 @@
+use chrono::Local;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+/// アップロード履歴
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UploadLogEntry {
+    pub date: String,
+    pub success: bool,
+    pub message: String,
+    pub uploaded_count: usize,
+    pub uploaded_at: String,
+}
+
+/// アップロードの状態（フロントエンド用）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UploadStatus {
+    pub enabled: bool,
+    pub auto_upload: bool,
+    pub auto_upload_interval_minutes: u32,
+    /// 未送信のアクティビティ件数
+    pub pending_activities: i64,
+    /// 未送信のアクティビティを含む日付（古い順）
+    pub pending_dates: Vec<String>,
+    pub last_upload: Option<UploadLogEntry>,
+}
+
+pub fn create_tables(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS upload_log (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            date TEXT NOT NULL,
+            success INTEGER NOT NULL,
+            message TEXT NOT NULL,
+            uploaded_count INTEGER NOT NULL,
+            uploaded_at TEXT NOT NULL
+        )",
+        [],
+    )?;
+
+    Ok(())
+}
+
+fn now_string() -> String {
+    Local::now().format("%Y-%m-%dT%H:%M:%S").to_string()
+}
+
+/// 未送信のアクティビティを含む日付（古い順）
+pub fn pending_dates(conn: &Connection) -> Result<Vec<String>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT DISTINCT substr(start_time, 1, 10) AS day
+             FROM activities
+             WHERE uploaded_at IS NULL
+             ORDER BY day ASC",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let dates = stmt
+        .query_map([], |row| row.get(0))
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(dates)
+}
+
+/// 指定日の最大のアクティビティID（集計時点までのものだけを送信済みにするため）
+pub fn max_activity_id(conn: &Connection, date: &str) -> Result<Option<i64>, String> {
+    conn.query_row(
+        "SELECT MAX(id) FROM activities WHERE start_time >= ?1 AND start_time <= ?2",
+        params![format!("{}T00:00:00", date), format!("{}T23:59:59", date)],
+        |row| row.get(0),
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// 指定日のアクティビティを送信済みにする（up_to_id までのもの）
+pub fn mark_uploaded(conn: &Connection, date: &str, up_to_id: i64) -> Result<(), String> {
+    conn.execute(
+        "UPDATE activities SET uploaded_at = ?1
+         WHERE start_time >= ?2 AND start_time <= ?3 AND id <= ?4",
+        params![
+            now_string(),
+            format!("{}T00:00:00", date),
+            format!("{}T23:59:59", date),
+            up_to_id
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// アップロード結果を記録
+pub fn log_result(
+    conn: &Connection,
+    date: &str,
+    success: bool,
+    message: &str,
+    uploaded_count: usize,
+) {
+    let _ = conn.execute(
+        "INSERT INTO upload_log (date, success, message, uploaded_count, uploaded_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![date, success, message, uploaded_count as i64, now_string()],
+    );
+}
+
+/// 直近のアップロード結果
+pub fn last_upload(conn: &Connection) -> Result<Option<UploadLogEntry>, String> {
+    conn.query_row(
+        "SELECT date, success, message, uploaded_count, uploaded_at
+         FROM upload_log ORDER BY id DESC LIMIT 1",
+        [],
+        |row| {
+            Ok(UploadLogEntry {
+                date: row.get(0)?,
+                success: row.get(1)?,
+                message: row.get(2)?,
+                uploaded_count: row.get::<_, i64>(3)? as usize,
+                uploaded_at: row.get(4)?,
+            })
+        },
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+/// 未送信のアクティビティ件数
+pub fn pending_count(conn: &Connection) -> Result<i64, String> {
+    conn.query_row(
+        "SELECT COUNT(*) FROM activities WHERE uploaded_at IS NULL",
+        [],
+        |row| row.get(0),
+    )
+    .map_err(|e| e.to_string())
+}
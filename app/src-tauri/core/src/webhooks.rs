@@ -0,0 +1,235 @@
+use chrono::Local;
+use hmac::{Hmac, Mac};
+use rusqlite::params;
+use serde::Serialize;
+use sha2::Sha256;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+use crate::live::LiveEvent;
+use crate::plugins::config::{WebhookEndpoint, WebhooksConfig};
+use crate::secrets;
+use crate::AppState;
+
+/// アクティビティが終わった（保存前、除外対象は送らない）
+pub const EVENT_ACTIVITY_FINISHED: &str = "activity.finished";
+/// 目標を達成した・上限を超えた
+pub const EVENT_GOAL_REACHED: &str = "goal.reached";
+/// 今日の合計が daily_thresholds_hours のいずれかを超えた
+pub const EVENT_DAILY_THRESHOLD: &str = "daily.threshold";
+
+/// 署名のヘッダー（"sha256=" + 本文の HMAC-SHA256 の16進）
+const SIGNATURE_HEADER: &str = "X-TimeTracker-Signature";
+const EVENT_HEADER: &str = "X-TimeTracker-Event";
+
+#[derive(Debug, Serialize)]
+struct Payload<'a, T: Serialize> {
+    event: &'a str,
+    sent_at: String,
+    data: T,
+}
+
+impl WebhookEndpoint {
+    /// events が空なら全イベントを送る
+    fn subscribes(&self, event: &str) -> bool {
+        self.events.is_empty() || self.events.iter().any(|e| e == event)
+    }
+}
+
+/// 購読しているエンドポイントにイベントを送る（エンドポイントごとに並行して送り、失敗したら指数バックオフで再送）
+pub fn send<T: Serialize>(config: &WebhooksConfig, event: &str, data: T) {
+    let endpoints: Vec<WebhookEndpoint> = config
+        .endpoints
+        .iter()
+        .filter(|endpoint| endpoint.subscribes(event))
+        .cloned()
+        .collect();
+    if endpoints.is_empty() {
+        return;
+    }
+
+    let payload = Payload {
+        event,
+        sent_at: Local::now().format("%Y-%m-%dT%H:%M:%S").to_string(),
+        data,
+    };
+    let body: Arc<[u8]> = match serde_json::to_vec(&payload) {
+        Ok(body) => body.into(),
+        Err(e) => {
+            eprintln!("Failed to encode webhook payload: {}", e);
+            return;
+        }
+    };
+
+    let client = reqwest::Client::new();
+    for endpoint in endpoints {
+        let client = client.clone();
+        let event = event.to_string();
+        let body = body.clone();
+        let max_retries = config.max_retries;
+        crate::runtime::spawn(async move {
+            if let Err(e) = deliver(&client, &endpoint, &event, &body, max_retries).await {
+                eprintln!("Webhook {} to {} failed: {}", event, endpoint.url, e);
+            }
+        });
+    }
+}
+
+/// 署名に使う鍵（キーリングの値を優先。キーリングはブロックするのでワーカーの外で読む）
+async fn signing_key(endpoint: &WebhookEndpoint) -> Result<Option<String>, String> {
+    let Some(alias) = endpoint.secret_secret.clone() else {
+        return Ok(endpoint.secret.clone().filter(|s| !s.is_empty()));
+    };
+    let secret = crate::runtime::spawn_blocking({
+        let alias = alias.clone();
+        move || secrets::get_integration(&alias)
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    secret
+        .map(Some)
+        .ok_or_else(|| format!("Webhook secret not found in the OS keyring: {}", alias))
+}
+
+/// "sha256=" + 本文の HMAC-SHA256 の16進
+fn sign(key: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(key.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(body);
+    format!("sha256={}", hex(&mac.finalize().into_bytes()))
+}
+
+async fn deliver(
+    client: &reqwest::Client,
+    endpoint: &WebhookEndpoint,
+    event: &str,
+    body: &[u8],
+    max_retries: u32,
+) -> Result<(), String> {
+    let signature = signing_key(endpoint).await?.map(|key| sign(&key, body));
+    let mut attempt = 0;
+    loop {
+        let mut request = client
+            .post(&endpoint.url)
+            .header("Content-Type", "application/json")
+            .header(EVENT_HEADER, event)
+            .timeout(Duration::from_secs(10))
+            .body(body.to_vec());
+        if let Some(signature) = &signature {
+            request = request.header(SIGNATURE_HEADER, signature);
+        }
+
+        let error = match request.send().await {
+            Ok(response) if response.status().is_success() => return Ok(()),
+            // 4xx（429 を除く）は再送しても結果が変わらない
+            Ok(response)
+                if response.status().is_client_error()
+                    && response.status() != reqwest::StatusCode::TOO_MANY_REQUESTS =>
+            {
+                return Err(format!("status {}", response.status()));
+            }
+            Ok(response) => format!("status {}", response.status()),
+            Err(e) => e.to_string(),
+        };
+
+        if attempt >= max_retries {
+            return Err(error);
+        }
+        tokio::time::sleep(Duration::from_secs(1 << attempt.min(6))).await;
+        attempt += 1;
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[derive(Debug, Serialize)]
+struct DailyThreshold {
+    date: String,
+    threshold_hours: f64,
+    total_seconds: i64,
+}
+
+/// 今日の合計（DB のロックで tokio のワーカーを止めないよう別スレッドで読む）
+async fn today_total(state: &Arc<AppState>, date: &str) -> Result<i64, String> {
+    let state = state.clone();
+    let date = date.to_string();
+    crate::runtime::spawn_blocking(move || {
+        state
+            .db
+            .lock()
+            .query_row(
+                "SELECT COALESCE(SUM(duration_seconds), 0) FROM activities
+                 WHERE start_time >= ?1 AND start_time <= ?2",
+                params![format!("{}T00:00:00", date), format!("{}T23:59:59", date)],
+                |row| row.get(0),
+            )
+            .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// アクティビティの終了と今日の合計のしきい値を Webhook で送る（目標は目標の通知と一緒に送る）
+pub fn start(state: Arc<AppState>, config: WebhooksConfig) {
+    if config.endpoints.is_empty() {
+        return;
+    }
+
+    let mut receiver = state.live.subscribe();
+    crate::runtime::spawn(async move {
+        // 送信済みの (日付, しきい値の秒数)。起動時に超えているしきい値は送らない
+        let mut crossed: HashSet<(String, i64)> = HashSet::new();
+        let date = Local::now().format("%Y-%m-%d").to_string();
+        if let Ok(total_seconds) = today_total(&state, &date).await {
+            for &hours in &config.daily_thresholds_hours {
+                let threshold = (hours * 3600.0) as i64;
+                if total_seconds >= threshold {
+                    crossed.insert((date.clone(), threshold));
+                }
+            }
+        }
+
+        loop {
+            match receiver.recv().await {
+                Ok(event @ LiveEvent::ActivityEnd { .. }) => {
+                    send(&config, EVENT_ACTIVITY_FINISHED, event);
+                }
+                Ok(LiveEvent::ActivitiesSaved { .. })
+                    if !config.daily_thresholds_hours.is_empty() =>
+                {
+                    let date = Local::now().format("%Y-%m-%d").to_string();
+                    let total_seconds = match today_total(&state, &date).await {
+                        Ok(total) => total,
+                        Err(e) => {
+                            eprintln!("Failed to load today's total: {}", e);
+                            continue;
+                        }
+                    };
+
+                    for &hours in &config.daily_thresholds_hours {
+                        let threshold = (hours * 3600.0) as i64;
+                        if total_seconds < threshold || !crossed.insert((date.clone(), threshold)) {
+                            continue;
+                        }
+                        send(
+                            &config,
+                            EVENT_DAILY_THRESHOLD,
+                            DailyThreshold {
+                                date: date.clone(),
+                                threshold_hours: hours,
+                                total_seconds,
+                            },
+                        );
+                    }
+                }
+                Ok(_) => {}
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+}
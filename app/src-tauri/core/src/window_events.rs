@@ -0,0 +1,177 @@
+use parking_lot::{Condvar, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// 前回の待機以降にフォアグラウンドウィンドウが切り替わったか（タイトルの変更を含む）
+static CHANGED: Mutex<bool> = Mutex::new(false);
+static SIGNAL: Condvar = Condvar::new();
+/// セッションがロックされている
+static LOCKED: AtomicBool = AtomicBool::new(false);
+/// スリープに入るところ（再開の通知で false に戻る）
+static SUSPENDED: AtomicBool = AtomicBool::new(false);
+
+/// ウィンドウの切り替えを通知してウォッチャーを起こす
+pub fn notify() {
+    *CHANGED.lock() = true;
+    SIGNAL.notify_all();
+}
+
+/// ロック・スリープの状態を更新してウォッチャーを起こす
+#[cfg(target_os = "windows")]
+fn set_session_flag(flag: &AtomicBool, value: bool) {
+    flag.store(value, Ordering::SeqCst);
+    notify();
+}
+
+/// 画面ロック中またはスリープ中（通知を受け取れる環境のみ）
+pub fn is_away() -> bool {
+    LOCKED.load(Ordering::SeqCst) || SUSPENDED.load(Ordering::SeqCst)
+}
+
+/// 切り替えの通知が来るか timeout が過ぎるまで待つ
+///
+/// フックがない環境では通知が来ないので、単に timeout ごとのポーリングになる。
+pub fn wait(timeout: Duration) {
+    let mut changed = CHANGED.lock();
+    if !*changed {
+        SIGNAL.wait_for(&mut changed, timeout);
+    }
+    *changed = false;
+}
+
+/// ロック/ロック解除とスリープ/復帰の通知を受け取る非表示ウィンドウを作る
+#[cfg(target_os = "windows")]
+unsafe fn create_session_window() -> windows::core::Result<()> {
+    use windows::core::w;
+    use windows::Win32::Foundation::{HWND, LPARAM, LRESULT, WPARAM};
+    use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+    use windows::Win32::System::RemoteDesktop::WTSRegisterSessionNotification;
+    use windows::Win32::UI::WindowsAndMessaging::{
+        CreateWindowExW, DefWindowProcW, RegisterClassW, HMENU, WINDOW_EX_STYLE, WM_POWERBROADCAST,
+        WM_WTSSESSION_CHANGE, WNDCLASSW, WS_OVERLAPPED,
+    };
+
+    const NOTIFY_FOR_THIS_SESSION: u32 = 0;
+    const WTS_SESSION_LOCK: u32 = 0x7;
+    const WTS_SESSION_UNLOCK: u32 = 0x8;
+    const PBT_APMSUSPEND: u32 = 0x4;
+    const PBT_APMRESUMESUSPEND: u32 = 0x7;
+    const PBT_APMRESUMEAUTOMATIC: u32 = 0x12;
+
+    unsafe extern "system" fn session_proc(
+        hwnd: HWND,
+        msg: u32,
+        wparam: WPARAM,
+        lparam: LPARAM,
+    ) -> LRESULT {
+        match (msg, wparam.0 as u32) {
+            (WM_WTSSESSION_CHANGE, WTS_SESSION_LOCK) => set_session_flag(&LOCKED, true),
+            (WM_WTSSESSION_CHANGE, WTS_SESSION_UNLOCK) => set_session_flag(&LOCKED, false),
+            (WM_POWERBROADCAST, PBT_APMSUSPEND) => set_session_flag(&SUSPENDED, true),
+            (WM_POWERBROADCAST, PBT_APMRESUMESUSPEND | PBT_APMRESUMEAUTOMATIC) => {
+                set_session_flag(&SUSPENDED, false)
+            }
+            _ => {}
+        }
+        DefWindowProcW(hwnd, msg, wparam, lparam)
+    }
+
+    let instance = GetModuleHandleW(None)?.into();
+    let class_name = w!("TimeTrackerSessionEvents");
+    let class = WNDCLASSW {
+        lpfnWndProc: Some(session_proc),
+        hInstance: instance,
+        lpszClassName: class_name,
+        ..Default::default()
+    };
+    RegisterClassW(&class);
+
+    // 電源の通知はトップレベルウィンドウにしか届かないため、メッセージ専用ではなく非表示のウィンドウにする
+    let hwnd = CreateWindowExW(
+        WINDOW_EX_STYLE::default(),
+        class_name,
+        w!("TimeTracker"),
+        WS_OVERLAPPED,
+        0,
+        0,
+        0,
+        0,
+        HWND::default(),
+        HMENU::default(),
+        instance,
+        None,
+    )?;
+    WTSRegisterSessionNotification(hwnd, NOTIFY_FOR_THIS_SESSION)
+}
+
+/// フォアグラウンドの切り替え・タイトル変更のフックとロック・スリープの通知を登録（フックを登録できたら true）
+#[cfg(target_os = "windows")]
+pub fn start() -> bool {
+    use std::sync::mpsc;
+    use windows::Win32::Foundation::{HMODULE, HWND};
+    use windows::Win32::UI::Accessibility::{SetWinEventHook, UnhookWinEvent, HWINEVENTHOOK};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        DispatchMessageW, GetForegroundWindow, GetMessageW, TranslateMessage,
+        EVENT_OBJECT_NAMECHANGE, EVENT_SYSTEM_FOREGROUND, MSG, OBJID_WINDOW, WINEVENT_OUTOFCONTEXT,
+        WINEVENT_SKIPOWNPROCESS,
+    };
+
+    unsafe extern "system" fn on_event(
+        _hook: HWINEVENTHOOK,
+        event: u32,
+        hwnd: HWND,
+        id_object: i32,
+        _id_child: i32,
+        _event_thread: u32,
+        _event_time: u32,
+    ) {
+        // 名前の変更は全ウィンドウ・全コントロールで届くので、フォアグラウンドのタイトルだけ拾う
+        if event == EVENT_OBJECT_NAMECHANGE
+            && (id_object != OBJID_WINDOW.0 || hwnd != GetForegroundWindow())
+        {
+            return;
+        }
+        notify();
+    }
+
+    let hook = |event| unsafe {
+        SetWinEventHook(
+            event,
+            event,
+            HMODULE::default(),
+            Some(on_event),
+            0,
+            0,
+            WINEVENT_OUTOFCONTEXT | WINEVENT_SKIPOWNPROCESS,
+        )
+    };
+
+    // フックは登録したスレッドのメッセージループで呼ばれるため専用スレッドを立てる
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || unsafe {
+        let hooks = [hook(EVENT_SYSTEM_FOREGROUND), hook(EVENT_OBJECT_NAMECHANGE)];
+        // どちらかが欠けるとタイトルの変更を待ちきれないので、両方そろったときだけ通知に頼る
+        let installed = hooks.iter().all(|hook| !hook.is_invalid());
+        let _ = tx.send(installed);
+        if let Err(e) = create_session_window() {
+            eprintln!("Failed to register session notifications: {}", e);
+        }
+
+        let mut msg = MSG::default();
+        while GetMessageW(&mut msg, HWND::default(), 0, 0).as_bool() {
+            let _ = TranslateMessage(&msg);
+            DispatchMessageW(&msg);
+        }
+        for hook in hooks.into_iter().filter(|hook| !hook.is_invalid()) {
+            let _ = UnhookWinEvent(hook);
+        }
+    });
+
+    rx.recv().unwrap_or(false)
+}
+
+/// フォアグラウンドの切り替えフックを登録（フックのない環境では false）
+#[cfg(not(target_os = "windows"))]
+pub fn start() -> bool {
+    false
+}
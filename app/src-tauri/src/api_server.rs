@@ -0,0 +1,394 @@
+use axum::extract::{Query, Request, State};
+use axum::http::{HeaderMap, Method, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use chrono::{Local, NaiveDate, NaiveDateTime};
+use futures_util::stream::{self, Stream, StreamExt};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
+use std::sync::Arc;
+use tauri::AppHandle;
+use tokio::net::TcpListener;
+use tokio::sync::broadcast;
+
+use crate::api_auth;
+use crate::assignments;
+use crate::live::{CurrentActivity, LiveEvent};
+use crate::metrics;
+use crate::plugins::config::ApiServerConfig;
+use crate::runtime;
+use crate::summary::{self, GroupBy, RangeSummary};
+use crate::{load_activities, set_tracking, ActivityRecord, AppState};
+
+#[derive(Clone)]
+struct ApiContext {
+    app: AppHandle,
+    state: Arc<AppState>,
+}
+
+/// エラーは {"error": "..."} で返す
+struct ApiError(StatusCode, String);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(serde_json::json!({ "error": self.1 }))).into_response()
+    }
+}
+
+impl From<String> for ApiError {
+    fn from(message: String) -> Self {
+        ApiError(StatusCode::BAD_REQUEST, message)
+    }
+}
+
+type ApiResult<T> = Result<Json<T>, ApiError>;
+
+/// DB を使う処理を別スレッドで実行する
+///
+/// 圧縮やバックフィルが DB のロックを長く持つことがあるので、共有のランタイムのワーカーでは待たない。
+async fn with_db<T, F>(state: &Arc<AppState>, f: F) -> Result<T, ApiError>
+where
+    T: Send + 'static,
+    F: FnOnce(&AppState) -> Result<T, String> + Send + 'static,
+{
+    let state = state.clone();
+    runtime::spawn_blocking(move || f(&state))
+        .await
+        .map_err(|e| ApiError(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .map_err(ApiError::from)
+}
+
+#[derive(Debug, Deserialize)]
+struct ActivitiesQuery {
+    /// 省略時は今日
+    date: Option<String>,
+    limit: Option<u32>,
+    offset: Option<u32>,
+    #[serde(default)]
+    merge: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct SummaryQuery {
+    date: Option<String>,
+    end_date: Option<String>,
+    /// "day" | "week" | "month"
+    group_by: Option<String>,
+}
+
+/// GET /current の応答
+#[derive(Debug, Serialize)]
+struct CurrentResponse {
+    tracking: bool,
+    paused: bool,
+    activity: Option<CurrentActivity>,
+}
+
+/// POST /tracking/start・/tracking/stop の応答
+#[derive(Debug, Serialize)]
+struct TrackingResponse {
+    tracking: bool,
+}
+
+/// POST /entries で登録する手動エントリ
+#[derive(Debug, Deserialize)]
+struct ManualEntry {
+    /// 省略時は "Manual"
+    process_name: Option<String>,
+    window_title: String,
+    /// "%Y-%m-%dT%H:%M:%S"（ローカル時刻）
+    start_time: String,
+    end_time: String,
+    project: Option<String>,
+    note: Option<String>,
+}
+
+/// 手動エントリのアプリ名の既定値
+const MANUAL_PROCESS_NAME: &str = "Manual";
+
+/// ローカルの REST API サーバーを起動する
+///
+/// GET は read スコープ、POST は control スコープの API トークンが必要。
+/// GET /events は WebSocket の配信と同じイベントを Server-Sent Events で送る。
+pub async fn serve(
+    app: AppHandle,
+    state: Arc<AppState>,
+    config: ApiServerConfig,
+) -> Result<(), String> {
+    let context = ApiContext { app, state };
+    let mut router = Router::new()
+        .route("/activities", get(activities))
+        .route("/summary", get(summary))
+        .route("/current", get(current))
+        .route("/events", get(events))
+        .route("/tracking/start", post(start_tracking))
+        .route("/tracking/stop", post(stop_tracking))
+        .route("/entries", post(create_entry));
+    if config.metrics {
+        router = router.route("/metrics", get(metrics));
+    }
+    let router = router
+        .layer(middleware::from_fn_with_state(context.clone(), authorize))
+        .with_state(context);
+
+    let addr = format!("{}:{}", config.bind_address, config.port);
+    let listener = TcpListener::bind(&addr)
+        .await
+        .map_err(|e| format!("Failed to bind {}: {}", addr, e))?;
+    println!("REST API listening on http://{}", addr);
+
+    axum::serve(listener, router)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+fn bearer_token(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get("Authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::to_string)
+}
+
+/// トークンを検証し、リクエストを api_request_log に記録する
+async fn authorize(State(context): State<ApiContext>, request: Request, next: Next) -> Response {
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+
+    let token = bearer_token(request.headers());
+    let authorized = with_db(&context.state, move |state| {
+        let token = token.ok_or_else(|| "Missing token".to_string())?;
+        api_auth::authorize(&state.db.lock(), &token, api_auth::SCOPE_READ)
+    })
+    .await
+    .map_err(|ApiError(_, message)| message);
+
+    let (token_id, response) = match authorized {
+        Ok(token) if method != Method::GET && token.scope != api_auth::SCOPE_CONTROL => (
+            Some(token.id),
+            ApiError(
+                StatusCode::FORBIDDEN,
+                "Token does not have control scope".to_string(),
+            )
+            .into_response(),
+        ),
+        Ok(token) => (Some(token.id), next.run(request).await),
+        Err(e) => (None, ApiError(StatusCode::UNAUTHORIZED, e).into_response()),
+    };
+
+    // 記録は応答を待たせずに書き込む
+    let state = context.state.clone();
+    let status = response.status().as_u16();
+    runtime::spawn_blocking(move || {
+        api_auth::log_request(&state.db.lock(), token_id, method.as_str(), &path, status);
+    });
+    response
+}
+
+fn today() -> String {
+    Local::now().format("%Y-%m-%d").to_string()
+}
+
+fn parse_date(s: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").map_err(|e| e.to_string())
+}
+
+/// GET /activities?date=&limit=&offset=&merge=
+async fn activities(
+    State(context): State<ApiContext>,
+    Query(query): Query<ActivitiesQuery>,
+) -> ApiResult<Vec<ActivityRecord>> {
+    let date = query.date.unwrap_or_else(today);
+    let records = with_db(&context.state, move |state| {
+        load_activities(state, &date, query.limit, query.offset, query.merge)
+    })
+    .await?;
+    Ok(Json(records))
+}
+
+/// GET /summary?date=&end_date=&group_by=
+async fn summary(
+    State(context): State<ApiContext>,
+    Query(query): Query<SummaryQuery>,
+) -> ApiResult<RangeSummary> {
+    let start = parse_date(&query.date.unwrap_or_else(today))?;
+    let end = match query.end_date {
+        Some(end_date) => parse_date(&end_date)?,
+        None => start,
+    };
+    let group_by = GroupBy::parse(query.group_by.as_deref().unwrap_or("day"))?;
+
+    let summary = with_db(&context.state, move |state| {
+        summary::range_summary(&state.db.lock(), start, end, group_by)
+    })
+    .await?;
+    Ok(Json(summary))
+}
+
+/// GET /current
+async fn current(State(context): State<ApiContext>) -> ApiResult<CurrentResponse> {
+    let state = &context.state;
+    Ok(Json(CurrentResponse {
+        tracking: *state.is_tracking.lock(),
+        paused: state.pause.lock().is_some(),
+        activity: state.live.current(),
+    }))
+}
+
+/// GET /metrics（Prometheus のテキスト形式、[api_server] metrics = true のときのみ）
+async fn metrics(State(context): State<ApiContext>) -> Result<Response, ApiError> {
+    let state = &context.state;
+    let snapshot = metrics::Snapshot {
+        tracking: *state.is_tracking.lock(),
+        today_seconds: *state.today_seconds.lock(),
+    };
+    let body = metrics::render(&state.metrics, &snapshot);
+    Ok((
+        [(
+            axum::http::header::CONTENT_TYPE,
+            "text/plain; version=0.0.4; charset=utf-8",
+        )],
+        body,
+    )
+        .into_response())
+}
+
+fn sse_event(event: &LiveEvent) -> Event {
+    Event::default()
+        .event(event.event_name())
+        .data(serde_json::to_string(event).unwrap_or_default())
+}
+
+/// GET /events（接続直後に現在のトラッキング状態を送り、以降は発生したイベントを送る）
+async fn events(
+    State(context): State<ApiContext>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let state = &context.state;
+    let hello = LiveEvent::TrackingState {
+        tracking: *state.is_tracking.lock(),
+        paused: state.pause.lock().is_some(),
+    };
+    let updates = stream::unfold(state.live.subscribe(), |mut receiver| async move {
+        loop {
+            match receiver.recv().await {
+                Ok(event) => return Some((event, receiver)),
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    });
+
+    let events = stream::once(async move { hello })
+        .chain(updates)
+        .map(|event| Ok(sse_event(&event)));
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+/// POST /tracking/start
+async fn start_tracking(State(context): State<ApiContext>) -> ApiResult<TrackingResponse> {
+    // 一時停止中なら停止していた区間を DB に記録する
+    let app = context.app.clone();
+    with_db(&context.state, move |state| {
+        set_tracking(&app, state, true);
+        Ok(())
+    })
+    .await?;
+    Ok(Json(TrackingResponse { tracking: true }))
+}
+
+/// POST /tracking/stop
+async fn stop_tracking(State(context): State<ApiContext>) -> ApiResult<TrackingResponse> {
+    let app = context.app.clone();
+    with_db(&context.state, move |state| {
+        set_tracking(&app, state, false);
+        Ok(())
+    })
+    .await?;
+    Ok(Json(TrackingResponse { tracking: false }))
+}
+
+/// POST /entries（カテゴリは分類ルールで決め、project は手動の割り当てとして保存）
+async fn create_entry(
+    State(context): State<ApiContext>,
+    Json(entry): Json<ManualEntry>,
+) -> ApiResult<ActivityRecord> {
+    let parse = |s: &str| {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S")
+            .map_err(|e| format!("Invalid time {}: {}", s, e))
+    };
+    let start = parse(&entry.start_time)?;
+    let end = parse(&entry.end_time)?;
+    let duration_seconds = (end - start).num_seconds();
+    if duration_seconds < 1 {
+        return Err("end_time must be after start_time".to_string().into());
+    }
+
+    let state = &context.state;
+    let process_name = entry
+        .process_name
+        .filter(|name| !name.trim().is_empty())
+        .unwrap_or_else(|| MANUAL_PROCESS_NAME.to_string());
+    let category = state
+        .categorizer
+        .classify(&process_name, &entry.window_title, None);
+    let note = entry
+        .note
+        .map(|note| note.trim().to_string())
+        .filter(|note| !note.is_empty());
+
+    let row = (
+        process_name.clone(),
+        entry.window_title.clone(),
+        entry.start_time.clone(),
+        entry.end_time.clone(),
+        note.clone(),
+    );
+    let id = with_db(state, move |state| {
+        let (process_name, window_title, start_time, end_time, note) = row;
+        let db = state.db.lock();
+        let tx = db.unchecked_transaction().map_err(|e| e.to_string())?;
+        tx.execute(
+            "INSERT INTO activities (process_name, window_title, start_time, end_time, duration_seconds, category, note)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                process_name,
+                window_title,
+                start_time,
+                end_time,
+                duration_seconds,
+                category,
+                note
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+        let id = tx.last_insert_rowid();
+        if let Some(project) = entry.project.as_deref().filter(|p| !p.is_empty()) {
+            assignments::assign(
+                &tx,
+                id,
+                None,
+                None,
+                Some(project),
+                assignments::SOURCE_MANUAL,
+            )?;
+        }
+        tx.commit().map_err(|e| e.to_string())?;
+        Ok(id)
+    })
+    .await?;
+
+    Ok(Json(ActivityRecord {
+        id,
+        process_name,
+        window_title: entry.window_title,
+        domain: None,
+        start_time: entry.start_time,
+        end_time: entry.end_time,
+        duration_seconds,
+        note,
+    }))
+}
@@ -1,491 +1,242 @@
-use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
-use parking_lot::Mutex;
-use reqwest;
+use chrono::{Local, NaiveDate};
 use rusqlite::{params, Connection};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::thread;
 use std::time::Duration;
 use tauri::{
-    menu::{Menu, MenuItem},
+    image::Image,
+    menu::{CheckMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu},
     tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
-    AppHandle, Manager, State, WindowEvent,
+    AppHandle, Emitter, Manager, State, WindowEvent,
 };
+use tauri_plugin_autostart::{MacosLauncher, ManagerExt};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+use tokio::sync::broadcast;
 
-pub mod plugins;
+use timetracker_core::*;
+
+pub mod api_server;
 
 use plugins::{
-    config::IntegrationsConfig,
-    traits::{ActivityInfo, SyncResult},
-    PluginManager, UploadConfig,
+    config::{
+        AssignmentRule, ExclusionConfig, IdleConfig, IntegrationConfig, IntegrationEntry,
+        IntegrationsConfig, Productivity, ProductivityConfig, ProjectConfig, RedactionProfile,
+        RetentionConfig,
+    },
+    integrations::SlackIntegration,
+    traits::{ActivityInfo, IssueSummary, SyncResult, TicketSyncResult},
+    UploadConfig,
 };
 
-#[cfg(target_os = "windows")]
-mod windows_watcher {
-    use url::Url;
-    use windows::Win32::Foundation::HWND;
-    use windows::Win32::System::Com::{
-        CoCreateInstance, CoInitializeEx, CLSCTX_ALL, COINIT_MULTITHREADED,
-    };
-    use windows::Win32::System::ProcessStatus::GetModuleBaseNameW;
-    use windows::Win32::System::Threading::{
-        OpenProcess, PROCESS_QUERY_INFORMATION, PROCESS_VM_READ,
-    };
-    use windows::Win32::UI::Accessibility::{
-        CUIAutomation, IUIAutomation, IUIAutomationCondition, IUIAutomationElement,
-        TreeScope_Subtree, UIA_ControlTypePropertyId, UIA_EditControlTypeId, UIA_NamePropertyId,
-        UIA_ValueValuePropertyId,
-    };
-    use windows::Win32::UI::WindowsAndMessaging::{
-        GetForegroundWindow, GetWindowTextW, GetWindowThreadProcessId,
-    };
-
-    // Browser process names
-    const BROWSER_PROCESSES: &[&str] = &[
-        "chrome.exe",
-        "msedge.exe",
-        "firefox.exe",
-        "brave.exe",
-        "opera.exe",
-        "vivaldi.exe",
-        "iexplore.exe",
-    ];
-
-    pub fn is_browser(process_name: &str) -> bool {
-        let lower = process_name.to_lowercase();
-        BROWSER_PROCESSES.iter().any(|b| lower == *b)
-    }
-
-    pub fn extract_domain(url_str: &str) -> Option<String> {
-        // Try parsing as full URL first
-        if let Ok(url) = Url::parse(url_str) {
-            return url.host_str().map(|h| h.to_string());
-        }
-
-        // Try adding https:// prefix for URLs without protocol
-        // Many browsers show URLs without the protocol (e.g., "github.com" instead of "https://github.com")
-        let with_protocol = format!("https://{}", url_str);
-        if let Ok(url) = Url::parse(&with_protocol) {
-            // Validate it looks like a real domain (has at least one dot)
-            if let Some(host) = url.host_str() {
-                if host.contains('.') {
-                    return Some(host.to_string());
-                }
-            }
-        }
-
-        None
-    }
-
-    /// Check if a string looks like a URL (with or without protocol)
-    fn looks_like_url(s: &str) -> bool {
-        // Check for protocol prefix
-        if s.starts_with("http://") || s.starts_with("https://") {
-            return true;
-        }
-
-        // Check if it looks like a domain (contains dot and no spaces)
-        // e.g., "github.com", "www.google.com/search?q=test"
-        if !s.contains(' ') && !s.is_empty() {
-            // Find the domain part (before any path)
-            let domain_part = s.split('/').next().unwrap_or(s);
-            // Must contain a dot and have reasonable structure
-            if domain_part.contains('.') {
-                // Check it's not just numbers (like "1.0")
-                let parts: Vec<&str> = domain_part.split('.').collect();
-                if parts.len() >= 2 {
-                    // At least one part should not be purely numeric or empty
-                    let has_alpha = parts
-                        .iter()
-                        .any(|p| !p.is_empty() && p.chars().any(|c| c.is_alphabetic()));
-                    return has_alpha;
-                }
-            }
-        }
-
-        false
-    }
-
-    pub fn get_browser_url(hwnd: HWND) -> Option<String> {
-        unsafe {
-            // Initialize COM
-            let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
-
-            // Create UI Automation instance
-            let automation: IUIAutomation = match CoCreateInstance(&CUIAutomation, None, CLSCTX_ALL)
-            {
-                Ok(a) => a,
-                Err(_) => return None,
-            };
-
-            // Get element from window handle
-            let element: IUIAutomationElement = match automation.ElementFromHandle(hwnd) {
-                Ok(e) => e,
-                Err(_) => return None,
-            };
-
-            // Create condition to find Edit controls (address bar)
-            let edit_condition: IUIAutomationCondition = match automation.CreatePropertyCondition(
-                UIA_ControlTypePropertyId,
-                &windows::core::VARIANT::from(UIA_EditControlTypeId.0),
-            ) {
-                Ok(c) => c,
-                Err(_) => return None,
-            };
+use api_auth::{ApiRequestLog, ApiToken, IssuedToken};
+use assignments::{AssignmentSuggestion, PendingAssignment, TicketAssignment};
+use budgets::WeeklyBudgetStatus;
+use capture::{CaptureSourceInfo, CapturedWindow};
+use categories::CategoryInfo;
+use database::EncryptionStatus;
+use focus::{ContextSwitchReport, FocusReport};
+use goals::{Goal, GoalProgress};
+use heatmap::Heatmap;
+use importer::CsvMapping;
+use input_meter::{InputIntensity, InputMeterStatus};
+use invoice::InvoiceReport;
+use learning::RuleCandidate;
+use live::{CurrentActivity, LiveEvent, LiveEvents, EVENT_ACTIVITY_CHANGED};
+use maintenance::{CompactionReport, DedupeReport};
+use meetings::MeetingReport;
+use normalize::RenormalizeReport;
+use notes::ActivityNote;
+use pause::{Pause, PauseStatus};
+use pending_sync::{PendingSync, PushResult};
+use placement::PlacementSummary;
+use productivity::ProductivityReport;
+use projects::ProjectSummary;
+use report::ReportPeriod;
+use retention::{DailySummary, RetentionReport};
+use rules::{ApplyRulesReport, ExtractionTestReport, RuleKind, RuleOp, RuleSet};
+use search::{SearchPage, SearchQuery};
+use settings::ConfigIssue;
+use summary::{GroupBy, PeriodComparison, RangeSummary, TitleSummary};
+use sync_log::SyncLogEntry;
+use sync_queue::{FlushReport, QueuedSync};
+use ticket_info::TicketInfo;
+use timeline::TimelineSegment;
+use transfer::ImportReport;
+use uploader::UploadStatus;
+use user_info::{get_machine_name, get_user_upn};
 
-            // Find all Edit elements
-            let elements = match element.FindAll(TreeScope_Subtree, &edit_condition) {
-                Ok(e) => e,
-                Err(_) => return None,
-            };
+#[tauri::command]
+fn start_tracking(app: AppHandle, state: State<Arc<AppState>>) -> Result<(), String> {
+    set_tracking(&app, &state, true);
+    Ok(())
+}
 
-            let count = elements.Length().unwrap_or(0);
-
-            for i in 0..count {
-                if let Ok(elem) = elements.GetElement(i) {
-                    // Check if this is the address bar by looking at the name property
-                    if let Ok(name) = elem.GetCurrentPropertyValue(UIA_NamePropertyId) {
-                        let name_str = name.to_string().to_lowercase();
-                        // Common address bar identifiers (English, Japanese, and browser-specific)
-                        if name_str.contains("address")
-                            || name_str.contains("url")
-                            || name_str.contains("アドレス")
-                            || name_str.contains("location")
-                            || name_str.contains("omnibox")
-                            || name_str.contains("検索または")
-                            || name_str.contains("search or")
-                            || name_str.contains("検索またはアドレス")
-                        {
-                            // Get the value (URL)
-                            if let Ok(value) =
-                                elem.GetCurrentPropertyValue(UIA_ValueValuePropertyId)
-                            {
-                                let url_str = value.to_string();
-                                // Check for URLs with or without protocol prefix
-                                if looks_like_url(&url_str) {
-                                    return Some(url_str);
-                                }
-                            }
-                        }
-                    }
-                }
-            }
+#[tauri::command]
+fn stop_tracking(app: AppHandle, state: State<Arc<AppState>>) -> Result<(), String> {
+    set_tracking(&app, &state, false);
+    Ok(())
+}
 
-            // Fallback: try to find any edit control with a URL-like value
-            for i in 0..count {
-                if let Ok(elem) = elements.GetElement(i) {
-                    if let Ok(value) = elem.GetCurrentPropertyValue(UIA_ValueValuePropertyId) {
-                        let url_str = value.to_string();
-                        // Check for URLs with or without protocol prefix
-                        if looks_like_url(&url_str) {
-                            return Some(url_str);
-                        }
-                    }
-                }
-            }
+/// トラッキングを開始/停止（一時停止中なら解除する）
+fn set_tracking(app: &AppHandle, state: &AppState, tracking: bool) {
+    end_pause(state);
+    let mut is_tracking = state.is_tracking.lock();
+    *is_tracking = tracking;
+    state.live.publish(LiveEvent::TrackingState {
+        tracking,
+        paused: false,
+    });
+    drop(is_tracking);
+    refresh_tray_status(app, state);
+}
 
-            None
-        }
+/// 指定した時間だけトラッキングを止め、経過後に自動で再開する
+#[tauri::command]
+fn pause_tracking(
+    app: AppHandle,
+    state: State<Arc<AppState>>,
+    duration_minutes: u32,
+) -> Result<PauseStatus, String> {
+    let pause = Pause::new(duration_minutes)?;
+    let was_paused = state.pause.lock().is_some();
+    if !was_paused && !*state.is_tracking.lock() {
+        return Err("Tracking is not running".to_string());
     }
 
-    pub fn get_active_window_info() -> Option<(String, String, Option<String>)> {
-        unsafe {
-            let hwnd: HWND = GetForegroundWindow();
-            if hwnd.0.is_null() {
-                return None;
-            }
-
-            // Get window title
-            let mut title_buf = [0u16; 512];
-            let title_len = GetWindowTextW(hwnd, &mut title_buf);
-            let title = if title_len > 0 {
-                String::from_utf16_lossy(&title_buf[..title_len as usize])
-            } else {
-                String::new()
-            };
-
-            // Get process name
-            let mut process_id: u32 = 0;
-            GetWindowThreadProcessId(hwnd, Some(&mut process_id));
-
-            let process_name = if process_id != 0 {
-                if let Ok(handle) = OpenProcess(
-                    PROCESS_QUERY_INFORMATION | PROCESS_VM_READ,
-                    false,
-                    process_id,
-                ) {
-                    let mut name_buf = [0u16; 256];
-                    let name_len = GetModuleBaseNameW(handle, None, &mut name_buf);
-                    if name_len > 0 {
-                        String::from_utf16_lossy(&name_buf[..name_len as usize])
-                    } else {
-                        String::from("Unknown")
-                    }
-                } else {
-                    String::from("Unknown")
-                }
-            } else {
-                String::from("Unknown")
-            };
-
-            // Get domain if it's a browser
-            let domain = if is_browser(&process_name) {
-                get_browser_url(hwnd).and_then(|url| extract_domain(&url))
-            } else {
-                None
-            };
+    // 一時停止中に呼ばれたらそこまでの区間を記録して延長する
+    end_pause(&state);
+    *state.is_tracking.lock() = false;
+    *state.pause.lock() = Some(pause);
+    state.live.publish(LiveEvent::TrackingState {
+        tracking: false,
+        paused: true,
+    });
+    refresh_tray_status(&app, &state);
+    Ok(pause.status())
+}
 
-            Some((process_name, title, domain))
-        }
-    }
+/// 一時停止中なら再開予定時刻と残り時間
+#[tauri::command]
+fn get_pause_status(state: State<Arc<AppState>>) -> Option<PauseStatus> {
+    state.pause.lock().as_ref().map(Pause::status)
 }
 
-#[cfg(not(target_os = "windows"))]
-mod windows_watcher {
-    pub fn get_active_window_info() -> Option<(String, String, Option<String>)> {
-        // Stub for non-Windows platforms (development only)
-        // Simulate browser with domain for testing
-        static COUNTER: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
-        let count = COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
-
-        // Alternate between browser and non-browser for demo
-        if count % 3 == 0 {
-            Some((
-                String::from("chrome.exe"),
-                String::from("GitHub - Demo Page"),
-                Some(String::from("github.com")),
-            ))
-        } else if count % 3 == 1 {
-            Some((
-                String::from("chrome.exe"),
-                String::from("Google Search"),
-                Some(String::from("google.com")),
-            ))
-        } else {
-            Some((
-                String::from("Code.exe"),
-                String::from("lib.rs - timetracker"),
-                None,
-            ))
-        }
+/// 一時停止を終了して区間を記録（一時停止中でなければ何もしない）
+fn end_pause(state: &AppState) {
+    let Some(pause) = state.pause.lock().take() else {
+        return;
+    };
+    let db = state.db.lock();
+    if let Err(e) = pause::record(&db, &pause, Local::now()) {
+        eprintln!("Failed to save paused period: {}", e);
     }
 }
 
-use windows_watcher::get_active_window_info;
-
-// ========== ユーザー情報取得 ==========
-
-#[cfg(target_os = "windows")]
-mod user_info {
-    use windows::Win32::Foundation::ERROR_MORE_DATA;
-    use windows::Win32::Security::{GetUserNameExW, NameUserPrincipal, NameSamCompatible};
-
-    /// Get the current Windows user's UPN (User Principal Name)
-    /// e.g., "user@domain.com"
-    pub fn get_user_upn() -> Option<String> {
-        unsafe {
-            // First, try UPN format (user@domain.com)
-            let mut size: u32 = 0;
-            let _ = GetUserNameExW(NameUserPrincipal, None, &mut size);
-
-            if size > 0 {
-                let mut buffer = vec![0u16; size as usize];
-                if GetUserNameExW(
-                    NameUserPrincipal,
-                    Some(windows::core::PWSTR(buffer.as_mut_ptr())),
-                    &mut size,
-                ).is_ok() {
-                    let name = String::from_utf16_lossy(&buffer[..size as usize - 1]);
-                    if !name.is_empty() {
-                        return Some(name);
-                    }
-                }
+/// 一時停止の時間が過ぎたらトラッキングを再開し、トレイに残り時間を表示する
+fn start_pause_timer(app: AppHandle, state: Arc<AppState>) {
+    thread::spawn(move || {
+        let mut last_tooltip = String::new();
+        loop {
+            thread::sleep(Duration::from_secs(1));
+
+            let due = matches!(*state.pause.lock(), Some(pause) if pause.is_over());
+            if due {
+                end_pause(&state);
+                *state.is_tracking.lock() = true;
+                state.live.publish(LiveEvent::TrackingState {
+                    tracking: true,
+                    paused: false,
+                });
+                refresh_tray_status(&app, &state);
             }
 
-            // Fallback to SAM compatible format (DOMAIN\user)
-            let mut size: u32 = 0;
-            let _ = GetUserNameExW(NameSamCompatible, None, &mut size);
-
-            if size > 0 {
-                let mut buffer = vec![0u16; size as usize];
-                if GetUserNameExW(
-                    NameSamCompatible,
-                    Some(windows::core::PWSTR(buffer.as_mut_ptr())),
-                    &mut size,
-                ).is_ok() {
-                    let name = String::from_utf16_lossy(&buffer[..size as usize - 1]);
-                    if !name.is_empty() {
-                        return Some(name);
-                    }
+            let tooltip = tray_tooltip(&state);
+            if tooltip != last_tooltip {
+                if let Some(tray) = app.tray_by_id(TRAY_ID) {
+                    let _ = tray.set_tooltip(Some(tooltip.as_str()));
                 }
+                last_tooltip = tooltip;
             }
-
-            None
         }
-    }
-
-    /// Get machine name
-    pub fn get_machine_name() -> Option<String> {
-        std::env::var("COMPUTERNAME").ok()
-    }
-}
-
-#[cfg(not(target_os = "windows"))]
-mod user_info {
-    /// Stub for non-Windows platforms
-    pub fn get_user_upn() -> Option<String> {
-        // For development, return a demo user
-        Some(String::from("demo.user@example.com"))
-    }
-
-    pub fn get_machine_name() -> Option<String> {
-        std::env::var("HOSTNAME").ok().or_else(|| Some(String::from("dev-machine")))
-    }
+    });
 }
 
-use user_info::{get_machine_name, get_user_upn};
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ActivityRecord {
-    pub id: i64,
-    pub process_name: String,
-    pub window_title: String,
-    pub domain: Option<String>,
-    pub start_time: String,
-    pub end_time: String,
-    pub duration_seconds: i64,
+#[tauri::command]
+fn is_tracking(state: State<Arc<AppState>>) -> bool {
+    *state.is_tracking.lock()
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AppSummary {
-    pub process_name: String,
-    pub total_seconds: i64,
-    pub percentage: f64,
+/// 進行中のアクティビティ（アプリ・タイトル・経過秒数）。切り替わるたびに activity-changed イベントも送る
+#[tauri::command]
+fn get_current_activity(state: State<Arc<AppState>>) -> Option<CurrentActivity> {
+    state.live.current()
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct DomainSummary {
-    pub domain: String,
-    pub total_seconds: i64,
-    pub percentage: f64,
-}
-
-pub struct AppState {
-    db: Mutex<Connection>,
-    is_tracking: Mutex<bool>,
-    plugin_manager: PluginManager,
-}
-
-impl AppState {
-    fn new() -> Result<Self, rusqlite::Error> {
-        let db_path = dirs::data_local_dir()
-            .unwrap_or_else(|| std::path::PathBuf::from("."))
-            .join("timetracker")
-            .join("activities.db");
-
-        std::fs::create_dir_all(db_path.parent().unwrap()).ok();
-
-        let conn = Connection::open(&db_path)?;
-
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS activities (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                process_name TEXT NOT NULL,
-                window_title TEXT NOT NULL,
-                domain TEXT,
-                start_time TEXT NOT NULL,
-                end_time TEXT NOT NULL,
-                duration_seconds INTEGER NOT NULL
-            )",
-            [],
-        )?;
-
-        // Add domain column if it doesn't exist (migration for existing databases)
-        let _ = conn.execute("ALTER TABLE activities ADD COLUMN domain TEXT", []);
-
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_start_time ON activities(start_time)",
-            [],
-        )?;
-
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_domain ON activities(domain)",
-            [],
-        )?;
-
-        // プラグインマネージャーを初期化
-        let plugin_manager = PluginManager::new();
-        if let Err(e) = plugin_manager.load_from_config() {
-            eprintln!("Failed to load plugins: {}", e);
-        }
-
-        Ok(Self {
-            db: Mutex::new(conn),
-            is_tracking: Mutex::new(false),
-            plugin_manager,
-        })
-    }
+#[tauri::command]
+fn get_activities(
+    state: State<Arc<AppState>>,
+    date: String,
+    limit: Option<u32>,
+    offset: Option<u32>,
+    merge_consecutive: Option<bool>,
+) -> Result<Vec<ActivityRecord>, String> {
+    load_activities(
+        &state,
+        &date,
+        limit,
+        offset,
+        merge_consecutive.unwrap_or(false),
+    )
 }
 
+/// 履歴を条件で検索（タイトル・メモの部分一致、アプリ・カテゴリ・プロジェクト・ドメイン・期間・最短時間）
 #[tauri::command]
-fn start_tracking(state: State<Arc<AppState>>) -> Result<(), String> {
-    let mut is_tracking = state.is_tracking.lock();
-    *is_tracking = true;
-    Ok(())
+fn search_activities(
+    state: State<Arc<AppState>>,
+    query: SearchQuery,
+) -> Result<SearchPage, String> {
+    let db = state.db.lock();
+    search::search(&db, &query)
 }
 
+/// アクティビティにメモを付ける（空文字で削除）
 #[tauri::command]
-fn stop_tracking(state: State<Arc<AppState>>) -> Result<(), String> {
-    let mut is_tracking = state.is_tracking.lock();
-    *is_tracking = false;
-    Ok(())
+fn add_note(state: State<Arc<AppState>>, activity_id: i64, text: String) -> Result<(), String> {
+    let db = state.db.lock();
+    notes::set(&db, activity_id, &text)
 }
 
+/// 指定日のメモつきのアクティビティ
 #[tauri::command]
-fn is_tracking(state: State<Arc<AppState>>) -> bool {
-    *state.is_tracking.lock()
+fn get_notes(state: State<Arc<AppState>>, date: String) -> Result<Vec<ActivityNote>, String> {
+    let db = state.db.lock();
+    notes::for_date(&db, &date)
 }
 
+/// アプリ別の合計（virtual_desktop を指定するとその仮想デスクトップの記録だけ）
+///
+/// group_by: "app"（既定） | "category"（カテゴリ別にまとめてアプリ別の内訳を付ける）
 #[tauri::command]
-fn get_activities(
+fn get_app_summary(
     state: State<Arc<AppState>>,
     date: String,
-) -> Result<Vec<ActivityRecord>, String> {
+    virtual_desktop: Option<String>,
+    group_by: Option<String>,
+) -> Result<AppSummaryResult, String> {
     let db = state.db.lock();
-    let start_of_day = format!("{}T00:00:00", date);
-    let end_of_day = format!("{}T23:59:59", date);
-
-    let mut stmt = db
-        .prepare(
-            "SELECT id, process_name, window_title, domain, start_time, end_time, duration_seconds
-             FROM activities
-             WHERE start_time >= ?1 AND start_time <= ?2
-             ORDER BY start_time ASC",
-        )
-        .map_err(|e| e.to_string())?;
-
-    let records = stmt
-        .query_map(params![start_of_day, end_of_day], |row| {
-            Ok(ActivityRecord {
-                id: row.get(0)?,
-                process_name: row.get(1)?,
-                window_title: row.get(2)?,
-                domain: row.get(3)?,
-                start_time: row.get(4)?,
-                end_time: row.get(5)?,
-                duration_seconds: row.get(6)?,
-            })
-        })
-        .map_err(|e| e.to_string())?
-        .filter_map(|r| r.ok())
-        .collect();
-
-    Ok(records)
-}
+    match group_by.as_deref().unwrap_or("app") {
+        "app" => {}
+        "category" => return category_summary(&db, &date, virtual_desktop.as_deref()),
+        other => {
+            return Err(format!(
+                "Invalid group_by: {} (expected app or category)",
+                other
+            ))
+        }
+    }
 
-#[tauri::command]
-fn get_app_summary(state: State<Arc<AppState>>, date: String) -> Result<Vec<AppSummary>, String> {
-    let db = state.db.lock();
     let start_of_day = format!("{}T00:00:00", date);
     let end_of_day = format!("{}T23:59:59", date);
 
@@ -494,13 +245,14 @@ fn get_app_summary(state: State<Arc<AppState>>, date: String) -> Result<Vec<AppS
             "SELECT process_name, SUM(duration_seconds) as total
              FROM activities
              WHERE start_time >= ?1 AND start_time <= ?2
+               AND (?3 IS NULL OR virtual_desktop = ?3)
              GROUP BY process_name
              ORDER BY total DESC",
         )
         .map_err(|e| e.to_string())?;
 
     let summaries: Vec<(String, i64)> = stmt
-        .query_map(params![start_of_day, end_of_day], |row| {
+        .query_map(params![start_of_day, end_of_day, virtual_desktop], |row| {
             Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
         })
         .map_err(|e| e.to_string())?
@@ -522,153 +274,2219 @@ fn get_app_summary(state: State<Arc<AppState>>, date: String) -> Result<Vec<AppS
         })
         .collect();
 
-    Ok(result)
+    Ok(AppSummaryResult::Apps(result))
 }
 
-#[tauri::command]
-fn get_domain_summary(
-    state: State<Arc<AppState>>,
-    date: String,
-) -> Result<Vec<DomainSummary>, String> {
-    let db = state.db.lock();
+/// カテゴリ別の合計（アプリ別の内訳付き、未分類は "Uncategorized"）
+fn category_summary(
+    db: &Connection,
+    date: &str,
+    virtual_desktop: Option<&str>,
+) -> Result<AppSummaryResult, String> {
     let start_of_day = format!("{}T00:00:00", date);
     let end_of_day = format!("{}T23:59:59", date);
 
     let mut stmt = db
         .prepare(
-            "SELECT domain, SUM(duration_seconds) as total
-             FROM activities
-             WHERE start_time >= ?1 AND start_time <= ?2 AND domain IS NOT NULL AND domain != ''
-             GROUP BY domain
+            "SELECT COALESCE(a.category, ?3), a.process_name, SUM(a.duration_seconds) as total, c.color
+             FROM activities a
+             LEFT JOIN categories c ON c.name = a.category
+             WHERE a.start_time >= ?1 AND a.start_time <= ?2
+               AND (?4 IS NULL OR a.virtual_desktop = ?4)
+             GROUP BY COALESCE(a.category, ?3), a.process_name
              ORDER BY total DESC",
         )
         .map_err(|e| e.to_string())?;
 
-    let summaries: Vec<(String, i64)> = stmt
-        .query_map(params![start_of_day, end_of_day], |row| {
-            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
-        })
+    let rows: Vec<(String, String, i64, Option<String>)> = stmt
+        .query_map(
+            params![
+                start_of_day,
+                end_of_day,
+                categories::UNCATEGORIZED,
+                virtual_desktop
+            ],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )
         .map_err(|e| e.to_string())?
         .filter_map(|r| r.ok())
         .collect();
 
-    let total_seconds: i64 = summaries.iter().map(|(_, s)| s).sum();
+    let total_seconds: i64 = rows.iter().map(|(_, _, secs, _)| secs).sum();
+    let percentage = |secs: i64| {
+        if total_seconds > 0 {
+            (secs as f64 / total_seconds as f64) * 100.0
+        } else {
+            0.0
+        }
+    };
 
-    let result = summaries
-        .into_iter()
-        .map(|(domain, secs)| DomainSummary {
-            domain,
+    let mut result: Vec<CategorySummary> = Vec::new();
+    for (category, process_name, secs, color) in rows {
+        let app = AppSummary {
+            process_name,
             total_seconds: secs,
-            percentage: if total_seconds > 0 {
-                (secs as f64 / total_seconds as f64) * 100.0
-            } else {
-                0.0
-            },
-        })
-        .collect();
+            percentage: percentage(secs),
+        };
+        match result.iter_mut().find(|c| c.category == category) {
+            Some(summary) => {
+                summary.total_seconds += secs;
+                summary.apps.push(app);
+            }
+            None => result.push(CategorySummary {
+                category,
+                color,
+                total_seconds: secs,
+                percentage: 0.0,
+                apps: vec![app],
+            }),
+        }
+    }
 
-    Ok(result)
+    for summary in &mut result {
+        summary.percentage = percentage(summary.total_seconds);
+    }
+    result.sort_by_key(|summary| std::cmp::Reverse(summary.total_seconds));
+
+    Ok(AppSummaryResult::Categories(result))
 }
 
-// ========== プラグイン関連コマンド ==========
+/// 期間内の記録時間をモニター・仮想デスクトップごとに集計（サブモニターでの作業時間の分析用）
+#[tauri::command]
+fn get_placement_summary(
+    state: State<Arc<AppState>>,
+    start_date: String,
+    end_date: String,
+) -> Result<Vec<PlacementSummary>, String> {
+    let db = state.db.lock();
+    placement::summary(&db, &start_date, &end_date)
+}
 
-/// プラグイン一覧を取得
+/// 正規化ルールを記録済みのウィンドウタイトルに適用し直す（元のタイトルから、日付未指定なら全期間）
 #[tauri::command]
-fn get_plugins(state: State<Arc<AppState>>) -> Vec<String> {
-    state.plugin_manager.list_plugins()
+fn renormalize_titles(
+    state: State<Arc<AppState>>,
+    start_date: Option<String>,
+    end_date: Option<String>,
+    dry_run: bool,
+) -> Result<RenormalizeReport, String> {
+    state.title_normalizer.load_from_config();
+    let report = {
+        let db = state.db.lock();
+        state.title_normalizer.renormalize(
+            &db,
+            start_date.as_deref(),
+            end_date.as_deref(),
+            dry_run,
+        )?
+    };
+    if report.changed > 0 && !dry_run {
+        start_ticket_extraction_backfill(state.inner().clone());
+    }
+    Ok(report)
 }
 
-/// プラグイン設定を再読み込み
+// ========== プロジェクト ==========
+
+/// 期間内のプロジェクト別の作業時間
 #[tauri::command]
-fn reload_plugins(state: State<Arc<AppState>>) -> Result<(), String> {
-    state.plugin_manager.load_from_config()
+fn get_project_summary(
+    state: State<Arc<AppState>>,
+    start_date: String,
+    end_date: String,
+) -> Result<Vec<ProjectSummary>, String> {
+    let db = state.db.lock();
+    projects::summary(&db, &start_date, &end_date)
+}
+
+/// クライアントの期間内の請求時間と金額
+#[tauri::command]
+fn generate_invoice_report(
+    state: State<Arc<AppState>>,
+    client: String,
+    start_date: String,
+    end_date: String,
+) -> Result<InvoiceReport, String> {
+    let config = IntegrationsConfig::load();
+    let db = state.db.lock();
+    invoice::generate(
+        &db,
+        &config.projects,
+        &config.billing,
+        &client,
+        &start_date,
+        &end_date,
+    )
+}
+
+/// 請求レポートをエクスポート（format: "csv"（既定） | "html"）
+#[tauri::command]
+fn export_invoice_report(
+    state: State<Arc<AppState>>,
+    client: String,
+    start_date: String,
+    end_date: String,
+    format: Option<String>,
+) -> Result<String, String> {
+    let report = generate_invoice_report(state, client, start_date, end_date)?;
+    match format.as_deref().unwrap_or("csv") {
+        "csv" => Ok(invoice::to_csv(&report)),
+        "html" => Ok(invoice::to_html(&report)),
+        other => Err(format!("Unknown export format: {}", other)),
+    }
+}
+
+/// 日次/週次レポートを HTML ファイルに書き出してパスを返す（PDF はブラウザの印刷で作成）
+#[tauri::command]
+fn generate_report(
+    state: State<Arc<AppState>>,
+    period: String,
+    date: Option<String>,
+) -> Result<String, String> {
+    let period = ReportPeriod::parse(&period)?;
+    let date = match date {
+        Some(date) => NaiveDate::parse_from_str(&date, "%Y-%m-%d").map_err(|e| e.to_string())?,
+        None => Local::now().date_naive(),
+    };
+    let db = state.db.lock();
+    let path = report::write(&db, period, date)?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// トレイメニューから今日のレポートを作成して開く
+fn open_today_report(app: &AppHandle) -> Result<(), String> {
+    use tauri_plugin_opener::OpenerExt;
+
+    let state = app.state::<Arc<AppState>>();
+    flush_activities(&state);
+    let path = {
+        let db = state.db.lock();
+        report::write(&db, ReportPeriod::Daily, Local::now().date_naive())?
+    };
+    app.opener()
+        .open_path(path.to_string_lossy(), None::<&str>)
+        .map_err(|e| e.to_string())
+}
+
+/// プロジェクト定義の一覧
+#[tauri::command]
+fn list_projects() -> Vec<ProjectConfig> {
+    IntegrationsConfig::load().projects
+}
+
+/// プロジェクト定義を追加/更新（同名なら置き換え）して設定ファイルに保存
+#[tauri::command]
+fn save_project(
+    state: State<Arc<AppState>>,
+    project: ProjectConfig,
+) -> Result<Vec<ProjectConfig>, String> {
+    if project.name.trim().is_empty() {
+        return Err("Project name is required".to_string());
+    }
+    for pattern in &project.title_patterns {
+        regex::Regex::new(pattern).map_err(|e| format!("Invalid pattern {:?}: {}", pattern, e))?;
+    }
+
+    let mut config = IntegrationsConfig::try_load()?;
+    match config.projects.iter_mut().find(|p| p.name == project.name) {
+        Some(existing) => *existing = project,
+        None => config.projects.push(project),
+    }
+    config.save()?;
+
+    state.projects.set_projects(&config.projects);
+    Ok(config.projects)
+}
+
+/// プロジェクト定義を削除（割り当て済みのアクティビティはそのまま）
+#[tauri::command]
+fn delete_project(state: State<Arc<AppState>>, name: String) -> Result<Vec<ProjectConfig>, String> {
+    let mut config = IntegrationsConfig::try_load()?;
+    let before = config.projects.len();
+    config.projects.retain(|p| p.name != name);
+    if config.projects.len() == before {
+        return Err(format!("Project not found: {}", name));
+    }
+    config.save()?;
+
+    state.projects.set_projects(&config.projects);
+    Ok(config.projects)
+}
+
+// ========== 除外リスト ==========
+
+/// 記録から除外しているアプリ・タイトル・ドメインを取得
+#[tauri::command]
+fn get_exclusions() -> ExclusionConfig {
+    IntegrationsConfig::load().exclusions
+}
+
+/// 除外リストに追加して設定ファイルに保存（kind: "process" | "title" | "domain"）
+#[tauri::command]
+fn add_exclusion(
+    state: State<Arc<AppState>>,
+    kind: String,
+    value: String,
+) -> Result<ExclusionConfig, String> {
+    let mut config = IntegrationsConfig::try_load()?;
+    exclusions::add(&mut config.exclusions, &kind, &value)?;
+    config.save()?;
+
+    state.exclusions.set(&config.exclusions);
+    Ok(config.exclusions)
+}
+
+/// 除外リストから削除して設定ファイルに保存
+#[tauri::command]
+fn remove_exclusion(
+    state: State<Arc<AppState>>,
+    kind: String,
+    value: String,
+) -> Result<ExclusionConfig, String> {
+    let mut config = IntegrationsConfig::try_load()?;
+    exclusions::remove(&mut config.exclusions, &kind, &value)?;
+    config.save()?;
+
+    state.exclusions.set(&config.exclusions);
+    Ok(config.exclusions)
+}
+
+/// 指定日のタイムライン（同じアプリの連続をまとめ、アイドル/記録なしの区間を含む）
+#[tauri::command]
+fn get_timeline(
+    state: State<Arc<AppState>>,
+    date: String,
+    merge_threshold_seconds: Option<i64>,
+) -> Result<Vec<TimelineSegment>, String> {
+    let db = state.db.lock();
+    timeline::build(
+        &db,
+        &date,
+        merge_threshold_seconds.unwrap_or(timeline::DEFAULT_MERGE_THRESHOLD_SECONDS),
+    )
+}
+
+/// 期間の使用時間を日/週/月ごとに集計（group_by: "day"（既定） | "week" | "month"）
+#[tauri::command]
+fn get_summary_range(
+    state: State<Arc<AppState>>,
+    start: String,
+    end: String,
+    group_by: Option<String>,
+) -> Result<RangeSummary, String> {
+    let start = NaiveDate::parse_from_str(&start, "%Y-%m-%d").map_err(|e| e.to_string())?;
+    let end = NaiveDate::parse_from_str(&end, "%Y-%m-%d").map_err(|e| e.to_string())?;
+    let group_by = GroupBy::parse(group_by.as_deref().unwrap_or("day"))?;
+
+    let db = state.db.lock();
+    summary::range_summary(&db, start, end, group_by)
+}
+
+/// 指定日の集中区間と統計（min_minutes を省略すると25分）
+#[tauri::command]
+fn get_focus_sessions(
+    state: State<Arc<AppState>>,
+    date: String,
+    min_minutes: Option<i64>,
+) -> Result<FocusReport, String> {
+    let min_minutes = min_minutes.unwrap_or(focus::DEFAULT_MIN_FOCUS_MINUTES);
+    if min_minutes < 1 {
+        return Err("min_minutes must be at least 1".to_string());
+    }
+    let db = state.db.lock();
+    focus::sessions(&db, &date, min_minutes)
+}
+
+/// 指定日のアプリ切り替えの分析（時間帯別の回数・割り込んだアプリ・戻るまでの時間）
+#[tauri::command]
+fn get_context_switches(
+    state: State<Arc<AppState>>,
+    date: String,
+) -> Result<ContextSwitchReport, String> {
+    let db = state.db.lock();
+    focus::context_switches(&db, &date)
+}
+
+/// 曜日×時間帯の使用時間（ヒートマップ用、end_date を省略すると date から4週間）
+#[tauri::command]
+fn get_activity_heatmap(
+    state: State<Arc<AppState>>,
+    date: String,
+    end_date: Option<String>,
+) -> Result<Heatmap, String> {
+    let start = NaiveDate::parse_from_str(&date, "%Y-%m-%d").map_err(|e| e.to_string())?;
+    let end = match end_date {
+        Some(end_date) => {
+            NaiveDate::parse_from_str(&end_date, "%Y-%m-%d").map_err(|e| e.to_string())?
+        }
+        None => start + chrono::Duration::days(27),
+    };
+
+    let config = IntegrationsConfig::load();
+    let db = state.db.lock();
+    heatmap::build(&db, &config.productivity, start, end)
+}
+
+/// 期間の生産性スコアと日別の推移（end_date を省略すると date の1日）
+#[tauri::command]
+fn get_productivity_score(
+    state: State<Arc<AppState>>,
+    date: String,
+    end_date: Option<String>,
+) -> Result<ProductivityReport, String> {
+    let start = NaiveDate::parse_from_str(&date, "%Y-%m-%d").map_err(|e| e.to_string())?;
+    let end = match end_date {
+        Some(end_date) => {
+            NaiveDate::parse_from_str(&end_date, "%Y-%m-%d").map_err(|e| e.to_string())?
+        }
+        None => start,
+    };
+
+    let config = IntegrationsConfig::load();
+    let db = state.db.lock();
+    productivity::score(&db, &config.productivity, start, end)
+}
+
+#[tauri::command]
+fn get_productivity_labels() -> ProductivityConfig {
+    IntegrationsConfig::load().productivity
+}
+
+/// カテゴリ（kind = "category"）またはアプリ（kind = "app"）の区分を設定（None で解除）
+#[tauri::command]
+fn set_productivity_label(
+    kind: String,
+    name: String,
+    level: Option<Productivity>,
+) -> Result<ProductivityConfig, String> {
+    let mut config = IntegrationsConfig::try_load()?;
+    let labels = match kind.as_str() {
+        "category" => &mut config.productivity.categories,
+        "app" => &mut config.productivity.apps,
+        _ => return Err(format!("Unknown productivity label kind: {}", kind)),
+    };
+    match level {
+        Some(level) => labels.insert(name, level),
+        None => labels.remove(&name),
+    };
+    config.save()?;
+    Ok(config.productivity)
+}
+
+/// カテゴリ一覧
+#[tauri::command]
+fn list_categories(state: State<Arc<AppState>>) -> Result<Vec<CategoryInfo>, String> {
+    let db = state.db.lock();
+    categories::list(&db)
+}
+
+/// カテゴリを作成
+#[tauri::command]
+fn create_category(
+    state: State<Arc<AppState>>,
+    name: String,
+    color: Option<String>,
+    description: Option<String>,
+) -> Result<(), String> {
+    let db = state.db.lock();
+    categories::create(&db, &name, color.as_deref(), description.as_deref())
+}
+
+/// カテゴリを更新（new_name で名前を変更すると分類ルール・予算・記録済みアクティビティも付け替える）
+#[tauri::command]
+fn update_category(
+    state: State<Arc<AppState>>,
+    name: String,
+    new_name: Option<String>,
+    color: Option<String>,
+    description: Option<String>,
+) -> Result<(), String> {
+    let mut config = IntegrationsConfig::try_load()?;
+    {
+        let db = state.db.lock();
+        categories::update(
+            &db,
+            &mut config,
+            &name,
+            new_name.as_deref(),
+            color.as_deref(),
+            description.as_deref(),
+        )?;
+    }
+    state.categorizer.set_rules(&config.categories);
+    Ok(())
+}
+
+/// カテゴリを削除（記録済みアクティビティは未分類に戻す、分類ルール・週の予算・生産性の区分で使われていれば削除しない）
+#[tauri::command]
+fn delete_category(state: State<Arc<AppState>>, name: String) -> Result<(), String> {
+    let config = IntegrationsConfig::try_load()?;
+    let db = state.db.lock();
+    categories::delete(&db, &config, &name)
+}
+
+/// 期間 a（a_start〜a_end）と基準の期間 b（b_start〜b_end）のアプリ別・カテゴリ別の増減
+#[tauri::command]
+fn compare_periods(
+    state: State<Arc<AppState>>,
+    a_start: String,
+    a_end: String,
+    b_start: String,
+    b_end: String,
+) -> Result<PeriodComparison, String> {
+    let parse = |s: &str| NaiveDate::parse_from_str(s, "%Y-%m-%d").map_err(|e| e.to_string());
+    let range_a = (parse(&a_start)?, parse(&a_end)?);
+    let range_b = (parse(&b_start)?, parse(&b_end)?);
+
+    let db = state.db.lock();
+    summary::compare_periods(&db, range_a, range_b)
+}
+
+/// 1アプリのタイトル別の使用時間（date から end_date まで）
+#[tauri::command]
+fn get_title_summary(
+    state: State<Arc<AppState>>,
+    process_name: String,
+    date: String,
+    end_date: Option<String>,
+) -> Result<Vec<TitleSummary>, String> {
+    let start = NaiveDate::parse_from_str(&date, "%Y-%m-%d").map_err(|e| e.to_string())?;
+    let end = match end_date {
+        Some(end_date) => {
+            NaiveDate::parse_from_str(&end_date, "%Y-%m-%d").map_err(|e| e.to_string())?
+        }
+        None => start,
+    };
+
+    let db = state.db.lock();
+    summary::title_summary(&db, &process_name, start, end)
+}
+
+/// ドメイン別の使用時間（date から end_date まで、process_name でブラウザを絞り込める）
+#[tauri::command]
+fn get_domain_summary(
+    state: State<Arc<AppState>>,
+    date: String,
+    end_date: Option<String>,
+    process_name: Option<String>,
+) -> Result<Vec<DomainSummary>, String> {
+    let db = state.db.lock();
+    let start_of_day = format!("{}T00:00:00", date);
+    let end_of_day = format!("{}T23:59:59", end_date.as_deref().unwrap_or(&date));
+
+    let mut stmt = db
+        .prepare(
+            "SELECT domain, SUM(duration_seconds) as total
+             FROM activities
+             WHERE start_time >= ?1 AND start_time <= ?2 AND domain IS NOT NULL AND domain != ''
+               AND (?3 IS NULL OR process_name = ?3)
+             GROUP BY domain
+             ORDER BY total DESC",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let summaries: Vec<(String, i64)> = stmt
+        .query_map(params![start_of_day, end_of_day, process_name], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let total_seconds: i64 = summaries.iter().map(|(_, s)| s).sum();
+
+    let result = summaries
+        .into_iter()
+        .map(|(domain, secs)| DomainSummary {
+            domain,
+            total_seconds: secs,
+            percentage: if total_seconds > 0 {
+                (secs as f64 / total_seconds as f64) * 100.0
+            } else {
+                0.0
+            },
+        })
+        .collect();
+
+    Ok(result)
+}
+
+/// 記録済みプロセスのメタデータ一覧を取得
+#[tauri::command]
+fn get_process_metadata(state: State<Arc<AppState>>) -> Result<Vec<ProcessMetadata>, String> {
+    let db = state.db.lock();
+
+    let mut stmt = db
+        .prepare(
+            "SELECT process_name, exe_path, company_name, product_name, file_version
+             FROM process_metadata
+             ORDER BY process_name ASC",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let records = stmt
+        .query_map([], |row| {
+            Ok(ProcessMetadata {
+                process_name: row.get(0)?,
+                exe_path: row.get(1)?,
+                company_name: row.get(2)?,
+                product_name: row.get(3)?,
+                file_version: row.get(4)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(records)
+}
+
+/// ベンダー別の使用時間サマリーを取得（メタデータ未取得のプロセスは "Unknown"）
+#[tauri::command]
+fn get_vendor_summary(
+    state: State<Arc<AppState>>,
+    date: String,
+) -> Result<Vec<VendorSummary>, String> {
+    let db = state.db.lock();
+    let start_of_day = format!("{}T00:00:00", date);
+    let end_of_day = format!("{}T23:59:59", date);
+
+    let mut stmt = db
+        .prepare(
+            "SELECT COALESCE(NULLIF(m.company_name, ''), 'Unknown') as vendor,
+                    SUM(a.duration_seconds) as total
+             FROM activities a
+             LEFT JOIN process_metadata m ON m.process_name = a.process_name
+             WHERE a.start_time >= ?1 AND a.start_time <= ?2
+             GROUP BY vendor
+             ORDER BY total DESC",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let summaries: Vec<(String, i64)> = stmt
+        .query_map(params![start_of_day, end_of_day], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let total_seconds: i64 = summaries.iter().map(|(_, s)| s).sum();
+
+    let result = summaries
+        .into_iter()
+        .map(|(company_name, secs)| VendorSummary {
+            company_name,
+            total_seconds: secs,
+            percentage: if total_seconds > 0 {
+                (secs as f64 / total_seconds as f64) * 100.0
+            } else {
+                0.0
+            },
+        })
+        .collect();
+
+    Ok(result)
+}
+
+// ========== 未割り当て時間のレビュー ==========
+
+/// チケットが割り当てられていないアクティビティを取得
+///
+/// チケットの割り当てがなく（プロジェクトのみの割り当ては含む）、保存時にどのプラグインのルールにもマッチしなかったものが対象。
+#[tauri::command]
+fn get_unassigned(
+    state: State<Arc<AppState>>,
+    date: String,
+) -> Result<Vec<ActivityRecord>, String> {
+    let db = state.db.lock();
+    let start_of_day = format!("{}T00:00:00", date);
+    let end_of_day = format!("{}T23:59:59", date);
+
+    let mut stmt = db
+        .prepare(
+            "SELECT a.id, a.process_name, a.window_title, a.domain, a.start_time, a.end_time, a.duration_seconds, a.note,
+                    a.extracted_at
+             FROM activities a
+             LEFT JOIN ticket_assignments t ON t.activity_id = a.id
+             WHERE a.start_time >= ?1 AND a.start_time <= ?2
+               AND t.ticket_id IS NULL AND a.extracted_ticket_id IS NULL
+             ORDER BY a.start_time ASC",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let records: Vec<(ActivityRecord, bool)> = stmt
+        .query_map(params![start_of_day, end_of_day], |row| {
+            let record = ActivityRecord {
+                id: row.get(0)?,
+                process_name: row.get(1)?,
+                window_title: row.get(2)?,
+                domain: row.get(3)?,
+                start_time: row.get(4)?,
+                end_time: row.get(5)?,
+                duration_seconds: row.get(6)?,
+                note: row.get(7)?,
+            };
+            let extracted_at: Option<String> = row.get(8)?;
+            Ok((record, extracted_at.is_some()))
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    // 抽出前に保存されたアクティビティ（バックフィル待ち）だけはその場で抽出する
+    let unassigned = records
+        .into_iter()
+        .filter(|(record, is_extracted)| {
+            *is_extracted
+                || state
+                    .plugin_manager
+                    .extract_ticket_id(&to_activity_info(record))
+                    .is_none()
+        })
+        .map(|(record, _)| record)
+        .collect();
+
+    Ok(unassigned)
+}
+
+/// アクティビティにチケットまたはプロジェクトを手動で割り当て
+#[tauri::command]
+fn assign_activity(
+    state: State<Arc<AppState>>,
+    activity_id: i64,
+    plugin: Option<String>,
+    ticket_id: Option<String>,
+    project: Option<String>,
+) -> Result<TicketAssignment, String> {
+    let db = state.db.lock();
+    assignments::assign(
+        &db,
+        activity_id,
+        plugin.as_deref(),
+        ticket_id.as_deref(),
+        project.as_deref(),
+        assignments::SOURCE_MANUAL,
+    )
+}
+
+/// 進行中のアクティビティにチケットを割り当てる（アクティビティの切り替わり時に保存）
+#[tauri::command]
+fn assign_current_activity(
+    state: State<Arc<AppState>>,
+    ticket_id: String,
+    plugin: Option<String>,
+) -> Result<(), String> {
+    let ticket_id = ticket_id.trim();
+    if ticket_id.is_empty() {
+        return Err("Ticket ID is required".to_string());
+    }
+    if !*state.is_tracking.lock() {
+        return Err("Tracking is not running".to_string());
+    }
+
+    *state.current_assignment.lock() = Some(PendingAssignment {
+        plugin,
+        ticket_id: Some(ticket_id.to_string()),
+        project: None,
+    });
+    Ok(())
+}
+
+/// 最近割り当てたプロジェクト（ピン留めしたプロジェクトを先頭に）
+#[tauri::command]
+fn get_recent_projects(state: State<Arc<AppState>>) -> Result<Vec<String>, String> {
+    project_menu_entries(&state)
+}
+
+/// プロジェクトの上書きを取得
+#[tauri::command]
+fn get_project_override(state: State<Arc<AppState>>) -> Option<String> {
+    state.project_override.lock().clone()
+}
+
+/// プロジェクトの上書きを設定（None で解除）
+#[tauri::command]
+fn set_project_override(
+    app: AppHandle,
+    state: State<Arc<AppState>>,
+    project: Option<String>,
+) -> Result<(), String> {
+    let project = project
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty());
+    *state.project_override.lock() = project;
+    refresh_tray_menu(&app, &state);
+    Ok(())
+}
+
+fn project_menu_entries(state: &AppState) -> Result<Vec<String>, String> {
+    let config = IntegrationsConfig::load();
+    let recent = {
+        let db = state.db.lock();
+        assignments::recent_projects(&db, 5)?
+    };
+
+    let mut projects: Vec<String> = Vec::new();
+    let active = state.project_override.lock().clone();
+    for project in config
+        .pinned_projects
+        .into_iter()
+        .chain(active)
+        .chain(recent)
+    {
+        if !projects.contains(&project) {
+            projects.push(project);
+        }
+    }
+    Ok(projects)
+}
+
+/// 割り当てを解除（レビューキューに戻す）
+#[tauri::command]
+fn unassign_activity(state: State<Arc<AppState>>, activity_id: i64) -> Result<(), String> {
+    let db = state.db.lock();
+    assignments::unassign(&db, activity_id)
+}
+
+/// 期間内の重複アクティビティ（同一区間・包含区間）を統合（dry_run で確認のみ）
+#[tauri::command]
+fn dedupe_activities(
+    state: State<Arc<AppState>>,
+    start_date: String,
+    end_date: String,
+    dry_run: bool,
+) -> Result<DedupeReport, String> {
+    let db = state.db.lock();
+    maintenance::dedupe_activities(&db, &start_date, &end_date, dry_run)
+}
+
+/// 連続したアクティビティを1件にまとめる
+#[tauri::command]
+fn merge_activities(state: State<Arc<AppState>>, ids: Vec<i64>) -> Result<ActivityRecord, String> {
+    let db = state.db.lock();
+    maintenance::merge_activities(&db, &ids)
+}
+
+/// アクティビティを指定時刻で2件に分ける
+#[tauri::command]
+fn split_activity(
+    state: State<Arc<AppState>>,
+    id: i64,
+    at_time: String,
+) -> Result<(ActivityRecord, ActivityRecord), String> {
+    let db = state.db.lock();
+    maintenance::split_activity(&db, id, &at_time)
+}
+
+/// 古い断片レコードを設定に従って統合（dry_run で確認のみ）
+#[tauri::command]
+fn compact_activities(
+    state: State<Arc<AppState>>,
+    dry_run: bool,
+) -> Result<CompactionReport, String> {
+    let config = IntegrationsConfig::load();
+    let report = maintenance::compact_fragments(
+        &state.db.lock(),
+        &config.compaction,
+        Local::now(),
+        dry_run,
+    )?;
+    if report.merged > 0 && !dry_run {
+        start_ticket_extraction_backfill(state.inner().clone());
+    }
+    Ok(report)
+}
+
+/// ログイン時に起動する（トレイに最小化した状態で起動）
+#[tauri::command]
+fn enable_autostart(app: AppHandle) -> Result<(), String> {
+    app.autolaunch().enable().map_err(|e| e.to_string())
+}
+
+/// ログイン時の起動を解除
+#[tauri::command]
+fn disable_autostart(app: AppHandle) -> Result<(), String> {
+    app.autolaunch().disable().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn is_autostart_enabled(app: AppHandle) -> Result<bool, String> {
+    app.autolaunch().is_enabled().map_err(|e| e.to_string())
+}
+
+/// データベースの暗号化の状態
+#[tauri::command]
+fn get_database_encryption() -> EncryptionStatus {
+    database::status(IntegrationsConfig::load().database.encrypted)
+}
+
+/// データベースを暗号化/復号して開き直し、設定に保存する
+#[tauri::command]
+fn set_database_encryption(
+    state: State<Arc<AppState>>,
+    enabled: bool,
+) -> Result<EncryptionStatus, String> {
+    let mut config = IntegrationsConfig::try_load()?;
+    if config.database.encrypted == enabled {
+        return Ok(database::status(enabled));
+    }
+
+    flush_activities(&state);
+    let path = database::db_path();
+    let mut db = state.db.lock();
+
+    // 変換のために一度閉じる
+    let placeholder = Connection::open_in_memory().map_err(|e| e.to_string())?;
+    if let Err((conn, e)) = std::mem::replace(&mut *db, placeholder).close() {
+        *db = conn;
+        return Err(e.to_string());
+    }
+
+    match database::open(&path, enabled) {
+        Ok(conn) => *db = conn,
+        Err(e) => {
+            *db = database::open(&path, config.database.encrypted)?;
+            return Err(e);
+        }
+    }
+
+    config.database.encrypted = enabled;
+    config.save()?;
+    Ok(database::status(enabled))
+}
+
+/// 入力の計測の状態
+#[tauri::command]
+fn get_input_meter() -> InputMeterStatus {
+    input_meter::status(IntegrationsConfig::load().input_meter.enabled)
+}
+
+/// 入力の計測を有効/無効にする（無効にするとすぐにフックを外す）
+#[tauri::command]
+fn set_input_meter(enabled: bool) -> Result<InputMeterStatus, String> {
+    let mut config = IntegrationsConfig::try_load()?;
+    if enabled {
+        input_meter::start()?;
+    } else {
+        input_meter::stop();
+    }
+    config.input_meter.enabled = enabled;
+    config.save()?;
+    Ok(input_meter::status(enabled))
+}
+
+/// 期間内のアプリごとの1分あたりの入力回数
+#[tauri::command]
+fn get_input_intensity(
+    state: State<Arc<AppState>>,
+    start_date: String,
+    end_date: String,
+) -> Result<Vec<InputIntensity>, String> {
+    let db = state.db.lock();
+    input_meter::intensity(&db, &start_date, &end_date)
+}
+
+/// アイドル判定の設定を取得
+#[tauri::command]
+fn get_idle_settings(state: State<Arc<AppState>>) -> IdleConfig {
+    state.idle.lock().clone()
+}
+
+/// アイドル判定の設定を保存（再起動せずに次のポーリングから反映する）
+#[tauri::command]
+fn set_idle_settings(state: State<Arc<AppState>>, settings: IdleConfig) -> Result<(), String> {
+    if settings.enabled && settings.threshold_seconds == 0 {
+        return Err("threshold_seconds must be at least 1".to_string());
+    }
+    let mut config = IntegrationsConfig::try_load()?;
+    config.idle = settings.clone();
+    config.save()?;
+    *state.idle.lock() = settings;
+    Ok(())
+}
+
+/// 保持ポリシーの設定を取得
+#[tauri::command]
+fn get_retention_settings() -> RetentionConfig {
+    IntegrationsConfig::load().retention
+}
+
+/// 保持ポリシーの設定を保存
+#[tauri::command]
+fn set_retention_settings(settings: RetentionConfig) -> Result<(), String> {
+    if settings.raw_days == 0 {
+        return Err("raw_days must be at least 1".to_string());
+    }
+    let mut config = IntegrationsConfig::try_load()?;
+    config.retention = settings;
+    config.save()
+}
+
+/// 保持期間を過ぎたアクティビティを日別集計にまとめて削除（dry_run で確認のみ）
+#[tauri::command]
+fn prune_old_activities(
+    state: State<Arc<AppState>>,
+    dry_run: bool,
+) -> Result<RetentionReport, String> {
+    let config = IntegrationsConfig::load();
+    let db = state.db.lock();
+    retention::prune(&db, &config.retention, Local::now(), dry_run)
+}
+
+/// 日別集計（生データを削除済みの期間を含む）
+#[tauri::command]
+fn get_daily_summaries(
+    state: State<Arc<AppState>>,
+    start_date: String,
+    end_date: String,
+) -> Result<Vec<DailySummary>, String> {
+    let db = state.db.lock();
+    retention::daily_summaries(&db, &start_date, &end_date)
+}
+
+/// カレンダーの予定を今すぐ取り込み、会議と集中の比率を返す
+#[tauri::command]
+async fn sync_calendar(
+    state: State<'_, Arc<AppState>>,
+    date: String,
+) -> Result<MeetingReport, String> {
+    let date = NaiveDate::parse_from_str(&date, "%Y-%m-%d").map_err(|e| e.to_string())?;
+    sync_calendar_events(&state, date).await
+}
+
+/// 取り込み済みの会議と、会議・集中の比率
+#[tauri::command]
+fn get_meeting_report(state: State<Arc<AppState>>, date: String) -> Result<MeetingReport, String> {
+    let db = state.db.lock();
+    meetings::report(&db, &date)
+}
+
+/// バックフィル結果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackfillReport {
+    /// 未割り当てだったアクティビティの件数
+    pub scanned: usize,
+    /// チケットIDを抽出して割り当てた件数
+    pub assigned: usize,
+}
+
+/// 過去のチケット未割り当てのアクティビティにプラグインのチケットID抽出を実行して割り当てを保存
+#[tauri::command]
+fn backfill_ticket_ids(
+    state: State<Arc<AppState>>,
+    plugin_name: String,
+    start_date: String,
+    end_date: String,
+) -> Result<BackfillReport, String> {
+    let plugin = state
+        .plugin_manager
+        .get_plugin(&plugin_name)
+        .ok_or_else(|| format!("Plugin not found: {}", plugin_name))?;
+
+    let db = state.db.lock();
+    let range_start = format!("{}T00:00:00", start_date);
+    let range_end = format!("{}T23:59:59", end_date);
+
+    let mut stmt = db
+        .prepare(
+            "SELECT a.id, a.process_name, a.window_title, a.domain, a.start_time, a.end_time, a.duration_seconds, a.note,
+                    t.project
+             FROM activities a
+             LEFT JOIN ticket_assignments t ON t.activity_id = a.id
+             WHERE a.start_time >= ?1 AND a.start_time <= ?2 AND t.ticket_id IS NULL
+             ORDER BY a.start_time ASC",
+        )
+        .map_err(|e| e.to_string())?;
+
+    // プロジェクトのみ割り当て済みのものはプロジェクトを残してチケットを追加する
+    let activities: Vec<ActivityInfo> = stmt
+        .query_map(params![range_start, range_end], |row| {
+            Ok(ActivityInfo {
+                id: row.get(0)?,
+                process_name: row.get(1)?,
+                window_title: row.get(2)?,
+                domain: row.get(3)?,
+                start_time: row.get(4)?,
+                end_time: row.get(5)?,
+                duration_seconds: row.get(6)?,
+                note: row.get(7)?,
+                project: row.get(8)?,
+                category: None,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let tx = db.unchecked_transaction().map_err(|e| e.to_string())?;
+    let mut assigned = 0;
+
+    for activity in &activities {
+        if let Some(ticket_id) = plugin.extract_ticket_id(activity) {
+            assignments::assign(
+                &tx,
+                activity.id,
+                Some(plugin.name()),
+                Some(&ticket_id),
+                activity.project.as_deref(),
+                assignments::SOURCE_BACKFILL,
+            )?;
+            assigned += 1;
+        }
+    }
+
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(BackfillReport {
+        scanned: activities.len(),
+        assigned,
+    })
+}
+
+/// 過去の割り当て履歴から割り当て候補を提案
+#[tauri::command]
+fn suggest_assignment(
+    state: State<Arc<AppState>>,
+    activity_id: i64,
+) -> Result<Vec<AssignmentSuggestion>, String> {
+    let db = state.db.lock();
+
+    let (process_name, window_title, domain): (String, String, Option<String>) = db
+        .query_row(
+            "SELECT process_name, window_title, domain FROM activities WHERE id = ?1",
+            params![activity_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .map_err(|e| e.to_string())?;
+
+    assignments::suggest(
+        &db,
+        activity_id,
+        &process_name,
+        &window_title,
+        domain.as_deref(),
+        5,
+    )
+}
+
+/// 手動割り当ての繰り返しから割り当てルールの候補を生成（デフォルト3件以上）
+#[tauri::command]
+fn get_rule_candidates(
+    state: State<Arc<AppState>>,
+    min_occurrences: Option<usize>,
+) -> Result<Vec<RuleCandidate>, String> {
+    let config = IntegrationsConfig::load();
+    let db = state.db.lock();
+    learning::rule_candidates(&db, &config.assignment_rules, min_occurrences.unwrap_or(3))
+}
+
+/// 確認済みのルール候補を設定ファイルに保存して有効化
+#[tauri::command]
+fn accept_rule_candidate(state: State<Arc<AppState>>, rule: AssignmentRule) -> Result<(), String> {
+    regex::Regex::new(&rule.pattern).map_err(|e| format!("Invalid pattern: {}", e))?;
+
+    let mut config = IntegrationsConfig::try_load()?;
+    if !config.assignment_rules.contains(&rule) {
+        config.assignment_rules.push(rule);
+        config.save()?;
+    }
+
+    state.rule_assigner.set_rules(&config.assignment_rules);
+    Ok(())
+}
+
+// ========== ルール管理 ==========
+
+/// ルール一覧を取得（kind: "category" | "assignment" | "extraction"）
+#[tauri::command]
+fn get_rules(kind: RuleKind, integration: Option<String>) -> Result<serde_json::Value, String> {
+    rules::list(&IntegrationsConfig::load(), kind, integration.as_deref())
+}
+
+/// ルールを作成/更新/削除/並べ替えして設定ファイルに保存
+#[tauri::command]
+fn edit_rules(
+    state: State<Arc<AppState>>,
+    kind: RuleKind,
+    integration: Option<String>,
+    op: RuleOp,
+) -> Result<serde_json::Value, String> {
+    let mut config = IntegrationsConfig::try_load()?;
+    rules::apply(&mut config, kind, integration.as_deref(), op)?;
+    config.save()?;
+
+    // 変更を即座に反映
+    match kind {
+        RuleKind::Category => {
+            state.categorizer.set_rules(&config.categories);
+            let db = state.db.lock();
+            categories::ensure_from_rules(&db, &config.categories)?;
+        }
+        RuleKind::Assignment => state.rule_assigner.set_rules(&config.assignment_rules),
+        RuleKind::Extraction => {
+            state.plugin_manager.load_from_config()?;
+            refresh_extracted_tickets(&state)?;
+        }
+    }
+
+    rules::list(&config, kind, integration.as_deref())
+}
+
+/// 期間内の既存アクティビティに正規化/カテゴリ/割り当て/プロジェクトのルールを再適用
+#[tauri::command]
+fn apply_rules(
+    state: State<Arc<AppState>>,
+    start_date: String,
+    end_date: String,
+) -> Result<ApplyRulesReport, String> {
+    let rules = RuleSet {
+        normalizer: &state.title_normalizer,
+        categorizer: &state.categorizer,
+        assigner: &state.rule_assigner,
+        projects: &state.projects,
+    };
+    let report = rules::reapply(&state.db.lock(), &rules, &start_date, &end_date)?;
+    if report.renormalized > 0 {
+        start_ticket_extraction_backfill(state.inner().clone());
+    }
+    Ok(report)
+}
+
+/// サンプルのタイトル・プロセス名・ドメインで抽出ルールを試す（どのルールで何が取れたか）
+#[tauri::command]
+fn test_extraction_rules(
+    state: State<Arc<AppState>>,
+    sample_title: String,
+    sample_process: String,
+    sample_domain: Option<String>,
+) -> Result<ExtractionTestReport, String> {
+    let activity = ActivityInfo {
+        id: 0,
+        process_name: sample_process,
+        window_title: sample_title,
+        domain: sample_domain.filter(|d| !d.trim().is_empty()),
+        start_time: String::new(),
+        end_time: String::new(),
+        duration_seconds: 0,
+        note: None,
+        project: None,
+        category: None,
+    };
+    let config = IntegrationsConfig::try_load()?;
+    Ok(rules::test_extraction(
+        &config,
+        &state.plugin_manager,
+        &state.title_normalizer,
+        &activity,
+    ))
+}
+
+// ========== ローカルAPIのアクセス制御 ==========
+
+/// APIトークンを発行（scope: "read" | "control"）。平文トークンはこの応答でのみ返す
+#[tauri::command]
+fn create_api_token(
+    state: State<Arc<AppState>>,
+    name: String,
+    scope: String,
+) -> Result<IssuedToken, String> {
+    let db = state.db.lock();
+    api_auth::create_token(&db, &name, &scope)
+}
+
+/// APIトークンを再発行
+#[tauri::command]
+fn rotate_api_token(state: State<Arc<AppState>>, id: i64) -> Result<IssuedToken, String> {
+    let db = state.db.lock();
+    api_auth::rotate_token(&db, id)
+}
+
+/// APIトークンを失効
+#[tauri::command]
+fn revoke_api_token(state: State<Arc<AppState>>, id: i64) -> Result<(), String> {
+    let db = state.db.lock();
+    api_auth::revoke_token(&db, id)
+}
+
+/// APIトークン一覧を取得
+#[tauri::command]
+fn list_api_tokens(state: State<Arc<AppState>>) -> Result<Vec<ApiToken>, String> {
+    let db = state.db.lock();
+    api_auth::list_tokens(&db)
+}
+
+/// APIリクエストログを取得（デフォルト直近100件）
+#[tauri::command]
+fn get_api_request_log(
+    state: State<Arc<AppState>>,
+    limit: Option<i64>,
+) -> Result<Vec<ApiRequestLog>, String> {
+    let db = state.db.lock();
+    api_auth::recent_requests(&db, limit.unwrap_or(100))
+}
+
+// ========== カテゴリ予算 ==========
+
+/// 指定日を含む週のカテゴリ別予算の進捗を取得
+#[tauri::command]
+fn get_weekly_budget_status(
+    state: State<Arc<AppState>>,
+    date: String,
+) -> Result<WeeklyBudgetStatus, String> {
+    let date = chrono::NaiveDate::parse_from_str(&date, "%Y-%m-%d").map_err(|e| e.to_string())?;
+    let config = IntegrationsConfig::load();
+    let db = state.db.lock();
+    budgets::weekly_status(&db, &config.weekly_budgets, date, Local::now())
+}
+
+/// 予算のしきい値到達と目標の上限超過・達成を定期的にチェックしてデスクトップ通知を出す（目標は Webhook にも送る）
+fn start_budget_notifier(app: AppHandle, state: Arc<AppState>) {
+    use tauri_plugin_notification::NotificationExt;
+
+    thread::spawn(move || {
+        // 同じ状態は期間（予算は週、目標は日/週）ごとに1回だけ通知する
+        let first_notice = |subject: &str, period_start: &str, status: &str| {
+            let db = state.db.lock();
+            budgets::mark_notified(&db, subject, period_start, status).unwrap_or_else(|e| {
+                eprintln!("Failed to record notification: {}", e);
+                false
+            })
+        };
+        let notify = |title: &str, message: String| {
+            let _ = app
+                .notification()
+                .builder()
+                .title(title)
+                .body(message)
+                .show();
+        };
+
+        loop {
+            thread::sleep(Duration::from_secs(60));
+
+            let config = IntegrationsConfig::load();
+            let now = Local::now();
+
+            if !config.weekly_budgets.is_empty() {
+                let status = {
+                    let db = state.db.lock();
+                    budgets::weekly_status(&db, &config.weekly_budgets, now.date_naive(), now)
+                };
+                match status {
+                    Ok(status) => {
+                        for budget in status.budgets {
+                            let message = match budget.status.as_str() {
+                                "warning" => format!(
+                                    "{}: {:.0}% of weekly budget used",
+                                    budget.category, budget.progress
+                                ),
+                                "exceeded" => {
+                                    format!("{}: weekly budget exceeded", budget.category)
+                                }
+                                "met" => format!("{}: weekly target reached", budget.category),
+                                _ => continue,
+                            };
+
+                            let subject = format!("budget:{}", budget.category);
+                            if first_notice(&subject, &status.week_start, &budget.status) {
+                                notify("TimeTracker - Weekly budget", message);
+                            }
+                        }
+                    }
+                    Err(e) => eprintln!("Failed to evaluate weekly budgets: {}", e),
+                }
+            }
+
+            let progress = {
+                let db = state.db.lock();
+                goals::progress(&db, now.date_naive())
+            };
+            match progress {
+                Ok(progress) => {
+                    for entry in progress {
+                        let period = if entry.goal.period == goals::PERIOD_WEEK {
+                            "weekly"
+                        } else {
+                            "daily"
+                        };
+                        let message = match entry.status.as_str() {
+                            "exceeded" => format!(
+                                "{}: {} limit of {}h exceeded",
+                                entry.goal.category, period, entry.goal.hours
+                            ),
+                            "met" => format!(
+                                "{}: {} target of {}h reached",
+                                entry.goal.category, period, entry.goal.hours
+                            ),
+                            _ => continue,
+                        };
+
+                        let subject = format!("goal:{}", entry.goal.id);
+                        if first_notice(&subject, &entry.period_start, &entry.status) {
+                            webhooks::send(&config.webhooks, webhooks::EVENT_GOAL_REACHED, &entry);
+                            notify("TimeTracker - Goal", message);
+                        }
+                    }
+                }
+                Err(e) => eprintln!("Failed to evaluate goals: {}", e),
+            }
+        }
+    });
+}
+
+/// 目標一覧を取得
+#[tauri::command]
+fn list_goals(state: State<Arc<AppState>>) -> Result<Vec<Goal>, String> {
+    let db = state.db.lock();
+    goals::list(&db)
+}
+
+/// 目標を作成（period: "day" | "week"、kind: "min" | "max"）
+#[tauri::command]
+fn create_goal(
+    state: State<Arc<AppState>>,
+    category: String,
+    period: String,
+    kind: String,
+    hours: f64,
+) -> Result<Goal, String> {
+    let db = state.db.lock();
+    goals::create(&db, &category, &period, &kind, hours)
+}
+
+/// 目標の時間・有効/無効を変更
+#[tauri::command]
+fn update_goal(
+    state: State<Arc<AppState>>,
+    id: i64,
+    hours: Option<f64>,
+    enabled: Option<bool>,
+) -> Result<(), String> {
+    let db = state.db.lock();
+    goals::update(&db, id, hours, enabled)
+}
+
+/// 目標を削除
+#[tauri::command]
+fn delete_goal(state: State<Arc<AppState>>, id: i64) -> Result<(), String> {
+    let db = state.db.lock();
+    goals::delete(&db, id)
+}
+
+/// 指定日の目標の進捗
+#[tauri::command]
+fn get_goal_progress(
+    state: State<Arc<AppState>>,
+    date: String,
+) -> Result<Vec<GoalProgress>, String> {
+    let date = NaiveDate::parse_from_str(&date, "%Y-%m-%d").map_err(|e| e.to_string())?;
+    let db = state.db.lock();
+    goals::progress(&db, date)
+}
+
+// ========== プラグイン関連コマンド ==========
+
+/// キャプチャソースの一覧（優先度順）
+#[tauri::command]
+fn get_capture_sources(state: State<Arc<AppState>>) -> Vec<CaptureSourceInfo> {
+    state.capture.list()
+}
+
+/// ブラウザ拡張/カレンダーからのイベントを通知（valid_seconds 経過で失効、既定5秒）
+#[tauri::command]
+fn report_capture_event(
+    state: State<Arc<AppState>>,
+    source: String,
+    window: CapturedWindow,
+    valid_seconds: Option<u64>,
+) -> Result<(), String> {
+    let provider = match source.as_str() {
+        capture::SOURCE_BROWSER_EXTENSION => &state.capture.browser_extension,
+        capture::SOURCE_CALENDAR => &state.capture.calendar,
+        _ => return Err(format!("Unknown capture source: {}", source)),
+    };
+    provider.push(window, Duration::from_secs(valid_seconds.unwrap_or(5)));
+    Ok(())
+}
+
+/// 手動タイマーを開始（停止するまで他のソースより優先して記録）
+#[tauri::command]
+fn start_manual_timer(state: State<Arc<AppState>>, window: CapturedWindow) {
+    state.capture.manual_timer.start(window);
+}
+
+/// 手動タイマーを停止
+#[tauri::command]
+fn stop_manual_timer(state: State<Arc<AppState>>) -> bool {
+    state.capture.manual_timer.stop()
+}
+
+/// プラグイン一覧を取得
+#[tauri::command]
+fn get_plugins(state: State<Arc<AppState>>) -> Vec<String> {
+    state.plugin_manager.list_plugins()
+}
+
+/// 連携の認証情報をOSのキーリングに保存し、設定ファイルからは平文のキーを消す
+///
+/// キーリング上の名前は連携名と同じにする。
+#[tauri::command]
+fn set_integration_secret(
+    state: State<Arc<AppState>>,
+    integration: String,
+    secret: String,
+) -> Result<(), String> {
+    let secret = secret.trim();
+    if secret.is_empty() {
+        return Err("Secret must not be empty".to_string());
+    }
+
+    let mut config = IntegrationsConfig::try_load()?;
+    let entry = config
+        .integrations
+        .iter_mut()
+        .find(|e| e.name == integration)
+        .ok_or_else(|| format!("Integration not found: {}", integration))?;
+    let IntegrationConfig::Redmine(redmine) = &mut entry.config else {
+        return Err(format!("{} does not support keyring secrets", integration));
+    };
+
+    secrets::set_integration(&integration, secret)?;
+    redmine.api_key.clear();
+    redmine.api_key_secret = Some(integration.clone());
+    config.save()?;
+
+    state.plugin_manager.load_from_config()
+}
+
+/// キーリングから連携の認証情報を削除
+#[tauri::command]
+fn delete_integration_secret(
+    state: State<Arc<AppState>>,
+    integration: String,
+) -> Result<(), String> {
+    let mut config = IntegrationsConfig::try_load()?;
+    secrets::delete_integration(&integration)?;
+
+    let mut changed = false;
+    for entry in &mut config.integrations {
+        if let IntegrationConfig::Redmine(redmine) = &mut entry.config {
+            if redmine.api_key_secret.as_deref() == Some(integration.as_str()) {
+                redmine.api_key_secret = None;
+                changed = true;
+            }
+        }
+    }
+    if changed {
+        config.save()?;
+    }
+
+    state.plugin_manager.load_from_config()
+}
+
+/// 設定ファイルの内容をそのまま取得（設定画面用）
+#[tauri::command]
+fn get_integrations_config() -> Result<IntegrationsConfig, String> {
+    IntegrationsConfig::try_load()
+}
+
+/// 設定ファイルを検証（問題がなければ空）
+#[tauri::command]
+fn validate_config() -> Result<Vec<ConfigIssue>, String> {
+    settings::validate_file()
+}
+
+/// 設定ファイルを読めず既定値で動いている場合はその理由
+#[tauri::command]
+fn get_config_error() -> Option<String> {
+    IntegrationsConfig::try_load().err()
+}
+
+/// 連携を追加・更新（original_name を指定すると名前を変更して置き換える）
+#[tauri::command]
+fn set_integration(
+    state: State<Arc<AppState>>,
+    entry: IntegrationEntry,
+    original_name: Option<String>,
+) -> Result<(), String> {
+    let mut config = IntegrationsConfig::try_load()?;
+    settings::upsert_integration(&mut config, entry, original_name.as_deref())?;
+    config.save()?;
+
+    state.plugin_manager.load_from_config()
+}
+
+/// 連携を削除（キーリングの認証情報は残す）
+#[tauri::command]
+fn remove_integration(state: State<Arc<AppState>>, name: String) -> Result<(), String> {
+    let mut config = IntegrationsConfig::try_load()?;
+    settings::remove_integration(&mut config, &name)?;
+    config.save()?;
+
+    state.plugin_manager.load_from_config()
+}
+
+/// アップロード設定を保存（None で削除。自動アップロードは次回の確認時から反映）
+#[tauri::command]
+fn set_upload_config(config: Option<UploadConfig>) -> Result<(), String> {
+    let mut integrations = IntegrationsConfig::try_load()?;
+    settings::set_upload(&mut integrations, config)?;
+    integrations.save()
+}
+
+/// プラグイン設定を再読み込み（カテゴリ分類ルールも含む。ファイルの変更は自動でも読み直す）
+#[tauri::command]
+fn reload_plugins(state: State<Arc<AppState>>) -> Result<(), String> {
+    config_watcher::reload_and_notify(&state)
+}
+
+/// サンプル設定ファイルを作成
+#[tauri::command]
+fn create_sample_plugin_config() -> Result<String, String> {
+    plugins::create_sample_config()?;
+    Ok(IntegrationsConfig::config_path()
+        .to_string_lossy()
+        .to_string())
+}
+
+/// 設定ファイルのパスを取得
+#[tauri::command]
+fn get_plugin_config_path() -> String {
+    IntegrationsConfig::config_path()
+        .to_string_lossy()
+        .to_string()
+}
+
+/// アクティビティからチケットIDを抽出
+#[tauri::command]
+fn extract_ticket_ids(
+    state: State<Arc<AppState>>,
+    activity_id: i64,
+) -> Result<Vec<(String, String)>, String> {
+    let db = state.db.lock();
+
+    let mut stmt = db
+        .prepare(
+            "SELECT id, process_name, window_title, domain, start_time, end_time, duration_seconds, note
+             FROM activities WHERE id = ?1",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let activity = stmt
+        .query_row(params![activity_id], |row| {
+            Ok(ActivityInfo {
+                id: row.get(0)?,
+                process_name: row.get(1)?,
+                window_title: row.get(2)?,
+                domain: row.get(3)?,
+                start_time: row.get(4)?,
+                end_time: row.get(5)?,
+                duration_seconds: row.get(6)?,
+                note: row.get(7)?,
+                project: None,
+                category: None,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+
+    Ok(state.plugin_manager.extract_all_ticket_ids(&activity))
+}
+
+/// 作業時間を外部サービスに同期（同期済みのアクティビティは force 指定時のみ再送）
+#[tauri::command]
+async fn sync_time_entry(
+    state: State<'_, Arc<AppState>>,
+    plugin_name: String,
+    activity_id: i64,
+    ticket_id: String,
+    force: Option<bool>,
+) -> Result<SyncResult, String> {
+    let activity = {
+        let db = state.db.lock();
+
+        if !force.unwrap_or(false) {
+            if let Some(entry) = sync_log::find(&db, activity_id, &plugin_name)? {
+                return Err(format!(
+                    "Activity {} was already synced to {} ({}) at {}",
+                    activity_id, plugin_name, entry.ticket_id, entry.synced_at
+                ));
+            }
+            if pending_sync::staged_activity_ids(&db, &plugin_name)?.contains(&activity_id) {
+                return Err(format!(
+                    "Activity {} is waiting for review in the pending sync list",
+                    activity_id
+                ));
+            }
+        }
+
+        let mut stmt = db
+            .prepare(
+                "SELECT a.id, a.process_name, a.window_title, a.domain, a.start_time, a.end_time, a.duration_seconds, a.note,
+                        t.project, a.category
+                 FROM activities a
+                 LEFT JOIN ticket_assignments t ON t.activity_id = a.id
+                 WHERE a.id = ?1",
+            )
+            .map_err(|e| e.to_string())?;
+
+        stmt.query_row(params![activity_id], |row| {
+            Ok(ActivityInfo {
+                id: row.get(0)?,
+                process_name: row.get(1)?,
+                window_title: row.get(2)?,
+                domain: row.get(3)?,
+                start_time: row.get(4)?,
+                end_time: row.get(5)?,
+                duration_seconds: row.get(6)?,
+                note: row.get(7)?,
+                project: row.get(8)?,
+                category: row.get(9)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+    };
+
+    // キューに保留する場合も送信時と同じコメント・時間で残す
+    let date = activity
+        .start_time
+        .get(..10)
+        .unwrap_or_default()
+        .to_string();
+    let used_seconds = {
+        let db = state.db.lock();
+        day_used_seconds(&db, &plugin_name, &date)?
+    };
+    let mut entries = [activity];
+    state
+        .plugin_manager
+        .prepare_entries(&plugin_name, &mut entries, used_seconds);
+    let [activity] = entries;
+
+    // 1日の上限に達していれば送信せず、同期済みとして扱う
+    let has_cap = state
+        .plugin_manager
+        .rounding(&plugin_name)
+        .daily_cap_hours
+        .is_some();
+    if has_cap && activity.duration_seconds == 0 {
+        let db = state.db.lock();
+        sync_log::record(&db, &[activity.id], &plugin_name, &ticket_id, None, 0)?;
+        return Ok(SyncResult {
+            success: false,
+            message: "Skipped: daily cap reached".to_string(),
+            external_id: None,
+        });
+    }
+
+    let result = state
+        .plugin_manager
+        .sync_time_entry(&plugin_name, &activity, &ticket_id)
+        .await;
+
+    // 接続できない場合はキューに保留し、接続が戻ったら自動で送信する
+    match result {
+        Err(e) if e.is_network() => {
+            state.metrics.sync_failure();
+            let db = state.db.lock();
+            let message = match sync_queue::enqueue(
+                &db,
+                &plugin_name,
+                &ticket_id,
+                &activity,
+                &[activity.id],
+                e.message(),
+            )? {
+                Some(_) => "Offline: queued for sync when connectivity returns".to_string(),
+                None => "Already queued or synced".to_string(),
+            };
+            Ok(SyncResult {
+                success: false,
+                message,
+                external_id: None,
+            })
+        }
+        Ok(result) if result.success => {
+            let db = state.db.lock();
+            record_sync(
+                &state,
+                &db,
+                &[activity.id],
+                &plugin_name,
+                &ticket_id,
+                result.external_id.as_deref(),
+                activity.duration_seconds,
+            )?;
+            Ok(result)
+        }
+        result => {
+            state.metrics.sync_failure();
+            result.map_err(String::from)
+        }
+    }
+}
+
+/// 指定日のアクティビティの同期履歴
+#[tauri::command]
+fn get_sync_status(state: State<Arc<AppState>>, date: String) -> Result<Vec<SyncLogEntry>, String> {
+    let db = state.db.lock();
+    sync_log::for_range(
+        &db,
+        &format!("{}T00:00:00", date),
+        &format!("{}T23:59:59", date),
+    )
+}
+
+/// チケットごとの合計時間（プラグイン単位）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TicketTotal {
+    pub plugin: String,
+    pub ticket_id: String,
+    /// キャッシュ済みのチケットの件名（取得していなければ None）
+    pub subject: Option<String>,
+    pub duration_seconds: i64,
+    /// 同期済みのアクティビティの時間
+    pub synced_seconds: i64,
+    pub activity_ids: Vec<i64>,
+    /// 未同期のアクティビティ（一括同期の対象）
+    pub unsynced_activity_ids: Vec<i64>,
+}
+
+/// 期間内のアクティビティをプラグインごと・チケットごとに集計（割り当てを抽出結果より優先）
+#[tauri::command]
+fn get_ticket_summary(
+    state: State<Arc<AppState>>,
+    start_date: String,
+    end_date: String,
+) -> Result<Vec<TicketTotal>, String> {
+    let range_start = format!("{}T00:00:00", start_date);
+    let range_end = format!("{}T23:59:59", end_date);
+    let db = state.db.lock();
+
+    let mut stmt = db
+        .prepare(
+            "SELECT id, process_name, window_title, domain, start_time, end_time, duration_seconds, note
+             FROM activities
+             WHERE start_time >= ?1 AND start_time <= ?2
+             ORDER BY start_time ASC",
+        )
+        .map_err(|e| e.to_string())?;
+    let activities: Vec<ActivityInfo> = stmt
+        .query_map(params![range_start, range_end], |row| {
+            Ok(ActivityInfo {
+                id: row.get(0)?,
+                process_name: row.get(1)?,
+                window_title: row.get(2)?,
+                domain: row.get(3)?,
+                start_time: row.get(4)?,
+                end_time: row.get(5)?,
+                duration_seconds: row.get(6)?,
+                note: row.get(7)?,
+                project: None,
+                category: None,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let assigned = activity_tickets(&state.plugin_manager, &db, &range_start, &range_end)?;
+
+    let synced: HashSet<(String, i64)> = sync_log::for_range(&db, &range_start, &range_end)?
+        .into_iter()
+        .map(|entry| (entry.plugin, entry.activity_id))
+        .collect();
+
+    let no_assignments = HashMap::new();
+    let mut totals = Vec::new();
+    for plugin in state.plugin_manager.list_plugins() {
+        let plugin_assigned = assigned.get(&plugin).unwrap_or(&no_assignments);
+        let groups = state
+            .plugin_manager
+            .group_by_ticket(&plugin, &activities, plugin_assigned)?;
+        for (ticket_id, members) in groups {
+            let is_synced = |a: &ActivityInfo| synced.contains(&(plugin.clone(), a.id));
+            let subject = ticket_info::cached(&db, &plugin, &ticket_id)?.map(|info| info.subject);
+            totals.push(TicketTotal {
+                plugin: plugin.clone(),
+                ticket_id,
+                subject,
+                duration_seconds: members.iter().map(|a| a.duration_seconds).sum(),
+                synced_seconds: members
+                    .iter()
+                    .filter(|a| is_synced(a))
+                    .map(|a| a.duration_seconds)
+                    .sum(),
+                activity_ids: members.iter().map(|a| a.id).collect(),
+                unsynced_activity_ids: members
+                    .iter()
+                    .filter(|a| !is_synced(a))
+                    .map(|a| a.id)
+                    .collect(),
+            });
+        }
+    }
+
+    totals.sort_by_key(|total| std::cmp::Reverse(total.duration_seconds));
+    Ok(totals)
+}
+
+/// 指定日のアクティビティをチケットごとにまとめて同期
+#[tauri::command]
+async fn sync_day(
+    state: State<'_, Arc<AppState>>,
+    plugin_name: String,
+    date: String,
+) -> Result<Vec<TicketSyncResult>, String> {
+    sync_day_activities(&state, &plugin_name, &date).await
+}
+
+/// 指定日の未同期のアクティビティから送信前に確認する候補を作成（作成した候補を返す）
+#[tauri::command]
+fn generate_pending_sync(
+    state: State<'_, Arc<AppState>>,
+    plugin_name: String,
+    date: String,
+) -> Result<Vec<PendingSync>, String> {
+    let db = state.db.lock();
+    let (activities, assigned) =
+        unsynced_day_activities(&state.plugin_manager, &db, &plugin_name, &date)?;
+    let used_seconds = day_used_seconds(&db, &plugin_name, &date)?;
+
+    // 候補にしたアクティビティ（却下したものを含む）とキューで送信待ちのものは重ねて候補にしない
+    let staged = pending_sync::staged_activity_ids(&db, &plugin_name)?;
+    let queued = sync_queue::queued_activity_ids(&db, &plugin_name)?;
+    let activities: Vec<ActivityInfo> = activities
+        .into_iter()
+        .filter(|activity| !staged.contains(&activity.id) && !queued.contains(&activity.id))
+        .collect();
+
+    let groups = state
+        .plugin_manager
+        .group_by_ticket(&plugin_name, &activities, &assigned)?;
+
+    let mut merged: Vec<ActivityInfo> = groups
+        .iter()
+        .map(|(_, members)| plugins::merge_activities(members))
+        .collect();
+    state
+        .plugin_manager
+        .prepare_entries(&plugin_name, &mut merged, used_seconds);
+
+    let mut created = Vec::new();
+    for ((ticket_id, members), activity) in groups.iter().zip(merged) {
+        let activity_ids: Vec<i64> = members.iter().map(|a| a.id).collect();
+        let id = pending_sync::propose(
+            &db,
+            &plugin_name,
+            ticket_id,
+            &activity,
+            &activity_ids,
+            activity.note.as_deref().unwrap_or_default(),
+        )?;
+        created.extend(pending_sync::get(&db, id)?);
+    }
+
+    Ok(created)
+}
+
+/// 送信前の候補一覧
+#[tauri::command]
+fn list_pending_sync(
+    state: State<'_, Arc<AppState>>,
+    plugin_name: Option<String>,
+    date: Option<String>,
+    status: Option<String>,
+) -> Result<Vec<PendingSync>, String> {
+    let db = state.db.lock();
+    pending_sync::list(
+        &db,
+        plugin_name.as_deref(),
+        date.as_deref(),
+        status.as_deref(),
+    )
+}
+
+/// 候補のチケット・日付・時間・コメントを編集（承認は取り消される）
+#[tauri::command]
+fn update_pending_sync(
+    state: State<'_, Arc<AppState>>,
+    id: i64,
+    ticket_id: Option<String>,
+    date: Option<String>,
+    duration_seconds: Option<i64>,
+    comment: Option<String>,
+) -> Result<PendingSync, String> {
+    let db = state.db.lock();
+    pending_sync::update(
+        &db,
+        id,
+        ticket_id.as_deref(),
+        date.as_deref(),
+        duration_seconds,
+        comment.as_deref(),
+    )
+}
+
+/// 複数の候補を1件にまとめる
+#[tauri::command]
+fn merge_pending_sync(
+    state: State<'_, Arc<AppState>>,
+    ids: Vec<i64>,
+) -> Result<PendingSync, String> {
+    let db = state.db.lock();
+    pending_sync::merge(&db, &ids)
+}
+
+/// 候補を承認（approved）・却下（rejected）・確認待ち（pending）にする
+#[tauri::command]
+fn set_pending_sync_status(
+    state: State<'_, Arc<AppState>>,
+    ids: Vec<i64>,
+    status: String,
+) -> Result<(), String> {
+    let db = state.db.lock();
+    pending_sync::set_status(&db, &ids, &status)
+}
+
+/// 候補を削除（含まれていたアクティビティは再び候補にできる）
+#[tauri::command]
+fn discard_pending_sync(state: State<'_, Arc<AppState>>, id: i64) -> Result<(), String> {
+    let db = state.db.lock();
+    pending_sync::discard(&db, id)
+}
+
+/// 承認済みの候補だけを送信（失敗した候補は承認済みのまま残す）
+///
+/// 丸めと上限は候補の作成時に適用済みなので、承認した時間のまま送る。
+#[tauri::command]
+async fn push_approved_sync(
+    state: State<'_, Arc<AppState>>,
+    plugin_name: Option<String>,
+) -> Result<Vec<PushResult>, String> {
+    let approved = {
+        let db = state.db.lock();
+        pending_sync::list(
+            &db,
+            plugin_name.as_deref(),
+            None,
+            Some(pending_sync::STATUS_APPROVED),
+        )?
+    };
+
+    let mut results = Vec::new();
+    for entry in &approved {
+        // 承認後に別の経路で送信されたアクティビティを含む候補は送らない
+        let already_synced = {
+            let db = state.db.lock();
+            let synced = sync_log::any_synced(&db, &entry.activity_ids, &entry.plugin)?;
+            if synced {
+                pending_sync::mark_synced(&db, entry.id, None)?;
+            }
+            synced
+        };
+        if already_synced {
+            results.push(PushResult {
+                id: entry.id,
+                plugin: entry.plugin.clone(),
+                ticket_id: entry.ticket_id.clone(),
+                result: SyncResult {
+                    success: false,
+                    message: "Skipped: already synced".to_string(),
+                    external_id: None,
+                },
+            });
+            continue;
+        }
+
+        let result = state
+            .plugin_manager
+            .sync_time_entry(&entry.plugin, &entry.to_activity(), &entry.ticket_id)
+            .await
+            .unwrap_or_else(|e| SyncResult {
+                success: false,
+                message: e.into(),
+                external_id: None,
+            });
+
+        let db = state.db.lock();
+        if result.success {
+            record_sync(
+                &state,
+                &db,
+                &entry.activity_ids,
+                &entry.plugin,
+                &entry.ticket_id,
+                result.external_id.as_deref(),
+                entry.duration_seconds,
+            )?;
+            pending_sync::mark_synced(&db, entry.id, result.external_id.as_deref())?;
+        } else {
+            state.metrics.sync_failure();
+            pending_sync::mark_error(&db, entry.id, &result.message)?;
+        }
+
+        results.push(PushResult {
+            id: entry.id,
+            plugin: entry.plugin.clone(),
+            ticket_id: entry.ticket_id.clone(),
+            result,
+        });
+    }
+
+    Ok(results)
+}
+
+/// 同期キューの一覧（status: "pending" | "synced" | "failed"、未指定なら全件）
+#[tauri::command]
+fn get_sync_queue(
+    state: State<Arc<AppState>>,
+    status: Option<String>,
+) -> Result<Vec<QueuedSync>, String> {
+    let db = state.db.lock();
+    sync_queue::list(&db, status.as_deref())
+}
+
+/// 保留中の同期を今すぐ送信（再送待ちの間隔は無視する）
+#[tauri::command]
+async fn flush_sync_queue(state: State<'_, Arc<AppState>>) -> Result<FlushReport, String> {
+    flush_pending_syncs(&state, false).await
 }
 
-/// サンプル設定ファイルを作成
+/// 失敗した同期を送信待ちに戻す
 #[tauri::command]
-fn create_sample_plugin_config() -> Result<String, String> {
-    plugins::create_sample_config()?;
-    Ok(IntegrationsConfig::config_path()
-        .to_string_lossy()
-        .to_string())
+fn retry_sync_queue_entry(state: State<Arc<AppState>>, id: i64) -> Result<(), String> {
+    let db = state.db.lock();
+    sync_queue::retry(&db, id)
 }
 
-/// 設定ファイルのパスを取得
+/// 同期キューのエントリを削除
 #[tauri::command]
-fn get_plugin_config_path() -> String {
-    IntegrationsConfig::config_path()
-        .to_string_lossy()
-        .to_string()
+fn discard_sync_queue_entry(state: State<Arc<AppState>>, id: i64) -> Result<(), String> {
+    let db = state.db.lock();
+    sync_queue::discard(&db, id)
 }
 
-/// アクティビティからチケットIDを抽出
+/// 自分に割り当てられたチケット一覧（チケット選択用、refresh でキャッシュを無視）
 #[tauri::command]
-fn extract_ticket_ids(
-    state: State<Arc<AppState>>,
-    activity_id: i64,
-) -> Result<Vec<(String, String)>, String> {
+async fn list_my_issues(
+    state: State<'_, Arc<AppState>>,
+    plugin_name: String,
+    refresh: Option<bool>,
+) -> Result<Vec<IssueSummary>, String> {
+    let issues = state
+        .plugin_manager
+        .list_my_issues(&plugin_name, refresh.unwrap_or(false))
+        .await?;
+
+    // 一覧で取れた件名はチケット情報のキャッシュにも入れておく
     let db = state.db.lock();
+    for issue in &issues {
+        if let Err(e) = ticket_info::store(&db, &plugin_name, &issue.id, issue) {
+            eprintln!("Failed to cache ticket {}: {}", issue.id, e);
+        }
+    }
+    Ok(issues)
+}
 
-    let mut stmt = db
-        .prepare(
-            "SELECT id, process_name, window_title, domain, start_time, end_time, duration_seconds
-             FROM activities WHERE id = ?1",
-        )
-        .map_err(|e| e.to_string())?;
+/// チケットの件名・状態を取得（TTL 内ならキャッシュを返す。取得に失敗したら期限切れのキャッシュを返す）
+#[tauri::command]
+async fn get_ticket_info(
+    state: State<'_, Arc<AppState>>,
+    plugin: String,
+    id: String,
+    refresh: Option<bool>,
+) -> Result<Option<TicketInfo>, String> {
+    let cached = {
+        let db = state.db.lock();
+        ticket_info::cached(&db, &plugin, &id)?
+    };
+    if let Some(info) = &cached {
+        if !refresh.unwrap_or(false) && info.is_fresh() {
+            return Ok(cached);
+        }
+    }
 
-    let activity = stmt
-        .query_row(params![activity_id], |row| {
-            Ok(ActivityInfo {
-                id: row.get(0)?,
-                process_name: row.get(1)?,
-                window_title: row.get(2)?,
-                domain: row.get(3)?,
-                start_time: row.get(4)?,
-                end_time: row.get(5)?,
-                duration_seconds: row.get(6)?,
-            })
-        })
-        .map_err(|e| e.to_string())?;
+    match state.plugin_manager.get_ticket_info(&plugin, &id).await {
+        Ok(Some(issue)) => {
+            let db = state.db.lock();
+            ticket_info::store(&db, &plugin, &id, &issue).map(Some)
+        }
+        Ok(None) => {
+            let db = state.db.lock();
+            ticket_info::remove(&db, &plugin, &id)?;
+            Ok(None)
+        }
+        Err(e) => match cached {
+            Some(info) => Ok(Some(TicketInfo {
+                stale: true,
+                ..info
+            })),
+            None => Err(e),
+        },
+    }
+}
 
-    Ok(state.plugin_manager.extract_all_ticket_ids(&activity))
+/// チケットの見積もりに対する作業時間の進捗
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TicketProgress {
+    pub plugin: String,
+    pub ticket_id: String,
+    pub tracked_seconds: i64,
+    /// 見積もり時間（連携が見積もりに対応しない/未設定なら None）
+    pub estimated_hours: Option<f64>,
+    /// 見積もりに対する進捗（%）
+    pub progress: Option<f64>,
+    pub exceeded: bool,
 }
 
-/// 作業時間を外部サービスに同期
+/// チケットの作業時間を見積もりと比較（notify=true なら超過時に通知）
 #[tauri::command]
-async fn sync_time_entry(
+async fn get_ticket_progress(
+    app: AppHandle,
     state: State<'_, Arc<AppState>>,
     plugin_name: String,
-    activity_id: i64,
     ticket_id: String,
-) -> Result<SyncResult, String> {
-    let activity = {
-        let db = state.db.lock();
-
-        let mut stmt = db
-            .prepare(
-                "SELECT id, process_name, window_title, domain, start_time, end_time, duration_seconds
-                 FROM activities WHERE id = ?1",
-            )
-            .map_err(|e| e.to_string())?;
+    notify: Option<bool>,
+) -> Result<TicketProgress, String> {
+    use tauri_plugin_notification::NotificationExt;
 
-        stmt.query_row(params![activity_id], |row| {
-            Ok(ActivityInfo {
-                id: row.get(0)?,
-                process_name: row.get(1)?,
-                window_title: row.get(2)?,
-                domain: row.get(3)?,
-                start_time: row.get(4)?,
-                end_time: row.get(5)?,
-                duration_seconds: row.get(6)?,
-            })
-        })
-        .map_err(|e| e.to_string())?
+    let tracked_seconds = {
+        let db = state.db.lock();
+        assignments::tracked_seconds(&db, &plugin_name, &ticket_id)?
     };
 
-    state
+    let estimated_hours = state
         .plugin_manager
-        .sync_time_entry(&plugin_name, &activity, &ticket_id)
-        .await
+        .get_ticket_estimate(&plugin_name, &ticket_id)
+        .await?
+        .filter(|hours| *hours > 0.0);
+
+    let progress = estimated_hours.map(|hours| tracked_seconds as f64 / (hours * 3600.0) * 100.0);
+    let exceeded = progress.is_some_and(|p| p > 100.0);
+
+    if exceeded && notify.unwrap_or(false) {
+        let _ = app
+            .notification()
+            .builder()
+            .title("TimeTracker - Estimate exceeded")
+            .body(format!(
+                "#{}: {:.1}h tracked / {:.1}h estimated",
+                ticket_id,
+                tracked_seconds as f64 / 3600.0,
+                estimated_hours.unwrap_or_default()
+            ))
+            .show();
+    }
+
+    Ok(TicketProgress {
+        plugin: plugin_name,
+        ticket_id,
+        tracked_seconds,
+        estimated_hours,
+        progress,
+        exceeded,
+    })
 }
 
 /// プラグインの接続テスト
@@ -680,64 +2498,136 @@ async fn test_plugin_connection(
     state.plugin_manager.test_connection(&plugin_name).await
 }
 
-/// タイムラインをCSV形式でエクスポート
+fn slack_integration() -> Result<SlackIntegration, String> {
+    let config = IntegrationsConfig::load()
+        .slack
+        .ok_or("Slack is not configured")?;
+    SlackIntegration::new(config)
+}
+
+/// Slack の接続テスト（"ユーザー名 (ワークスペース)" を返す）
+#[tauri::command]
+async fn test_slack_connection() -> Result<String, String> {
+    slack_integration()?.test_connection().await
+}
+
+/// 指定日（省略時は今日）のサマリーを Slack に投稿
+#[tauri::command]
+async fn post_slack_summary(
+    state: State<'_, Arc<AppState>>,
+    date: Option<String>,
+) -> Result<(), String> {
+    let date = match date {
+        Some(date) => NaiveDate::parse_from_str(&date, "%Y-%m-%d").map_err(|e| e.to_string())?,
+        None => Local::now().date_naive(),
+    };
+    slack_status::post_summary(&state, &slack_integration()?, date).await
+}
+
+/// タイムラインをCSV形式でエクスポート（profile でマスキング方法を指定）
+#[tauri::command]
+fn export_timeline_csv(
+    state: State<Arc<AppState>>,
+    date: String,
+    profile: Option<String>,
+) -> Result<String, String> {
+    timeline_csv(&state, &date, profile.as_deref())
+}
+
+/// 指定日のアクティビティを iCalendar 形式でエクスポート
+///
+/// group_by: "app"（既定） | "category"。連続する同じアプリ/カテゴリを1つの予定にまとめる。
 #[tauri::command]
-fn export_timeline_csv(state: State<Arc<AppState>>, date: String) -> Result<String, String> {
+fn export_ics(
+    state: State<Arc<AppState>>,
+    date: String,
+    group_by: Option<String>,
+    profile: Option<String>,
+) -> Result<String, String> {
+    let by_category = match group_by.as_deref().unwrap_or("app") {
+        "app" => false,
+        "category" => true,
+        other => return Err(format!("Unknown group_by: {}", other)),
+    };
+    let profile = redaction::resolve_profile(&IntegrationsConfig::load(), profile.as_deref())?;
     let db = state.db.lock();
     let start_of_day = format!("{}T00:00:00", date);
     let end_of_day = format!("{}T23:59:59", date);
 
     let mut stmt = db
         .prepare(
-            "SELECT id, process_name, window_title, domain, start_time, end_time, duration_seconds
+            "SELECT process_name, window_title, category, start_time, end_time, duration_seconds
              FROM activities
              WHERE start_time >= ?1 AND start_time <= ?2
              ORDER BY start_time ASC",
         )
         .map_err(|e| e.to_string())?;
 
-    let records: Vec<ActivityRecord> = stmt
+    let activities: Vec<ics::IcsActivity> = stmt
         .query_map(params![start_of_day, end_of_day], |row| {
-            Ok(ActivityRecord {
-                id: row.get(0)?,
-                process_name: row.get(1)?,
-                window_title: row.get(2)?,
-                domain: row.get(3)?,
-                start_time: row.get(4)?,
-                end_time: row.get(5)?,
-                duration_seconds: row.get(6)?,
+            Ok(ics::IcsActivity {
+                process_name: row.get(0)?,
+                window_title: row.get(1)?,
+                category: row.get(2)?,
+                start_time: row.get(3)?,
+                end_time: row.get(4)?,
+                duration_seconds: row.get(5)?,
             })
         })
         .map_err(|e| e.to_string())?
         .filter_map(|r| r.ok())
+        .map(|activity| ics::IcsActivity {
+            process_name: profile
+                .process_name(&activity.process_name)
+                .unwrap_or_default(),
+            window_title: profile
+                .window_title(&activity.window_title)
+                .unwrap_or_default(),
+            ..activity
+        })
         .collect();
 
-    // Build CSV
-    let mut csv = String::new();
-    csv.push_str("start_time,end_time,duration_seconds,process_name,window_title,domain\n");
-
-    for record in records {
-        csv.push_str(&format!(
-            "{},{},{},{},{},{}\n",
-            escape_csv_field(&record.start_time),
-            escape_csv_field(&record.end_time),
-            record.duration_seconds,
-            escape_csv_field(&record.process_name),
-            escape_csv_field(&record.window_title),
-            escape_csv_field(&record.domain.unwrap_or_default()),
-        ));
+    Ok(ics::build_calendar(&date, &activities, by_category))
+}
+
+/// 全アクティビティとカテゴリをエクスポート（format: "json"（既定） | "jsonl"）
+#[tauri::command]
+fn export_json(state: State<Arc<AppState>>, format: Option<String>) -> Result<String, String> {
+    let db = state.db.lock();
+    let bundle = transfer::export(&db)?;
+
+    match format.as_deref().unwrap_or("json") {
+        "json" => serde_json::to_string_pretty(&bundle).map_err(|e| e.to_string()),
+        "jsonl" => transfer::to_jsonl(&bundle),
+        other => Err(format!("Unknown export format: {}", other)),
     }
+}
+
+/// 他のタイムトラッカーのエクスポートを取り込む（format: "activitywatch" | "manictime" | "toggl"）
+#[tauri::command]
+fn import_activities(
+    state: State<Arc<AppState>>,
+    format: String,
+    content: String,
+    mapping: Option<CsvMapping>,
+) -> Result<ImportReport, String> {
+    let bundle = importer::parse(&format, &content, mapping.as_ref())?;
+    let db = state.db.lock();
+    transfer::import(&db, &bundle)
+}
 
-    Ok(csv)
+/// export_json の出力（JSON / JSONL）を取り込む
+#[tauri::command]
+fn import_json(state: State<Arc<AppState>>, content: String) -> Result<ImportReport, String> {
+    let bundle = transfer::parse(&content)?;
+    let db = state.db.lock();
+    transfer::import(&db, &bundle)
 }
 
-/// CSV用にフィールドをエスケープ
-fn escape_csv_field(field: &str) -> String {
-    if field.contains(',') || field.contains('"') || field.contains('\n') || field.contains('\r') {
-        format!("\"{}\"", field.replace('"', "\"\""))
-    } else {
-        field.to_string()
-    }
+/// 利用可能なマスキングプロファイル一覧を取得
+#[tauri::command]
+fn list_redaction_profiles() -> Vec<RedactionProfile> {
+    redaction::list_profiles(&IntegrationsConfig::load())
 }
 
 // ========== ユーザー情報・アップロード関連コマンド ==========
@@ -757,34 +2647,7 @@ pub struct UploadConfigInfo {
     pub auto_upload: bool,
     pub auto_upload_interval_minutes: u32,
     pub min_duration_seconds: u32,
-}
-
-/// アプリ使用時間サマリー（アップロード用）
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AppUsageSummary {
-    pub process_name: String,
-    pub total_seconds: i64,
-    /// ブラウザの場合のドメイン（ブラウザ以外はnull）
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub domain: Option<String>,
-}
-
-/// アップロードリクエスト（集計データ形式）
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct UploadRequest {
-    user_id: String,
-    machine_name: Option<String>,
-    date: String,
-    min_duration_seconds: u32,
-    app_summaries: Vec<AppUsageSummary>,
-}
-
-/// アップロード結果
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct UploadResult {
-    pub success: bool,
-    pub message: String,
-    pub uploaded_count: usize,
+    pub redaction_profile: Option<String>,
 }
 
 /// 現在のユーザー情報を取得
@@ -808,279 +2671,399 @@ fn get_upload_config() -> Option<UploadConfigInfo> {
         auto_upload: c.auto_upload,
         auto_upload_interval_minutes: c.auto_upload_interval_minutes,
         min_duration_seconds: c.min_duration_seconds,
+        redaction_profile: c.redaction_profile,
     })
 }
 
-/// ブラウザプロセス名のリスト
-const BROWSER_PROCESSES: &[&str] = &[
-    "chrome.exe",
-    "msedge.exe",
-    "firefox.exe",
-    "brave.exe",
-    "opera.exe",
-    "vivaldi.exe",
-    "iexplore.exe",
-];
-
-/// プロセス名がブラウザかどうかを判定
-fn is_browser_process(process_name: &str) -> bool {
-    let lower = process_name.to_lowercase();
-    BROWSER_PROCESSES.iter().any(|b| lower == *b)
-}
-
 /// 指定日のアクティビティをサーバーにアップロード（集計・フィルタリング済み）
 #[tauri::command]
 async fn upload_activities(
     state: State<'_, Arc<AppState>>,
     date: String,
 ) -> Result<UploadResult, String> {
-    use std::collections::HashMap;
+    let upload_config = enabled_upload_config()?;
+    upload_date(&state, &upload_config, &date).await
+}
 
-    // アップロード設定を取得
-    let upload_config =
-        plugins::get_upload_config().ok_or_else(|| "Upload not configured".to_string())?;
+/// 未送信のアクティビティを含む日付をすべてアップロード
+#[tauri::command]
+async fn upload_now(state: State<'_, Arc<AppState>>) -> Result<UploadResult, String> {
+    let upload_config = enabled_upload_config()?;
+    upload_pending(&state, &upload_config).await
+}
 
-    if !upload_config.enabled {
-        return Err("Upload is disabled".to_string());
-    }
+/// アップロードの状態を取得
+#[tauri::command]
+fn get_upload_status(state: State<Arc<AppState>>) -> Result<UploadStatus, String> {
+    let upload_config = plugins::get_upload_config();
+    let db = state.db.lock();
 
-    if upload_config.server_url.is_empty() {
-        return Err("Server URL is not configured".to_string());
-    }
+    Ok(UploadStatus {
+        enabled: upload_config.as_ref().is_some_and(|c| c.enabled),
+        auto_upload: upload_config.as_ref().is_some_and(|c| c.auto_upload),
+        auto_upload_interval_minutes: upload_config
+            .as_ref()
+            .map(|c| c.auto_upload_interval_minutes)
+            .unwrap_or(0),
+        pending_activities: uploader::pending_count(&db)?,
+        pending_dates: uploader::pending_dates(&db)?,
+        last_upload: uploader::last_upload(&db)?,
+    })
+}
 
-    let min_duration = upload_config.min_duration_seconds as i64;
+const TRAY_ID: &str = "main";
+/// トレイのプロジェクト切り替えメニュー項目のIDプレフィックス（空のプロジェクト名は解除）
+const PROJECT_MENU_PREFIX: &str = "project:";
 
-    // ユーザー情報を取得
-    let user_id = get_user_upn().ok_or_else(|| "Failed to get user information".to_string())?;
-    let machine_name = get_machine_name();
+/// トレイメニューを構築（プロジェクト切り替えのサブメニューを含む）
+fn build_tray_menu(app: &AppHandle, state: &AppState) -> tauri::Result<Menu<tauri::Wry>> {
+    let active = state.project_override.lock().clone();
+    let projects = project_menu_entries(state).unwrap_or_else(|e| {
+        eprintln!("Failed to load recent projects: {}", e);
+        Vec::new()
+    });
 
-    // 指定日のアクティビティを取得して集計
-    let app_summaries = {
-        let db = state.db.lock();
-        let start_of_day = format!("{}T00:00:00", date);
-        let end_of_day = format!("{}T23:59:59", date);
+    let project_menu = Submenu::with_id(app, "projects", "Project", true)?;
+    for project in &projects {
+        let item = CheckMenuItem::with_id(
+            app,
+            format!("{}{}", PROJECT_MENU_PREFIX, project),
+            project,
+            true,
+            active.as_deref() == Some(project.as_str()),
+            None::<&str>,
+        )?;
+        project_menu.append(&item)?;
+    }
+    if !projects.is_empty() {
+        project_menu.append(&PredefinedMenuItem::separator(app)?)?;
+    }
+    let clear = MenuItem::with_id(
+        app,
+        PROJECT_MENU_PREFIX,
+        "No project override",
+        active.is_some(),
+        None::<&str>,
+    )?;
+    project_menu.append(&clear)?;
+
+    let toggle_label = if *state.is_tracking.lock() {
+        "Pause Tracking"
+    } else {
+        "Resume Tracking"
+    };
+    let toggle = MenuItem::with_id(app, "toggle_tracking", toggle_label, true, None::<&str>)?;
+    let report = MenuItem::with_id(app, "report", "Today's Report", true, None::<&str>)?;
+    let quit = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
+    let show = MenuItem::with_id(app, "show", "Show Window", true, None::<&str>)?;
+    Menu::with_items(app, &[&show, &toggle, &project_menu, &report, &quit])
+}
 
-        // (process_name, domain) をキーにして集計
-        // ブラウザ以外: (process_name, None)
-        // ブラウザ: (process_name, Some(domain))
-        let mut totals: HashMap<(String, Option<String>), i64> = HashMap::new();
+/// プロジェクトの上書きが変わったらトレイメニューを作り直す
+fn refresh_tray_menu(app: &AppHandle, state: &AppState) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    match build_tray_menu(app, state) {
+        Ok(menu) => {
+            let _ = tray.set_menu(Some(menu));
+        }
+        Err(e) => eprintln!("Failed to rebuild tray menu: {}", e),
+    }
+    let _ = tray.set_tooltip(Some(tray_tooltip(state)));
+}
 
-        let mut stmt = db
-            .prepare(
-                "SELECT process_name, domain, duration_seconds
-                 FROM activities
-                 WHERE start_time >= ?1 AND start_time <= ?2",
-            )
-            .map_err(|e| e.to_string())?;
+fn refresh_tray_tooltip(app: &AppHandle, state: &AppState) {
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        let _ = tray.set_tooltip(Some(tray_tooltip(state)));
+    }
+}
 
-        let rows = stmt
-            .query_map(params![start_of_day, end_of_day], |row| {
-                Ok((
-                    row.get::<_, String>(0)?,
-                    row.get::<_, Option<String>>(1)?,
-                    row.get::<_, i64>(2)?,
-                ))
-            })
-            .map_err(|e| e.to_string())?;
+/// 監視スレッドからの通知でトレイを更新する
+struct TrayObserver(AppHandle);
 
-        for row in rows.flatten() {
-            let (process_name, domain, duration) = row;
+impl WatcherObserver for TrayObserver {
+    fn today_updated(&self, state: &AppState) {
+        refresh_tray_tooltip(&self.0, state);
+    }
 
-            let key = if is_browser_process(&process_name) {
-                // ブラウザの場合は (process_name, domain) で集計
-                let d = domain.filter(|s| !s.is_empty());
-                if d.is_some() {
-                    (process_name, d)
-                } else {
-                    continue; // ドメインがない場合はスキップ
-                }
-            } else {
-                // ブラウザ以外は (process_name, None) で集計
-                (process_name, None)
-            };
+    fn status_changed(&self, state: &AppState) {
+        refresh_tray_status(&self.0, state);
+    }
+}
 
-            *totals.entry(key).or_insert(0) += duration;
+/// トラッキング状態が変わったらメニュー・アイコン・ツールチップを更新する
+fn refresh_tray_status(app: &AppHandle, state: &AppState) {
+    refresh_tray_menu(app, state);
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        if let Some(icon) = tray_icon(app, TrayStatus::of(state)) {
+            let _ = tray.set_icon(Some(icon));
         }
+    }
+}
 
-        // 閾値以上のものだけフィルタリング
-        let app_summaries: Vec<AppUsageSummary> = totals
-            .into_iter()
-            .filter(|(_, total)| *total >= min_duration)
-            .map(|((process_name, domain), total_seconds)| AppUsageSummary {
-                process_name,
-                total_seconds,
-                domain,
-            })
-            .collect();
-
-        app_summaries
-    };
+/// トレイアイコンで表す状態
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TrayStatus {
+    Tracking,
+    Idle,
+    Paused,
+}
 
-    if app_summaries.is_empty() {
-        return Ok(UploadResult {
-            success: true,
-            message: format!(
-                "No data to upload (no apps/domains used for {}+ seconds)",
-                min_duration
-            ),
-            uploaded_count: 0,
-        });
+impl TrayStatus {
+    /// 停止中と一時停止中はどちらも Paused として表示する
+    fn of(state: &AppState) -> Self {
+        if !*state.is_tracking.lock() {
+            TrayStatus::Paused
+        } else if *state.is_idle.lock() {
+            TrayStatus::Idle
+        } else {
+            TrayStatus::Tracking
+        }
     }
+}
 
-    let uploaded_count = app_summaries.len();
-
-    // アップロードリクエストを作成
-    let request = UploadRequest {
-        user_id,
-        machine_name,
-        date: date.clone(),
-        min_duration_seconds: upload_config.min_duration_seconds,
-        app_summaries,
-    };
+/// 既定のアイコンを状態に合わせて加工する（Paused はグレースケール、Idle は半透明）
+fn tray_icon(app: &AppHandle, status: TrayStatus) -> Option<Image<'static>> {
+    let base = app.default_window_icon()?;
+    let mut rgba = base.rgba().to_vec();
+    for pixel in rgba.chunks_exact_mut(4) {
+        match status {
+            TrayStatus::Tracking => {}
+            TrayStatus::Idle => pixel[3] /= 2,
+            TrayStatus::Paused => {
+                let gray = (u32::from(pixel[0]) * 30
+                    + u32::from(pixel[1]) * 59
+                    + u32::from(pixel[2]) * 11)
+                    / 100;
+                pixel[0] = gray as u8;
+                pixel[1] = gray as u8;
+                pixel[2] = gray as u8;
+            }
+        }
+    }
+    Some(Image::new_owned(rgba, base.width(), base.height()))
+}
 
-    // HTTPクライアントでアップロード
-    let client = reqwest::Client::new();
-    let response = client
-        .post(&upload_config.server_url)
-        .header("Content-Type", "application/json")
-        .json(&request)
-        .send()
-        .await
-        .map_err(|e| format!("Failed to upload: {}", e))?;
-
-    if response.status().is_success() {
-        Ok(UploadResult {
-            success: true,
-            message: format!(
-                "Uploaded {} records (min {}s)",
-                uploaded_count, min_duration
-            ),
-            uploaded_count,
-        })
+/// トレイのツールチップ（状態と今日の合計。一時停止中は再開までの残り時間）
+fn tray_tooltip(state: &AppState) -> String {
+    let today = format_hours_minutes(*state.today_seconds.lock());
+    if let Some(pause) = *state.pause.lock() {
+        let minutes = (pause.remaining_seconds() + 59) / 60;
+        return format!(
+            "TimeTracker - Paused ({} min left)\nToday: {}",
+            minutes, today
+        );
+    }
+    if !*state.is_tracking.lock() {
+        return format!("TimeTracker - Stopped\nToday: {}", today);
+    }
+    let status = if *state.is_idle.lock() {
+        "Idle"
     } else {
-        let status = response.status();
-        let body = response
-            .text()
-            .await
-            .unwrap_or_else(|_| "Unknown error".to_string());
-        Err(format!("Upload failed with status {}: {}", status, body))
+        "Running"
+    };
+    match state.project_override.lock().as_deref() {
+        Some(project) => format!("TimeTracker - {} ({})\nToday: {}", status, project, today),
+        None => format!("TimeTracker - {}\nToday: {}", status, today),
     }
 }
 
-fn start_watcher_thread(state: Arc<AppState>) {
-    thread::spawn(move || {
-        let mut last_process = String::new();
-        let mut last_title = String::new();
-        let mut last_domain: Option<String> = None;
-        let mut activity_start: Option<DateTime<Local>> = None;
-
+/// イベントを Tauri のイベントとしてフロントエンドにも送る（フロントエンドがポーリングせずに表示を更新できる）
+fn forward_to_frontend(app: AppHandle, events: &LiveEvents) {
+    let mut receiver = events.subscribe();
+    tauri::async_runtime::spawn(async move {
         loop {
-            thread::sleep(Duration::from_secs(1));
-
-            if !*state.is_tracking.lock() {
-                // Save current activity before pausing
-                if let Some(start) = activity_start.take() {
-                    save_activity(
-                        &state,
-                        &last_process,
-                        &last_title,
-                        last_domain.as_deref(),
-                        start,
-                    );
+            match receiver.recv().await {
+                Ok(LiveEvent::ActivityStart {
+                    process_name,
+                    window_title,
+                    domain,
+                    source,
+                    start_time,
+                }) => {
+                    let current = CurrentActivity {
+                        process_name,
+                        window_title,
+                        domain,
+                        source,
+                        start_time,
+                        elapsed_seconds: 0,
+                    };
+                    let _ = app.emit(EVENT_ACTIVITY_CHANGED, current);
                 }
-                last_process.clear();
-                last_title.clear();
-                last_domain = None;
-                continue;
-            }
-
-            if let Some((process_name, window_title, domain)) = get_active_window_info() {
-                let changed = process_name != last_process
-                    || window_title != last_title
-                    || domain != last_domain;
-
-                if changed {
-                    // Save previous activity
-                    if let Some(start) = activity_start.take() {
-                        save_activity(
-                            &state,
-                            &last_process,
-                            &last_title,
-                            last_domain.as_deref(),
-                            start,
-                        );
-                    }
-
-                    // Start new activity
-                    last_process = process_name;
-                    last_title = window_title;
-                    last_domain = domain;
-                    activity_start = Some(Local::now());
+                Ok(event) => {
+                    let _ = app.emit(event.event_name(), event);
                 }
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
             }
         }
     });
 }
 
-fn save_activity(
-    state: &Arc<AppState>,
-    process_name: &str,
-    window_title: &str,
-    domain: Option<&str>,
-    start: DateTime<Local>,
-) {
-    if process_name.is_empty() {
-        return;
-    }
+/// 自動起動時に付ける引数（ウィンドウを出さずにトレイだけで起動する）
+const MINIMIZED_ARG: &str = "--minimized";
 
-    let end = Local::now();
-    let duration = (end - start).num_seconds();
+const DEFAULT_QUICK_ENTRY_HOTKEY: &str = "CommandOrControl+Shift+T";
 
-    if duration < 1 {
-        return;
+/// メインウィンドウを表示し、フロントエンドにクイック入力を開かせる
+fn show_quick_entry(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.set_focus();
     }
-
-    let db = state.db.lock();
-    let _ = db.execute(
-        "INSERT INTO activities (process_name, window_title, domain, start_time, end_time, duration_seconds)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-        params![
-            process_name,
-            window_title,
-            domain,
-            start.format("%Y-%m-%dT%H:%M:%S").to_string(),
-            end.format("%Y-%m-%dT%H:%M:%S").to_string(),
-            duration,
-        ],
-    );
+    let _ = app.emit("quick-entry", ());
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    // Tauri のコマンド・イベントも core と同じランタイムで動かす
+    tauri::async_runtime::set(runtime::handle().clone());
+
     let app_state = Arc::new(AppState::new().expect("Failed to initialize database"));
     let watcher_state = app_state.clone();
+    recover_open_activity(&app_state);
 
     // Start tracking by default
     *app_state.is_tracking.lock() = true;
 
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_autostart::init(
+            MacosLauncher::LaunchAgent,
+            Some(vec![MINIMIZED_ARG]),
+        ))
+        .plugin(
+            tauri_plugin_global_shortcut::Builder::new()
+                .with_handler(|app, _shortcut, event| {
+                    if event.state() == ShortcutState::Pressed {
+                        show_quick_entry(app);
+                    }
+                })
+                .build(),
+        )
         .manage(app_state)
         .setup(move |app| {
             // Start the background watcher
-            start_watcher_thread(watcher_state);
+            start_budget_notifier(app.handle().clone(), watcher_state.clone());
+            start_pause_timer(app.handle().clone(), watcher_state.clone());
+            config_watcher::start(watcher_state.clone());
+            // timetracker-cli watch が記録中なら、二重に記録・送信しないよう表示だけにする
+            match instance::acquire() {
+                Ok(lock) => {
+                    app.manage(lock);
+                    start_compaction_job(watcher_state.clone());
+                    start_ticket_extraction_backfill(watcher_state.clone());
+                    start_retention_job(watcher_state.clone());
+                    start_calendar_sync(watcher_state.clone());
+                    start_sync_queue_flusher(watcher_state.clone());
+                    start_upload_job(watcher_state.clone());
+                    start_watcher_thread(
+                        Some(Box::new(TrayObserver(app.handle().clone()))),
+                        watcher_state,
+                    );
+                }
+                Err(e) => {
+                    eprintln!("Not starting the window watcher: {}", e);
+                    *watcher_state.is_tracking.lock() = false;
+                }
+            }
+
+            // ログイン時の自動起動ではウィンドウを表示しない
+            if std::env::args().any(|arg| arg == MINIMIZED_ARG) {
+                if let Some(window) = app.get_webview_window("main") {
+                    let _ = window.hide();
+                }
+            }
+
+            // クイック入力のホットキーを登録
+            let config = IntegrationsConfig::load();
+            let hotkey = config
+                .quick_entry_hotkey
+                .as_deref()
+                .unwrap_or(DEFAULT_QUICK_ENTRY_HOTKEY);
+            match hotkey.parse::<Shortcut>() {
+                Ok(shortcut) => {
+                    if let Err(e) = app.global_shortcut().register(shortcut) {
+                        eprintln!("Failed to register hotkey {}: {}", hotkey, e);
+                    }
+                }
+                Err(e) => eprintln!("Invalid hotkey {}: {}", hotkey, e),
+            }
+
+            // フロントエンドへのイベント送信
+            forward_to_frontend(app.handle().clone(), &app.state::<Arc<AppState>>().live);
+
+            // 入力の計測（明示的に有効にした場合のみ）
+            if config.input_meter.enabled {
+                if let Err(e) = input_meter::start() {
+                    eprintln!("Failed to start input meter: {}", e);
+                }
+            }
+
+            // リアルタイム配信のWebSocketサーバー
+            if let Some(live_config) = config.live_stream.filter(|c| c.enabled) {
+                let state = app.state::<Arc<AppState>>().inner().clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = live::serve(state, live_config).await {
+                        eprintln!("Live activity stream stopped: {}", e);
+                    }
+                });
+            }
+
+            // 外部ツール向けの REST API
+            if let Some(api_config) = config.api_server.filter(|c| c.enabled) {
+                let app_handle = app.handle().clone();
+                let state = app.state::<Arc<AppState>>().inner().clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = api_server::serve(app_handle, state, api_config).await {
+                        eprintln!("REST API server stopped: {}", e);
+                    }
+                });
+            }
+
+            // MQTT での状態の配信
+            if let Some(mqtt_config) = config.mqtt.filter(|c| c.enabled) {
+                if let Err(e) =
+                    mqtt::start(app.state::<Arc<AppState>>().inner().clone(), mqtt_config)
+                {
+                    eprintln!("Failed to start MQTT: {}", e);
+                }
+            }
+
+            // Slack のステータス・日次サマリー
+            if let Some(slack_config) = config.slack.filter(|c| c.enabled) {
+                match SlackIntegration::new(slack_config) {
+                    Ok(slack) => slack_status::start(
+                        app.state::<Arc<AppState>>().inner().clone(),
+                        Arc::new(slack),
+                    ),
+                    Err(e) => eprintln!("Failed to start Slack integration: {}", e),
+                }
+            }
+
+            // イベントの Webhook 通知
+            webhooks::start(
+                app.state::<Arc<AppState>>().inner().clone(),
+                config.webhooks,
+            );
 
             // Setup system tray
-            let quit = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
-            let show = MenuItem::with_id(app, "show", "Show Window", true, None::<&str>)?;
-            let menu = Menu::with_items(app, &[&show, &quit])?;
+            let (menu, tooltip) = {
+                let state = app.state::<Arc<AppState>>();
+                update_today_seconds(&state, 0);
+                (build_tray_menu(app.handle(), &state)?, tray_tooltip(&state))
+            };
 
-            let _tray = TrayIconBuilder::new()
+            let _tray = TrayIconBuilder::with_id(TRAY_ID)
                 .menu(&menu)
-                .tooltip("TimeTracker - Running")
+                .tooltip(tooltip)
                 .icon(app.default_window_icon().unwrap().clone())
                 .on_menu_event(|app, event| match event.id.as_ref() {
                     "quit" => {
+                        flush_activities(&app.state::<Arc<AppState>>());
                         app.exit(0);
                     }
                     "show" => {
@@ -1089,7 +3072,30 @@ pub fn run() {
                             let _ = window.set_focus();
                         }
                     }
-                    _ => {}
+                    "toggle_tracking" => {
+                        let state = app.state::<Arc<AppState>>();
+                        let result = if *state.is_tracking.lock() {
+                            stop_tracking(app.clone(), state.clone())
+                        } else {
+                            start_tracking(app.clone(), state.clone())
+                        };
+                        if let Err(e) = result {
+                            eprintln!("Failed to toggle tracking: {}", e);
+                        }
+                    }
+                    "report" => {
+                        if let Err(e) = open_today_report(app) {
+                            eprintln!("Failed to open report: {}", e);
+                        }
+                    }
+                    id => {
+                        if let Some(project) = id.strip_prefix(PROJECT_MENU_PREFIX) {
+                            let state = app.state::<Arc<AppState>>();
+                            let project = Some(project.to_string()).filter(|p| !p.is_empty());
+                            *state.project_override.lock() = project;
+                            refresh_tray_menu(app, &state);
+                        }
+                    }
                 })
                 .on_tray_icon_event(|tray, event| {
                     if let TrayIconEvent::Click {
@@ -1119,21 +3125,139 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             start_tracking,
             stop_tracking,
+            pause_tracking,
+            get_pause_status,
             is_tracking,
+            get_current_activity,
             get_activities,
+            search_activities,
+            add_note,
+            get_notes,
             get_app_summary,
+            get_placement_summary,
             get_domain_summary,
+            get_title_summary,
+            compare_periods,
+            get_focus_sessions,
+            get_context_switches,
+            get_productivity_score,
+            get_activity_heatmap,
+            get_productivity_labels,
+            set_productivity_label,
+            get_summary_range,
+            get_timeline,
+            list_goals,
+            create_goal,
+            update_goal,
+            delete_goal,
+            get_goal_progress,
+            renormalize_titles,
+            get_project_summary,
+            list_projects,
+            generate_invoice_report,
+            export_invoice_report,
+            generate_report,
+            save_project,
+            delete_project,
+            get_exclusions,
+            add_exclusion,
+            remove_exclusion,
+            list_categories,
+            create_category,
+            update_category,
+            delete_category,
+            get_process_metadata,
+            get_vendor_summary,
+            get_weekly_budget_status,
+            get_unassigned,
+            assign_activity,
+            unassign_activity,
+            assign_current_activity,
+            suggest_assignment,
+            get_rule_candidates,
+            accept_rule_candidate,
+            get_rules,
+            edit_rules,
+            apply_rules,
+            test_extraction_rules,
+            backfill_ticket_ids,
+            dedupe_activities,
+            merge_activities,
+            split_activity,
+            compact_activities,
+            get_database_encryption,
+            set_database_encryption,
+            enable_autostart,
+            disable_autostart,
+            is_autostart_enabled,
+            get_input_meter,
+            set_input_meter,
+            get_input_intensity,
+            get_idle_settings,
+            set_idle_settings,
+            get_retention_settings,
+            set_retention_settings,
+            prune_old_activities,
+            get_daily_summaries,
+            create_api_token,
+            rotate_api_token,
+            revoke_api_token,
+            list_api_tokens,
+            get_api_request_log,
+            get_capture_sources,
+            report_capture_event,
+            start_manual_timer,
+            stop_manual_timer,
+            get_recent_projects,
+            get_project_override,
+            set_project_override,
             get_plugins,
             reload_plugins,
+            get_integrations_config,
+            validate_config,
+            get_config_error,
+            set_integration,
+            remove_integration,
+            set_upload_config,
+            set_integration_secret,
+            delete_integration_secret,
             create_sample_plugin_config,
             get_plugin_config_path,
             extract_ticket_ids,
             sync_time_entry,
+            sync_day,
+            generate_pending_sync,
+            list_pending_sync,
+            update_pending_sync,
+            merge_pending_sync,
+            set_pending_sync_status,
+            discard_pending_sync,
+            push_approved_sync,
+            get_ticket_summary,
+            get_sync_status,
             test_plugin_connection,
+            test_slack_connection,
+            sync_calendar,
+            get_meeting_report,
+            post_slack_summary,
+            get_sync_queue,
+            flush_sync_queue,
+            retry_sync_queue_entry,
+            discard_sync_queue_entry,
+            get_ticket_progress,
+            list_my_issues,
+            get_ticket_info,
             export_timeline_csv,
+            export_ics,
+            export_json,
+            import_json,
+            import_activities,
+            list_redaction_profiles,
             get_current_user,
             get_upload_config,
             upload_activities,
+            upload_now,
+            get_upload_status,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -1,3 +0,0 @@
-pub mod redmine;
-
-pub use redmine::RedmineIntegration;
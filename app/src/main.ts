@@ -1,4 +1,5 @@
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 
 interface ActivityRecord {
   id: number;
@@ -8,6 +9,7 @@ interface ActivityRecord {
   start_time: string;
   end_time: string;
   duration_seconds: number;
+  note: string | null;
 }
 
 interface AppSummary {
@@ -28,6 +30,15 @@ interface SyncResult {
   external_id: string | null;
 }
 
+interface SyncLogEntry {
+  id: number;
+  activity_id: number;
+  plugin: string;
+  ticket_id: string;
+  external_id: string | null;
+  synced_at: string;
+}
+
 interface CurrentUserInfo {
   user_id: string;
   machine_name: string | null;
@@ -41,15 +52,34 @@ interface UploadConfigInfo {
   min_duration_seconds: number;
 }
 
+interface UploadStatus {
+  pending_activities: number;
+  pending_dates: string[];
+  last_upload: { date: string; success: boolean; message: string; uploaded_at: string } | null;
+}
+
 interface UploadResult {
   success: boolean;
   message: string;
   uploaded_count: number;
 }
 
+interface TicketInfo {
+  plugin: string;
+  ticket_id: string;
+  subject: string;
+  project: string | null;
+  status: string | null;
+  fetched_at: string;
+  stale: boolean;
+}
+
 // Store detected ticket IDs for each activity
 const activityTickets: Map<number, Array<[string, string]>> = new Map();
 
+// Ticket subjects by "plugin:ticket" (null = not available)
+const ticketInfoCache: Map<string, TicketInfo | null> = new Map();
+
 // Current app filter (null = show all)
 let currentAppFilter: string | null = null;
 
@@ -117,6 +147,15 @@ async function loadActivities(date: string): Promise<void> {
     const plugins = await invoke<string[]>("get_plugins");
     const hasPlugins = plugins.length > 0;
 
+    // Activities already synced, keyed by "activityId:plugin"
+    const syncedKeys = new Set<string>();
+    if (hasPlugins) {
+      const syncLog = await invoke<SyncLogEntry[]>("get_sync_status", { date });
+      for (const entry of syncLog) {
+        syncedKeys.add(`${entry.activity_id}:${entry.plugin}`);
+      }
+    }
+
     // Apply app filter to activities
     const filteredActivities = currentAppFilter
       ? activities.filter((a) => a.process_name === currentAppFilter)
@@ -153,12 +192,13 @@ async function loadActivities(date: string): Promise<void> {
           const color = getAppColor(activity.process_name);
           const tickets = activityTickets.get(activity.id) || [];
           const ticketBadges = tickets
-            .map(([plugin, ticketId]) => `<span class="ticket-badge" title="${escapeHtml(plugin)}">#${escapeHtml(ticketId)}</span>`)
+            .map(([plugin, ticketId]) => `<span class="ticket-badge" title="${escapeHtml(plugin)}" data-plugin="${escapeHtml(plugin)}" data-ticket="${escapeHtml(ticketId)}">#${escapeHtml(ticketId)}</span>`)
             .join("");
           const syncButtons = tickets
-            .map(
-              ([plugin, ticketId]) =>
-                `<button class="btn-sync" data-activity-id="${activity.id}" data-plugin="${escapeHtml(plugin)}" data-ticket="${escapeHtml(ticketId)}">Sync to ${escapeHtml(plugin)} #${escapeHtml(ticketId)}</button>`
+            .map(([plugin, ticketId]) =>
+              syncedKeys.has(`${activity.id}:${plugin}`)
+                ? `<button class="btn-sync synced" disabled>Synced to ${escapeHtml(plugin)} #${escapeHtml(ticketId)}</button>`
+                : `<button class="btn-sync" data-activity-id="${activity.id}" data-plugin="${escapeHtml(plugin)}" data-ticket="${escapeHtml(ticketId)}">Sync to ${escapeHtml(plugin)} #${escapeHtml(ticketId)}</button>`
             )
             .join("");
 
@@ -170,8 +210,12 @@ async function loadActivities(date: string): Promise<void> {
               <div class="timeline-content">
                 <div class="timeline-app">${escapeHtml(activity.process_name)}${ticketBadges}</div>
                 <div class="timeline-title">${escapeHtml(activity.window_title)}</div>
+                ${activity.note ? `<div class="timeline-note">${escapeHtml(activity.note)}</div>` : ""}
                 <div class="timeline-duration">${formatDuration(activity.duration_seconds)}</div>
-                ${syncButtons ? `<div class="timeline-actions">${syncButtons}</div>` : ""}
+                <div class="timeline-actions">
+                  <button class="btn-note" data-activity-id="${activity.id}" data-note="${escapeHtml(activity.note ?? "")}">${activity.note ? "Edit note" : "Add note"}</button>
+                  ${syncButtons}
+                </div>
               </div>
             </div>
           `;
@@ -179,9 +223,13 @@ async function loadActivities(date: string): Promise<void> {
         .join("");
 
       // Add click handlers for sync buttons
-      timelineEl.querySelectorAll(".btn-sync").forEach((btn) => {
+      timelineEl.querySelectorAll(".btn-sync:not(.synced)").forEach((btn) => {
         btn.addEventListener("click", handleSyncClick);
       });
+      timelineEl.querySelectorAll(".btn-note").forEach((btn) => {
+        btn.addEventListener("click", handleNoteClick);
+      });
+      void fillTicketSubjects(timelineEl);
     }
 
     // Render summary
@@ -242,6 +290,28 @@ async function loadActivities(date: string): Promise<void> {
   }
 }
 
+// Show "#1234 Subject" on ticket badges (the backend caches subjects)
+async function fillTicketSubjects(container: HTMLElement): Promise<void> {
+  const badges = container.querySelectorAll<HTMLElement>(".ticket-badge[data-plugin]");
+  for (const badge of badges) {
+    const plugin = badge.dataset.plugin || "";
+    const ticketId = badge.dataset.ticket || "";
+    const key = `${plugin}:${ticketId}`;
+    if (!ticketInfoCache.has(key)) {
+      try {
+        ticketInfoCache.set(key, await invoke<TicketInfo | null>("get_ticket_info", { plugin, id: ticketId }));
+      } catch {
+        ticketInfoCache.set(key, null);
+      }
+    }
+    const info = ticketInfoCache.get(key);
+    if (info) {
+      badge.textContent = `#${ticketId} ${info.subject}`;
+      badge.title = [plugin, info.project, info.status].filter(Boolean).join(" · ");
+    }
+  }
+}
+
 function escapeHtml(text: string): string {
   const div = document.createElement("div");
   div.textContent = text;
@@ -284,14 +354,78 @@ function clearAppFilter(): void {
   loadActivities(datePicker.value);
 }
 
+interface PauseStatus {
+  started_at: string;
+  resume_at: string;
+  remaining_seconds: number;
+}
+
+interface CurrentActivity {
+  process_name: string;
+  window_title: string;
+  domain: string | null;
+  source: string;
+  start_time: string;
+  elapsed_seconds: number;
+}
+
+// In-progress activity shown in the footer (pushed by activity-changed events)
+let currentActivity: CurrentActivity | null = null;
+let currentActivityReceivedAt = 0;
+
+function setCurrentActivity(activity: CurrentActivity | null): void {
+  currentActivity = activity;
+  currentActivityReceivedAt = Date.now();
+  renderCurrentActivity();
+}
+
+function renderCurrentActivity(): void {
+  const el = document.getElementById("current-activity")!;
+  if (!currentActivity) {
+    el.textContent = "";
+    return;
+  }
+  const elapsed =
+    currentActivity.elapsed_seconds + Math.floor((Date.now() - currentActivityReceivedAt) / 1000);
+  el.textContent = `${currentActivity.process_name} - ${currentActivity.window_title} (${formatDuration(elapsed)})`;
+}
+
+async function loadCurrentActivity(): Promise<void> {
+  setCurrentActivity(await invoke<CurrentActivity | null>("get_current_activity"));
+}
+
 async function updateTrackingButton(): Promise<void> {
   const button = document.getElementById("toggle-tracking")!;
   const isTracking = await invoke<boolean>("is_tracking");
-  button.textContent = `Tracking: ${isTracking ? "ON" : "OFF"}`;
+  const pause = await invoke<PauseStatus | null>("get_pause_status");
+  if (pause) {
+    const minutes = Math.ceil(pause.remaining_seconds / 60);
+    button.textContent = `Tracking: PAUSED (${minutes} min)`;
+  } else {
+    button.textContent = `Tracking: ${isTracking ? "ON" : "OFF"}`;
+  }
   button.classList.toggle("tracking-on", isTracking);
   button.classList.toggle("tracking-off", !isTracking);
 }
 
+async function pauseTracking(): Promise<void> {
+  const input = window.prompt("Pause tracking for how many minutes?", "30");
+  if (!input || !input.trim()) {
+    return;
+  }
+  const minutes = parseInt(input, 10);
+  if (isNaN(minutes) || minutes <= 0) {
+    alert("Invalid pause duration");
+    return;
+  }
+  try {
+    await invoke("pause_tracking", { durationMinutes: minutes });
+  } catch (error) {
+    alert(`Failed to pause tracking: ${error}`);
+  }
+  await updateTrackingButton();
+}
+
 async function toggleTracking(): Promise<void> {
   const isTracking = await invoke<boolean>("is_tracking");
   if (isTracking) {
@@ -302,6 +436,38 @@ async function toggleTracking(): Promise<void> {
   await updateTrackingButton();
 }
 
+// Quick entry: attribute the ongoing activity to a ticket (opened by global hotkey)
+async function openQuickEntry(): Promise<void> {
+  const ticketId = window.prompt("Ticket ID for the current activity:");
+  if (!ticketId || !ticketId.trim()) {
+    return;
+  }
+
+  try {
+    await invoke("assign_current_activity", { ticketId: ticketId.trim() });
+  } catch (error) {
+    alert(`Failed to assign ticket: ${error}`);
+  }
+}
+
+// Annotate an activity; the note is used as the comment when syncing
+async function handleNoteClick(event: Event): Promise<void> {
+  const btn = event.target as HTMLButtonElement;
+  const activityId = parseInt(btn.dataset.activityId || "0", 10);
+  const text = window.prompt("Note for this activity (empty to remove):", btn.dataset.note || "");
+  if (text === null) {
+    return;
+  }
+
+  try {
+    await invoke("add_note", { activityId, text });
+    const datePicker = document.getElementById("date-picker") as HTMLInputElement;
+    await loadActivities(datePicker.value);
+  } catch (error) {
+    alert(`Failed to save note: ${error}`);
+  }
+}
+
 // ========== Plugin Integration Functions ==========
 
 async function handleSyncClick(event: Event): Promise<void> {
@@ -445,6 +611,18 @@ async function loadUserInfo(): Promise<void> {
   }
 }
 
+// integrations.toml could not be read: the app is running on defaults until it is fixed
+async function checkConfigError(): Promise<void> {
+  try {
+    const error = await invoke<string | null>("get_config_error");
+    if (error) {
+      alert(`integrations.toml could not be loaded, so default settings are in use:\n${error}`);
+    }
+  } catch (error) {
+    console.error("Failed to check integrations config:", error);
+  }
+}
+
 async function loadUploadConfig(): Promise<void> {
   const uploadConfigStatusEl = document.getElementById("upload-config-status")!;
   const uploadBtn = document.getElementById("upload-btn") as HTMLButtonElement;
@@ -453,6 +631,10 @@ async function loadUploadConfig(): Promise<void> {
     const config = await invoke<UploadConfigInfo | null>("get_upload_config");
 
     if (config && config.enabled) {
+      const status = await invoke<UploadStatus>("get_upload_status");
+      const lastUpload = status.last_upload
+        ? `${status.last_upload.success ? "OK" : "Failed"} at ${formatTime(status.last_upload.uploaded_at)} (${escapeHtml(status.last_upload.date)})`
+        : "Never";
       const minMinutes = Math.floor(config.min_duration_seconds / 60);
       uploadConfigStatusEl.innerHTML = `
         <div class="upload-status-item">
@@ -467,6 +649,14 @@ async function loadUploadConfig(): Promise<void> {
           <span class="label">Auto-upload:</span>
           <span class="value">${config.auto_upload ? `Enabled (every ${config.auto_upload_interval_minutes} min)` : "Disabled"}</span>
         </div>
+        <div class="upload-status-item">
+          <span class="label">Pending:</span>
+          <span class="value">${status.pending_activities} activities (${status.pending_dates.length} days)</span>
+        </div>
+        <div class="upload-status-item">
+          <span class="label">Last upload:</span>
+          <span class="value">${lastUpload}</span>
+        </div>
       `;
       uploadBtn.disabled = false;
       uploadBtn.title = "Upload activities to server";
@@ -526,6 +716,7 @@ async function uploadActivities(): Promise<void> {
 window.addEventListener("DOMContentLoaded", async () => {
   const datePicker = document.getElementById("date-picker") as HTMLInputElement;
   const toggleButton = document.getElementById("toggle-tracking")!;
+  const pauseButton = document.getElementById("pause-tracking")!;
   const integrationsBtn = document.getElementById("integrations-btn")!;
   const closeModalBtn = document.getElementById("close-modal")!;
   const createSampleBtn = document.getElementById("create-sample-config")!;
@@ -542,7 +733,9 @@ window.addEventListener("DOMContentLoaded", async () => {
   await loadActivities(datePicker.value);
   await updateTrackingButton();
   await loadUserInfo();
+  await checkConfigError();
   await loadUploadConfig();
+  await loadCurrentActivity();
 
   // Event listeners
   datePicker.addEventListener("change", () => {
@@ -550,6 +743,7 @@ window.addEventListener("DOMContentLoaded", async () => {
   });
 
   toggleButton.addEventListener("click", toggleTracking);
+  pauseButton.addEventListener("click", pauseTracking);
 
   // Filter clear button
   clearFilterBtn.addEventListener("click", clearAppFilter);
@@ -566,6 +760,41 @@ window.addEventListener("DOMContentLoaded", async () => {
   // Upload button
   uploadBtn.addEventListener("click", uploadActivities);
 
+  // Global hotkey quick entry
+  await listen("quick-entry", openQuickEntry);
+
+  // Live ticker of the current activity
+  await listen<CurrentActivity>("activity-changed", (event) => setCurrentActivity(event.payload));
+  await listen("activity-ended", () => setCurrentActivity(null));
+  setInterval(renderCurrentActivity, 1000);
+
+  // React to backend state changes immediately instead of waiting for the refresh below
+  await listen("tracking-state-changed", async () => {
+    await updateTrackingButton();
+    await loadCurrentActivity();
+  });
+  await listen("idle-changed", loadCurrentActivity);
+  const reloadToday = async () => {
+    if (datePicker.value === getToday()) {
+      await loadActivities(datePicker.value);
+    }
+  };
+  await listen("activity-saved", reloadToday);
+  await listen("sync-completed", reloadToday);
+
+  // integrations.toml was edited (or reloaded): categories, projects and plugins may have changed
+  await listen<{ error: string | null }>("config-reloaded", async (event) => {
+    if (event.payload.error) {
+      console.error(`Failed to reload integrations config: ${event.payload.error}`);
+      return;
+    }
+    if (!modal.classList.contains("hidden")) {
+      await refreshPluginsList(document.getElementById("plugins-list")!);
+    }
+    await loadUploadConfig();
+    await reloadToday();
+  });
+
   // Close modal when clicking outside
   modal.addEventListener("click", (e) => {
     if (e.target === modal) {
@@ -575,6 +804,8 @@ window.addEventListener("DOMContentLoaded", async () => {
 
   // Auto-refresh every 30 seconds
   setInterval(async () => {
+    await updateTrackingButton();
+    await loadCurrentActivity();
     if (datePicker.value === getToday()) {
       await loadActivities(datePicker.value);
     }